@@ -0,0 +1,29 @@
+//! Generates the C header of the `capi` feature using cbindgen. The header
+//! is written to the output directory instead of the checkout, and the copy
+//! in `include/sudoku_solver.h` is checked against it by the tests.
+
+fn main() {
+    #[cfg(feature = "capi")]
+    generate_header();
+}
+
+#[cfg(feature = "capi")]
+fn generate_header() {
+    let dir = std::env::var("CARGO_MANIFEST_DIR").expect("set by Cargo");
+    let out = std::env::var("OUT_DIR").expect("set by Cargo");
+    let header = format!("{}/sudoku_solver.h", out);
+    println!("cargo:rerun-if-changed=src/capi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rustc-env=SUDOKU_SOLVER_HEADER={}", header);
+    // Only the C interface is parsed, such that no other public items of the
+    // crate end up in the header.
+    cbindgen::Builder::new()
+        .with_src(format!("{}/src/capi.rs", dir))
+        .with_config(
+            cbindgen::Config::from_file(format!("{}/cbindgen.toml", dir))
+                .expect("cbindgen.toml is valid"),
+        )
+        .generate()
+        .expect("C header is generated")
+        .write_to_file(header);
+}
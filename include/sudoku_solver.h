@@ -0,0 +1,91 @@
+/* Generated by cbindgen from src/capi.rs, do not edit. */
+
+#ifndef SUDOKU_SOLVER_H
+#define SUDOKU_SOLVER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Size of a buffer holding a puzzle or solution, including the terminating
+// NUL character.
+#define SUDOKU_BUFFER_SIZE 82
+
+// Outcome of a call.
+typedef enum SudokuStatus {
+  // The call succeeded.
+  SUDOKU_OK = 0,
+  // A pointer was null, a string was not valid UTF-8, or a difficulty was
+  // out of range.
+  SUDOKU_INVALID_ARGUMENT = 1,
+  // The buffer is smaller than `SUDOKU_BUFFER_SIZE`.
+  SUDOKU_BUFFER_TOO_SMALL = 2,
+  // The puzzle could not be parsed.
+  SUDOKU_INVALID_PUZZLE = 3,
+  // The puzzle has no solution.
+  SUDOKU_UNSOLVABLE = 4,
+  // No puzzle of the requested difficulty was generated.
+  SUDOKU_NOT_GENERATED = 5,
+} SudokuStatus;
+
+// Difficulty tier of a puzzle.
+typedef enum SudokuDifficulty {
+  SUDOKU_EASY = 0,
+  SUDOKU_MEDIUM = 1,
+  SUDOKU_HARD = 2,
+  SUDOKU_EXPERT = 3,
+  SUDOKU_EXTREME = 4,
+} SudokuDifficulty;
+
+// Difficulty rating of a puzzle.
+typedef struct SudokuRating {
+  enum SudokuDifficulty difficulty;
+  // Sum of the scores of the techniques of all steps.
+  uint32_t score;
+  // Number of steps taken to solve the puzzle.
+  size_t steps;
+} SudokuRating;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Solve a puzzle, writing its solution into a buffer of `len` bytes, which
+// must be at least `SUDOKU_BUFFER_SIZE`.
+//
+// # Safety
+//
+// `puzzle` must point to a NUL-terminated string, and `solution` must point
+// to a writable buffer of `len` bytes.
+enum SudokuStatus sudoku_solve(const char *puzzle, char *solution, size_t len);
+
+// Rate the difficulty of a puzzle.
+//
+// # Safety
+//
+// `puzzle` must point to a NUL-terminated string, and `rating` must point to
+// a writable `SudokuRating`.
+enum SudokuStatus sudoku_rate(const char *puzzle, struct SudokuRating *rating);
+
+// Generate a random puzzle with a unique solution, writing it into a buffer
+// of `len` bytes, which must be at least `SUDOKU_BUFFER_SIZE`. The same seed
+// generates the same puzzle. If `difficulty` is not null, a puzzle of the
+// `SudokuDifficulty` it points to is generated.
+//
+// # Safety
+//
+// `difficulty` must be null or point to a `uint32_t`, and `puzzle` must
+// point to a writable buffer of `len` bytes.
+enum SudokuStatus sudoku_generate(uint64_t seed,
+                                  const uint32_t *difficulty,
+                                  char *puzzle,
+                                  size_t len);
+
+// Describe a `SudokuStatus` code, returning a static NUL-terminated string,
+// which tells that the code is unknown if it is out of range.
+const char *sudoku_status_message(uint32_t status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SUDOKU_SOLVER_H */
@@ -0,0 +1,128 @@
+//! Bindings for Node.js using napi-rs, such that Node backends can solve,
+//! rate and generate puzzles in-process instead of running the command line
+//! interface for each puzzle. Every function returns a promise, and the work
+//! is done on the thread pool of Node such that the event loop is not
+//! blocked. Puzzles are passed as strings of 81 values.
+
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Error, Result, Task};
+use napi_derive::napi;
+use std::time::{SystemTime, UNIX_EPOCH};
+use sudoku_solver::generate;
+use sudoku_solver::random::Rng;
+use sudoku_solver::rating::{self, Difficulty};
+use sudoku_solver::{Options, Puzzle, Solver};
+
+/// Maximum number of puzzles generated when looking for one of a requested
+/// difficulty.
+const GENERATE_ATTEMPTS: usize = 1000;
+
+/// Difficulty rating of a puzzle.
+#[napi(object)]
+pub struct PuzzleRating {
+    /// Name of the difficulty tier, such as "Hard".
+    pub difficulty: String,
+    /// Sum of the scores of the techniques of all steps.
+    pub score: u32,
+    /// Name of the hardest technique required, if any.
+    pub hardest: Option<String>,
+    /// Number of steps taken to solve the puzzle.
+    pub steps: u32,
+}
+
+fn parse(puzzle: &str) -> Result<Puzzle> {
+    puzzle.parse().map_err(Error::from_reason)
+}
+
+pub struct SolveTask(String);
+
+impl Task for SolveTask {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> Result<String> {
+        let solution = Solver::solve_with_options(&parse(&self.0)?, &Options::default())
+            .map_err(Error::from_reason)?;
+        Ok(solution.row_representation())
+    }
+
+    fn resolve(&mut self, _: Env, solution: String) -> Result<String> {
+        Ok(solution)
+    }
+}
+
+/// Solve a puzzle, resolving to its solution.
+#[napi]
+pub fn solve(puzzle: String) -> AsyncTask<SolveTask> {
+    AsyncTask::new(SolveTask(puzzle))
+}
+
+pub struct RateTask(String);
+
+impl Task for RateTask {
+    type Output = PuzzleRating;
+    type JsValue = PuzzleRating;
+
+    fn compute(&mut self) -> Result<PuzzleRating> {
+        let rating = rating::rate(&parse(&self.0)?).map_err(Error::from_reason)?;
+        Ok(PuzzleRating {
+            difficulty: rating.difficulty.name().to_string(),
+            score: rating.score,
+            hardest: rating.hardest.map(|technique| technique.name().to_string()),
+            steps: rating.steps as u32,
+        })
+    }
+
+    fn resolve(&mut self, _: Env, rating: PuzzleRating) -> Result<PuzzleRating> {
+        Ok(rating)
+    }
+}
+
+/// Rate the difficulty of a puzzle.
+#[napi]
+pub fn rate(puzzle: String) -> AsyncTask<RateTask> {
+    AsyncTask::new(RateTask(puzzle))
+}
+
+pub struct GenerateTask {
+    difficulty: Option<String>,
+    seed: u64,
+}
+
+impl Task for GenerateTask {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> Result<String> {
+        let mut rng = Rng::new(self.seed);
+        let puzzle = match &self.difficulty {
+            None => generate::generate(&mut rng),
+            Some(difficulty) => {
+                let difficulty: Difficulty = difficulty.parse().map_err(Error::from_reason)?;
+                generate::generate_with_difficulty(&mut rng, difficulty, GENERATE_ATTEMPTS)
+                    .map_err(Error::from_reason)?
+            }
+        };
+        Ok(puzzle.to_string())
+    }
+
+    fn resolve(&mut self, _: Env, puzzle: String) -> Result<String> {
+        Ok(puzzle)
+    }
+}
+
+/// Generate a random puzzle with a unique solution, optionally of a specific
+/// difficulty such as "hard". The same seed generates the same puzzle, and a
+/// seed is taken from the current time if none is given.
+#[napi]
+pub fn generate(difficulty: Option<String>, seed: Option<i64>) -> AsyncTask<GenerateTask> {
+    let seed = seed.map_or_else(
+        || {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+        },
+        |seed| seed as u64,
+    );
+    AsyncTask::new(GenerateTask { difficulty, seed })
+}
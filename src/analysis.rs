@@ -0,0 +1,311 @@
+//! Analysis of puzzles and of grids filled in by players, such as where the
+//! solutions of an improper puzzle differ for setters refining their puzzles,
+//! where a player went wrong, or measures of hardness for research.
+
+use crate::logic::{cell_name, sees, Grid, Technique};
+use crate::rating::{self, Rating};
+use crate::{Options, Puzzle, Solution, Solver};
+use std::fmt;
+
+/// A cell of which the digit differs across the solutions of a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AmbiguousCell {
+    pub cell: (usize, usize),
+    /// The digits the cell takes in any of the solutions, in ascending order.
+    pub digits: Vec<u8>,
+}
+
+impl fmt::Display for AmbiguousCell {
+    /// Describe the cell along with its digits, such as `r1c2 (3/7)`.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let digits: Vec<String> = self.digits.iter().map(u8::to_string).collect();
+        write!(fmt, "{} ({})", cell_name(self.cell), digits.join("/"))
+    }
+}
+
+/// Find the cells of which the digit differs across the solutions of a
+/// puzzle, along with the digits they take, such that a setter can see where
+/// a clue would disambiguate the puzzle. Every digit of every empty cell is
+/// tried instead of enumerating the solutions, of which there may be too
+/// many. A puzzle with a unique solution has no ambiguous cells.
+pub fn ambiguous_cells(puzzle: &Puzzle) -> Result<Vec<AmbiguousCell>, &'static str> {
+    match Solver::count_solutions(puzzle, 2) {
+        0 => return Err("puzzle has no solution"),
+        1 => return Ok(Vec::new()),
+        _ => {}
+    }
+    let mut ambiguous = Vec::new();
+    for row in 0..9 {
+        for col in 0..9 {
+            if puzzle.cells[row][col] != 0 {
+                continue;
+            }
+            let digits: Vec<u8> = (1..=9)
+                .filter(|digit| {
+                    let mut filled = *puzzle;
+                    filled.cells[row][col] = *digit;
+                    Solver::count_solutions(&filled, 1) > 0
+                })
+                .collect();
+            if digits.len() > 1 {
+                ambiguous.push(AmbiguousCell {
+                    cell: (row, col),
+                    digits,
+                });
+            }
+        }
+    }
+    Ok(ambiguous)
+}
+
+/// Outcome of checking a grid filled in by a player against a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GridCheck {
+    /// Entries differing from the unique solution, in reading order.
+    pub mistakes: Vec<(usize, usize)>,
+    /// Cells of which the value also occurs in a peer, including givens.
+    pub conflicts: Vec<(usize, usize)>,
+}
+
+impl GridCheck {
+    /// The first entry differing from the solution in reading order, if any.
+    pub fn first_mistake(&self) -> Option<(usize, usize)> {
+        self.mistakes.first().copied()
+    }
+
+    /// Whether the entries agree with the solution so far.
+    pub fn is_correct(&self) -> bool {
+        self.mistakes.is_empty()
+    }
+}
+
+/// Check a possibly partial and possibly wrong grid filled in by a player
+/// against the unique solution of a puzzle, finding both the entries which
+/// differ from it and those which conflict with their peers. Entries can be
+/// wrong without conflicting with anything filled in so far. The grid must
+/// keep the givens of the puzzle.
+pub fn check_grid(puzzle: &Puzzle, grid: &Puzzle) -> Result<GridCheck, &'static str> {
+    let cells = || (0..9).flat_map(|row| (0..9).map(move |col| (row, col)));
+    if cells().any(|(row, col)| {
+        puzzle.cells[row][col] != 0 && grid.cells[row][col] != puzzle.cells[row][col]
+    }) {
+        return Err("grid changes a given of the puzzle");
+    }
+    if Solver::count_solutions(puzzle, 2) > 1 {
+        return Err("puzzle has multiple solutions");
+    }
+    let solution = Solver::solve_with_options(puzzle, &Options::default())?;
+    let value = |(row, col): (usize, usize)| grid.cells[row][col];
+    Ok(GridCheck {
+        mistakes: cells()
+            .filter(|cell| value(*cell) != 0 && value(*cell) != solution.cells()[cell.0][cell.1])
+            .collect(),
+        conflicts: cells()
+            .filter(|cell| {
+                value(*cell) != 0
+                    && cells().any(|peer| sees(*cell, peer) && value(peer) == value(*cell))
+            })
+            .collect(),
+    })
+}
+
+/// A value filled into a cell.
+pub type Assignment = ((usize, usize), u8);
+
+/// Fill a grid using naked and hidden singles for as long as they apply,
+/// returning whether that solves it.
+fn fill_singles(grid: &mut Grid) -> bool {
+    while let Some(step) = grid
+        .find(Technique::NakedSingle)
+        .or_else(|| grid.find(Technique::HiddenSingle))
+    {
+        if grid.apply(&step).is_err() {
+            return false;
+        }
+    }
+    grid.is_solved()
+}
+
+/// Find a smallest backdoor of a puzzle, being a set of cells which, once
+/// filled with the values of the unique solution, leave a puzzle solvable by
+/// naked and hidden singles alone. Its size is a common measure of hardness,
+/// being zero for puzzles which singles solve. Sets are tried by increasing
+/// size, so `None` is returned if none of at most the maximum size exists.
+/// The number of sets grows quickly with their size, so a maximum of three
+/// or more may take long for hard puzzles.
+pub fn backdoor(puzzle: &Puzzle, max_size: usize) -> Result<Option<Vec<Assignment>>, &'static str> {
+    if Solver::count_solutions(puzzle, 2) != 1 {
+        return Err("puzzle does not have a unique solution");
+    }
+    let solution = Solver::solve_with_options(puzzle, &Options::default())?;
+    // Cells filled by singles anyway are never part of a smallest backdoor.
+    let mut base = Grid::new(puzzle)?;
+    if fill_singles(&mut base) {
+        return Ok(Some(Vec::new()));
+    }
+    let cells: Vec<(usize, usize)> = (0..9)
+        .flat_map(|row| (0..9).map(move |col| (row, col)))
+        .filter(|cell| base.value(*cell).is_none())
+        .collect();
+    let mut chosen = Vec::new();
+    for size in 1..=max_size.min(cells.len()) {
+        if find_backdoor(&base, &cells, size, &solution, &mut chosen) {
+            return Ok(Some(chosen));
+        }
+    }
+    Ok(None)
+}
+
+/// Extend the chosen cells with cells of the remaining ones up to a size,
+/// returning whether a backdoor was found, which is then left chosen.
+fn find_backdoor(
+    base: &Grid,
+    cells: &[(usize, usize)],
+    size: usize,
+    solution: &Solution,
+    chosen: &mut Vec<Assignment>,
+) -> bool {
+    if chosen.len() == size {
+        let mut grid = base.clone();
+        return chosen
+            .iter()
+            .all(|(cell, value)| grid.place(*cell, *value).is_ok())
+            && fill_singles(&mut grid);
+    }
+    for (idx, cell) in cells.iter().enumerate() {
+        chosen.push((*cell, solution.cells()[cell.0][cell.1]));
+        if find_backdoor(base, &cells[idx + 1..], size, solution, chosen) {
+            return true;
+        }
+        chosen.pop();
+    }
+    false
+}
+
+/// Find the small unavoidable sets of a solution grid, being sets of cells
+/// of which the values can be rearranged into another valid grid. Every
+/// puzzle with this solution has a clue in each of them, which setters use
+/// to reason about where clues are needed. The sets are found by trying all
+/// rearrangements of the cells of any two or three digits, of which the sets
+/// of at most the maximum size are returned by increasing size. Sets which
+/// contain a smaller set are left out, as clues in the latter hit both.
+pub fn unavoidable_sets(
+    grid: &Puzzle,
+    max_size: usize,
+) -> Result<Vec<Vec<(usize, usize)>>, &'static str> {
+    Grid::new(grid)?;
+    if grid.clues() != 81 {
+        return Err("grid is not completely filled");
+    }
+    let mut sets: Vec<Vec<(usize, usize)>> = Vec::new();
+    for digits in 1..(1u16 << 9) {
+        if !(2..=3).contains(&digits.count_ones()) {
+            continue;
+        }
+        let cells: Vec<(usize, usize)> = (0..9)
+            .flat_map(|row| (0..9).map(move |col| (row, col)))
+            .filter(|(row, col)| digits & (1 << (grid.cells[*row][*col] - 1)) != 0)
+            .collect();
+        let mut filled = *grid;
+        for (row, col) in &cells {
+            filled.cells[*row][*col] = 0;
+        }
+        rearrange(&mut filled, &cells, digits, &mut |rearranged| {
+            let set: Vec<(usize, usize)> = cells
+                .iter()
+                .copied()
+                .filter(|(row, col)| rearranged.cells[*row][*col] != grid.cells[*row][*col])
+                .collect();
+            if !set.is_empty() && set.len() <= max_size {
+                sets.push(set);
+            }
+        });
+    }
+    sets.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    sets.dedup();
+    let mut minimal: Vec<Vec<(usize, usize)>> = Vec::new();
+    for set in sets {
+        if !minimal
+            .iter()
+            .any(|smaller| smaller.iter().all(|cell| set.contains(cell)))
+        {
+            minimal.push(set);
+        }
+    }
+    Ok(minimal)
+}
+
+/// Fill the given empty cells of a grid with the given digits in all valid
+/// ways, calling back with each completed grid.
+fn rearrange(
+    grid: &mut Puzzle,
+    cells: &[(usize, usize)],
+    digits: u16,
+    found: &mut impl FnMut(&Puzzle),
+) {
+    let Some((&(row, col), rest)) = cells.split_first() else {
+        found(grid);
+        return;
+    };
+    for digit in (1..=9).filter(|digit| digits & (1 << (digit - 1)) != 0) {
+        let conflicts = (0..9)
+            .flat_map(|r| (0..9).map(move |c| (r, c)))
+            .any(|peer| sees((row, col), peer) && grid.cells[peer.0][peer.1] == digit);
+        if !conflicts {
+            grid.cells[row][col] = digit;
+            rearrange(grid, rest, digits, found);
+            grid.cells[row][col] = 0;
+        }
+    }
+}
+
+/// Effect of removing a clue from a puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClueRemoval {
+    pub cell: (usize, usize),
+    pub value: u8,
+    /// Rating of the puzzle without the clue, if it still has a unique
+    /// solution such that the clue is redundant.
+    pub rating: Option<Rating>,
+    /// Change of the score when removing the clue, if it is redundant.
+    pub score_change: Option<i64>,
+}
+
+impl ClueRemoval {
+    /// Whether the puzzle keeps a unique solution without the clue.
+    pub fn is_redundant(&self) -> bool {
+        self.rating.is_some()
+    }
+}
+
+/// Find for each clue of a puzzle with a unique solution, in reading order,
+/// whether removing it preserves the uniqueness and how the difficulty then
+/// changes, such that setters can fine-tune their puzzles.
+pub fn clue_removals(puzzle: &Puzzle) -> Result<Vec<ClueRemoval>, &'static str> {
+    if Solver::count_solutions(puzzle, 2) != 1 {
+        return Err("puzzle does not have a unique solution");
+    }
+    let score = rating::rate(puzzle)?.score;
+    let mut removals = Vec::new();
+    for row in 0..9 {
+        for col in 0..9 {
+            let value = puzzle.cells[row][col];
+            if value == 0 {
+                continue;
+            }
+            let mut removed = *puzzle;
+            removed.cells[row][col] = 0;
+            let rating = match Solver::count_solutions(&removed, 2) {
+                1 => Some(rating::rate(&removed)?),
+                _ => None,
+            };
+            removals.push(ClueRemoval {
+                cell: (row, col),
+                value,
+                rating,
+                score_change: rating.map(|rating| rating.score as i64 - score as i64),
+            });
+        }
+    }
+    Ok(removals)
+}
@@ -0,0 +1,40 @@
+//! Wrappers running the solver on the blocking thread pool of tokio, such
+//! that async services can solve and generate puzzles without blocking
+//! their executor. They must be awaited within a tokio runtime.
+
+use crate::generate;
+use crate::random::Rng;
+use crate::rating::Difficulty;
+use crate::{Options, Puzzle, Solution, Solver};
+use tokio::task;
+
+/// Error of a wrapper when its task was cancelled or panicked, such as when
+/// the runtime shuts down.
+pub const TASK_FAILED: &str = "blocking task failed";
+
+/// Solve a puzzle like [`Solver::solve_with_options`] on the blocking pool.
+pub async fn solve_async(puzzle: Puzzle, options: Options) -> Result<Solution, &'static str> {
+    task::spawn_blocking(move || Solver::solve_with_options(&puzzle, &options))
+        .await
+        .map_err(|_| TASK_FAILED)?
+}
+
+/// Generate a random puzzle with a unique solution on the blocking pool,
+/// optionally of a specific difficulty, giving up after the given number of
+/// attempts like [`generate::generate_with_difficulty`]. The same seed
+/// generates the same puzzle.
+pub async fn generate_async(
+    difficulty: Option<Difficulty>,
+    attempts: usize,
+    seed: u64,
+) -> Result<Puzzle, &'static str> {
+    task::spawn_blocking(move || {
+        let mut rng = Rng::new(seed);
+        match difficulty {
+            None => Ok(generate::generate(&mut rng)),
+            Some(difficulty) => generate::generate_with_difficulty(&mut rng, difficulty, attempts),
+        }
+    })
+    .await
+    .map_err(|_| TASK_FAILED)?
+}
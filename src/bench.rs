@@ -0,0 +1,241 @@
+//! Benchmarking of the solver over corpora of puzzles, such as the well-known
+//! top1465 or 17-clue collections, with comparisons against saved baselines.
+
+use crate::{Options, Puzzle, Solver};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+/// The result of solving a single puzzle of a corpus.
+#[derive(Clone, Debug)]
+pub struct Measurement {
+    pub puzzle: Puzzle,
+    pub elapsed: Duration,
+    /// Number of brute-force fills, or `None` if the puzzle could not be solved.
+    pub brute_forces: Option<u8>,
+}
+
+/// The measurements of a benchmark run over a corpus, in corpus order.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub measurements: Vec<Measurement>,
+}
+
+/// A puzzle which got slower or needed more brute-force fills compared to
+/// the baseline.
+#[derive(Clone, Debug)]
+pub struct Regression {
+    pub puzzle: Puzzle,
+    pub baseline: BaselineEntry,
+    pub current: BaselineEntry,
+}
+
+/// The recorded performance of a single puzzle in a baseline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaselineEntry {
+    pub elapsed: Duration,
+    pub brute_forces: u8,
+}
+
+/// Previously saved measurements to compare a benchmark run against.
+#[derive(Clone, Debug, Default)]
+pub struct Baseline {
+    entries: HashMap<Puzzle, BaselineEntry>,
+}
+
+/// Read a corpus containing a puzzle on each line. Empty lines are skipped.
+pub fn load_corpus(reader: impl BufRead) -> io::Result<Vec<Puzzle>> {
+    let mut puzzles = Vec::new();
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let puzzle = line.parse().map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", idx + 1, err),
+            )
+        })?;
+        puzzles.push(puzzle);
+    }
+    Ok(puzzles)
+}
+
+/// Solve every puzzle of a corpus, measuring the time taken by each.
+pub fn run(puzzles: impl IntoIterator<Item = Puzzle>, options: &Options) -> Report {
+    let mut solver = Solver::new();
+    let measurements = puzzles
+        .into_iter()
+        .map(|puzzle| {
+            let start = Instant::now();
+            let result = solver.load(&puzzle).and_then(|_| solver.run(options));
+            Measurement {
+                puzzle,
+                elapsed: start.elapsed(),
+                brute_forces: result.ok().map(|solution| solution.brute_forces),
+            }
+        })
+        .collect();
+    Report { measurements }
+}
+
+impl Report {
+    /// Total time spent solving.
+    pub fn total(&self) -> Duration {
+        self.measurements
+            .iter()
+            .fold(Duration::ZERO, |total, m| total.saturating_add(m.elapsed))
+    }
+
+    /// Mean time spent per puzzle.
+    pub fn mean(&self) -> Duration {
+        match self.measurements.len() {
+            0 => Duration::ZERO,
+            n => {
+                let nanos = self.total().as_nanos() / n as u128;
+                Duration::new(
+                    (nanos / 1_000_000_000) as u64,
+                    (nanos % 1_000_000_000) as u32,
+                )
+            }
+        }
+    }
+
+    /// The measurement of the slowest puzzle.
+    pub fn slowest(&self) -> Option<&Measurement> {
+        self.measurements.iter().max_by_key(|m| m.elapsed)
+    }
+
+    /// The measurements of the `n` slowest puzzles, from slowest to fastest.
+    pub fn n_slowest(&self, n: usize) -> Vec<&Measurement> {
+        let mut measurements: Vec<&Measurement> = self.measurements.iter().collect();
+        measurements.sort_by_key(|m| Reverse(m.elapsed));
+        measurements.truncate(n);
+        measurements
+    }
+
+    /// The time spent per puzzle below which the given percentage (e.g. 95.0)
+    /// of the puzzles were solved, using the nearest-rank method.
+    pub fn percentile(&self, percentage: f64) -> Duration {
+        let mut elapsed: Vec<Duration> = self.measurements.iter().map(|m| m.elapsed).collect();
+        if elapsed.is_empty() {
+            return Duration::ZERO;
+        }
+        elapsed.sort();
+        let rank = (percentage / 100.0 * elapsed.len() as f64).ceil() as usize;
+        elapsed[rank.clamp(1, elapsed.len()) - 1]
+    }
+
+    /// Number of puzzles solved per second.
+    pub fn throughput(&self) -> f64 {
+        self.measurements.len() as f64 / self.total().as_secs_f64()
+    }
+
+    /// Total number of brute-force fills over all solved puzzles.
+    pub fn brute_forces(&self) -> u64 {
+        self.measurements
+            .iter()
+            .filter_map(|m| m.brute_forces)
+            .map(u64::from)
+            .sum()
+    }
+
+    /// Number of puzzles which could not be solved.
+    pub fn failures(&self) -> usize {
+        self.measurements
+            .iter()
+            .filter(|m| m.brute_forces.is_none())
+            .count()
+    }
+
+    /// Write the measurements of solved puzzles as a baseline, with on each
+    /// line the puzzle, elapsed nanoseconds and number of brute-force fills.
+    pub fn save_baseline(&self, mut writer: impl Write) -> io::Result<()> {
+        for m in &self.measurements {
+            if let Some(brute_forces) = m.brute_forces {
+                writeln!(
+                    writer,
+                    "{} {} {}",
+                    m.puzzle,
+                    m.elapsed.as_nanos(),
+                    brute_forces
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the puzzles which are slower than in the baseline by more than
+    /// the tolerated fraction (e.g. 0.1 for 10%), or which need more
+    /// brute-force fills. Puzzles absent from the baseline are ignored.
+    pub fn regressions(&self, baseline: &Baseline, tolerance: f64) -> Vec<Regression> {
+        self.measurements
+            .iter()
+            .filter_map(|m| {
+                let former = *baseline.entries.get(&m.puzzle)?;
+                let current = BaselineEntry {
+                    elapsed: m.elapsed,
+                    brute_forces: m.brute_forces?,
+                };
+                let slower = current.elapsed.as_secs_f64()
+                    > former.elapsed.as_secs_f64() * (1.0 + tolerance);
+                if slower || current.brute_forces > former.brute_forces {
+                    Some(Regression {
+                        puzzle: m.puzzle,
+                        baseline: former,
+                        current,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+impl Baseline {
+    /// Read a baseline as written by [`Report::save_baseline`].
+    pub fn load(reader: impl BufRead) -> io::Result<Self> {
+        let invalid = |idx: usize| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: invalid baseline entry", idx + 1),
+            )
+        };
+
+        let mut entries = HashMap::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut fields = line.split_whitespace();
+            let (Some(puzzle), Some(elapsed), Some(brute_forces), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid(idx));
+            };
+            let puzzle = puzzle.parse().map_err(|_| invalid(idx))?;
+            let elapsed = elapsed.parse().map_err(|_| invalid(idx))?;
+            let brute_forces = brute_forces.parse().map_err(|_| invalid(idx))?;
+            entries.insert(
+                puzzle,
+                BaselineEntry {
+                    elapsed: Duration::from_nanos(elapsed),
+                    brute_forces,
+                },
+            );
+        }
+        Ok(Self { entries })
+    }
+
+    /// Number of puzzles in the baseline.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the baseline contains no puzzles.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,194 @@
+//! Compact binary encoding of puzzles and solutions, for storing massive
+//! collections. Each record starts with a header byte holding the version of
+//! the format in its high four bits and the encoding in its low four bits,
+//! followed by the values of the cells as four-bit nibbles, high nibble
+//! first, padded with a zero nibble to a whole number of bytes.
+//!
+//! Packed records hold the 81 values with zeroes for empty cells, such that
+//! every record is [`PACKED_LEN`] bytes long and a collection of them can be
+//! memory-mapped and indexed directly. Run-length records instead write each
+//! run of empty cells as a zero nibble followed by the length of the run
+//! minus one as a varint, in groups of three bits with the fourth bit set on
+//! all but the last group. The encoder falls back to packing if that would
+//! not be shorter, so no record is longer than [`PACKED_LEN`] bytes.
+
+use crate::Puzzle;
+
+/// Version of the format, which is raised whenever it changes.
+pub const VERSION: u8 = 1;
+
+/// Length of a packed record in bytes, which is the maximum of any record.
+pub const PACKED_LEN: usize = 1 + 41;
+
+/// Encoding of the values of a record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// A nibble for each cell, such that all records have the same length.
+    Packed,
+    /// Runs of empty cells are shortened, which suits puzzles with few clues.
+    RunLength,
+}
+
+impl Encoding {
+    fn code(self) -> u8 {
+        match self {
+            Encoding::Packed => 0,
+            Encoding::RunLength => 1,
+        }
+    }
+}
+
+/// Nibbles written into a record after its header.
+struct Nibbles {
+    bytes: [u8; PACKED_LEN],
+    len: usize,
+}
+
+impl Nibbles {
+    /// Append a nibble, returning whether it still fits.
+    fn push(&mut self, nibble: u8) -> bool {
+        let idx = 1 + self.len / 2;
+        if idx >= PACKED_LEN {
+            return false;
+        }
+        match self.len % 2 {
+            0 => self.bytes[idx] = nibble << 4,
+            _ => self.bytes[idx] |= nibble,
+        }
+        self.len += 1;
+        true
+    }
+
+    /// Number of bytes of the record, including the header and padding.
+    fn record_len(&self) -> usize {
+        1 + self.len.div_ceil(2)
+    }
+}
+
+/// Encode a puzzle into a record, returning its length in bytes, which is at
+/// most [`PACKED_LEN`].
+pub fn encode(puzzle: &Puzzle, encoding: Encoding, out: &mut [u8; PACKED_LEN]) -> usize {
+    if encoding == Encoding::RunLength {
+        if let Some(nibbles) = run_length(puzzle) {
+            *out = nibbles.bytes;
+            out[0] = VERSION << 4 | Encoding::RunLength.code();
+            return nibbles.record_len();
+        }
+    }
+    let mut nibbles = Nibbles {
+        bytes: [0; PACKED_LEN],
+        len: 0,
+    };
+    for value in puzzle.cells.iter().flatten() {
+        nibbles.push(*value);
+    }
+    *out = nibbles.bytes;
+    out[0] = VERSION << 4 | Encoding::Packed.code();
+    nibbles.record_len()
+}
+
+/// Encode the values of a puzzle with runs of empty cells shortened, if that
+/// is shorter than packing them.
+fn run_length(puzzle: &Puzzle) -> Option<Nibbles> {
+    let mut nibbles = Nibbles {
+        bytes: [0; PACKED_LEN],
+        len: 0,
+    };
+    let values: [u8; 81] = core::array::from_fn(|idx| puzzle.cells[idx / 9][idx % 9]);
+    let mut idx = 0;
+    while idx < 81 {
+        if values[idx] != 0 {
+            if !nibbles.push(values[idx]) {
+                return None;
+            }
+            idx += 1;
+            continue;
+        }
+        let run = values[idx..]
+            .iter()
+            .take_while(|value| **value == 0)
+            .count();
+        idx += run;
+        let mut rest = run - 1;
+        if !nibbles.push(0) {
+            return None;
+        }
+        loop {
+            let group = (rest & 0b111) as u8;
+            rest >>= 3;
+            let more = rest != 0;
+            if !nibbles.push(group | (more as u8) << 3) {
+                return None;
+            }
+            if !more {
+                break;
+            }
+        }
+    }
+    match nibbles.record_len() < PACKED_LEN {
+        true => Some(nibbles),
+        false => None,
+    }
+}
+
+/// Decode the record at the start of the bytes, returning the puzzle along
+/// with the length of the record, such that a sequence of records can be
+/// decoded one after the other.
+pub fn decode(bytes: &[u8]) -> Result<(Puzzle, usize), &'static str> {
+    let header = *bytes.first().ok_or("binary record is empty")?;
+    if header >> 4 != VERSION {
+        return Err("unsupported version of binary record");
+    }
+    let mut len = 0;
+    let mut nibble = || {
+        let byte = *bytes.get(1 + len / 2).ok_or("binary record is truncated")?;
+        let nibble = match len % 2 {
+            0 => byte >> 4,
+            _ => byte & 0xf,
+        };
+        len += 1;
+        Ok::<_, &str>(nibble)
+    };
+    let mut values = [0; 81];
+    let mut idx = 0;
+    match header & 0xf {
+        0 => {
+            for value in values.iter_mut() {
+                *value = nibble()?;
+            }
+        }
+        1 => {
+            while idx < 81 {
+                let value = nibble()?;
+                if value != 0 {
+                    values[idx] = value;
+                    idx += 1;
+                    continue;
+                }
+                let mut run = 0;
+                for shift in (0..).step_by(3) {
+                    let group = nibble()? as usize;
+                    if shift > 6 {
+                        return Err("invalid run of empty cells in binary record");
+                    }
+                    run |= (group & 0b111) << shift;
+                    if group & 0b1000 == 0 {
+                        break;
+                    }
+                }
+                idx += run + 1;
+            }
+            if idx > 81 {
+                return Err("invalid run of empty cells in binary record");
+            }
+        }
+        _ => return Err("unknown encoding of binary record"),
+    }
+    if values.iter().any(|value| *value > 9) {
+        return Err("invalid value in binary record");
+    }
+    let puzzle = Puzzle {
+        cells: core::array::from_fn(|row| core::array::from_fn(|col| values[9 * row + col])),
+    };
+    Ok((puzzle, 1 + len.div_ceil(2)))
+}
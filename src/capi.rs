@@ -0,0 +1,231 @@
+//! C-compatible interface, such that C, C++ and Swift applications can embed
+//! the solver. Puzzles are passed as NUL-terminated strings of 81 values, and
+//! results are written to buffers provided by the caller. Every function
+//! returns a status code, of which `sudoku_status_message` describes the
+//! meaning. Enumerations are taken from C as plain integers, which are
+//! checked, as out-of-range values of Rust enumerations are undefined
+//! behavior. The header `include/sudoku_solver.h` is generated from this
+//! module by cbindgen.
+
+use crate::generate;
+use crate::random::Rng;
+use crate::rating::{self, Difficulty};
+use crate::{Options, Puzzle, Solver};
+use core::ffi::{c_char, CStr};
+
+/// Maximum number of puzzles generated when looking for one of a requested
+/// difficulty.
+const GENERATE_ATTEMPTS: usize = 1000;
+
+/// Size of a buffer holding a puzzle or solution, including the terminating
+/// NUL character.
+pub const SUDOKU_BUFFER_SIZE: usize = 82;
+
+/// Outcome of a call.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SudokuStatus {
+    /// The call succeeded.
+    SudokuOk = 0,
+    /// A pointer was null, a string was not valid UTF-8, or a difficulty was
+    /// out of range.
+    SudokuInvalidArgument = 1,
+    /// The buffer is smaller than `SUDOKU_BUFFER_SIZE`.
+    SudokuBufferTooSmall = 2,
+    /// The puzzle could not be parsed.
+    SudokuInvalidPuzzle = 3,
+    /// The puzzle has no solution.
+    SudokuUnsolvable = 4,
+    /// No puzzle of the requested difficulty was generated.
+    SudokuNotGenerated = 5,
+}
+
+impl SudokuStatus {
+    /// All statuses, in order of their codes.
+    const ALL: [SudokuStatus; 6] = [
+        SudokuStatus::SudokuOk,
+        SudokuStatus::SudokuInvalidArgument,
+        SudokuStatus::SudokuBufferTooSmall,
+        SudokuStatus::SudokuInvalidPuzzle,
+        SudokuStatus::SudokuUnsolvable,
+        SudokuStatus::SudokuNotGenerated,
+    ];
+}
+
+/// Difficulty tier of a puzzle.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SudokuDifficulty {
+    SudokuEasy = 0,
+    SudokuMedium = 1,
+    SudokuHard = 2,
+    SudokuExpert = 3,
+    SudokuExtreme = 4,
+}
+
+impl From<Difficulty> for SudokuDifficulty {
+    fn from(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Easy => SudokuDifficulty::SudokuEasy,
+            Difficulty::Medium => SudokuDifficulty::SudokuMedium,
+            Difficulty::Hard => SudokuDifficulty::SudokuHard,
+            Difficulty::Expert => SudokuDifficulty::SudokuExpert,
+            Difficulty::Extreme => SudokuDifficulty::SudokuExtreme,
+        }
+    }
+}
+
+/// Difficulty rating of a puzzle.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SudokuRating {
+    pub difficulty: SudokuDifficulty,
+    /// Sum of the scores of the techniques of all steps.
+    pub score: u32,
+    /// Number of steps taken to solve the puzzle.
+    pub steps: usize,
+}
+
+/// Parse a puzzle from a C string.
+///
+/// # Safety
+///
+/// The pointer must be null or point to a NUL-terminated string.
+unsafe fn parse(puzzle: *const c_char) -> Result<Puzzle, SudokuStatus> {
+    if puzzle.is_null() {
+        return Err(SudokuStatus::SudokuInvalidArgument);
+    }
+    let puzzle = unsafe { CStr::from_ptr(puzzle) }
+        .to_str()
+        .map_err(|_| SudokuStatus::SudokuInvalidArgument)?;
+    puzzle
+        .parse()
+        .map_err(|_| SudokuStatus::SudokuInvalidPuzzle)
+}
+
+/// Write a grid as a NUL-terminated string of 81 values into a buffer.
+///
+/// # Safety
+///
+/// The pointer must be null or point to a buffer of `len` bytes.
+unsafe fn write(grid: &str, buffer: *mut c_char, len: usize) -> SudokuStatus {
+    if buffer.is_null() {
+        return SudokuStatus::SudokuInvalidArgument;
+    }
+    if len < SUDOKU_BUFFER_SIZE {
+        return SudokuStatus::SudokuBufferTooSmall;
+    }
+    let buffer = unsafe { core::slice::from_raw_parts_mut(buffer.cast::<u8>(), len) };
+    buffer[..grid.len()].copy_from_slice(grid.as_bytes());
+    buffer[grid.len()] = 0;
+    SudokuStatus::SudokuOk
+}
+
+/// Solve a puzzle, writing its solution into a buffer of `len` bytes, which
+/// must be at least `SUDOKU_BUFFER_SIZE`.
+///
+/// # Safety
+///
+/// `puzzle` must point to a NUL-terminated string, and `solution` must point
+/// to a writable buffer of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_solve(
+    puzzle: *const c_char,
+    solution: *mut c_char,
+    len: usize,
+) -> SudokuStatus {
+    let puzzle = match unsafe { parse(puzzle) } {
+        Ok(puzzle) => puzzle,
+        Err(status) => return status,
+    };
+    match Solver::solve_with_options(&puzzle, &Options::default()) {
+        Ok(result) => unsafe { write(&result.row_representation(), solution, len) },
+        Err(_) => SudokuStatus::SudokuUnsolvable,
+    }
+}
+
+/// Rate the difficulty of a puzzle.
+///
+/// # Safety
+///
+/// `puzzle` must point to a NUL-terminated string, and `rating` must point to
+/// a writable `SudokuRating`.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_rate(
+    puzzle: *const c_char,
+    rating: *mut SudokuRating,
+) -> SudokuStatus {
+    let puzzle = match unsafe { parse(puzzle) } {
+        Ok(puzzle) => puzzle,
+        Err(status) => return status,
+    };
+    if rating.is_null() {
+        return SudokuStatus::SudokuInvalidArgument;
+    }
+    match rating::rate(&puzzle) {
+        Ok(result) => {
+            unsafe {
+                rating.write(SudokuRating {
+                    difficulty: result.difficulty.into(),
+                    score: result.score,
+                    steps: result.steps,
+                })
+            };
+            SudokuStatus::SudokuOk
+        }
+        Err(_) => SudokuStatus::SudokuUnsolvable,
+    }
+}
+
+/// Generate a random puzzle with a unique solution, writing it into a buffer
+/// of `len` bytes, which must be at least `SUDOKU_BUFFER_SIZE`. The same seed
+/// generates the same puzzle. If `difficulty` is not null, a puzzle of the
+/// `SudokuDifficulty` it points to is generated.
+///
+/// # Safety
+///
+/// `difficulty` must be null or point to a `uint32_t`, and `puzzle` must
+/// point to a writable buffer of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_generate(
+    seed: u64,
+    difficulty: *const u32,
+    puzzle: *mut c_char,
+    len: usize,
+) -> SudokuStatus {
+    let mut rng = Rng::new(seed);
+    let generated = match unsafe { difficulty.as_ref() } {
+        None => generate::generate(&mut rng),
+        Some(&difficulty) => {
+            let Some(&difficulty) = Difficulty::ALL.get(difficulty as usize) else {
+                return SudokuStatus::SudokuInvalidArgument;
+            };
+            match generate::generate_with_difficulty(&mut rng, difficulty, GENERATE_ATTEMPTS) {
+                Ok(generated) => generated,
+                Err(_) => return SudokuStatus::SudokuNotGenerated,
+            }
+        }
+    };
+    unsafe { write(&generated.to_string(), puzzle, len) }
+}
+
+/// Describe a `SudokuStatus` code, returning a static NUL-terminated string,
+/// which tells that the code is unknown if it is out of range.
+#[no_mangle]
+pub extern "C" fn sudoku_status_message(status: u32) -> *const c_char {
+    let status = SudokuStatus::ALL
+        .into_iter()
+        .find(|known| *known as u32 == status);
+    let message = match status {
+        Some(SudokuStatus::SudokuOk) => c"success",
+        Some(SudokuStatus::SudokuInvalidArgument) => {
+            c"null pointer, invalid UTF-8 string or unknown difficulty"
+        }
+        Some(SudokuStatus::SudokuBufferTooSmall) => c"buffer is too small",
+        Some(SudokuStatus::SudokuInvalidPuzzle) => c"puzzle could not be parsed",
+        Some(SudokuStatus::SudokuUnsolvable) => c"puzzle has no solution",
+        Some(SudokuStatus::SudokuNotGenerated) => c"no puzzle of the difficulty was generated",
+        None => c"unknown status",
+    };
+    message.as_ptr()
+}
@@ -0,0 +1,136 @@
+/// Set of cells in a sudoku grid, stored as a bitmask in which bit
+/// `9 * row + col` is set if the cell at `(row, col)` is in the set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct CellSet(u128);
+
+/// The cells sharing a row, column or square with each cell, excluding the
+/// cell itself.
+pub(crate) static PEERS: [CellSet; 81] = peers();
+
+/// The cells of each house: the nine rows, followed by the nine columns and
+/// the nine squares in row-major order.
+pub(crate) static HOUSES: [CellSet; 27] = houses();
+
+impl CellSet {
+    /// The set containing no cells.
+    pub(crate) const EMPTY: Self = Self(0);
+
+    /// The set containing all cells of the grid.
+    pub(crate) const ALL: Self = Self((1 << 81) - 1);
+
+    /// Whether the set contains no cells.
+    pub(crate) fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Number of cells in the set.
+    pub(crate) fn len(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Whether the set contains a cell.
+    #[cfg(feature = "rater")]
+    pub(crate) fn contains(self, coords: (usize, usize)) -> bool {
+        self.0 & 1 << (9 * coords.0 + coords.1) != 0
+    }
+
+    /// Add a cell to the set.
+    pub(crate) fn insert(&mut self, coords: (usize, usize)) {
+        self.0 |= 1 << (9 * coords.0 + coords.1);
+    }
+
+    /// Remove a cell from the set.
+    pub(crate) fn remove(&mut self, coords: (usize, usize)) {
+        self.0 &= !(1 << (9 * coords.0 + coords.1));
+    }
+
+    /// The first cell of the set in row-major order, if any.
+    pub(crate) fn first(self) -> Option<(usize, usize)> {
+        if self.is_empty() {
+            None
+        } else {
+            let idx = self.0.trailing_zeros() as usize;
+            Some((idx / 9, idx % 9))
+        }
+    }
+
+    /// Iterate over the cells in the set in row-major order.
+    pub(crate) fn iter(self) -> impl Iterator<Item = (usize, usize)> {
+        let mut cells = self;
+        core::iter::from_fn(move || {
+            let coords = cells.first()?;
+            cells.0 &= cells.0 - 1;
+            Some(coords)
+        })
+    }
+
+    /// The cells of the set which are not in the other set.
+    #[cfg(feature = "rater")]
+    pub(crate) fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// The cells contained in both sets.
+    pub(crate) fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+}
+
+const fn rows() -> [CellSet; 9] {
+    let mut rows = [CellSet(0); 9];
+    let mut idx = 0;
+    while idx < 81 {
+        rows[idx / 9].0 |= 1 << idx;
+        idx += 1;
+    }
+    rows
+}
+
+const fn columns() -> [CellSet; 9] {
+    let mut columns = [CellSet(0); 9];
+    let mut idx = 0;
+    while idx < 81 {
+        columns[idx % 9].0 |= 1 << idx;
+        idx += 1;
+    }
+    columns
+}
+
+const fn squares() -> [[CellSet; 3]; 3] {
+    let mut squares = [[CellSet(0); 3]; 3];
+    let mut idx = 0;
+    while idx < 81 {
+        squares[idx / 27][idx % 9 / 3].0 |= 1 << idx;
+        idx += 1;
+    }
+    squares
+}
+
+const fn houses() -> [CellSet; 27] {
+    let rows = rows();
+    let columns = columns();
+    let squares = squares();
+    let mut houses = [CellSet(0); 27];
+    let mut idx = 0;
+    while idx < 9 {
+        houses[idx] = rows[idx];
+        houses[9 + idx] = columns[idx];
+        houses[18 + idx] = squares[idx / 3][idx % 3];
+        idx += 1;
+    }
+    houses
+}
+
+const fn peers() -> [CellSet; 81] {
+    let rows = rows();
+    let columns = columns();
+    let squares = squares();
+    let mut peers = [CellSet(0); 81];
+    let mut idx = 0;
+    while idx < 81 {
+        let (row, col) = (idx / 9, idx % 9);
+        peers[idx].0 = (rows[row].0 | columns[col].0 | squares[row / 3][col / 3].0) & !(1 << idx);
+        idx += 1;
+    }
+    peers
+}
@@ -0,0 +1,212 @@
+//! Command line interface, consisting of a subcommand for each task.
+
+mod bench;
+mod calibrate;
+mod check;
+mod checkpoint;
+mod color;
+mod completions;
+mod config;
+mod convert;
+mod count;
+mod crosscheck;
+#[cfg(feature = "sqlite")]
+mod database;
+mod dedup;
+mod filter;
+mod format;
+mod generate;
+mod hodoku;
+mod input;
+mod jobs;
+mod lessons;
+#[cfg(feature = "serve")]
+mod limits;
+#[cfg(feature = "mmap")]
+mod mapped;
+#[cfg(feature = "serve")]
+mod metrics;
+mod progress;
+#[cfg(feature = "serve")]
+mod queue;
+mod rate;
+mod record;
+mod render;
+mod repl;
+mod replay;
+mod report;
+mod rules;
+#[cfg(feature = "serve")]
+mod serve;
+#[cfg(feature = "serve")]
+mod socket;
+mod solve;
+mod split;
+mod stats;
+mod streams;
+#[cfg(feature = "arrow")]
+mod table;
+mod transform;
+#[cfg(feature = "tui")]
+mod tui;
+mod usage;
+mod validate;
+mod verbosity;
+mod worksheet;
+
+use clap::{ArgAction, Parser, Subcommand};
+use std::{env, fmt, io};
+
+/// Exit status when some puzzles failed, while the others were processed.
+pub const EXIT_FAILURES: i32 = 1;
+
+/// Exit status when processing could not continue at all.
+pub const EXIT_FATAL: i32 = 2;
+
+/// Sudoku solver which uses logic as much as possible, and only relies on
+/// brute-force techniques when absolutely necessary.
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Compress written files and standard output, instead of detecting the
+    /// compression from the extension of files (.gz or .zst)
+    #[arg(long, global = true, value_enum)]
+    compress: Option<streams::Compression>,
+    /// Overwrite existing output files
+    #[arg(long, global = true, conflicts_with = "append")]
+    force: bool,
+    /// Append to existing output files instead of overwriting them
+    #[arg(long, global = true)]
+    append: bool,
+    /// Only report failures, without the summary and progress bar
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Show the solve time and number of guesses of each puzzle, or also the
+    /// steps taken to solve it when given twice
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    Solve(solve::Args),
+    Rate(rate::Args),
+    Calibrate(calibrate::Args),
+    Validate(validate::Args),
+    Count(count::Args),
+    Transform(transform::Args),
+    Convert(convert::Args),
+    Dedup(dedup::Args),
+    Generate(generate::Args),
+    Stats(stats::Args),
+    Bench(bench::Args),
+    #[cfg(feature = "serve")]
+    Serve(serve::Args),
+    Check(check::Args),
+    Crosscheck(crosscheck::Args),
+    Hodoku(hodoku::Args),
+    Filter(filter::Args),
+    Split(split::Args),
+    Lessons(lessons::Args),
+    Worksheet(worksheet::Args),
+    Replay(replay::Args),
+    Record(record::Args),
+    Repl(repl::Args),
+    Render(render::Args),
+    #[cfg(feature = "tui")]
+    Tui(tui::Args),
+    #[command(hide = true)]
+    Completions(completions::Args),
+}
+
+/// Error ending a subcommand.
+#[derive(Debug)]
+pub enum Error {
+    /// Some puzzles failed, each of which has already been reported, while
+    /// the others were processed.
+    Failures(String),
+    /// Processing could not continue at all.
+    Fatal(io::Error),
+}
+
+impl Error {
+    /// The exit status of the process corresponding to the error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Failures(_) => EXIT_FAILURES,
+            Error::Fatal(_) => EXIT_FATAL,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Failures(summary) => write!(fmt, "{}", summary),
+            Error::Fatal(err) => write!(fmt, "{}", err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Fatal(err)
+    }
+}
+
+/// Result of a subcommand.
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+impl Cli {
+    /// Parse the command line arguments, completed with the defaults from the
+    /// configuration file.
+    pub fn parse_with_config() -> Result<Self> {
+        let args = config::apply(env::args_os().collect())?;
+        Ok(Self::parse_from(args))
+    }
+
+    /// Run the requested subcommand.
+    pub fn run(self) -> Result {
+        if let Some(compression) = self.compress {
+            streams::set_output_compression(compression);
+        }
+        streams::remove_temporary_files_on_interrupt();
+        verbosity::set(self.quiet, self.verbose);
+        if self.force {
+            streams::set_output_mode(streams::OutputMode::Overwrite);
+        } else if self.append {
+            streams::set_output_mode(streams::OutputMode::Append);
+        }
+        match self.command {
+            Command::Solve(args) => solve::run(args),
+            Command::Rate(args) => rate::run(args),
+            Command::Calibrate(args) => calibrate::run(args),
+            Command::Validate(args) => validate::run(args),
+            Command::Count(args) => count::run(args),
+            Command::Transform(args) => transform::run(args),
+            Command::Convert(args) => convert::run(args),
+            Command::Dedup(args) => dedup::run(args),
+            Command::Generate(args) => generate::run(args),
+            Command::Stats(args) => stats::run(args),
+            Command::Bench(args) => bench::run(args),
+            #[cfg(feature = "serve")]
+            Command::Serve(args) => serve::run(args),
+            Command::Check(args) => check::run(args),
+            Command::Crosscheck(args) => crosscheck::run(args),
+            Command::Hodoku(args) => hodoku::run(args),
+            Command::Filter(args) => filter::run(args),
+            Command::Split(args) => split::run(args),
+            Command::Lessons(args) => lessons::run(args),
+            Command::Worksheet(args) => worksheet::run(args),
+            Command::Replay(args) => replay::run(args),
+            Command::Record(args) => record::run(args),
+            Command::Repl(args) => repl::run(args),
+            Command::Render(args) => render::run(args),
+            #[cfg(feature = "tui")]
+            Command::Tui(args) => tui::run(args),
+            Command::Completions(args) => completions::run(args),
+        }
+    }
+}
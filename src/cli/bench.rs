@@ -0,0 +1,103 @@
+use super::input::{Failures, Inputs};
+use super::{streams, Error, Result};
+use clap::Parser;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use sudoku_solver::bench::{self, Baseline};
+use sudoku_solver::Options;
+
+/// Benchmark the solver on a corpus of puzzles, reporting the total runtime,
+/// throughput and per-puzzle latency percentiles.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// List this many of the slowest puzzles
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    slowest: usize,
+    /// Compare against a baseline, failing if any puzzle regressed
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+    /// Fraction by which a puzzle may be slower than in the baseline
+    #[arg(long, default_value_t = 0.1, requires = "baseline")]
+    tolerance: f64,
+    /// Save the measurements as a baseline for later comparisons
+    #[arg(long, value_name = "FILE")]
+    save_baseline: Option<PathBuf>,
+    /// Explore brute-force branches in parallel
+    #[cfg(feature = "parallel")]
+    #[arg(long)]
+    parallel: bool,
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let mut failures = Failures::default();
+    let mut puzzles = Vec::new();
+    for line in files.lines() {
+        let line = line?;
+        match line.puzzle() {
+            Ok(puzzle) => puzzles.push(puzzle),
+            Err(err) => failures.report(&line.location, err),
+        }
+    }
+    let options = Options {
+        #[cfg(feature = "parallel")]
+        parallel: args.parallel,
+        ..Default::default()
+    };
+    let report = bench::run(puzzles, &options);
+
+    println!("Puzzles: {}", report.measurements.len());
+    println!("Failures: {}", report.failures());
+    println!("Total time: {:?}", report.total());
+    println!("Puzzles per second: {:.1}", report.throughput());
+    println!("Mean: {:?}", report.mean());
+    println!("p50: {:?}", report.percentile(50.0));
+    println!("p95: {:?}", report.percentile(95.0));
+    println!("p99: {:?}", report.percentile(99.0));
+    println!("Brute-force fills: {}", report.brute_forces());
+
+    if args.slowest > 0 {
+        println!();
+        println!("Slowest puzzles:");
+        for m in report.n_slowest(args.slowest) {
+            println!("  {} {:?}", m.puzzle, m.elapsed);
+        }
+    }
+
+    if let Some(path) = &args.save_baseline {
+        let mut writer = streams::create_output(Some(path))?;
+        report.save_baseline(&mut writer)?;
+        writer.finish()?;
+    }
+
+    if let Some(path) = &args.baseline {
+        let baseline = Baseline::load(BufReader::new(File::open(path)?))?;
+        let regressions = report.regressions(&baseline, args.tolerance);
+        println!();
+        println!(
+            "Regressions against baseline of {} puzzles: {}",
+            baseline.len(),
+            regressions.len()
+        );
+        for r in &regressions {
+            println!(
+                "  {} {:?} -> {:?}, {} -> {} fills",
+                r.puzzle,
+                r.baseline.elapsed,
+                r.current.elapsed,
+                r.baseline.brute_forces,
+                r.current.brute_forces
+            );
+        }
+        if !regressions.is_empty() {
+            return Err(Error::Failures(format!(
+                "{} puzzles regressed",
+                regressions.len()
+            )));
+        }
+    }
+    failures.finish()
+}
@@ -0,0 +1,262 @@
+//! Calibration of the rating to a difficulty scale of puzzles which people
+//! have labeled. Calibrations are JSON files holding the weight of each
+//! technique and the levels of the scale from easiest to hardest, along with
+//! the lowest score of each level:
+//!
+//! ```json
+//! {
+//!   "weights": {"Naked Single": 4, "Hidden Single": 14, "X-Wing": 280},
+//!   "levels": [
+//!     {"label": "gentle", "min_score": 0},
+//!     {"label": "tough", "min_score": 900}
+//!   ]
+//! }
+//! ```
+//!
+//! Techniques without a weight keep their usual score.
+
+use super::input::{self, Failures, Inputs};
+use super::jobs::{self, Jobs};
+use super::progress::Progress;
+use super::{streams, verbosity, Error, Result};
+use clap::Parser;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::io::{self, prelude::*};
+use std::path::{Path, PathBuf};
+use std::{fs, iter};
+use sudoku_solver::logic::{self, Step, Technique};
+use sudoku_solver::rating::{Calibration, Rating};
+
+/// Fit the rating to puzzles which people have labeled with their
+/// difficulty, such as those of an existing app, writing a calibration which
+/// `rate` applies using --calibration. Each line holds a label followed by a
+/// comma or tab and a puzzle, as in a CSV file of two columns, of which a
+/// header is skipped.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// File to write the calibration to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Labels of the levels of the scale from easiest to hardest, separated
+    /// by commas [default: the labels ordered by the median score of their
+    /// puzzles]
+    #[arg(long, value_name = "LABELS", value_delimiter = ',')]
+    scale: Vec<String>,
+    #[command(flatten)]
+    jobs: Jobs,
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let mut writer = streams::create_output(args.output.as_deref())?;
+
+    let mut samples: Vec<(Vec<Step>, String)> = Vec::new();
+    let mut num_labeled = Vec::new();
+    let mut failures = Failures::default();
+
+    let mut progress = Progress::new(&files);
+    jobs::map_ordered(
+        args.jobs.count(),
+        files.lines(),
+        || (),
+        |_, line| {
+            // A header is recognized by the first line not holding a puzzle.
+            let rated = match line.puzzle() {
+                Err(_) if line.location.line == 1 => None,
+                puzzle => Some(puzzle.and_then(|puzzle| {
+                    let label = line
+                        .location
+                        .label()
+                        .filter(|label| !label.is_empty())
+                        .ok_or("missing difficulty label")?;
+                    Ok((logic::solve(&puzzle)?, label.to_string()))
+                })),
+            };
+            (line.location, rated)
+        },
+        |(location, rated)| {
+            progress.inc();
+            let on_scale = |label: &String| args.scale.is_empty() || args.scale.contains(label);
+            match rated {
+                None => {}
+                Some(Ok((_, label))) if !on_scale(&label) => {
+                    failures.report(&location, format!("label {} is not on the scale", label));
+                }
+                Some(Ok(sample)) => {
+                    input::tally(&mut num_labeled, location.file);
+                    samples.push(sample);
+                }
+                Some(Err(err)) => failures.report(&location, err),
+            }
+            Ok(())
+        },
+    )?;
+    progress.finish();
+    if samples.is_empty() {
+        return Err(Error::Fatal(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no labeled puzzles to calibrate with",
+        )));
+    }
+
+    let labels = match args.scale.is_empty() {
+        true => scale(&samples),
+        false => args.scale.clone(),
+    };
+    if let Some(label) = labels
+        .iter()
+        .find(|label| samples.iter().all(|(_, other)| other != *label))
+    {
+        return Err(Error::Fatal(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("no puzzles are labeled {}, which is on the scale", label),
+        )));
+    }
+    let samples: Vec<(Vec<Step>, usize)> = samples
+        .into_iter()
+        .map(|(steps, label)| {
+            let level = labels.iter().position(|other| *other == label);
+            (steps, level.unwrap_or_default())
+        })
+        .collect();
+    let calibration = Calibration::fit(labels, &samples)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    serde_json::to_writer_pretty(&mut writer, &to_json(&calibration)).map_err(io::Error::from)?;
+    writeln!(writer)?;
+    writer.finish()?;
+
+    if verbosity::summary() {
+        files.summarize(&num_labeled, "labeled", failures.per_file(), "failed");
+        let levels = calibration.labels().len();
+        let mut labeled = vec![0; levels];
+        let mut agreeing = vec![0; levels];
+        let mut within_one = 0;
+        for (steps, label) in &samples {
+            let level = calibration.level(calibration.score(steps));
+            labeled[*label] += 1;
+            if level == *label {
+                agreeing[*label] += 1;
+            }
+            if level.abs_diff(*label) <= 1 {
+                within_one += 1;
+            }
+        }
+        let min_scores = iter::once(&0).chain(calibration.thresholds());
+        let scale = calibration.labels().iter().zip(min_scores);
+        for (level, (label, min_score)) in scale.enumerate() {
+            eprintln!(
+                "{}: from score {}, {} puzzles, {} agreeing",
+                label, min_score, labeled[level], agreeing[level]
+            );
+        }
+        let share = |count: usize| 100.0 * count as f64 / samples.len() as f64;
+        let agreeing = agreeing.iter().sum();
+        eprintln!("Agreement: {} ({:.1}%)", agreeing, share(agreeing));
+        eprintln!(
+            "Within one level: {} ({:.1}%)",
+            within_one,
+            share(within_one)
+        );
+        eprintln!("Failed: {}", failures.count());
+    }
+    failures.finish()
+}
+
+/// Labels of the samples ordered by the median score of their puzzles, from
+/// easiest to hardest.
+fn scale(samples: &[(Vec<Step>, String)]) -> Vec<String> {
+    let mut scores: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+    for (steps, label) in samples {
+        let score = Rating::from_steps(steps).score;
+        scores.entry(label).or_default().push(score);
+    }
+    let mut medians: Vec<(u32, &str)> = scores
+        .into_iter()
+        .map(|(label, mut scores)| {
+            scores.sort_unstable();
+            (scores[scores.len() / 2], label)
+        })
+        .collect();
+    medians.sort();
+    medians
+        .into_iter()
+        .map(|(_, label)| label.to_string())
+        .collect()
+}
+
+/// The calibration file of a calibration.
+fn to_json(calibration: &Calibration) -> Value {
+    let weights: Map<String, Value> = Technique::ALL
+        .iter()
+        .zip(calibration.weights())
+        .map(|(technique, weight)| (technique.name().to_string(), json!(weight)))
+        .collect();
+    let min_scores = iter::once(&0).chain(calibration.thresholds());
+    let levels: Vec<Value> = calibration
+        .labels()
+        .iter()
+        .zip(min_scores)
+        .map(|(label, min_score)| json!({"label": label, "min_score": min_score}))
+        .collect();
+    json!({"weights": weights, "levels": levels})
+}
+
+/// Load a calibration file.
+pub fn load(path: &Path) -> io::Result<Calibration> {
+    let text = fs::read_to_string(path)?;
+    serde_json::from_str(&text)
+        .map_err(|err| format!("invalid JSON: {}", err))
+        .and_then(|value| parse(&value))
+        .map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        })
+}
+
+/// Read the calibration of a calibration file.
+fn parse(value: &Value) -> std::result::Result<Calibration, String> {
+    let object = value
+        .as_object()
+        .ok_or("expected an object of weights and levels")?;
+    let whole = |value: &Value| value.as_u64().and_then(|value| u32::try_from(value).ok());
+    let mut weights = Technique::ALL.map(Technique::score);
+    if let Some(given) = object.get("weights") {
+        let given = given
+            .as_object()
+            .ok_or("weights: expected an object of techniques")?;
+        for (name, weight) in given {
+            let technique: Technique = name
+                .parse()
+                .map_err(|err| format!("weights: {}: {}", name, err))?;
+            weights[technique as usize] = whole(weight)
+                .ok_or_else(|| format!("weights: {}: expected a whole number", name))?;
+        }
+    }
+    let levels = object
+        .get("levels")
+        .and_then(Value::as_array)
+        .ok_or("levels: expected an array of levels")?;
+    let mut labels = Vec::with_capacity(levels.len());
+    let mut thresholds = Vec::with_capacity(levels.len());
+    for (idx, level) in levels.iter().enumerate() {
+        let label = level
+            .get("label")
+            .and_then(Value::as_str)
+            .ok_or("levels: expected a label for every level")?;
+        // The lowest score of the easiest level is implied.
+        if idx > 0 {
+            let min_score = level
+                .get("min_score")
+                .and_then(whole)
+                .ok_or_else(|| format!("levels: {}: expected a whole min_score", label))?;
+            thresholds.push(min_score);
+        }
+        labels.push(label.to_string());
+    }
+    Calibration::new(weights, labels, thresholds).map_err(|err| format!("levels: {}", err))
+}
@@ -0,0 +1,88 @@
+use super::{streams, verbosity, Error};
+use clap::Parser;
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
+use sudoku_solver::{Options, Puzzle, Solver};
+
+/// Solve all puzzles in a file and compare the solutions to the expected
+/// solutions on the same lines of another file. Mismatches are printed along
+/// with their line number, and the exit status is nonzero if there are any.
+#[derive(Parser)]
+pub struct Args {
+    /// File containing a puzzle on each line
+    puzzles: PathBuf,
+    /// File containing the expected solution of each puzzle on each line
+    expected: PathBuf,
+}
+
+pub fn run(args: Args) -> super::Result {
+    let puzzles = streams::open_input(Some(&args.puzzles))?.lines();
+    let mut expected = streams::open_input(Some(&args.expected))?.lines();
+    let mut stdout = io::stdout().lock();
+
+    let mut solver = Solver::new();
+    let options = Options::default();
+    let mut num_checked = 0;
+    let mut num_mismatches = 0;
+
+    for (idx, line) in puzzles.enumerate() {
+        let line = line?;
+        let Some(expected) = expected.next().transpose()? else {
+            writeln!(stdout, "line {}: no expected solution", idx + 1)?;
+            num_mismatches += 1;
+            break;
+        };
+        num_checked += 1;
+
+        let mismatch = match check(&mut solver, &options, line.trim(), expected.trim()) {
+            Ok(()) => continue,
+            Err(mismatch) => mismatch,
+        };
+        writeln!(stdout, "line {}: {}", idx + 1, mismatch)?;
+        num_mismatches += 1;
+    }
+    if expected.next().is_some() {
+        writeln!(stdout, "more expected solutions than puzzles")?;
+        num_mismatches += 1;
+    }
+    stdout.flush()?;
+
+    if verbosity::summary() {
+        eprintln!("Puzzle file: {}", args.puzzles.display());
+        eprintln!("Expected file: {}", args.expected.display());
+        eprintln!("Total checked: {}", num_checked);
+        eprintln!("Total mismatches: {}", num_mismatches);
+    }
+
+    if num_mismatches > 0 {
+        return Err(Error::Failures(format!("{} mismatches", num_mismatches)));
+    }
+    Ok(())
+}
+
+/// Solve a puzzle and compare its solution to the expected one.
+fn check(
+    solver: &mut Solver,
+    options: &Options,
+    puzzle: &str,
+    expected: &str,
+) -> Result<(), String> {
+    let puzzle: Puzzle = puzzle
+        .parse()
+        .map_err(|err| format!("invalid puzzle: {}", err))?;
+    let expected: Puzzle = expected
+        .parse()
+        .map_err(|err| format!("invalid expected solution: {}", err))?;
+    let solution = solver
+        .load(&puzzle)
+        .and_then(|_| solver.run(options))
+        .map_err(|err| format!("failed to solve: {}", err))?;
+    if solution.cells() != expected.cells() {
+        return Err(format!(
+            "expected {}, got {}",
+            expected,
+            solution.row_representation()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//! Checkpoints recording the progress of a run, such that a run over a very
+//! large input which was interrupted can be resumed where it left off.
+
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Minimum time between saving checkpoints.
+const INTERVAL: Duration = Duration::from_secs(5);
+
+/// Progress of a run at the moment a checkpoint was saved.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct State {
+    /// Number of puzzles of which the results have been written.
+    pub puzzles: u64,
+    /// Length of the output file after writing those results, or `None` if
+    /// the output is standard output.
+    pub output_length: Option<u64>,
+}
+
+/// File in which checkpoints are saved periodically.
+pub struct Checkpoint {
+    path: PathBuf,
+    last_save: Instant,
+}
+
+impl Checkpoint {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            last_save: Instant::now(),
+        }
+    }
+
+    /// Load the last saved state, if the file exists.
+    pub fn load(&self) -> io::Result<Option<State>> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: invalid checkpoint", self.path.display()),
+            )
+        };
+        let value: Value = serde_json::from_str(&text).map_err(|_| invalid())?;
+        Ok(Some(State {
+            puzzles: value["puzzles"].as_u64().ok_or_else(invalid)?,
+            output_length: value["output_length"].as_u64(),
+        }))
+    }
+
+    /// Whether it is time to save a checkpoint again.
+    pub fn due(&self) -> bool {
+        self.last_save.elapsed() >= INTERVAL
+    }
+
+    /// Save a state, replacing the previous one atomically such that an
+    /// interruption while saving does not lose the checkpoint.
+    pub fn save(&mut self, state: State) -> io::Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let value = json!({
+            "puzzles": state.puzzles,
+            "output_length": state.output_length,
+        });
+        fs::write(&temporary, format!("{}\n", value))?;
+        fs::rename(&temporary, &self.path)?;
+        self.last_save = Instant::now();
+        Ok(())
+    }
+
+    /// Remove the checkpoint after the run has finished.
+    pub fn remove(self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Coloring of grids written to terminals, distinguishing the given clues
+//! from the cells filled in by solving.
+
+use super::streams;
+use clap::ValueEnum;
+use std::env;
+use std::path::Path;
+use sudoku_solver::Puzzle;
+
+/// Style of the given clues of a puzzle.
+pub const CLUE: &str = "\x1b[1m";
+
+/// Style of the cells filled in by solving.
+pub const SOLVED: &str = "\x1b[36m";
+
+/// Reset of the style.
+pub const RESET: &str = "\x1b[0m";
+
+/// When to color grids.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Only when writing to a terminal, unless NO_COLOR is set
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color grids written to an output.
+    pub fn enabled(self, output: Option<&Path>) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                streams::is_terminal(output) && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// Represent the values of a grid as a framed grid like the representation of
+/// a solution, with dots for empty cells. If colored, the clues of the puzzle
+/// are distinguished from the other cells.
+pub fn framed(cells: &[[u8; 9]; 9], puzzle: &Puzzle, color: bool) -> String {
+    let mut grid = String::new();
+    for (row_idx, row) in cells.iter().enumerate() {
+        if row_idx % 3 == 0 {
+            grid.push_str("+-------+-------+-------+\n");
+        }
+        for (col_idx, value) in row.iter().enumerate() {
+            if col_idx % 3 == 0 {
+                grid.push_str("| ");
+            }
+            let style = match puzzle.cells()[row_idx][col_idx] {
+                _ if !color => "",
+                0 => SOLVED,
+                _ => CLUE,
+            };
+            match value {
+                0 => grid.push_str(". "),
+                v if style.is_empty() => grid.push_str(&format!("{} ", v)),
+                v => grid.push_str(&format!("{}{}{} ", style, v, RESET)),
+            }
+        }
+        grid.push_str("|\n");
+    }
+    grid.push_str("+-------+-------+-------+");
+    grid
+}
@@ -0,0 +1,20 @@
+use super::Cli;
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
+use std::io;
+
+/// Generate a completion script for a shell, which is written to standard
+/// output.
+#[derive(Parser)]
+pub struct Args {
+    /// Shell to generate the completion script for
+    #[arg(value_enum)]
+    shell: Shell,
+}
+
+pub fn run(args: Args) -> super::Result {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(args.shell, &mut command, name, &mut io::stdout());
+    Ok(())
+}
@@ -0,0 +1,170 @@
+//! Configuration file providing defaults for options, such that they do not
+//! have to be repeated on every invocation. Keys are the long names of
+//! options. Keys at the top level apply to every subcommand having such an
+//! option, while those in a table named after a subcommand only apply to it:
+//!
+//! ```toml
+//! jobs = 4
+//!
+//! [solve]
+//! format = "jsonl"
+//! timeout = 2.5
+//! ```
+//!
+//! Options given on the command line take precedence over the configuration.
+
+use super::{Cli, Error, Result};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Command, CommandFactory};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Name of the configuration file in the current directory.
+const LOCAL_FILE: &str = "sudoku-solver.toml";
+
+/// Find the configuration file, which is either in the current directory or
+/// in the user configuration directory following the XDG base directories.
+fn find() -> Option<PathBuf> {
+    let local = PathBuf::from(LOCAL_FILE);
+    if local.is_file() {
+        return Some(local);
+    }
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    let path = dir.join("sudoku-solver").join("config.toml");
+    path.is_file().then_some(path)
+}
+
+/// Load the configuration file, if any.
+fn load() -> Result<Option<(PathBuf, Table)>> {
+    let Some(path) = find() else {
+        return Ok(None);
+    };
+    let text = fs::read_to_string(&path).map_err(|err| invalid(&path, err.to_string()))?;
+    let table = text
+        .parse::<Table>()
+        .map_err(|err| invalid(&path, err.message().to_string()))?;
+    Ok(Some((path, table)))
+}
+
+/// Add the options from the configuration file to command line arguments,
+/// for those options of the invoked subcommand not given on the command
+/// line and not conflicting with those given, such that defaults never make
+/// a valid command line invalid. Arguments which cannot be parsed are left
+/// as is, such that their errors are reported as usual.
+pub fn apply(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let Some((path, table)) = load()? else {
+        return Ok(args);
+    };
+    let Ok(matches) = Cli::command().try_get_matches_from(&args) else {
+        return Ok(args);
+    };
+    let Some((name, sub_matches)) = matches.subcommand() else {
+        return Ok(args);
+    };
+    let command = Cli::command();
+    let Some(subcommand) = command.find_subcommand(name) else {
+        return Ok(args);
+    };
+
+    // Options of the subcommand's table override those at the top level.
+    let mut options: Vec<(&str, &Value, bool)> = Vec::new();
+    for (key, value) in &table {
+        if !value.is_table() {
+            options.push((key, value, false));
+        }
+    }
+    if let Some(section) = table.get(name) {
+        let section = section
+            .as_table()
+            .ok_or_else(|| invalid(&path, format!("expected a table for subcommand '{}'", name)))?;
+        for (key, value) in section {
+            options.retain(|(other, _, _)| other != key);
+            options.push((key, value, true));
+        }
+    }
+
+    let mut added: Vec<OsString> = Vec::new();
+    for (key, value, specific) in options {
+        let Some(arg) = find_arg(subcommand, &command, key) else {
+            if specific {
+                return Err(invalid(
+                    &path,
+                    format!("unknown option '{}' for subcommand '{}'", key, name),
+                ));
+            }
+            continue;
+        };
+        let source = sub_matches
+            .value_source(arg.as_str())
+            .or_else(|| matches.value_source(arg.as_str()));
+        if source == Some(ValueSource::CommandLine) {
+            continue;
+        }
+        let values = match value {
+            Value::Array(items) => items.iter().collect(),
+            value => vec![value],
+        };
+        let mut option: Vec<OsString> = Vec::new();
+        for value in values {
+            match value {
+                Value::Boolean(true) => option.push(format!("--{}", key).into()),
+                Value::Boolean(false) => {}
+                Value::String(s) => option.extend([format!("--{}", key).into(), s.into()]),
+                Value::Integer(_) | Value::Float(_) => {
+                    option.extend([format!("--{}", key).into(), value.to_string().into()])
+                }
+                _ => {
+                    return Err(invalid(
+                        &path,
+                        format!("unsupported value for option '{}'", key),
+                    ))
+                }
+            }
+        }
+        // Options conflicting with those on the command line are left out,
+        // while conflicts among the configured ones are reported as usual.
+        let conflicts = Cli::command()
+            .try_get_matches_from(insert(&args, name, option.clone()))
+            .is_err_and(|err| err.kind() == ErrorKind::ArgumentConflict);
+        if !conflicts {
+            added.extend(option);
+        }
+    }
+    Ok(insert(&args, name, added))
+}
+
+/// Insert options into command line arguments right after the subcommand,
+/// before any positional arguments following a '--'.
+fn insert(args: &[OsString], name: &str, options: Vec<OsString>) -> Vec<OsString> {
+    let mut args = args.to_vec();
+    if let Some(idx) = args.iter().skip(1).position(|arg| arg == name) {
+        args.splice(idx + 2..idx + 2, options);
+    }
+    args
+}
+
+/// Find the identifier of an option by its long name, among the options of
+/// a subcommand and the global options.
+fn find_arg(subcommand: &Command, command: &Command, long: &str) -> Option<String> {
+    subcommand
+        .get_arguments()
+        .chain(command.get_arguments().filter(|arg| arg.is_global_set()))
+        .find(|arg| arg.get_long() == Some(long))
+        .map(|arg| arg.get_id().to_string())
+}
+
+/// Construct an error about the contents of the configuration file.
+fn invalid(path: &Path, message: String) -> Error {
+    Error::Fatal(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {}", path.display(), message),
+    ))
+}
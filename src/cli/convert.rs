@@ -0,0 +1,45 @@
+use super::format::{self, Format};
+use super::input::Inputs;
+use super::{streams, verbosity, Result};
+use clap::Parser;
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
+
+/// Convert a file of puzzles from one format to another.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// File to write the converted puzzles to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Format of the input [default: detected from its contents]
+    #[arg(long, value_enum)]
+    from: Option<Format>,
+    /// Format of the output
+    #[arg(long, value_enum, default_value_t = Format::Line)]
+    to: Format,
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let mut puzzles = Vec::new();
+    for path in files.paths() {
+        let mut bytes = Vec::new();
+        streams::open_input(path)?.read_to_end(&mut bytes)?;
+        let from = args.from.unwrap_or_else(|| format::detect_bytes(&bytes));
+        puzzles.extend(format::parse_bytes(&bytes, from)?);
+    }
+    let puzzles = files
+        .select(puzzles.into_iter().map(Ok))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let mut writer = streams::create_output(args.output.as_deref())?;
+    format::write(&mut writer, &puzzles, args.to)?;
+    writer.finish()?;
+
+    if verbosity::summary() {
+        eprintln!("Total converted: {}", puzzles.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use super::input::{self, Failures, Inputs};
+use super::jobs::{self, Jobs};
+use super::progress::Progress;
+use super::{streams, verbosity, Result};
+use clap::Parser;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use sudoku_solver::transform::{self, Group};
+use sudoku_solver::Solver;
+
+/// Count the solutions of all puzzles in a file. Each puzzle is written
+/// followed by its number of solutions, separated by a tab. Using --group,
+/// the number of essentially different solutions follows in another column.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// File to write the counts to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Stop counting the solutions of a puzzle once this many are found
+    #[arg(long, default_value_t = 1000)]
+    cap: usize,
+    /// Also count the essentially different solutions, of which none is
+    /// mapped onto another by a transformation of this group which maps the
+    /// puzzle onto itself: geometric, relabeling or geometric-relabeling
+    #[arg(long, value_name = "GROUP")]
+    group: Option<Group>,
+    #[command(flatten)]
+    jobs: Jobs,
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let mut writer = streams::create_output(args.output.as_deref())?;
+
+    let mut num_unique = 0;
+    let mut num_ambiguous = 0;
+    let mut num_unsolvable = 0;
+    let mut num_counted = Vec::new();
+    let mut failures = Failures::default();
+
+    let mut progress = Progress::new(&files);
+    jobs::map_ordered(
+        args.jobs.count(),
+        files.lines(),
+        || (),
+        |_, line| {
+            let counted = line.puzzle().map(|puzzle| match args.group {
+                Some(group) => {
+                    let (count, distinct) =
+                        transform::count_essentially_different(&puzzle, group, args.cap);
+                    (puzzle, count, Some(distinct))
+                }
+                None => (puzzle, Solver::count_solutions(&puzzle, args.cap), None),
+            });
+            (line.location, counted)
+        },
+        |(location, counted)| {
+            progress.inc();
+            let (puzzle, count, distinct) = match counted {
+                Ok(counted) => counted,
+                Err(err) => {
+                    failures.report(&location, err);
+                    return Ok(());
+                }
+            };
+            input::tally(&mut num_counted, location.file);
+            match count {
+                0 => num_unsolvable += 1,
+                1 => num_unique += 1,
+                _ => num_ambiguous += 1,
+            }
+            let capped = match count == args.cap && count > 1 {
+                true => "+",
+                false => "",
+            };
+            write!(
+                writer,
+                "{}{}\t{}{}",
+                location.prefix(),
+                puzzle,
+                count,
+                capped
+            )?;
+            if let Some(distinct) = distinct {
+                write!(writer, "\t{}{}", distinct, capped)?;
+            }
+            writeln!(writer)
+        },
+    )?;
+    progress.finish();
+    writer.finish()?;
+
+    if verbosity::summary() {
+        files.summarize(&num_counted, "counted", failures.per_file(), "invalid");
+        eprintln!("Unique: {}", num_unique);
+        eprintln!("Multiple solutions: {}", num_ambiguous);
+        eprintln!("No solution: {}", num_unsolvable);
+        eprintln!("Invalid: {}", failures.count());
+    }
+    failures.finish()
+}
@@ -0,0 +1,268 @@
+use super::input::{Failures, Inputs};
+use super::{verbosity, Error, Result};
+use clap::{Parser, ValueEnum};
+use std::io::{self, prelude::*};
+use std::time::{Duration, Instant};
+use sudoku_solver::{Options, Puzzle, Solver};
+
+/// Solve every puzzle with two engines and report any disagreement in their
+/// solutions or in whether the solution is unique, along with the time each
+/// engine took. Disagreements are printed along with their line number, and
+/// the exit status is nonzero if there are any.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Engine whose results are checked
+    #[arg(long, value_enum, default_value_t = Engine::Solver)]
+    engine: Engine,
+    /// Engine to check against
+    #[arg(long, value_enum, default_value_t = Engine::Reference)]
+    against: Engine,
+}
+
+/// An engine for solving puzzles and determining their uniqueness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Engine {
+    /// The solver of the library, brute-forcing using constraint propagation
+    Solver,
+    /// The solver of the library, exploring the first guess in parallel
+    #[cfg(feature = "parallel")]
+    Parallel,
+    /// A plain backtracking search independent of the library, which is
+    /// slow but simple enough to trust
+    Reference,
+}
+
+/// Results of an engine for a puzzle.
+#[derive(Debug, PartialEq, Eq)]
+struct Verdict {
+    /// The first solution found, if any.
+    solution: Option<[[u8; 9]; 9]>,
+    /// Whether the puzzle has exactly one solution.
+    unique: bool,
+}
+
+impl Engine {
+    /// Solve a puzzle and determine whether its solution is unique.
+    fn run(self, puzzle: &Puzzle) -> Verdict {
+        match self {
+            Engine::Solver => Verdict {
+                solution: Solver::solve_with_options(puzzle, &Options::default())
+                    .ok()
+                    .map(|solution| *solution.cells()),
+                unique: Solver::count_solutions(puzzle, 2) == 1,
+            },
+            #[cfg(feature = "parallel")]
+            Engine::Parallel => {
+                let options = Options {
+                    parallel: true,
+                    ..Default::default()
+                };
+                Verdict {
+                    solution: Solver::solve_with_options(puzzle, &options)
+                        .ok()
+                        .map(|solution| *solution.cells()),
+                    unique: Solver::count_solutions(puzzle, 2) == 1,
+                }
+            }
+            Engine::Reference => {
+                let mut search = Reference::new(puzzle);
+                search.run();
+                Verdict {
+                    solution: search.first,
+                    unique: search.count == 1,
+                }
+            }
+        }
+    }
+}
+
+/// A plain backtracking search counting up to two solutions, which fills the
+/// empty cell with the fewest candidates first.
+struct Reference {
+    cells: [[u8; 9]; 9],
+    /// Bitmasks of the values in each row, column and square.
+    rows: [u16; 9],
+    cols: [u16; 9],
+    squares: [u16; 9],
+    /// Whether the givens conflict, such that there is no solution.
+    conflict: bool,
+    first: Option<[[u8; 9]; 9]>,
+    count: usize,
+}
+
+impl Reference {
+    fn new(puzzle: &Puzzle) -> Self {
+        let mut search = Self {
+            cells: [[0; 9]; 9],
+            rows: [0; 9],
+            cols: [0; 9],
+            squares: [0; 9],
+            conflict: false,
+            first: None,
+            count: 0,
+        };
+        for (row, values) in puzzle.cells().iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                if *value == 0 {
+                    continue;
+                }
+                if search.candidates(row, col) & (1 << (value - 1)) == 0 {
+                    search.conflict = true;
+                }
+                search.set(row, col, *value);
+            }
+        }
+        search
+    }
+
+    fn candidates(&self, row: usize, col: usize) -> u16 {
+        !(self.rows[row] | self.cols[col] | self.squares[row / 3 * 3 + col / 3]) & 0x1ff
+    }
+
+    fn set(&mut self, row: usize, col: usize, value: u8) {
+        self.cells[row][col] = value;
+        let bit = 1 << (value - 1);
+        self.rows[row] |= bit;
+        self.cols[col] |= bit;
+        self.squares[row / 3 * 3 + col / 3] |= bit;
+    }
+
+    fn unset(&mut self, row: usize, col: usize, value: u8) {
+        self.cells[row][col] = 0;
+        let bit = !(1 << (value - 1));
+        self.rows[row] &= bit;
+        self.cols[col] &= bit;
+        self.squares[row / 3 * 3 + col / 3] &= bit;
+    }
+
+    fn run(&mut self) {
+        if !self.conflict {
+            self.search();
+        }
+    }
+
+    fn search(&mut self) {
+        let empty = (0..81)
+            .map(|idx| (idx / 9, idx % 9))
+            .filter(|(row, col)| self.cells[*row][*col] == 0)
+            .min_by_key(|(row, col)| self.candidates(*row, *col).count_ones());
+        let Some((row, col)) = empty else {
+            self.count += 1;
+            self.first.get_or_insert(self.cells);
+            return;
+        };
+        let candidates = self.candidates(row, col);
+        for value in 1..=9 {
+            if candidates & (1 << (value - 1)) == 0 {
+                continue;
+            }
+            self.set(row, col, value);
+            self.search();
+            self.unset(row, col, value);
+            if self.count >= 2 {
+                return;
+            }
+        }
+    }
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let mut stdout = io::stdout().lock();
+
+    let mut num_checked = 0;
+    let mut num_disagreements = 0;
+    let mut elapsed = [Duration::ZERO; 2];
+    let mut failures = Failures::default();
+
+    for line in files.lines() {
+        let line = line?;
+        let puzzle = match line.puzzle() {
+            Ok(puzzle) => puzzle,
+            Err(err) => {
+                failures.report(&line.location, err);
+                continue;
+            }
+        };
+        let mut verdicts = [args.engine, args.against].map(|engine| {
+            let start = Instant::now();
+            let verdict = engine.run(&puzzle);
+            (verdict, start.elapsed())
+        });
+        for (total, (_, time)) in elapsed.iter_mut().zip(&verdicts) {
+            *total += *time;
+        }
+        num_checked += 1;
+
+        let [(checked, _), (expected, _)] = &mut verdicts;
+        // Both solutions are valid if the puzzle has multiple, so only their
+        // existence is compared then.
+        if !checked.unique && !expected.unique {
+            checked.solution = checked.solution.map(|_| [[0; 9]; 9]);
+            expected.solution = expected.solution.map(|_| [[0; 9]; 9]);
+        }
+        if checked != expected {
+            writeln!(
+                stdout,
+                "{}: {} {}, {} {}",
+                line.location,
+                name(args.engine),
+                describe(checked),
+                name(args.against),
+                describe(expected)
+            )?;
+            num_disagreements += 1;
+        }
+    }
+    stdout.flush()?;
+
+    if verbosity::summary() {
+        files.print_name();
+        eprintln!("Total checked: {}", num_checked);
+        eprintln!("Total disagreements: {}", num_disagreements);
+        eprintln!("Total failed: {}", failures.count());
+        for (engine, time) in [args.engine, args.against].iter().zip(elapsed) {
+            eprintln!("Time of {}: {:?}", name(*engine), time);
+        }
+        if !elapsed[0].is_zero() {
+            eprintln!(
+                "Speedup of {}: {:.2}x",
+                name(args.engine),
+                elapsed[1].as_secs_f64() / elapsed[0].as_secs_f64()
+            );
+        }
+    }
+
+    if num_disagreements > 0 {
+        return Err(Error::Failures(format!(
+            "{} disagreements",
+            num_disagreements
+        )));
+    }
+    failures.finish()
+}
+
+/// Name of an engine as given on the command line.
+fn name(engine: Engine) -> String {
+    engine
+        .to_possible_value()
+        .map_or_else(String::new, |value| value.get_name().to_string())
+}
+
+/// Describe the results of an engine.
+fn describe(verdict: &Verdict) -> String {
+    match (&verdict.solution, verdict.unique) {
+        (None, _) => "found no solution".to_string(),
+        (Some(_), false) => "found multiple solutions".to_string(),
+        (Some(cells), true) => format!(
+            "found unique solution {}",
+            cells
+                .iter()
+                .flatten()
+                .map(|value| (b'0' + value) as char)
+                .collect::<String>()
+        ),
+    }
+}
@@ -0,0 +1,203 @@
+//! Recording of results in a SQLite database, such that datasets can be built
+//! incrementally over several runs. Each distinct puzzle is stored once, as
+//! identified by its fingerprint, and later results of the same puzzle update
+//! its row. The schema is:
+//!
+//! ```sql
+//! CREATE TABLE puzzles (
+//!     id INTEGER PRIMARY KEY,
+//!     fingerprint TEXT NOT NULL,  -- 128-bit fingerprint in hexadecimal
+//!     puzzle TEXT NOT NULL,       -- 81 values, with dots for empty cells
+//!     clues INTEGER NOT NULL,
+//!     solution TEXT,              -- set by solve
+//!     brute_forces INTEGER,       -- set by solve
+//!     guesses INTEGER,            -- set by solve
+//!     backtracks INTEGER,         -- set by solve
+//!     elapsed_us INTEGER,         -- set by solve
+//!     error TEXT,                 -- set by solve if the puzzle failed
+//!     difficulty TEXT,            -- set by rate, or solve when rating
+//!     score INTEGER,              -- set by rate, or solve when rating
+//!     hardest TEXT                -- as above, if any technique is needed
+//! );
+//! CREATE UNIQUE INDEX puzzles_fingerprint ON puzzles (fingerprint);
+//! ```
+
+use rusqlite::{params, Connection};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use sudoku_solver::logic::Technique;
+use sudoku_solver::rating::Rating;
+use sudoku_solver::{Puzzle, Solution};
+
+/// Version of the schema, stored as user version of the database.
+const SCHEMA_VERSION: i32 = 1;
+
+/// Statements creating the schema if it does not exist yet.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS puzzles (
+    id INTEGER PRIMARY KEY,
+    fingerprint TEXT NOT NULL,
+    puzzle TEXT NOT NULL,
+    clues INTEGER NOT NULL,
+    solution TEXT,
+    brute_forces INTEGER,
+    guesses INTEGER,
+    backtracks INTEGER,
+    elapsed_us INTEGER,
+    error TEXT,
+    difficulty TEXT,
+    score INTEGER,
+    hardest TEXT
+);
+CREATE UNIQUE INDEX IF NOT EXISTS puzzles_fingerprint ON puzzles (fingerprint);
+";
+
+/// Number of puzzles recorded per transaction, such that an interrupted run
+/// keeps most of its results without committing every row.
+const TRANSACTION_SIZE: u64 = 10_000;
+
+/// Database in which results are recorded.
+pub struct Database {
+    connection: Connection,
+    /// Number of puzzles recorded in the current transaction.
+    pending: u64,
+    /// Number of puzzles which were not in the database yet.
+    added: u64,
+    /// Number of puzzles which were already in the database.
+    existing: u64,
+}
+
+/// Convert a database error to an I/O error.
+fn error(err: rusqlite::Error) -> io::Error {
+    io::Error::other(format!("database: {}", err))
+}
+
+impl Database {
+    /// Open a database, creating it and its schema if needed.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let connection = Connection::open(path).map_err(error)?;
+        let version: i32 = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(error)?;
+        if version > SCHEMA_VERSION {
+            return Err(io::Error::other(format!(
+                "database: schema version {} is newer than supported",
+                version
+            )));
+        }
+        connection
+            .busy_timeout(Duration::from_secs(5))
+            .and_then(|_| connection.execute_batch(SCHEMA))
+            .and_then(|_| connection.pragma_update(None, "user_version", SCHEMA_VERSION))
+            .and_then(|_| connection.execute_batch("BEGIN"))
+            .map_err(error)?;
+        Ok(Self {
+            connection,
+            pending: 0,
+            added: 0,
+            existing: 0,
+        })
+    }
+
+    /// Make sure a puzzle has a row, returning its fingerprint.
+    fn insert(&mut self, puzzle: &Puzzle) -> io::Result<String> {
+        let fingerprint = format!("{:032x}", puzzle.fingerprint());
+        let inserted = self
+            .connection
+            .prepare_cached(
+                "INSERT OR IGNORE INTO puzzles (fingerprint, puzzle, clues) VALUES (?1, ?2, ?3)",
+            )
+            .and_then(|mut statement| {
+                statement.execute(params![
+                    fingerprint,
+                    puzzle.to_string(),
+                    puzzle.clues() as u32
+                ])
+            })
+            .map_err(error)?;
+        match inserted {
+            0 => self.existing += 1,
+            _ => self.added += 1,
+        }
+        Ok(fingerprint)
+    }
+
+    /// Commit the current transaction once it is large enough.
+    fn recorded(&mut self) -> io::Result<()> {
+        self.pending += 1;
+        if self.pending == TRANSACTION_SIZE {
+            self.connection
+                .execute_batch("COMMIT; BEGIN")
+                .map_err(error)?;
+            self.pending = 0;
+        }
+        Ok(())
+    }
+
+    /// Record the result of solving a puzzle, along with its rating if it
+    /// was computed.
+    pub fn record_solution(
+        &mut self,
+        puzzle: &Puzzle,
+        solution: &Result<Solution, &str>,
+        elapsed: Duration,
+        rating: Option<&Rating>,
+    ) -> io::Result<()> {
+        let fingerprint = self.insert(puzzle)?;
+        let solved = solution.as_ref().ok();
+        self.connection
+            .prepare_cached(
+                "UPDATE puzzles SET solution = ?2, brute_forces = ?3, guesses = ?4, \
+                 backtracks = ?5, elapsed_us = ?6, error = ?7 WHERE fingerprint = ?1",
+            )
+            .and_then(|mut statement| {
+                statement.execute(params![
+                    fingerprint,
+                    solved.map(Solution::row_representation),
+                    solved.map(|solution| solution.brute_forces),
+                    solved.map(|solution| solution.guesses),
+                    solved.map(|solution| solution.backtracks),
+                    elapsed.as_micros() as i64,
+                    solution.as_ref().err(),
+                ])
+            })
+            .map_err(error)?;
+        if let Some(rating) = rating {
+            self.update_rating(&fingerprint, rating)?;
+        }
+        self.recorded()
+    }
+
+    /// Record the rating of a puzzle.
+    pub fn record_rating(&mut self, puzzle: &Puzzle, rating: &Rating) -> io::Result<()> {
+        let fingerprint = self.insert(puzzle)?;
+        self.update_rating(&fingerprint, rating)?;
+        self.recorded()
+    }
+
+    fn update_rating(&mut self, fingerprint: &str, rating: &Rating) -> io::Result<()> {
+        self.connection
+            .prepare_cached(
+                "UPDATE puzzles SET difficulty = ?2, score = ?3, hardest = ?4 \
+                 WHERE fingerprint = ?1",
+            )
+            .and_then(|mut statement| {
+                statement.execute(params![
+                    fingerprint,
+                    rating.difficulty.name(),
+                    rating.score,
+                    rating.hardest.map(Technique::name),
+                ])
+            })
+            .map_err(error)?;
+        Ok(())
+    }
+
+    /// Commit the recorded results, returning the number of puzzles which
+    /// were added and which were already in the database.
+    pub fn finish(self) -> io::Result<(u64, u64)> {
+        self.connection.execute_batch("COMMIT").map_err(error)?;
+        Ok((self.added, self.existing))
+    }
+}
@@ -0,0 +1,62 @@
+use super::input::{Failures, Inputs};
+use super::{streams, verbosity, Result};
+use clap::Parser;
+use std::collections::HashSet;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use sudoku_solver::transform;
+
+/// Remove duplicate puzzles from a file, keeping the first occurrence of
+/// each. Puzzles are streamed and only their fingerprints are kept in
+/// memory, such that very large collections can be deduplicated. Using
+/// --canonical, puzzles which are transformations of each other, such as
+/// rotated or relabeled, are duplicates as well.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// File to write the unique puzzles to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Also treat puzzles as duplicates if they are transformations of each
+    /// other, by comparing their canonical forms, which is slower
+    #[arg(long)]
+    canonical: bool,
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let mut writer = streams::create_output(args.output.as_deref())?;
+
+    let mut seen = HashSet::new();
+    let mut num_read = 0;
+    let mut failures = Failures::default();
+
+    for line in files.lines() {
+        let line = line?;
+        let puzzle = match line.puzzle() {
+            Ok(puzzle) => puzzle,
+            Err(err) => {
+                failures.report(&line.location, err);
+                continue;
+            }
+        };
+
+        num_read += 1;
+        let key = match args.canonical {
+            true => transform::canonical(&puzzle),
+            false => puzzle,
+        };
+        if seen.insert(key.fingerprint()) {
+            writeln!(writer, "{}{}", line.location.prefix(), line.text)?;
+        }
+    }
+    writer.finish()?;
+
+    if verbosity::summary() {
+        eprintln!("Total read: {}", num_read);
+        eprintln!("Duplicates removed: {}", num_read - seen.len());
+        eprintln!("Invalid: {}", failures.count());
+    }
+    failures.finish()
+}
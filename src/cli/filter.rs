@@ -0,0 +1,91 @@
+use super::input::{self, Failures, Inputs};
+use super::jobs::{self, Jobs};
+use super::progress::Progress;
+use super::{streams, verbosity, Result};
+use clap::Parser;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use sudoku_solver::logic::{self, Technique};
+use sudoku_solver::rating::{Difficulty, Rating};
+
+/// Pass through only the puzzles matching a difficulty range and required
+/// techniques, for slicing collections. Each puzzle is solved the way a human
+/// would to determine its difficulty and the techniques it requires.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// File to write the matching puzzles to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Only pass puzzles of at least this difficulty
+    #[arg(long, value_name = "DIFFICULTY")]
+    min_difficulty: Option<Difficulty>,
+    /// Only pass puzzles of at most this difficulty
+    #[arg(long, value_name = "DIFFICULTY")]
+    max_difficulty: Option<Difficulty>,
+    /// Only pass puzzles requiring this technique, such as x-wing, which can
+    /// be repeated to require all of them
+    #[arg(long = "requires-technique", value_name = "TECHNIQUE")]
+    techniques: Vec<Technique>,
+    #[command(flatten)]
+    jobs: Jobs,
+}
+
+impl Args {
+    /// Whether a puzzle solved in the given steps matches the filter.
+    fn matches(&self, steps: &[logic::Step]) -> bool {
+        let difficulty = Rating::from_steps(steps).difficulty;
+        self.min_difficulty.is_none_or(|min| difficulty >= min)
+            && self.max_difficulty.is_none_or(|max| difficulty <= max)
+            && self
+                .techniques
+                .iter()
+                .all(|technique| steps.iter().any(|step| step.technique == *technique))
+    }
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let mut writer = streams::create_output(args.output.as_deref())?;
+
+    let mut num_matched = Vec::new();
+    let mut num_rejected = Vec::new();
+    let mut failures = Failures::default();
+
+    let mut progress = Progress::new(&files);
+    jobs::map_ordered(
+        args.jobs.count(),
+        files.lines(),
+        || (),
+        |_, line| {
+            let matched = line
+                .puzzle()
+                .and_then(|puzzle| logic::solve(&puzzle))
+                .map(|steps| args.matches(&steps));
+            (line, matched)
+        },
+        |(line, matched)| {
+            progress.inc();
+            match matched {
+                Ok(true) => {
+                    input::tally(&mut num_matched, line.location.file);
+                    writeln!(writer, "{}{}", line.location.prefix(), line.text)?;
+                }
+                Ok(false) => input::tally(&mut num_rejected, line.location.file),
+                Err(err) => failures.report(&line.location, err),
+            }
+            Ok(())
+        },
+    )?;
+    progress.finish();
+    writer.finish()?;
+
+    if verbosity::summary() {
+        files.summarize(&num_matched, "matched", failures.per_file(), "failed");
+        eprintln!("Total matched: {}", num_matched.iter().sum::<usize>());
+        eprintln!("Total rejected: {}", num_rejected.iter().sum::<usize>());
+        eprintln!("Total failed: {}", failures.count());
+    }
+    failures.finish()
+}
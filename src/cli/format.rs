@@ -0,0 +1,411 @@
+//! Formats in which collections of puzzles are read and written.
+
+use super::input;
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use sudoku_solver::binary::{self, Encoding};
+use sudoku_solver::hodoku::Entry;
+use sudoku_solver::Puzzle;
+
+/// A format for representing puzzles in files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// A puzzle of 81 values on each line, with dots for empty cells
+    Line,
+    /// Puzzles as grids of 9 lines, separated by empty lines
+    Grid,
+    /// SadMan Sudoku file with a single puzzle as grid and '#' comments
+    Sdk,
+    /// SadMan Sudoku file with a puzzle on each line, with zeroes for empty cells
+    Sdm,
+    /// JSON array of puzzle strings, or objects with a "puzzle" string
+    Json,
+    /// f-puzzles JSON, as also opened by SudokuPad of Cracking the Cryptic
+    Fpuzzles,
+    /// Hodoku library with an entry on each line, which can only be read
+    Hodoku,
+    /// Binary records of 42 bytes packing each value into four bits
+    Binary,
+    /// Binary records with runs of empty cells shortened, which are smaller
+    /// for puzzles but vary in length
+    BinaryRle,
+}
+
+/// Notation of techniques and steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Notation {
+    /// The names and notation of this solver
+    Default,
+    /// The names and notation of Hodoku and SudokuWiki
+    Hodoku,
+}
+
+/// Constraints of f-puzzles beyond the classic rules, which are rejected as
+/// the solver does not take them into account.
+const FPUZZLES_CONSTRAINTS: [&str; 29] = [
+    "diagonal+",
+    "diagonal-",
+    "antiknight",
+    "antiking",
+    "disjointgroups",
+    "nonconsecutive",
+    "killercage",
+    "cage",
+    "littlekillersum",
+    "sandwichsum",
+    "xsum",
+    "skyscraper",
+    "thermometer",
+    "palindrome",
+    "renban",
+    "whispers",
+    "regionsumline",
+    "betweenline",
+    "minimum",
+    "maximum",
+    "arrow",
+    "difference",
+    "ratio",
+    "xv",
+    "odd",
+    "even",
+    "clone",
+    "quadruple",
+    "extraregion",
+];
+
+/// Guess the format of the contents of a file, which may be binary.
+pub fn detect_bytes(bytes: &[u8]) -> Format {
+    match bytes.first() {
+        Some(header) if *header == binary::VERSION << 4 => Format::Binary,
+        Some(header) if header >> 4 == binary::VERSION => Format::BinaryRle,
+        _ => detect(&String::from_utf8_lossy(bytes)),
+    }
+}
+
+/// Guess the format of the contents of a text file.
+pub fn detect(text: &str) -> Format {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        let fpuzzles = match serde_json::from_str(text) {
+            Ok(Value::Object(object)) => object.contains_key("grid"),
+            Ok(Value::Array(items)) => items.first().is_some_and(|item| item.get("grid").is_some()),
+            _ => false,
+        };
+        return match fpuzzles {
+            true => Format::Fpuzzles,
+            false => Format::Json,
+        };
+    }
+    if text.lines().any(|line| line.starts_with(':')) {
+        return Format::Hodoku;
+    }
+    // Collections of puzzles on lines may have comments as well, while the
+    // grid of an sdk file is shorter than a line.
+    let comments = text.lines().any(|line| line.starts_with('#'));
+    match text
+        .lines()
+        .map(str::trim)
+        .find(|line| !input::is_skipped(line))
+    {
+        Some(line) if line.chars().count() < 81 && comments => Format::Sdk,
+        Some(line) if line.chars().count() < 81 => Format::Grid,
+        _ => Format::Line,
+    }
+}
+
+/// Construct an error about the contents of a file.
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Parse the puzzles contained in a file, which may be binary.
+pub fn parse_bytes(bytes: &[u8], format: Format) -> io::Result<Vec<Puzzle>> {
+    match format {
+        Format::Binary | Format::BinaryRle => parse_binary(bytes),
+        format => match std::str::from_utf8(bytes) {
+            Ok(text) => parse(text, format),
+            Err(err) => Err(invalid(err.to_string())),
+        },
+    }
+}
+
+/// Parse a sequence of binary records, of which the encoding may differ.
+fn parse_binary(mut bytes: &[u8]) -> io::Result<Vec<Puzzle>> {
+    let mut puzzles = Vec::new();
+    while !bytes.is_empty() {
+        let (puzzle, len) = binary::decode(bytes)
+            .map_err(|err| invalid(format!("record {}: {}", puzzles.len() + 1, err)))?;
+        puzzles.push(puzzle);
+        bytes = &bytes[len..];
+    }
+    Ok(puzzles)
+}
+
+/// Parse the puzzles contained in a text file.
+pub fn parse(text: &str, format: Format) -> io::Result<Vec<Puzzle>> {
+    match format {
+        Format::Binary | Format::BinaryRle => parse_binary(text.as_bytes()),
+        Format::Line | Format::Sdm => parse_lines(text),
+        Format::Grid | Format::Sdk => parse_grids(text),
+        Format::Json => parse_json(text),
+        Format::Fpuzzles => parse_fpuzzles(text),
+        Format::Hodoku => parse_hodoku(text),
+    }
+}
+
+/// Parse a puzzle on each line, skipping blank lines and comments.
+fn parse_lines(text: &str) -> io::Result<Vec<Puzzle>> {
+    let mut puzzles = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        if input::is_skipped(line) {
+            continue;
+        }
+        let line = line.trim();
+        let puzzle = line
+            .parse()
+            .map_err(|err| invalid(format!("line {}: {}", idx + 1, err)))?;
+        puzzles.push(puzzle);
+    }
+    Ok(puzzles)
+}
+
+/// Parse the grids of the entries of a Hodoku library, including the values
+/// placed after the start. Lines starting with '#' are comments.
+fn parse_hodoku(text: &str) -> io::Result<Vec<Puzzle>> {
+    let mut puzzles = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let entry: Entry = line
+            .parse()
+            .map_err(|err| invalid(format!("line {}: {}", idx + 1, err)))?;
+        puzzles.push(entry.puzzle);
+    }
+    Ok(puzzles)
+}
+
+/// Parse puzzles as grids of 9 rows. Lines starting with '#' are comments,
+/// and spaces and the border characters '|', '-' and '+' are ignored.
+fn parse_grids(text: &str) -> io::Result<Vec<Puzzle>> {
+    let mut puzzles = Vec::new();
+    let mut cells = String::new();
+    let mut rows = 0;
+    for (idx, line) in text.lines().enumerate() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let row: String = line
+            .chars()
+            .filter(|c| !c.is_whitespace() && !matches!(c, '|' | '-' | '+'))
+            .collect();
+        if row.is_empty() {
+            continue;
+        }
+        if row.chars().count() != 9 {
+            return Err(invalid(format!(
+                "line {}: expected a row of 9 cells",
+                idx + 1
+            )));
+        }
+        cells.push_str(&row);
+        rows += 1;
+        if rows == 9 {
+            let puzzle = cells
+                .parse()
+                .map_err(|err| invalid(format!("line {}: {}", idx + 1, err)))?;
+            puzzles.push(puzzle);
+            cells.clear();
+            rows = 0;
+        }
+    }
+    if rows != 0 {
+        return Err(invalid("incomplete grid at end of file".to_string()));
+    }
+    Ok(puzzles)
+}
+
+/// Parse a JSON array of puzzles, given as strings or as objects with a
+/// "puzzle" field. A single puzzle not contained in an array is accepted too.
+fn parse_json(text: &str) -> io::Result<Vec<Puzzle>> {
+    let value: Value =
+        serde_json::from_str(text).map_err(|err| invalid(format!("invalid JSON: {}", err)))?;
+    let items = match value {
+        Value::Array(items) => items,
+        item => vec![item],
+    };
+    items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| {
+            let puzzle = match item {
+                Value::Object(object) => object.get("puzzle").and_then(Value::as_str),
+                item => item.as_str(),
+            };
+            puzzle
+                .ok_or("expected a puzzle string")
+                .and_then(str::parse)
+                .map_err(|err| invalid(format!("puzzle {}: {}", idx + 1, err)))
+        })
+        .collect()
+}
+
+/// Parse f-puzzles JSON, which holds a single puzzle as object with a "grid"
+/// of rows of cells, of which the given ones have a "value". An array of such
+/// objects is accepted too. Puzzles with variant constraints or irregular
+/// regions are rejected, as their solutions differ from classic ones.
+fn parse_fpuzzles(text: &str) -> io::Result<Vec<Puzzle>> {
+    let value: Value =
+        serde_json::from_str(text).map_err(|err| invalid(format!("invalid JSON: {}", err)))?;
+    let items = match value {
+        Value::Array(items) => items,
+        item => vec![item],
+    };
+    items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| {
+            parse_fpuzzle(item).map_err(|err| invalid(format!("puzzle {}: {}", idx + 1, err)))
+        })
+        .collect()
+}
+
+/// Parse a single f-puzzles object.
+fn parse_fpuzzle(item: &Value) -> std::result::Result<Puzzle, String> {
+    if item.get("size").and_then(Value::as_u64) != Some(9) {
+        return Err("only puzzles of size 9 are supported".to_string());
+    }
+    let constraints: Vec<&str> = FPUZZLES_CONSTRAINTS
+        .into_iter()
+        .filter(|name| match item.get(name) {
+            Some(Value::Bool(enabled)) => *enabled,
+            Some(Value::Array(items)) => !items.is_empty(),
+            Some(Value::Null) | None => false,
+            Some(_) => true,
+        })
+        .collect();
+    if !constraints.is_empty() {
+        return Err(format!(
+            "unsupported variant constraints: {}",
+            constraints.join(", ")
+        ));
+    }
+    let rows = item
+        .get("grid")
+        .and_then(Value::as_array)
+        .filter(|rows| rows.len() == 9)
+        .ok_or("expected a grid of 9 rows")?;
+    let mut cells = String::with_capacity(81);
+    for (row, cols) in rows.iter().enumerate() {
+        let cols = cols
+            .as_array()
+            .filter(|cols| cols.len() == 9)
+            .ok_or("expected rows of 9 cells")?;
+        for (col, cell) in cols.iter().enumerate() {
+            // Regions are numbered from zero, and default to the square.
+            let region = cell.get("region").and_then(Value::as_u64);
+            if region.is_some_and(|region| region as usize != 3 * (row / 3) + col / 3) {
+                return Err("irregular regions are not supported".to_string());
+            }
+            let given = cell.get("given").and_then(Value::as_bool) == Some(true);
+            match cell.get("value").and_then(Value::as_u64) {
+                Some(value @ 1..=9) if given => cells.push(char::from(b'0' + value as u8)),
+                _ => cells.push('.'),
+            }
+        }
+    }
+    cells.parse().map_err(str::to_string)
+}
+
+/// Represent a puzzle as f-puzzles object.
+fn fpuzzle(puzzle: &Puzzle) -> Value {
+    let grid: Vec<Value> = puzzle
+        .cells()
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&value| match value {
+                    0 => json!({}),
+                    value => json!({ "value": value, "given": true }),
+                })
+                .collect()
+        })
+        .collect();
+    json!({ "size": 9, "grid": grid })
+}
+
+/// Write puzzles in a format.
+pub fn write(mut writer: impl Write, puzzles: &[Puzzle], format: Format) -> io::Result<()> {
+    match format {
+        Format::Line => {
+            for puzzle in puzzles {
+                writeln!(writer, "{}", puzzle)?;
+            }
+        }
+        Format::Sdm => {
+            for puzzle in puzzles {
+                writeln!(writer, "{}", puzzle.to_string().replace('.', "0"))?;
+            }
+        }
+        Format::Grid | Format::Sdk => {
+            if format == Format::Sdk && puzzles.len() != 1 {
+                return Err(invalid(
+                    "the sdk format holds exactly one puzzle".to_string(),
+                ));
+            }
+            for (idx, puzzle) in puzzles.iter().enumerate() {
+                if idx > 0 {
+                    writeln!(writer)?;
+                }
+                let line = puzzle.to_string();
+                for row in 0..9 {
+                    writeln!(writer, "{}", &line[9 * row..9 * row + 9])?;
+                }
+            }
+        }
+        Format::Json => {
+            let puzzles: Vec<String> = puzzles.iter().map(Puzzle::to_string).collect();
+            serde_json::to_writer_pretty(&mut writer, &puzzles)?;
+            writeln!(writer)?;
+        }
+        Format::Fpuzzles => {
+            let value = match puzzles {
+                [puzzle] => fpuzzle(puzzle),
+                puzzles => puzzles.iter().map(fpuzzle).collect(),
+            };
+            serde_json::to_writer(&mut writer, &value)?;
+            writeln!(writer)?;
+        }
+        Format::Hodoku => {
+            return Err(invalid("the hodoku format can only be read".to_string()));
+        }
+        Format::Binary | Format::BinaryRle => {
+            let encoding = match format {
+                Format::Binary => Encoding::Packed,
+                _ => Encoding::RunLength,
+            };
+            let mut record = [0; binary::PACKED_LEN];
+            for puzzle in puzzles {
+                let len = binary::encode(puzzle, encoding, &mut record);
+                writer.write_all(&record[..len])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Convert a grid of values to a puzzle.
+pub fn to_puzzle(cells: &[[u8; 9]; 9]) -> Puzzle {
+    let chars: Vec<char> = cells
+        .iter()
+        .flatten()
+        .map(|value| match value {
+            0 => '.',
+            value => char::from(b'0' + value),
+        })
+        .collect();
+    Puzzle::try_from(chars).expect("grid has 81 valid values")
+}
@@ -0,0 +1,103 @@
+use super::progress::Progress;
+use super::{streams, verbosity, Result};
+use clap::Parser;
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use sudoku_solver::generate;
+use sudoku_solver::logic::Technique;
+use sudoku_solver::random::Rng;
+use sudoku_solver::rating::Difficulty;
+
+/// Generate random puzzles with a unique solution, writing each on a line.
+/// Using --technique, practice puzzles are generated of which the logical
+/// solution requires that technique at least once and nothing harder.
+/// Using --pack, a pack of distinct puzzles of smoothly increasing difficulty
+/// is generated for level-based games, each written with its difficulty,
+/// score and hardest technique separated by tabs.
+#[derive(Parser)]
+pub struct Args {
+    /// Number of puzzles to generate
+    #[arg(short = 'n', long, default_value_t = 1)]
+    count: u64,
+    /// Only generate puzzles of this difficulty tier
+    #[arg(long, value_name = "DIFFICULTY")]
+    difficulty: Option<Difficulty>,
+    /// Only generate puzzles requiring this technique, such as x-wing, and
+    /// no harder one
+    #[arg(long, value_name = "TECHNIQUE", conflicts_with = "difficulty")]
+    technique: Option<Technique>,
+    /// Generate a pack of puzzles of increasing difficulty, along with their
+    /// ratings
+    #[arg(long, conflicts_with_all = ["difficulty", "technique"])]
+    pack: bool,
+    /// Give up after generating this many puzzles for each requested one,
+    /// as puzzles of some difficulties and techniques are rare
+    #[arg(long, value_name = "N", default_value_t = 100_000)]
+    attempts: usize,
+    /// Seed of the random generator, such that the same puzzles are
+    /// generated again [default: random, which is shown]
+    #[arg(long)]
+    seed: Option<u64>,
+    /// File to write the puzzles to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result {
+    let seed = args.seed.unwrap_or_else(|| {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        if verbosity::summary() {
+            eprintln!("Seed: {}", seed);
+        }
+        seed
+    });
+    let mut rng = Rng::new(seed);
+    let mut writer = streams::create_output(args.output.as_deref())?;
+
+    if args.pack {
+        let count = args.count as usize;
+        let pack = generate::generate_pack(&mut rng, count, args.attempts.saturating_mul(count))
+            .map_err(io::Error::other)?;
+        for (puzzle, rating) in pack {
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}",
+                puzzle,
+                rating.difficulty,
+                rating.score,
+                rating.hardest.map_or("None", Technique::name)
+            )?;
+        }
+        writer.finish()?;
+        if verbosity::summary() {
+            eprintln!("Total generated: {}", args.count);
+        }
+        return Ok(());
+    }
+
+    let mut progress = Progress::with_total(args.count);
+    for _ in 0..args.count {
+        let puzzle = match (args.difficulty, args.technique) {
+            (Some(difficulty), _) => {
+                generate::generate_with_difficulty(&mut rng, difficulty, args.attempts)
+            }
+            (None, Some(technique)) => {
+                generate::generate_with_technique(&mut rng, technique, args.attempts)
+            }
+            (None, None) => Ok(generate::generate(&mut rng)),
+        };
+        let puzzle = puzzle.map_err(io::Error::other)?;
+        writeln!(writer, "{}", puzzle)?;
+        progress.inc();
+    }
+    progress.finish();
+    writer.finish()?;
+
+    if verbosity::summary() {
+        eprintln!("Total generated: {}", args.count);
+    }
+    Ok(())
+}
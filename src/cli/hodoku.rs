@@ -0,0 +1,102 @@
+use super::verbosity::{self, Verbosity};
+use super::{streams, Error};
+use clap::Parser;
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
+use sudoku_solver::hodoku::Entry;
+use sudoku_solver::logic::cell_name;
+
+/// Cross-check the techniques against the entries of a Hodoku library file.
+/// Each entry is expected to have a step of its technique with exactly the
+/// listed eliminations and placements. Mismatches are printed along with
+/// their line number, and the exit status is nonzero if there are any.
+/// Entries of techniques which are not known are skipped.
+#[derive(Parser)]
+pub struct Args {
+    /// Hodoku library file, with an entry on each line
+    library: PathBuf,
+}
+
+pub fn run(args: Args) -> super::Result {
+    let lines = streams::open_input(Some(&args.library))?.lines();
+    let mut stdout = io::stdout().lock();
+
+    let mut num_checked = 0;
+    let mut num_skipped = 0;
+    let mut num_mismatches = 0;
+
+    for (idx, line) in lines.enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let entry: Entry = match line.parse() {
+            Ok(entry) => entry,
+            Err(err) => {
+                writeln!(stdout, "line {}: invalid entry: {}", idx + 1, err)?;
+                num_mismatches += 1;
+                continue;
+            }
+        };
+        let Some(technique) = entry.technique else {
+            if verbosity::get() >= Verbosity::Verbose {
+                eprintln!("line {}: skipped unknown technique {}", idx + 1, entry.code);
+            }
+            num_skipped += 1;
+            continue;
+        };
+        num_checked += 1;
+        match entry.find() {
+            Some(step) if entry.matches(&step) => {
+                if verbosity::get() >= Verbosity::Verbose {
+                    eprintln!("line {}: {}", idx + 1, step.hodoku());
+                }
+            }
+            found => {
+                let found = match found {
+                    Some(step) => format!("found {}", step.hodoku()),
+                    None => "found no step".to_string(),
+                };
+                writeln!(
+                    stdout,
+                    "line {}: expected {}: {}, {}",
+                    idx + 1,
+                    technique.hodoku_name(),
+                    effects(&entry),
+                    found
+                )?;
+                num_mismatches += 1;
+            }
+        }
+    }
+    stdout.flush()?;
+
+    if verbosity::summary() {
+        eprintln!("Library file: {}", args.library.display());
+        eprintln!("Total checked: {}", num_checked);
+        eprintln!("Total skipped: {}", num_skipped);
+        eprintln!("Total mismatches: {}", num_mismatches);
+    }
+
+    if num_mismatches > 0 {
+        return Err(Error::Failures(format!("{} mismatches", num_mismatches)));
+    }
+    Ok(())
+}
+
+/// Describe the expected effects of an entry.
+fn effects(entry: &Entry) -> String {
+    let placements = entry
+        .placements
+        .iter()
+        .map(|(coords, value)| format!("{}={}", cell_name(*coords), value));
+    let eliminations = entry
+        .eliminations
+        .iter()
+        .map(|(coords, value)| format!("{}<>{}", cell_name(*coords), value));
+    placements
+        .chain(eliminations)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,627 @@
+//! Reading of inputs containing a puzzle on each line, and reporting of the
+//! puzzles which fail to be processed.
+
+use super::streams::{self, Compression};
+use super::{progress, verbosity, Error, Result};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fs, iter, thread};
+use sudoku_solver::random::Rng;
+use sudoku_solver::Puzzle;
+
+/// Arguments for the inputs of a subcommand.
+#[derive(clap::Args)]
+pub struct Inputs {
+    /// Files containing a puzzle on each line, glob patterns matching such
+    /// files, or directories containing such files [default: standard input]
+    #[arg(value_name = "INPUT")]
+    paths: Vec<PathBuf>,
+    /// Skip this many puzzles at the start of the input
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    skip: usize,
+    /// Process at most this many puzzles
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+    /// Process a random sample of this many puzzles, preserving their order
+    #[arg(long, value_name = "N")]
+    sample: Option<usize>,
+    /// Seed for the random sample [default: current time]
+    #[arg(long, value_name = "SEED", requires = "sample")]
+    sample_seed: Option<u64>,
+}
+
+impl Inputs {
+    /// Resolve the input files, expanding glob patterns and directories in
+    /// sorted order. No paths or a dash ('-') refers to standard input.
+    pub fn files(&self) -> io::Result<Files> {
+        let selection = Selection {
+            skip: self.skip,
+            limit: self.limit,
+            sample: self.sample.map(|size| (size, self.seed())),
+        };
+        if self.paths.is_empty() {
+            return Ok(Files {
+                paths: vec![None],
+                selection,
+            });
+        }
+        let mut paths = Vec::new();
+        for path in &self.paths {
+            if path == Path::new("-") {
+                paths.push(None);
+            } else if path.is_dir() {
+                let mut entries = Vec::new();
+                for entry in fs::read_dir(path)? {
+                    let entry = entry?;
+                    if entry.file_type()?.is_file() {
+                        entries.push(entry.path());
+                    }
+                }
+                entries.sort();
+                paths.extend(entries.into_iter().map(Some));
+            } else if !path.exists() && is_pattern(path) {
+                let matches = expand(path)?;
+                if matches.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("no files match {}", path.display()),
+                    ));
+                }
+                paths.extend(matches.into_iter().map(Some));
+            } else {
+                paths.push(Some(path.clone()));
+            }
+        }
+        Ok(Files { paths, selection })
+    }
+
+    /// Seed for the random sample, which is shown if it was not given such
+    /// that the sample can be reproduced.
+    fn seed(&self) -> u64 {
+        self.sample_seed.unwrap_or_else(|| {
+            let seed = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+            if verbosity::summary() {
+                eprintln!("Sample seed: {}", seed);
+            }
+            seed
+        })
+    }
+
+    /// Follow the input files like `tail -f`, yielding batches of the lines
+    /// holding puzzles as they are appended to them, along with those of
+    /// files created in the input directories later on, which are numbered
+    /// after the input files. Compressed files are read as a whole once they
+    /// stop growing, while files which fail to be read are reported and no
+    /// longer followed. Standard input cannot be followed. Ends once the
+    /// limit is reached or the process is interrupted.
+    pub fn follow(&self, files: &Files) -> io::Result<Follow> {
+        let dirs: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .filter(|path| path.is_dir())
+            .cloned()
+            .collect();
+        let mut follow = Follow {
+            files: Vec::new(),
+            seen: HashSet::new(),
+            pending: Vec::new(),
+            named: files.len() > 1 || !dirs.is_empty(),
+            skip: self.skip,
+            remaining: self.limit.unwrap_or(usize::MAX),
+            dirs,
+        };
+        for path in files.paths() {
+            let Some(path) = path else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "standard input cannot be followed",
+                ));
+            };
+            // The input files must exist up front, unlike those created later.
+            File::open(path).map_err(|err| {
+                io::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+            })?;
+            follow.add(path.to_path_buf());
+        }
+        streams::stop_on_interrupt();
+        Ok(follow)
+    }
+}
+
+/// Interval at which followed inputs are checked for new lines and files.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Greatest number of lines of followed inputs read ahead at once.
+const FOLLOW_BATCH: usize = 4096;
+
+/// Batches of the lines of followed input files, see [`Inputs::follow`].
+pub struct Follow {
+    /// Directories of which new files are followed.
+    dirs: Vec<PathBuf>,
+    files: Vec<Followed>,
+    /// Paths of the files being followed.
+    seen: HashSet<PathBuf>,
+    /// Lines which have been read but not yet yielded.
+    pending: Vec<Line>,
+    /// Whether lines are attributed to their file by name.
+    named: bool,
+    /// Number of lines still to be skipped.
+    skip: usize,
+    /// Number of lines still to be yielded.
+    remaining: usize,
+}
+
+/// A followed input file.
+struct Followed {
+    path: PathBuf,
+    name: Arc<str>,
+    stream: Stream,
+}
+
+/// How a followed input file is read.
+enum Stream {
+    /// Too little has been written to tell whether the file is compressed.
+    Unknown,
+    /// Uncompressed text, which is read as it is appended.
+    Text {
+        reader: BufReader<File>,
+        /// Number of complete lines read so far.
+        lines: usize,
+        /// Start of a line of which the end has not been written yet.
+        partial: String,
+    },
+    /// Compressed file along with its length at the previous check, which is
+    /// decompressed once the length stays the same.
+    Compressed(u64),
+    /// Lines of a compressed file which has stopped growing.
+    Decompressed(Box<dyn Iterator<Item = io::Result<Line>>>),
+    /// File which has been read completely or failed to be read.
+    Done,
+}
+
+impl Follow {
+    /// Start following a file from its beginning.
+    fn add(&mut self, path: PathBuf) {
+        self.files.push(Followed {
+            name: Arc::from(path.display().to_string()),
+            path: path.clone(),
+            stream: Stream::Unknown,
+        });
+        self.seen.insert(path);
+    }
+
+    /// Pick up the files created in the followed directories in sorted order,
+    /// and read the lines appended to the followed files, up to a batch.
+    fn poll(&mut self) -> io::Result<()> {
+        let mut created = Vec::new();
+        for dir in &self.dirs {
+            for entry in fs::read_dir(dir)? {
+                let entry = entry?;
+                if entry.file_type()?.is_file() && !self.seen.contains(&entry.path()) {
+                    created.push(entry.path());
+                }
+            }
+        }
+        created.sort();
+        for path in created {
+            self.add(path);
+        }
+        for (file, followed) in self.files.iter_mut().enumerate() {
+            let name = self.named.then(|| followed.name.clone());
+            if let Err(err) = followed.read(file, name, &mut self.pending) {
+                progress::clear();
+                eprintln!("{}: {}, no longer following it", followed.name, err);
+                followed.stream = Stream::Done;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Followed {
+    /// Read the complete lines of the file which have not been read yet,
+    /// until the pending lines make up a batch.
+    fn read(
+        &mut self,
+        file: usize,
+        name: Option<Arc<str>>,
+        pending: &mut Vec<Line>,
+    ) -> io::Result<()> {
+        if let Stream::Unknown = self.stream {
+            let mut magic = Vec::with_capacity(4);
+            File::open(&self.path)?.take(4).read_to_end(&mut magic)?;
+            // Files holding a complete line may be too short for the magic.
+            if magic.len() < 4 && !magic.contains(&b'\n') {
+                return Ok(());
+            }
+            self.stream = match Compression::from_magic(&magic) {
+                Compression::None => Stream::Text {
+                    reader: BufReader::new(File::open(&self.path)?),
+                    lines: 0,
+                    partial: String::new(),
+                },
+                _ => Stream::Compressed(u64::MAX),
+            };
+        }
+        if let Stream::Compressed(length) = self.stream {
+            let current = fs::metadata(&self.path)?.len();
+            if current != length {
+                self.stream = Stream::Compressed(current);
+                return Ok(());
+            }
+            let reader = streams::open_input(Some(&self.path))?;
+            self.stream = Stream::Decompressed(Box::new(lines(reader, file, name.clone())));
+        }
+        while pending.len() < FOLLOW_BATCH {
+            match &mut self.stream {
+                Stream::Text {
+                    reader,
+                    lines,
+                    partial,
+                } => {
+                    if reader.read_line(partial)? == 0 || !partial.ends_with('\n') {
+                        break;
+                    }
+                    *lines += 1;
+                    let text = std::mem::take(partial);
+                    pending.extend(parse_line(&text, file, name.clone(), *lines));
+                }
+                Stream::Decompressed(lines) => match lines.next() {
+                    Some(line) => pending.push(line?),
+                    None => self.stream = Stream::Done,
+                },
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Follow {
+    type Item = io::Result<Vec<Line>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 && !streams::interrupted() {
+            if let Err(err) = self.poll() {
+                return Some(Err(err));
+            }
+            let read = self.pending.len();
+            let skipped = self.skip.min(read);
+            self.pending.drain(..skipped);
+            self.skip -= skipped;
+            self.pending.truncate(self.remaining);
+            if !self.pending.is_empty() {
+                self.remaining -= self.pending.len();
+                return Some(Ok(std::mem::take(&mut self.pending)));
+            }
+            if read == 0 {
+                thread::sleep(FOLLOW_INTERVAL);
+            }
+        }
+        None
+    }
+}
+
+/// Selection of the puzzles to process among those in the input files.
+struct Selection {
+    skip: usize,
+    limit: Option<usize>,
+    sample: Option<(usize, u64)>,
+}
+
+/// Whether a path contains glob pattern characters.
+fn is_pattern(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// Find the files matching a glob pattern, in sorted order.
+fn expand(pattern: &Path) -> io::Result<Vec<PathBuf>> {
+    let invalid =
+        |err: &dyn fmt::Display| io::Error::new(io::ErrorKind::InvalidInput, err.to_string());
+    let mut matches = Vec::new();
+    for path in glob::glob(&pattern.to_string_lossy()).map_err(|err| invalid(&err))? {
+        let path = path.map_err(|err| invalid(&err))?;
+        if path.is_file() {
+            matches.push(path);
+        }
+    }
+    Ok(matches)
+}
+
+/// The resolved input files of a subcommand, where `None` refers to standard
+/// input.
+pub struct Files {
+    paths: Vec<Option<PathBuf>>,
+    selection: Selection,
+}
+
+impl Files {
+    /// Number of input files.
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Paths of the input files.
+    pub fn paths(&self) -> impl Iterator<Item = Option<&Path>> {
+        self.paths.iter().map(Option::as_deref)
+    }
+
+    /// Name of an input file for showing to the user.
+    pub fn name(&self, file: usize) -> String {
+        streams::display_name(self.paths[file].as_ref(), "standard input")
+    }
+
+    /// Write the name of the input file to standard error, or the number of
+    /// input files if there are multiple.
+    pub fn print_name(&self) {
+        match self.len() {
+            1 => eprintln!("Input file: {}", self.name(0)),
+            n => eprintln!("Input files: {}", n),
+        }
+    }
+
+    /// Iterate over the selected lines holding puzzles of all input files in
+    /// order, skipping blank lines and comments.
+    /// Lines are only attributed to their file by name if there are multiple.
+    pub fn lines(&self) -> impl Iterator<Item = io::Result<Line>> + '_ {
+        self.select(self.paths.iter().enumerate().flat_map(move |(file, path)| {
+            let name = self.name(file);
+            let lines: Box<dyn Iterator<Item = io::Result<Line>>> =
+                match streams::open_input(path.as_deref()) {
+                    Ok(reader) => {
+                        let name = (self.len() > 1).then(|| Arc::from(name));
+                        Box::new(lines(reader, file, name))
+                    }
+                    Err(err) => Box::new(iter::once(Err(io::Error::new(
+                        err.kind(),
+                        format!("{}: {}", name, err),
+                    )))),
+                };
+            lines
+        }))
+    }
+
+    /// Select the items to process using `--skip`, `--limit` and `--sample`,
+    /// in that order. A sample is taken using reservoir sampling, which reads
+    /// all items up front but only keeps the sampled ones.
+    pub fn select<'a, T: 'a>(
+        &self,
+        items: impl Iterator<Item = io::Result<T>> + 'a,
+    ) -> Box<dyn Iterator<Item = io::Result<T>> + 'a> {
+        let items = items
+            .skip(self.selection.skip)
+            .take(self.selection.limit.unwrap_or(usize::MAX));
+        let Some((size, seed)) = self.selection.sample else {
+            return Box::new(items);
+        };
+        let mut rng = Rng::new(seed);
+        let mut reservoir = Vec::with_capacity(size);
+        for (idx, item) in items.enumerate() {
+            let item = match item {
+                Ok(item) => item,
+                Err(err) => return Box::new(iter::once(Err(err))),
+            };
+            if reservoir.len() < size {
+                reservoir.push((idx, item));
+            } else {
+                let replaced = rng.below(idx + 1);
+                if replaced < size {
+                    reservoir[replaced] = (idx, item);
+                }
+            }
+        }
+        reservoir.sort_by_key(|(idx, _)| *idx);
+        Box::new(reservoir.into_iter().map(|(_, item)| Ok(item)))
+    }
+
+    /// Number of items selected among a total number of items.
+    pub fn selected(&self, total: u64) -> u64 {
+        let mut selected = total.saturating_sub(self.selection.skip as u64);
+        if let Some(limit) = self.selection.limit {
+            selected = selected.min(limit as u64);
+        }
+        if let Some((size, _)) = self.selection.sample {
+            selected = selected.min(size as u64);
+        }
+        selected
+    }
+
+    /// Write the number of puzzles processed from each input file to
+    /// standard error, if there are multiple.
+    pub fn summarize(
+        &self,
+        done: &[usize],
+        done_label: &str,
+        failed: &[usize],
+        failed_label: &str,
+    ) {
+        if self.len() < 2 {
+            return;
+        }
+        for file in 0..self.len() {
+            eprintln!(
+                "{}: {} {}, {} {}",
+                self.name(file),
+                done.get(file).copied().unwrap_or(0),
+                done_label,
+                failed.get(file).copied().unwrap_or(0),
+                failed_label
+            );
+        }
+    }
+}
+
+/// Location of a line among the input files.
+#[derive(Clone)]
+pub struct Location {
+    /// Index of the input file.
+    pub file: usize,
+    /// Name of the input file, if there are multiple.
+    name: Option<Arc<str>>,
+    /// Line number, starting from one.
+    pub line: usize,
+    /// Label of the puzzle on the line along with the separator following
+    /// it, if any.
+    label: Option<(String, char)>,
+}
+
+impl Location {
+    /// Location of an unlabeled record of an input file, such as those of a
+    /// memory-mapped file, which are not read as lines.
+    #[cfg(feature = "mmap")]
+    pub fn record(files: &Files, file: usize, line: usize) -> Self {
+        Self {
+            file,
+            name: (files.len() > 1).then(|| Arc::from(files.name(file))),
+            line,
+            label: None,
+        }
+    }
+
+    /// Name of the input file, if there are multiple.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Label of the puzzle on the line, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_ref().map(|(label, _)| label.as_str())
+    }
+
+    /// Prefix of output lines about the puzzle on the line, consisting of its
+    /// label and the same separator as in the input, such that results can be
+    /// joined back to their labels.
+    pub fn prefix(&self) -> String {
+        match &self.label {
+            Some((label, separator)) => format!("{}{}", label, separator),
+            None => String::new(),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(fmt, "{}, line {}", name, self.line)?,
+            None => write!(fmt, "line {}", self.line)?,
+        }
+        match self.label() {
+            Some(label) => write!(fmt, " ({})", label),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A line of an input, which contains a puzzle optionally preceded
+/// by a label and a comma or tab, as in `id,puzzle`.
+pub struct Line {
+    /// Location of the line.
+    pub location: Location,
+    /// Puzzle on the line, without its label and surrounding whitespace.
+    pub text: String,
+}
+
+impl Line {
+    /// Parse the puzzle on the line.
+    pub fn puzzle(&self) -> std::result::Result<Puzzle, &'static str> {
+        self.text.parse()
+    }
+}
+
+/// Whether a line of an input holds no puzzle, being blank or a comment
+/// starting with '#', as is common in published collections.
+pub fn is_skipped(line: &str) -> bool {
+    let line = line.trim_start();
+    line.is_empty() || line.starts_with('#')
+}
+
+/// Iterate over the lines of an input file holding puzzles, keeping the line
+/// numbers of the file.
+fn lines(
+    reader: impl BufRead,
+    file: usize,
+    name: Option<Arc<str>>,
+) -> impl Iterator<Item = io::Result<Line>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(move |(idx, line)| match line {
+            Err(err) => Some(Err(err)),
+            Ok(line) => parse_line(&line, file, name.clone(), idx + 1).map(Ok),
+        })
+}
+
+/// Split a line of an input file into its label and puzzle, or `None` if it
+/// holds no puzzle.
+fn parse_line(line: &str, file: usize, name: Option<Arc<str>>, number: usize) -> Option<Line> {
+    if is_skipped(line) {
+        return None;
+    }
+    let (label, text) = match line.rfind([',', '\t']) {
+        Some(idx) => {
+            let separator = line[idx..].chars().next().unwrap_or(',');
+            let label = line[..idx].trim().to_string();
+            (Some((label, separator)), &line[idx + 1..])
+        }
+        None => (None, line),
+    };
+    Some(Line {
+        location: Location {
+            file,
+            name,
+            line: number,
+            label,
+        },
+        text: text.trim().to_string(),
+    })
+}
+
+/// Count an item of an input file, in a count per input file.
+pub fn tally(counts: &mut Vec<usize>, file: usize) {
+    if counts.len() <= file {
+        counts.resize(file + 1, 0);
+    }
+    counts[file] += 1;
+}
+
+/// Tally of the puzzles which failed, each of which is reported on standard
+/// error along with its location.
+#[derive(Default)]
+pub struct Failures {
+    per_file: Vec<usize>,
+}
+
+impl Failures {
+    /// Report the failure of the puzzle on a line.
+    pub fn report(&mut self, location: &Location, reason: impl fmt::Display) {
+        progress::clear();
+        eprintln!("{}: {}", location, reason);
+        tally(&mut self.per_file, location.file);
+    }
+
+    /// Number of puzzles which failed.
+    pub fn count(&self) -> usize {
+        self.per_file.iter().sum()
+    }
+
+    /// Number of puzzles which failed per input file.
+    pub fn per_file(&self) -> &[usize] {
+        &self.per_file
+    }
+
+    /// Finish processing, resulting in an error if any puzzle failed.
+    pub fn finish(self) -> Result {
+        match self.count() {
+            0 => Ok(()),
+            1 => Err(Error::Failures("1 puzzle failed".to_string())),
+            n => Err(Error::Failures(format!("{} puzzles failed", n))),
+        }
+    }
+}
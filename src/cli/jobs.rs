@@ -0,0 +1,108 @@
+//! Processing of puzzles across multiple threads, while preserving the order
+//! of their results.
+
+use std::io;
+use std::num::NonZeroUsize;
+use std::{panic, thread};
+
+/// Number of items handed to each thread at once.
+const CHUNK_SIZE: usize = 256;
+
+/// Arguments for configuring the number of threads.
+#[derive(clap::Args)]
+pub struct Jobs {
+    /// Number of threads to process puzzles on [default: number of CPUs]
+    #[arg(short, long)]
+    jobs: Option<NonZeroUsize>,
+}
+
+impl Jobs {
+    /// The configured number of threads.
+    pub fn count(&self) -> usize {
+        self.jobs
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+    }
+}
+
+/// Map items across a number of threads, consuming the results in the order
+/// of the items. Items are read in chunks such that memory use is bounded
+/// regardless of the number of items. Each thread initializes its own state
+/// for the mapping, such as a reusable solver.
+pub fn map_ordered<T: Send, S, R: Send>(
+    jobs: usize,
+    items: impl Iterator<Item = io::Result<T>>,
+    init: impl Fn() -> S + Sync,
+    map: impl Fn(&mut S, T) -> R + Sync,
+    mut consume: impl FnMut(R) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut items = items.fuse();
+    let mut state = init();
+    loop {
+        let chunk = items
+            .by_ref()
+            .take(jobs * CHUNK_SIZE)
+            .collect::<io::Result<Vec<T>>>()?;
+        if chunk.is_empty() {
+            return Ok(());
+        }
+
+        let results: Vec<R> = if jobs == 1 {
+            chunk
+                .into_iter()
+                .map(|item| map(&mut state, item))
+                .collect()
+        } else {
+            let mut parts = Vec::with_capacity(jobs);
+            let mut rest = chunk;
+            let part_size = rest.len().div_ceil(jobs);
+            while !rest.is_empty() {
+                let tail = rest.split_off(part_size.min(rest.len()));
+                parts.push(rest);
+                rest = tail;
+            }
+            thread::scope(|scope| {
+                let handles: Vec<_> = parts
+                    .into_iter()
+                    .map(|part| {
+                        scope.spawn(|| {
+                            let mut state = init();
+                            part.into_iter()
+                                .map(|item| map(&mut state, item))
+                                .collect::<Vec<R>>()
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .flat_map(|handle| {
+                        handle
+                            .join()
+                            .unwrap_or_else(|payload| panic::resume_unwind(payload))
+                    })
+                    .collect()
+            })
+        };
+
+        for result in results {
+            consume(result)?;
+        }
+    }
+}
+
+/// Map batches of items across a number of threads as they come, consuming
+/// the results of each batch before waiting for the next, such that the
+/// results of items which trickle in are not held back until a chunk is
+/// complete.
+pub fn map_batches<T: Send, S, R: Send>(
+    jobs: usize,
+    batches: impl Iterator<Item = io::Result<Vec<T>>>,
+    init: impl Fn() -> S + Sync,
+    map: impl Fn(&mut S, T) -> R + Sync,
+    mut consume: impl FnMut(R) -> io::Result<()>,
+) -> io::Result<()> {
+    for batch in batches {
+        map_ordered(jobs, batch?.into_iter().map(Ok), &init, &map, &mut consume)?;
+    }
+    Ok(())
+}
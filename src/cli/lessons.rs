@@ -0,0 +1,89 @@
+use super::input::{self, Failures, Inputs};
+use super::jobs::{self, Jobs};
+use super::progress::Progress;
+use super::{streams, verbosity, Result};
+use clap::Parser;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use sudoku_solver::logic::{self, Technique};
+
+/// Extract positions in which a technique is the key next step from the
+/// logical solves of the puzzles, for building tutorials and quizzes. Each
+/// position is written as a partially filled grid on a line, followed by the
+/// step taken using the technique separated by a tab.
+#[derive(Parser)]
+pub struct Args {
+    /// Technique to find positions for, such as x-wing
+    #[arg(long, value_name = "TECHNIQUE")]
+    technique: Technique,
+    /// Take at most this many positions from each puzzle
+    #[arg(long, value_name = "N")]
+    per_puzzle: Option<usize>,
+    #[command(flatten)]
+    inputs: Inputs,
+    /// File to write the positions to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    #[command(flatten)]
+    jobs: Jobs,
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let mut writer = streams::create_output(args.output.as_deref())?;
+
+    let mut num_positions = 0;
+    let mut num_with = Vec::new();
+    let mut num_without = Vec::new();
+    let mut failures = Failures::default();
+
+    let mut progress = Progress::new(&files);
+    jobs::map_ordered(
+        args.jobs.count(),
+        files.lines(),
+        || (),
+        |_, line| {
+            let examples = line
+                .puzzle()
+                .and_then(|puzzle| logic::examples(&puzzle, args.technique));
+            (line.location, examples)
+        },
+        |(location, examples)| {
+            progress.inc();
+            let examples = match examples {
+                Ok(examples) => examples,
+                Err(err) => {
+                    failures.report(&location, err);
+                    return Ok(());
+                }
+            };
+            match examples.is_empty() {
+                true => input::tally(&mut num_without, location.file),
+                false => input::tally(&mut num_with, location.file),
+            }
+            let count = args.per_puzzle.unwrap_or(usize::MAX);
+            for (position, step) in examples.into_iter().take(count) {
+                writeln!(writer, "{}{}\t{}", location.prefix(), position, step)?;
+                num_positions += 1;
+            }
+            Ok(())
+        },
+    )?;
+    progress.finish();
+    writer.finish()?;
+
+    if verbosity::summary() {
+        files.summarize(&num_with, "with positions", failures.per_file(), "failed");
+        eprintln!("Total positions: {}", num_positions);
+        eprintln!(
+            "Total puzzles with positions: {}",
+            num_with.iter().sum::<usize>()
+        );
+        eprintln!(
+            "Total puzzles without: {}",
+            num_without.iter().sum::<usize>()
+        );
+        eprintln!("Total failed: {}", failures.count());
+    }
+    failures.finish()
+}
@@ -0,0 +1,129 @@
+//! Limits on the requests of each client of the `serve` subcommand, such
+//! that a single client cannot starve others of the threads of the server,
+//! and on the WebSocket connections open at once, each of which takes a
+//! thread of its own.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of tracked clients above which idle clients are forgotten.
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Requests of a client which are being or have recently been handled.
+struct Client {
+    /// Number of requests the client may make right away, refilling at the
+    /// rate limit up to a burst of a minute's worth of requests.
+    tokens: f64,
+    updated: Instant,
+    /// Number of requests of the client being handled.
+    active: usize,
+}
+
+/// A request turned away due to the limits of its client.
+pub enum Rejection {
+    /// The client made too many requests recently, and may retry after the
+    /// given time.
+    RateLimited(Duration),
+    /// The client has too many requests being handled.
+    TooManyActive,
+}
+
+/// Limits on the requests of each client, identified by its IP address.
+pub struct Limits {
+    /// Number of requests a client may make each minute.
+    per_minute: Option<u32>,
+    /// Number of requests of a client which may be handled at once.
+    per_client: Option<usize>,
+    clients: Mutex<HashMap<IpAddr, Client>>,
+    /// Number of WebSocket connections which may be open at once.
+    max_connections: usize,
+    connections: AtomicUsize,
+}
+
+impl Limits {
+    pub fn new(per_minute: Option<u32>, per_client: Option<usize>, max_connections: usize) -> Self {
+        Self {
+            per_minute,
+            per_client,
+            clients: Mutex::new(HashMap::new()),
+            max_connections,
+            connections: AtomicUsize::new(0),
+        }
+    }
+
+    /// Open a WebSocket connection of any client, returning a guard which
+    /// closes it when dropped, or `None` if too many are open already.
+    pub fn connect(&self) -> Option<Connection<'_>> {
+        self.connections
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |open| {
+                (open < self.max_connections).then_some(open + 1)
+            })
+            .ok()
+            .map(|_| Connection { limits: self })
+    }
+
+    /// Admit a request of a client, returning a guard which releases it when
+    /// it has been handled.
+    pub fn admit(&self, ip: IpAddr) -> Result<Admission<'_>, Rejection> {
+        let now = Instant::now();
+        let burst = self.per_minute.map_or(0.0, f64::from);
+        let mut clients = self.clients.lock().unwrap();
+        if clients.len() > PRUNE_THRESHOLD {
+            clients.retain(|_, client| client.active > 0 || refill(client, burst, now) < burst);
+        }
+        let client = clients.entry(ip).or_insert(Client {
+            tokens: burst,
+            updated: now,
+            active: 0,
+        });
+        if self.per_client.is_some_and(|limit| client.active >= limit) {
+            return Err(Rejection::TooManyActive);
+        }
+        if self.per_minute.is_some() {
+            client.tokens = refill(client, burst, now);
+            client.updated = now;
+            if client.tokens < 1.0 {
+                let wait = (1.0 - client.tokens) * 60.0 / burst;
+                return Err(Rejection::RateLimited(Duration::from_secs_f64(wait)));
+            }
+            client.tokens -= 1.0;
+        }
+        client.active += 1;
+        Ok(Admission { limits: self, ip })
+    }
+}
+
+/// The tokens of a client at a point in time, refilled since its last update.
+fn refill(client: &Client, burst: f64, now: Instant) -> f64 {
+    let elapsed = now.duration_since(client.updated).as_secs_f64();
+    (client.tokens + elapsed * burst / 60.0).min(burst)
+}
+
+/// An admitted request, which is released when dropped.
+pub struct Admission<'a> {
+    limits: &'a Limits,
+    ip: IpAddr,
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        let mut clients = self.limits.clients.lock().unwrap();
+        if let Some(client) = clients.get_mut(&self.ip) {
+            client.active -= 1;
+        }
+    }
+}
+
+/// An open WebSocket connection, which is closed when dropped.
+pub struct Connection<'a> {
+    limits: &'a Limits,
+}
+
+impl Drop for Connection<'_> {
+    fn drop(&mut self) {
+        self.limits.connections.fetch_sub(1, Ordering::SeqCst);
+    }
+}
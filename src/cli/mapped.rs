@@ -0,0 +1,102 @@
+//! Memory-mapped input files of fixed-width records, which are iterated
+//! without reading them into buffers or allocating for each record, such
+//! that reading gets out of the way when solving huge collections.
+//!
+//! Records are either lines of exactly 81 values, ending in a line feed or a
+//! carriage return and line feed, or packed binary records as written by
+//! `convert --to binary`. The width is determined from the first record.
+
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use sudoku_solver::binary;
+use sudoku_solver::Puzzle;
+
+/// Encoding of the records of a file.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Text,
+    Binary,
+}
+
+/// A memory-mapped input file of fixed-width records.
+pub struct Mapped {
+    map: Mmap,
+    kind: Kind,
+    /// Length of each record in bytes, including its line ending.
+    width: usize,
+}
+
+impl Mapped {
+    /// Map an input file, determining the width of its records.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let invalid = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), message),
+            )
+        };
+        let file = File::open(path)?;
+        // SAFETY: the map is only read, and the file is not expected to be
+        // modified while solving, as is assumed for all inputs.
+        let map = unsafe { Mmap::map(&file)? };
+        let (kind, width) = match map.first() {
+            None => (Kind::Text, 82),
+            Some(header) if *header == binary::VERSION << 4 => (Kind::Binary, binary::PACKED_LEN),
+            Some(header) if *header >> 4 == binary::VERSION => {
+                return Err(invalid("run-length binary records vary in width"));
+            }
+            Some(_) => match map.iter().position(|byte| *byte == b'\n') {
+                Some(81) => (Kind::Text, 82),
+                Some(82) if map[81] == b'\r' => (Kind::Text, 83),
+                None if map.len() == 81 => (Kind::Text, 82),
+                _ => return Err(invalid("records must be lines of exactly 81 values")),
+            },
+        };
+        // The line ending of the last line may be missing.
+        let missing = kind == Kind::Text && map.len() % width == 81;
+        if map.len() % width != 0 && !missing {
+            return Err(invalid("file is not a whole number of records"));
+        }
+        Ok(Self { map, kind, width })
+    }
+
+    /// Number of records in the file.
+    pub fn len(&self) -> usize {
+        self.map.len().div_ceil(self.width)
+    }
+
+    /// Iterate over the records along with their line number, starting from
+    /// one. Text records not ending in a line ending fail.
+    pub fn records(&self) -> impl Iterator<Item = io::Result<(usize, &[u8])>> {
+        self.map
+            .chunks(self.width)
+            .enumerate()
+            .map(move |(idx, record)| {
+                let values = match self.kind {
+                    Kind::Binary => record,
+                    Kind::Text => match &record[81.min(record.len())..] {
+                        b"" | b"\n" | b"\r\n" if record.len() >= 81 => &record[..81],
+                        _ => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("line {}: records must be of equal width", idx + 1),
+                            ))
+                        }
+                    },
+                };
+                Ok((idx + 1, values))
+            })
+    }
+
+    /// Parse the puzzle of a record.
+    pub fn puzzle(&self, record: &[u8]) -> Result<Puzzle, &'static str> {
+        match self.kind {
+            Kind::Binary => binary::decode(record).map(|(puzzle, _)| puzzle),
+            Kind::Text => std::str::from_utf8(record)
+                .map_err(|_| "puzzle contains invalid characters")?
+                .parse(),
+        }
+    }
+}
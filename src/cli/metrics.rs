@@ -0,0 +1,154 @@
+//! Metrics of the `serve` subcommand, exposed in the text format of
+//! Prometheus on the /metrics endpoint.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+use sudoku_solver::logic::Step;
+
+/// Upper bounds in seconds of the buckets of the latency histograms.
+const BUCKETS: [f64; 10] = [0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+/// Latencies of the requests to an endpoint.
+#[derive(Default)]
+struct Histogram {
+    /// Number of requests within each bucket, not counting lower buckets.
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+/// The metrics collected since the server started.
+struct Registry {
+    /// Number of requests by endpoint and status code.
+    requests: BTreeMap<(&'static str, u16), u64>,
+    latencies: BTreeMap<&'static str, Histogram>,
+    /// Number of requests which exceeded a limit on the search, by endpoint.
+    timeouts: BTreeMap<&'static str, u64>,
+    /// Number of steps of each technique in rated or hinted puzzles.
+    techniques: BTreeMap<&'static str, u64>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    requests: BTreeMap::new(),
+    latencies: BTreeMap::new(),
+    timeouts: BTreeMap::new(),
+    techniques: BTreeMap::new(),
+});
+
+/// Record a request to an endpoint, along with its status code and how long
+/// it took to respond.
+pub fn request(endpoint: &'static str, status: u16, elapsed: Duration) {
+    let mut registry = REGISTRY.lock().unwrap();
+    *registry.requests.entry((endpoint, status)).or_default() += 1;
+    let histogram = registry.latencies.entry(endpoint).or_default();
+    let seconds = elapsed.as_secs_f64();
+    if let Some(bucket) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+        histogram.buckets[bucket] += 1;
+    }
+    histogram.count += 1;
+    histogram.sum += seconds;
+}
+
+/// Record a request to an endpoint which exceeded a limit on the search.
+pub fn timeout(endpoint: &'static str) {
+    *REGISTRY
+        .lock()
+        .unwrap()
+        .timeouts
+        .entry(endpoint)
+        .or_default() += 1;
+}
+
+/// Record the techniques of the steps of a rated or hinted puzzle.
+pub fn techniques(steps: &[Step]) {
+    let mut registry = REGISTRY.lock().unwrap();
+    for step in steps {
+        *registry
+            .techniques
+            .entry(step.technique.name())
+            .or_default() += 1;
+    }
+}
+
+/// The metrics in the text format of Prometheus.
+pub fn render() -> String {
+    let registry = REGISTRY.lock().unwrap();
+    let mut text = String::new();
+    writeln!(
+        text,
+        "# HELP sudoku_requests_total Number of requests by endpoint and status code."
+    )
+    .unwrap();
+    writeln!(text, "# TYPE sudoku_requests_total counter").unwrap();
+    for ((endpoint, status), count) in &registry.requests {
+        writeln!(
+            text,
+            "sudoku_requests_total{{endpoint=\"{}\",status=\"{}\"}} {}",
+            endpoint, status, count
+        )
+        .unwrap();
+    }
+    writeln!(
+        text,
+        "# HELP sudoku_request_duration_seconds Time taken to respond to requests."
+    )
+    .unwrap();
+    writeln!(text, "# TYPE sudoku_request_duration_seconds histogram").unwrap();
+    for (endpoint, histogram) in &registry.latencies {
+        let mut cumulative = 0;
+        for (bound, count) in BUCKETS.iter().zip(histogram.buckets) {
+            cumulative += count;
+            writeln!(
+                text,
+                "sudoku_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"{}\"}} {}",
+                endpoint, bound, cumulative
+            )
+            .unwrap();
+        }
+        writeln!(
+            text,
+            "sudoku_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"+Inf\"}} {}",
+            endpoint, histogram.count
+        )
+        .unwrap();
+        writeln!(
+            text,
+            "sudoku_request_duration_seconds_sum{{endpoint=\"{}\"}} {}",
+            endpoint, histogram.sum
+        )
+        .unwrap();
+        writeln!(
+            text,
+            "sudoku_request_duration_seconds_count{{endpoint=\"{}\"}} {}",
+            endpoint, histogram.count
+        )
+        .unwrap();
+    }
+    writeln!(
+        text,
+        "# HELP sudoku_timeouts_total Number of requests which exceeded a limit on the search."
+    )
+    .unwrap();
+    writeln!(text, "# TYPE sudoku_timeouts_total counter").unwrap();
+    for (endpoint, count) in &registry.timeouts {
+        writeln!(
+            text,
+            "sudoku_timeouts_total{{endpoint=\"{}\"}} {}",
+            endpoint, count
+        )
+        .unwrap();
+    }
+    writeln!(text, "# HELP sudoku_technique_steps_total Number of steps of each technique in rated or hinted puzzles.").unwrap();
+    writeln!(text, "# TYPE sudoku_technique_steps_total counter").unwrap();
+    for (technique, count) in &registry.techniques {
+        writeln!(
+            text,
+            "sudoku_technique_steps_total{{technique=\"{}\"}} {}",
+            technique, count
+        )
+        .unwrap();
+    }
+    text
+}
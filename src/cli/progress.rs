@@ -0,0 +1,157 @@
+//! Progress bar on standard error, showing the number of puzzles processed,
+//! the throughput and the estimated time remaining. It is only shown when
+//! standard error is a terminal, and neither when quiet nor when verbose.
+
+use super::input::{self, Files};
+use super::streams;
+use super::verbosity::{self, Verbosity};
+use std::io::{self, prelude::*, IsTerminal};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Minimum time between redraws of the progress bar.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Width of the bar itself, in characters.
+const BAR_WIDTH: usize = 30;
+
+/// Progress of processing a number of puzzles.
+pub struct Progress {
+    enabled: bool,
+    total: Option<u64>,
+    done: u64,
+    start: Instant,
+    last_draw: Option<Instant>,
+}
+
+impl Progress {
+    /// Start tracking the progress of processing the puzzles of the input
+    /// files. The total is known if none of the inputs is standard input, for
+    /// which the non-empty lines are counted up front.
+    pub fn new(files: &Files) -> Self {
+        let enabled = io::stderr().is_terminal() && verbosity::get() == Verbosity::Normal;
+        let total = if enabled {
+            files
+                .paths()
+                .map(|path| path.map_or(Err(()), |path| count_lines(path).map_err(|_| ())))
+                .sum::<Result<u64, ()>>()
+                .ok()
+                .map(|total| files.selected(total))
+        } else {
+            None
+        };
+        Self {
+            enabled,
+            total,
+            done: 0,
+            start: Instant::now(),
+            last_draw: None,
+        }
+    }
+
+    /// Start tracking the progress of processing a known number of puzzles,
+    /// such as when generating them.
+    pub fn with_total(total: u64) -> Self {
+        Self {
+            enabled: io::stderr().is_terminal() && verbosity::get() == Verbosity::Normal,
+            total: Some(total),
+            done: 0,
+            start: Instant::now(),
+            last_draw: None,
+        }
+    }
+
+    /// Start tracking the progress of processing an unknown number of
+    /// puzzles, such as when following inputs.
+    pub fn unbounded() -> Self {
+        Self {
+            enabled: io::stderr().is_terminal() && verbosity::get() == Verbosity::Normal,
+            total: None,
+            done: 0,
+            start: Instant::now(),
+            last_draw: None,
+        }
+    }
+
+    /// Exclude puzzles which are skipped from the total, such as those
+    /// processed before resuming.
+    pub fn exclude(&mut self, count: u64) {
+        self.total = self.total.map(|total| total.saturating_sub(count));
+    }
+
+    /// Record that a puzzle has been processed.
+    pub fn inc(&mut self) {
+        self.done += 1;
+        if self.enabled
+            && self
+                .last_draw
+                .is_none_or(|last| last.elapsed() >= REDRAW_INTERVAL)
+        {
+            self.draw();
+        }
+    }
+
+    /// Remove the progress bar, after processing has finished.
+    pub fn finish(&mut self) {
+        if self.enabled {
+            clear();
+        }
+    }
+
+    fn draw(&mut self) {
+        self.last_draw = Some(Instant::now());
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let rate = self.done as f64 / elapsed.max(f64::EPSILON);
+        let line = match self.total {
+            Some(total) if total > 0 => {
+                let fraction = (self.done as f64 / total as f64).min(1.0);
+                let filled = (fraction * BAR_WIDTH as f64) as usize;
+                let remaining = total.saturating_sub(self.done) as f64 / rate.max(f64::EPSILON);
+                format!(
+                    "[{}{}] {}/{} puzzles, {:.0}/s, ETA {}",
+                    "#".repeat(filled),
+                    "-".repeat(BAR_WIDTH - filled),
+                    self.done,
+                    total,
+                    rate,
+                    format_duration(remaining)
+                )
+            }
+            _ => format!("{} puzzles, {:.0}/s", self.done, rate),
+        };
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[K{}", line);
+        let _ = stderr.flush();
+    }
+}
+
+/// Clear the line of the progress bar if standard error is a terminal, such
+/// that other messages can be written.
+pub fn clear() {
+    let mut stderr = io::stderr().lock();
+    if stderr.is_terminal() {
+        let _ = write!(stderr, "\r\x1b[K");
+    }
+}
+
+/// Count the lines holding puzzles of a file, decompressing it if necessary.
+fn count_lines(path: &Path) -> io::Result<u64> {
+    let mut count = 0;
+    for line in streams::open_input(Some(path))?.lines() {
+        if !input::is_skipped(&line?) {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Format a number of seconds as hours, minutes and seconds.
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
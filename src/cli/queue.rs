@@ -0,0 +1,160 @@
+//! Asynchronous jobs of the `serve` subcommand, which process batches of
+//! puzzles in the background such that large batches need not be handled
+//! within a single request. Jobs are processed in order of submission by a
+//! few worker threads, and forgotten after many newer jobs have been
+//! submitted.
+
+use super::serve::{Budget, Failure, Handler};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Maximum number of jobs kept, beyond which the oldest finished job is
+/// forgotten.
+const MAX_JOBS: usize = 1000;
+
+/// Maximum number of jobs waiting to be processed, beyond which new jobs are
+/// refused.
+const MAX_QUEUED: usize = 100;
+
+/// Number of threads processing jobs.
+const WORKERS: usize = 4;
+
+/// A submitted batch of requests.
+struct Job {
+    total: usize,
+    started: bool,
+    /// Responses to the requests processed so far.
+    results: Vec<Value>,
+}
+
+impl Job {
+    fn done(&self) -> bool {
+        self.results.len() == self.total
+    }
+}
+
+/// The submitted jobs, along with the queue of the threads processing them.
+struct Queue {
+    jobs: BTreeMap<String, Job>,
+    /// Identifiers of the jobs in order of submission.
+    order: VecDeque<String>,
+    sender: Option<Sender<(String, Handler, Vec<Value>)>>,
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue {
+    jobs: BTreeMap::new(),
+    order: VecDeque::new(),
+    sender: None,
+});
+
+/// Submit a batch of requests to a handler as a job, returning its
+/// identifier.
+pub fn submit(handler: Handler, requests: Vec<Value>) -> Result<String, Failure> {
+    let mut queue = QUEUE.lock().unwrap();
+    if queue.jobs.values().filter(|job| !job.started).count() >= MAX_QUEUED {
+        return Err(Failure(503, "too many queued jobs".to_string()));
+    }
+    if queue.order.len() >= MAX_JOBS {
+        let jobs = &queue.jobs;
+        let finished = queue.order.iter().position(|id| jobs[id].done());
+        let Some(idx) = finished else {
+            return Err(Failure(503, "too many unfinished jobs".to_string()));
+        };
+        let id = queue.order.remove(idx).unwrap();
+        queue.jobs.remove(&id);
+    }
+
+    let id = new_id()?;
+    let job = Job {
+        total: requests.len(),
+        started: false,
+        results: Vec::with_capacity(requests.len()),
+    };
+    queue.jobs.insert(id.clone(), job);
+    queue.order.push_back(id.clone());
+    let sender = queue.sender.get_or_insert_with(|| {
+        let (sender, receiver) = mpsc::channel::<(String, Handler, Vec<Value>)>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..WORKERS {
+            let receiver = receiver.clone();
+            thread::spawn(move || loop {
+                // The lock is released before processing, such that the other
+                // workers can take the next jobs meanwhile.
+                let next = receiver.lock().unwrap().recv();
+                match next {
+                    Ok((id, handler, requests)) => process(&id, handler, requests),
+                    Err(_) => return,
+                }
+            });
+        }
+        sender
+    });
+    sender.send((id.clone(), handler, requests)).unwrap();
+    Ok(id)
+}
+
+/// Identifier of a new job, being 128 random bits from the operating system
+/// such that the jobs of other clients cannot be guessed.
+fn new_id() -> Result<String, Failure> {
+    let mut bytes = [0; 16];
+    getrandom::fill(&mut bytes)
+        .map_err(|err| Failure(500, format!("failed to generate a job id: {}", err)))?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Process the requests of a job, storing the response to each.
+fn process(id: &str, handler: Handler, requests: Vec<Value>) {
+    if let Some(job) = QUEUE.lock().unwrap().jobs.get_mut(id) {
+        job.started = true;
+    }
+    for request in requests {
+        let result = respond(handler, &request, Budget::start());
+        match QUEUE.lock().unwrap().jobs.get_mut(id) {
+            Some(job) => job.results.push(result),
+            None => return,
+        }
+    }
+}
+
+/// The response to a request of a batch or job within the given budget,
+/// which is an {"error"} object if it failed.
+pub fn respond(handler: Handler, request: &Value, budget: Budget) -> Value {
+    match handler(request, budget) {
+        Ok(response) => response,
+        Err(Failure(_, reason)) => json!({ "error": reason }),
+    }
+}
+
+/// The status of a job, with the number of requests processed so far.
+pub fn status(id: &str) -> Result<Value, Failure> {
+    let queue = QUEUE.lock().unwrap();
+    let job = queue.jobs.get(id).ok_or_else(unknown)?;
+    let status = match (job.started, job.done()) {
+        (_, true) => "done",
+        (true, false) => "running",
+        (false, false) => "queued",
+    };
+    Ok(json!({
+        "id": id,
+        "status": status,
+        "completed": job.results.len(),
+        "total": job.total,
+    }))
+}
+
+/// The responses to the requests of a finished job, in order.
+pub fn results(id: &str) -> Result<Value, Failure> {
+    let queue = QUEUE.lock().unwrap();
+    let job = queue.jobs.get(id).ok_or_else(unknown)?;
+    if !job.done() {
+        return Err(Failure(409, "job is not done".to_string()));
+    }
+    Ok(json!({ "id": id, "results": job.results }))
+}
+
+fn unknown() -> Failure {
+    Failure(404, "unknown job".to_string())
+}
@@ -0,0 +1,227 @@
+use super::calibrate;
+#[cfg(feature = "sqlite")]
+use super::database::Database;
+use super::format::Notation;
+use super::input::{self, Failures, Inputs};
+use super::jobs::{self, Jobs};
+use super::progress::Progress;
+use super::solve::NOT_UNIQUE;
+use super::usage::Usage;
+use super::{streams, verbosity, Result};
+use clap::{Parser, ValueEnum};
+use std::cmp::Reverse;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use sudoku_solver::logic::{self, Step};
+use sudoku_solver::rating::{self, Difficulty, Estimate, Rating};
+use sudoku_solver::Solver;
+
+/// Rate the difficulty of all puzzles in a file. Each puzzle is written
+/// followed by its difficulty, score and the hardest technique required,
+/// separated by tabs.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// File to write the rated puzzles to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Write the puzzles sorted by their score instead of in input order,
+    /// which keeps all of them in memory
+    #[arg(long, value_enum, value_name = "ORDER")]
+    sort: Option<Order>,
+    /// Notation of the names of the techniques
+    #[arg(long, value_enum, default_value_t = Notation::Default)]
+    notation: Notation,
+    /// Only estimate the difficulty using singles and locked candidates,
+    /// which is much faster and exact for easy and medium puzzles, while
+    /// harder puzzles are all estimated as hard. Each puzzle is written
+    /// followed by its estimated difficulty and the number of cells left
+    /// empty by those techniques
+    #[arg(long)]
+    estimate: bool,
+    /// Rate on the difficulty scale of this calibration file written by
+    /// `calibrate`, writing the level and score of each puzzle on that scale
+    /// instead of the difficulty tier and score
+    #[arg(long, value_name = "FILE", conflicts_with = "estimate")]
+    calibration: Option<PathBuf>,
+    /// Also record the ratings in this SQLite database, adding puzzles which
+    /// are not in it yet and updating the ratings of those which are
+    #[cfg(feature = "sqlite")]
+    #[arg(long, value_name = "FILE", conflicts_with = "estimate")]
+    database: Option<PathBuf>,
+    #[command(flatten)]
+    jobs: Jobs,
+}
+
+/// A puzzle rated by its steps, or only its estimated difficulty.
+enum Graded {
+    Rated(Vec<Step>),
+    Estimated(Estimate),
+}
+
+/// Order in which rated puzzles are sorted.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Order {
+    /// Easiest puzzles first
+    #[value(alias = "asc")]
+    Ascending,
+    /// Hardest puzzles first
+    #[value(alias = "desc")]
+    Descending,
+}
+
+pub fn run(args: Args) -> Result {
+    let calibration = args
+        .calibration
+        .as_deref()
+        .map(calibrate::load)
+        .transpose()?;
+    let files = args.inputs.files()?;
+    let mut writer = streams::create_output(args.output.as_deref())?;
+
+    let mut tally = [0u64; 5];
+    let mut levels = vec![
+        0u64;
+        calibration
+            .as_ref()
+            .map_or(0, |calibration| calibration.labels().len())
+    ];
+    let mut usage = Usage::default();
+    let mut num_rated = Vec::new();
+    let mut failures = Failures::default();
+    let mut sorted = Vec::new();
+
+    #[cfg(feature = "sqlite")]
+    let mut database = args.database.as_deref().map(Database::open).transpose()?;
+    let mut progress = Progress::new(&files);
+    jobs::map_ordered(
+        args.jobs.count(),
+        files.lines(),
+        || (),
+        |_, line| {
+            let graded = line.puzzle().and_then(|puzzle| {
+                // Ratings of puzzles with multiple solutions depend on the
+                // solution which happens to be found, so they are failures.
+                if Solver::count_solutions(&puzzle, 2) > 1 {
+                    return Err(NOT_UNIQUE);
+                }
+                let graded = match args.estimate {
+                    true => Graded::Estimated(rating::estimate(&puzzle)?),
+                    false => Graded::Rated(logic::solve(&puzzle)?),
+                };
+                Ok((puzzle, graded))
+            });
+            (line.location, graded)
+        },
+        |(location, graded)| {
+            progress.inc();
+            let (puzzle, steps) = match graded {
+                Ok((puzzle, Graded::Rated(steps))) => (puzzle, steps),
+                Ok((puzzle, Graded::Estimated(estimate))) => {
+                    tally[estimate.difficulty as usize] += 1;
+                    input::tally(&mut num_rated, location.file);
+                    let row = format!(
+                        "{}{}\t{}\t{}",
+                        location.prefix(),
+                        puzzle,
+                        estimate.difficulty,
+                        estimate.remaining
+                    );
+                    // Estimates are sorted by difficulty first, as the
+                    // remaining cells of easy and medium puzzles are zero.
+                    let key = (estimate.difficulty as u32, estimate.remaining as u32);
+                    match args.sort {
+                        Some(_) => sorted.push((key, row)),
+                        None => writeln!(writer, "{}", row)?,
+                    }
+                    return Ok(());
+                }
+                Err(err) => {
+                    failures.report(&location, err);
+                    return Ok(());
+                }
+            };
+            let rating = Rating::from_steps(&steps);
+            usage.add(&steps);
+            #[cfg(feature = "sqlite")]
+            if let Some(database) = &mut database {
+                database.record_rating(&puzzle, &rating)?;
+            }
+            tally[rating.difficulty as usize] += 1;
+            input::tally(&mut num_rated, location.file);
+            let (difficulty, score) = match &calibration {
+                Some(calibration) => {
+                    let score = calibration.score(&steps);
+                    levels[calibration.level(score)] += 1;
+                    (calibration.label(score), score)
+                }
+                None => (rating.difficulty.name(), rating.score),
+            };
+            let row = format!(
+                "{}{}\t{}\t{}\t{}",
+                location.prefix(),
+                puzzle,
+                difficulty,
+                score,
+                rating
+                    .hardest
+                    .map_or("None", |technique| match args.notation {
+                        Notation::Default => technique.name(),
+                        Notation::Hodoku => technique.hodoku_name(),
+                    })
+            );
+            match args.sort {
+                Some(_) => sorted.push(((score, 0), row)),
+                None => writeln!(writer, "{}", row)?,
+            }
+            Ok(())
+        },
+    )?;
+    progress.finish();
+
+    // Sorting is stable, such that puzzles of equal scores stay in order.
+    match args.sort {
+        Some(Order::Ascending) => sorted.sort_by_key(|(score, _)| *score),
+        Some(Order::Descending) => sorted.sort_by_key(|(score, _)| Reverse(*score)),
+        None => {}
+    }
+    for (_, row) in &sorted {
+        writeln!(writer, "{}", row)?;
+    }
+    writer.finish()?;
+    #[cfg(feature = "sqlite")]
+    let recorded = database.map(Database::finish).transpose()?;
+
+    if verbosity::summary() {
+        files.summarize(&num_rated, "rated", failures.per_file(), "failed");
+        match &calibration {
+            Some(calibration) => {
+                for (label, count) in calibration.labels().iter().zip(&levels) {
+                    eprintln!("{}: {}", label, count);
+                }
+            }
+            None => {
+                for (difficulty, count) in Difficulty::ALL.into_iter().zip(tally) {
+                    match (args.estimate, difficulty) {
+                        (true, Difficulty::Hard) => eprintln!("Hard or harder: {}", count),
+                        (true, Difficulty::Expert | Difficulty::Extreme) => {}
+                        _ => eprintln!("{}: {}", difficulty, count),
+                    }
+                }
+            }
+        }
+        eprintln!("Failed: {}", failures.count());
+        if !usage.is_empty() {
+            usage.print(args.notation);
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some((added, existing)) = recorded {
+            eprintln!(
+                "Database: {} new puzzles, {} already present",
+                added, existing
+            );
+        }
+    }
+    failures.finish()
+}
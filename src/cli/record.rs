@@ -0,0 +1,118 @@
+use super::solve::parse_timeout;
+use super::{streams, verbosity, Error};
+use clap::Parser;
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use sudoku_solver::decisions::{self, Recording};
+use sudoku_solver::{Options, Puzzle};
+
+/// Record the guesses made while brute-forcing a puzzle, such that a slow or
+/// otherwise interesting search can be shared as a small text file and
+/// reproduced elsewhere. Each guess is written on a line with its depth, the
+/// filled candidate and whether filling it was consistent. Using --replay, a
+/// recording is replayed instead, filling its guesses in order and checking
+/// that each has the recorded outcome, with a nonzero exit status at the
+/// first which differs. Using --dot, the search tree is written in the DOT
+/// language of Graphviz instead, with each guess annotated with the number
+/// of candidates of its cell and its outcome.
+#[derive(Parser)]
+pub struct Args {
+    /// Puzzle to record the guesses of
+    #[arg(required_unless_present = "replay")]
+    puzzle: Option<String>,
+    /// File to write the recording to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Give up after brute-forcing for this many seconds, recording the
+    /// guesses made until then
+    #[arg(long, value_name = "SECONDS", value_parser = parse_timeout)]
+    timeout: Option<Duration>,
+    /// Give up after making this many guesses
+    #[arg(long, value_name = "N")]
+    max_nodes: Option<u32>,
+    /// Write the search tree in the DOT language of Graphviz instead of the
+    /// recording, such as for rendering it using `dot -Tsvg`
+    #[arg(long)]
+    dot: bool,
+    /// Replay this recording instead of recording a puzzle, writing the
+    /// solution if it solves the puzzle
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["puzzle", "output", "timeout", "max_nodes", "dot"])]
+    replay: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> super::Result {
+    if let Some(path) = &args.replay {
+        return replay(path);
+    }
+    let puzzle: Puzzle = args
+        .puzzle
+        .as_deref()
+        .unwrap_or_default()
+        .parse()
+        .map_err(|err| invalid("puzzle", err))?;
+    let options = Options {
+        #[cfg(feature = "parallel")]
+        parallel: false,
+        max_guesses: args.max_nodes,
+        timeout: args.timeout,
+    };
+    let start = Instant::now();
+    let (solved, recording) = decisions::record(&puzzle, &options);
+    let elapsed = start.elapsed();
+
+    let mut writer = streams::create_output(args.output.as_deref())?;
+    match args.dot {
+        true => {
+            let tree = decisions::dot(&recording)
+                .map_err(|err| Error::Failures(format!("replaying the search: {}", err)))?;
+            write!(writer, "{}", tree)?;
+        }
+        false => write!(writer, "{}", recording)?,
+    }
+    writer.finish()?;
+    if verbosity::summary() {
+        eprintln!("Total decisions: {}", recording.decisions.len());
+        eprintln!("Solve time: {:?}", elapsed);
+    }
+    match solved {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::Failures(err.to_string())),
+    }
+}
+
+/// Replay a recording, writing the solution if it solves the puzzle.
+fn replay(path: &std::path::Path) -> super::Result {
+    let mut text = String::new();
+    streams::open_input(Some(path))?.read_to_string(&mut text)?;
+    let recording: Recording = text.parse().map_err(|err: String| {
+        Error::Fatal(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), err),
+        ))
+    })?;
+
+    let start = Instant::now();
+    let replayed = decisions::replay(&recording);
+    let elapsed = start.elapsed();
+    if let Ok(Some(solution)) = &replayed {
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", solution.row_representation())?;
+        stdout.flush()?;
+    }
+    if verbosity::summary() {
+        eprintln!("Total decisions: {}", recording.decisions.len());
+        eprintln!("Replay time: {:?}", elapsed);
+    }
+    match replayed {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::Failures(err.to_string())),
+    }
+}
+
+fn invalid(what: &str, err: &str) -> Error {
+    Error::Fatal(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("invalid {}: {}", what, err),
+    ))
+}
@@ -0,0 +1,123 @@
+use super::input::{Failures, Inputs};
+use super::{streams, verbosity, Error, Result};
+use clap::{Parser, ValueEnum};
+use std::io::{self, prelude::*};
+use std::path::{Path, PathBuf};
+use sudoku_solver::render::{self, MAX_CELL_SIZE, MIN_CELL_SIZE};
+use sudoku_solver::{Options, Solver};
+
+/// Render puzzles as SVG or PNG images. A single puzzle is written to the
+/// output file, while multiple puzzles are written to files numbered by their
+/// position, such that `puzzle.png` becomes `puzzle-1.png`, `puzzle-2.png`
+/// and so on.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// File to write the image to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Fill in the solution, drawing its values in a different color
+    #[arg(long)]
+    solution: bool,
+    /// Size of each cell in pixels
+    #[arg(long, value_name = "PX", default_value_t = 50,
+        value_parser = clap::value_parser!(u32).range(MIN_CELL_SIZE as i64..=MAX_CELL_SIZE as i64))]
+    cell_size: u32,
+    /// Format of the images [default: svg if the output file ends in .svg,
+    /// otherwise png when built with the image feature]
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+}
+
+/// Format of an image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Scalable vector graphics
+    Svg,
+    /// Portable network graphics
+    #[cfg(feature = "image")]
+    Png,
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let format = args
+        .format
+        .unwrap_or_else(|| detect(args.output.as_deref()));
+    let mut failures = Failures::default();
+    let mut images = Vec::new();
+    for line in files.lines() {
+        let line = line?;
+        let puzzle = match line.puzzle() {
+            Ok(puzzle) => puzzle,
+            Err(err) => {
+                failures.report(&line.location, err);
+                continue;
+            }
+        };
+        let solution = match args.solution {
+            false => None,
+            true => match Solver::solve_with_options(&puzzle, &Options::default()) {
+                Ok(solution) => Some(solution),
+                Err(err) => {
+                    failures.report(&line.location, err);
+                    continue;
+                }
+            },
+        };
+        images.push(match format {
+            Format::Svg => render::svg(&puzzle, solution.as_ref(), args.cell_size).into_bytes(),
+            #[cfg(feature = "image")]
+            Format::Png => render::png(&puzzle, solution.as_ref(), args.cell_size),
+        });
+    }
+
+    if images.len() > 1 && args.output.is_none() {
+        return Err(Error::Fatal(io::Error::other(
+            "multiple puzzles can only be rendered to an output file",
+        )));
+    }
+    let binary = format != Format::Svg;
+    if binary && streams::is_terminal(args.output.as_deref()) && !images.is_empty() {
+        return Err(Error::Fatal(io::Error::other(
+            "refusing to write an image to a terminal",
+        )));
+    }
+    for (idx, image) in images.iter().enumerate() {
+        let path = match (&args.output, images.len()) {
+            (Some(path), 2..) => Some(numbered(path, idx + 1)),
+            (path, _) => path.clone(),
+        };
+        let mut writer = streams::create_output(path.as_deref())?;
+        writer.write_all(image)?;
+        writer.finish()?;
+    }
+
+    if verbosity::summary() {
+        eprintln!("Total rendered: {}", images.len());
+    }
+    failures.finish()
+}
+
+/// Guess the format of an image from the extension of its file.
+fn detect(path: Option<&Path>) -> Format {
+    match path.and_then(Path::extension) {
+        Some(extension) if extension == "svg" => Format::Svg,
+        #[cfg(feature = "image")]
+        _ => Format::Png,
+        #[cfg(not(feature = "image"))]
+        _ => Format::Svg,
+    }
+}
+
+/// Path of the image of the puzzle at a position, which is numbered before
+/// the extension of the output file.
+fn numbered(path: &Path, position: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, position, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, position),
+    };
+    path.with_file_name(name)
+}
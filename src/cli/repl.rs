@@ -0,0 +1,228 @@
+use super::color::{self, ColorChoice};
+use super::format;
+use super::Result;
+use clap::Parser;
+use std::io::{self, prelude::*, IsTerminal};
+use sudoku_solver::logic::{self, Grid};
+use sudoku_solver::moves::{self, Move};
+use sudoku_solver::rating;
+use sudoku_solver::{Options, Puzzle, Solver};
+
+/// Experiment with a puzzle by typing commands, such as to solve it, show a
+/// hint, count its solutions or rate it, with the grid shown again after each
+/// command. A puzzle is loaded by pasting it as a line. Commands are read
+/// from standard input, so they can be piped in as well.
+#[derive(Parser)]
+pub struct Args {
+    /// Puzzle to start with, instead of an empty grid
+    puzzle: Option<Puzzle>,
+    /// When to color the clues and entered cells of the grid
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+}
+
+/// Commands and what they do, shown by `help`.
+const COMMANDS: [(&str, &str); 12] = [
+    ("<puzzle>", "load a puzzle pasted as a line"),
+    ("set r1c1=5", "enter values, such as r1c1=5, r2c3=7"),
+    ("clear r1c1", "clear an entered value"),
+    ("hint", "show the next step a human would take"),
+    ("step", "take the next step"),
+    ("solve", "fill in the solution"),
+    (
+        "count [LIMIT]",
+        "count the solutions, up to 1000 by default",
+    ),
+    ("rate", "rate the difficulty"),
+    ("undo", "undo the last change"),
+    ("reset", "clear all entered values"),
+    ("show", "show the grid"),
+    ("quit", "stop, as does the end of the input"),
+];
+
+/// Default limit when counting solutions.
+const COUNT_LIMIT: usize = 1000;
+
+/// State of the session.
+struct Session {
+    /// The loaded puzzle, of which the clues cannot be changed.
+    puzzle: Puzzle,
+    /// The clues along with the entered values.
+    grid: [[u8; 9]; 9],
+    /// The grids before each change, for undoing them.
+    history: Vec<[[u8; 9]; 9]>,
+}
+
+pub fn run(args: Args) -> Result {
+    let color = args.color.enabled(None);
+    let interactive = io::stdin().is_terminal();
+    let puzzle = args.puzzle.unwrap_or(format::to_puzzle(&[[0; 9]; 9]));
+    let mut session = Session {
+        puzzle,
+        grid: *puzzle.cells(),
+        history: Vec::new(),
+    };
+    let mut stdout = io::stdout().lock();
+    if interactive {
+        writeln!(stdout, "Type 'help' for the commands.")?;
+    }
+    writeln!(stdout, "{}", session.show(color))?;
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        if interactive {
+            write!(stdout, "> ")?;
+            stdout.flush()?;
+        }
+        let Some(line) = lines.next().transpose()? else {
+            break;
+        };
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let argument = argument.trim();
+        let message = match command {
+            "" => continue,
+            "quit" | "exit" => break,
+            "help" => {
+                for (command, description) in COMMANDS {
+                    writeln!(stdout, "  {:<14} {}", command, description)?;
+                }
+                continue;
+            }
+            "show" => Ok(String::new()),
+            "set" => session.set(argument),
+            "clear" => session.clear(argument),
+            "hint" => logic::hint(&session.to_puzzle()).map(|step| step.to_string()),
+            "step" => session.step(),
+            "solve" => session.solve(),
+            "count" => session.count(argument),
+            "rate" => rating::rate(&session.to_puzzle()).map(|rating| {
+                format!(
+                    "{} (score {}, hardest technique {}, {} steps)",
+                    rating.difficulty,
+                    rating.score,
+                    rating.hardest.map_or("none", |technique| technique.name()),
+                    rating.steps
+                )
+            }),
+            "undo" => session.undo(),
+            "reset" => session.change(*session.puzzle.cells()),
+            _ => session.load(line),
+        };
+        match message {
+            Ok(message) if message.is_empty() => {}
+            Ok(message) => writeln!(stdout, "{}", message)?,
+            Err(err) => writeln!(stdout, "error: {}", err)?,
+        }
+        writeln!(stdout, "{}", session.show(color))?;
+    }
+    Ok(())
+}
+
+impl Session {
+    /// The grid framed, with the clues distinguished from the entered values
+    /// if colored.
+    fn show(&self, color: bool) -> String {
+        color::framed(&self.grid, &self.puzzle, color)
+    }
+
+    /// The grid as a puzzle.
+    fn to_puzzle(&self) -> Puzzle {
+        format::to_puzzle(&self.grid)
+    }
+
+    /// Replace the grid, remembering the previous one for undoing.
+    fn change(&mut self, grid: [[u8; 9]; 9]) -> std::result::Result<String, &'static str> {
+        if grid != self.grid {
+            self.history.push(self.grid);
+            self.grid = grid;
+        }
+        Ok(String::new())
+    }
+
+    /// Load a pasted puzzle, which cannot be undone.
+    fn load(&mut self, line: &str) -> std::result::Result<String, &'static str> {
+        let puzzle: Puzzle = line
+            .parse()
+            .map_err(|_| "unknown command, type 'help' for the commands")?;
+        Grid::new(&puzzle)?;
+        self.puzzle = puzzle;
+        self.grid = *puzzle.cells();
+        self.history.clear();
+        Ok(format!("Loaded puzzle with {} clues", puzzle.clues()))
+    }
+
+    /// Enter a list of placements, all of which must be possible.
+    fn set(&mut self, argument: &str) -> std::result::Result<String, &'static str> {
+        let mut grid = self.grid;
+        for current in moves::parse(argument)? {
+            let Move::Place((row, col), digit) = current else {
+                return Err("only placements such as r1c1=5 can be set");
+            };
+            if self.puzzle.cells()[row][col] != 0 {
+                return Err("cannot change a clue");
+            }
+            grid[row][col] = digit;
+        }
+        Grid::new(&format::to_puzzle(&grid))
+            .map_err(|_| "value conflicts with another in its row, column or square")?;
+        self.change(grid)
+    }
+
+    /// Clear the entered values of a list of cells.
+    fn clear(&mut self, argument: &str) -> std::result::Result<String, &'static str> {
+        // Cells are parsed as eliminations, which accept the same cells.
+        let mut grid = self.grid;
+        for current in moves::parse(&format!("{}<>1", argument))? {
+            let (row, col) = current.cell();
+            if self.puzzle.cells()[row][col] != 0 {
+                return Err("cannot change a clue");
+            }
+            grid[row][col] = 0;
+        }
+        self.change(grid)
+    }
+
+    /// Take the next step a human would take, showing it.
+    fn step(&mut self) -> std::result::Result<String, &'static str> {
+        let step = logic::hint(&self.to_puzzle())?;
+        let mut grid = Grid::new(&self.to_puzzle())?;
+        grid.apply(&step)?;
+        self.change(*grid.to_puzzle().cells())?;
+        Ok(step.to_string())
+    }
+
+    /// Fill in the solution, showing the effort of finding it.
+    fn solve(&mut self) -> std::result::Result<String, &'static str> {
+        let solution = Solver::solve_with_options(&self.to_puzzle(), &Options::default())?;
+        self.change(*solution.cells())?;
+        Ok(format!(
+            "Solved with {} guesses and {} backtracks",
+            solution.guesses, solution.backtracks
+        ))
+    }
+
+    /// Count the solutions up to a limit.
+    fn count(&self, argument: &str) -> std::result::Result<String, &'static str> {
+        let limit = match argument {
+            "" => COUNT_LIMIT,
+            limit => limit
+                .parse()
+                .ok()
+                .filter(|limit| *limit > 0)
+                .ok_or("limit must be a positive number")?,
+        };
+        let puzzle = self.to_puzzle();
+        Grid::new(&puzzle)?;
+        Ok(match Solver::count_solutions(&puzzle, limit) {
+            count if count >= limit => format!("At least {} solutions", limit),
+            1 => "1 solution".to_string(),
+            count => format!("{} solutions", count),
+        })
+    }
+
+    /// Revert the last change.
+    fn undo(&mut self) -> std::result::Result<String, &'static str> {
+        self.grid = self.history.pop().ok_or("nothing to undo")?;
+        Ok(String::new())
+    }
+}
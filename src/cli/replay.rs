@@ -0,0 +1,57 @@
+use super::{streams, verbosity, Error};
+use clap::Parser;
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
+use sudoku_solver::logic::Grid;
+use sudoku_solver::moves;
+use sudoku_solver::Puzzle;
+
+/// Replay a list of moves on a puzzle, such as a solve log of this or another
+/// tool, and write the resulting grid. Placements are written in the common
+/// notation as `r1c3=5` and eliminations as `r4c7<>2`, separated by commas or
+/// on separate lines. Of logged steps, only the effects after the last colon
+/// or `=>` are read. If the puzzle has a unique solution, every move is
+/// verified against it, and the exit status is nonzero at the first wrong or
+/// impossible move.
+#[derive(Parser)]
+pub struct Args {
+    /// Puzzle to replay the moves on
+    puzzle: String,
+    /// File containing the moves [default: standard input]
+    moves: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> super::Result {
+    let puzzle: Puzzle = args.puzzle.parse().map_err(|err| invalid("puzzle", err))?;
+    let mut grid = Grid::new(&puzzle).map_err(|err| invalid("puzzle", err))?;
+    let mut script = String::new();
+    streams::open_input(args.moves.as_deref())?.read_to_string(&mut script)?;
+    let moves = moves::parse_script(&script).map_err(|err| invalid("moves", err))?;
+
+    let replayed = moves::replay(&mut grid, &moves);
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", grid.to_puzzle())?;
+    stdout.flush()?;
+
+    let num_replayed = match replayed {
+        Ok(()) => moves.len(),
+        Err(err) => err.index,
+    };
+    if verbosity::summary() {
+        eprintln!("Total moves: {}", moves.len());
+        eprintln!("Total replayed: {}", num_replayed);
+        eprintln!("Solved: {}", if grid.is_solved() { "yes" } else { "no" });
+    }
+
+    match replayed {
+        Ok(()) => Ok(()),
+        Err(err) => Err(Error::Failures(err.to_string())),
+    }
+}
+
+fn invalid(what: &str, err: &str) -> Error {
+    Error::Fatal(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("invalid {}: {}", what, err),
+    ))
+}
@@ -0,0 +1,277 @@
+//! Reports of a run, holding the result of every puzzle along with the
+//! aggregate statistics, the configuration and the timing. Reports are
+//! written as JSON, such that batch jobs can be monitored and compared
+//! programmatically, or as Markdown for pasting into issues and wikis.
+
+use super::color;
+use super::input::{Files, Location};
+use super::streams;
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sudoku_solver::logic::Technique;
+use sudoku_solver::rating::{Difficulty, Rating};
+use sudoku_solver::{Puzzle, Solution};
+
+/// A solved puzzle among the hardest of a run.
+struct Hard {
+    location: Location,
+    puzzle: Puzzle,
+    solution: [[u8; 9]; 9],
+    rating: Option<Rating>,
+    guesses: u32,
+}
+
+impl Hard {
+    /// How hard the puzzle is, by its rating score if rated and otherwise by
+    /// the number of guesses of the search.
+    fn key(&self) -> (u32, u32) {
+        (self.rating.map_or(0, |rating| rating.score), self.guesses)
+    }
+}
+
+/// Report of a run, which is collected while processing the puzzles.
+pub struct Report {
+    started: SystemTime,
+    start: Instant,
+    puzzles: Vec<Value>,
+    /// Number of rated puzzles per difficulty tier.
+    difficulties: [u64; 5],
+    /// The hardest solved puzzles, hardest first, of which at most
+    /// `num_hardest` are kept.
+    hardest: Vec<Hard>,
+    num_hardest: usize,
+}
+
+impl Report {
+    /// Start a report at the start of a run, keeping a number of the hardest
+    /// puzzles for showing in Markdown.
+    pub fn new(num_hardest: usize) -> Self {
+        Self {
+            started: SystemTime::now(),
+            start: Instant::now(),
+            puzzles: Vec::new(),
+            difficulties: [0; 5],
+            hardest: Vec::new(),
+            num_hardest,
+        }
+    }
+
+    /// Record the result of solving a puzzle.
+    pub fn add(
+        &mut self,
+        location: &Location,
+        puzzle: Option<&Puzzle>,
+        solution: &Result<Solution, &'static str>,
+        elapsed: Duration,
+        rating: Option<&Rating>,
+    ) {
+        let mut object = json!({
+            "line": location.line,
+            "elapsed_us": elapsed.as_micros() as u64,
+        });
+        if let Some(name) = location.name() {
+            object["file"] = json!(name);
+        }
+        if let Some(label) = location.label() {
+            object["label"] = json!(label);
+        }
+        match solution {
+            Ok(solution) => {
+                object["status"] = json!("solved");
+                object["brute_forces"] = json!(solution.brute_forces);
+                object["guesses"] = json!(solution.guesses);
+                object["backtracks"] = json!(solution.backtracks);
+            }
+            Err(err) => {
+                object["status"] = json!("failed");
+                object["error"] = json!(err);
+            }
+        }
+        if let Some(rating) = rating {
+            object["difficulty"] = json!(rating.difficulty.name());
+            self.difficulties[rating.difficulty as usize] += 1;
+        }
+        self.puzzles.push(object);
+        if let (Ok(solution), Some(puzzle)) = (solution, puzzle) {
+            self.add_hard(Hard {
+                location: location.clone(),
+                puzzle: *puzzle,
+                solution: *solution.cells(),
+                rating: rating.copied(),
+                guesses: solution.guesses,
+            });
+        }
+    }
+
+    /// Keep a solved puzzle if it is among the hardest so far. Of equally
+    /// hard puzzles, the earliest are kept.
+    fn add_hard(&mut self, hard: Hard) {
+        let idx = self
+            .hardest
+            .partition_point(|other| other.key() >= hard.key());
+        if idx < self.num_hardest {
+            self.hardest.insert(idx, hard);
+            self.hardest.truncate(self.num_hardest);
+        }
+    }
+
+    /// Write the report to a file, along with the inputs, the configuration
+    /// and aggregate statistics of the run. Files ending in `.md` get a
+    /// Markdown report, and others a JSON object.
+    pub fn write(
+        self,
+        path: &Path,
+        files: &Files,
+        configuration: Value,
+        aggregates: Value,
+    ) -> io::Result<()> {
+        let inputs: Vec<String> = (0..files.len()).map(|file| files.name(file)).collect();
+        let started = self
+            .started
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let elapsed = self.start.elapsed();
+        let mut writer = streams::create_output(Some(path))?;
+        if is_markdown(path) {
+            let markdown = self.markdown(&inputs, &configuration, &aggregates, elapsed);
+            writer.write_all(markdown.as_bytes())?;
+            return writer.finish();
+        }
+        let report = json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "inputs": inputs,
+            "configuration": configuration,
+            "started": started,
+            "elapsed_ms": elapsed.as_millis() as u64,
+            "aggregates": aggregates,
+            "puzzles": self.puzzles,
+        });
+        serde_json::to_writer_pretty(&mut writer, &report)?;
+        writer.write_all(b"\n")?;
+        writer.finish()
+    }
+
+    /// The report in Markdown, with tables of the statistics and the
+    /// hardest puzzles as framed grids next to their solutions.
+    fn markdown(
+        &self,
+        inputs: &[String],
+        configuration: &Value,
+        aggregates: &Value,
+        elapsed: Duration,
+    ) -> String {
+        let mut md = String::new();
+        writeln!(md, "# Solve report\n").unwrap();
+        writeln!(
+            md,
+            "Solved {} using sudoku-solver {} in {:.2?}.\n",
+            inputs
+                .iter()
+                .map(|input| format!("`{}`", input))
+                .collect::<Vec<_>>()
+                .join(", "),
+            env!("CARGO_PKG_VERSION"),
+            elapsed
+        )
+        .unwrap();
+
+        writeln!(md, "## Statistics\n").unwrap();
+        writeln!(md, "| Statistic | Value |\n|---|---:|").unwrap();
+        writeln!(md, "| puzzles | {} |", self.puzzles.len()).unwrap();
+        for (key, value) in aggregates.as_object().into_iter().flatten() {
+            if value.is_number() {
+                writeln!(md, "| {} | {} |", key.replace('_', " "), number(value)).unwrap();
+            }
+        }
+
+        writeln!(md, "\n## Configuration\n").unwrap();
+        writeln!(md, "| Option | Value |\n|---|---|").unwrap();
+        for (key, value) in configuration.as_object().into_iter().flatten() {
+            let value = match value {
+                Value::Null => "-".to_string(),
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            writeln!(md, "| {} | {} |", key.replace('_', " "), value).unwrap();
+        }
+
+        let rated: u64 = self.difficulties.iter().sum();
+        if rated > 0 {
+            writeln!(md, "\n## Difficulty\n").unwrap();
+            writeln!(md, "| Difficulty | Puzzles | Share |\n|---|---:|---:|").unwrap();
+            for (difficulty, count) in Difficulty::ALL.into_iter().zip(self.difficulties) {
+                let share = count as f64 / rated as f64 * 100.0;
+                writeln!(md, "| {} | {} | {:.1}% |", difficulty, count, share).unwrap();
+            }
+        }
+
+        if let Some(Value::Object(techniques)) = aggregates.get("techniques") {
+            writeln!(md, "\n## Techniques\n").unwrap();
+            writeln!(md, "| Technique | Steps | Puzzles |\n|---|---:|---:|").unwrap();
+            // The techniques are listed in order of difficulty.
+            for technique in Technique::ALL.map(Technique::name) {
+                let Some(usage) = techniques.get(technique) else {
+                    continue;
+                };
+                writeln!(
+                    md,
+                    "| {} | {} | {} |",
+                    technique, usage["steps"], usage["puzzles"]
+                )
+                .unwrap();
+            }
+        }
+
+        if let Some(Value::Array(histogram)) = aggregates.get("guesses_histogram") {
+            writeln!(md, "\n## Guesses\n").unwrap();
+            writeln!(md, "| Guesses | Puzzles |\n|---|---:|").unwrap();
+            let first = histogram
+                .iter()
+                .position(|count| count.as_u64().is_some_and(|count| count > 0));
+            for (bucket, count) in histogram.iter().enumerate().skip(first.unwrap_or(0)) {
+                let range = match bucket {
+                    0 => "0".to_string(),
+                    1 => "1".to_string(),
+                    n => format!("{}-{}", 1u64 << (n - 1), (1u64 << n) - 1),
+                };
+                writeln!(md, "| {} | {} |", range, count).unwrap();
+            }
+        }
+
+        if !self.hardest.is_empty() {
+            writeln!(md, "\n## Hardest puzzles").unwrap();
+            for hard in &self.hardest {
+                write!(md, "\n### {}\n\n", hard.location).unwrap();
+                if let Some(rating) = hard.rating {
+                    write!(md, "{}, score {}, ", rating.difficulty, rating.score).unwrap();
+                }
+                writeln!(md, "{} guesses\n", hard.guesses).unwrap();
+                writeln!(md, "```").unwrap();
+                let puzzle = color::framed(hard.puzzle.cells(), &hard.puzzle, false);
+                let solution = color::framed(&hard.solution, &hard.puzzle, false);
+                for (left, right) in puzzle.lines().zip(solution.lines()) {
+                    writeln!(md, "{}   {}", left, right).unwrap();
+                }
+                writeln!(md, "```").unwrap();
+            }
+        }
+        md
+    }
+}
+
+/// Whether a report is written in Markdown, by the extension of its file.
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "md" || extension == "markdown")
+}
+
+/// Format a number for a table, rounding fractions.
+fn number(value: &Value) -> String {
+    match value.as_f64() {
+        Some(value) if value.fract() != 0.0 => format!("{:.2}", value),
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,235 @@
+//! Descriptors of the rules of variant puzzles, declaring the constraints
+//! which apply on top of the classic rules. Descriptors are JSON or TOML
+//! files, by their extension, in which cells are written as `r1c2` or as
+//! coordinates `[1, 2]` of their row and column, and groups such as `r1c123`
+//! stand for all of their cells:
+//!
+//! ```toml
+//! diagonals = ["main", "anti"]
+//! regions = [["r1c123", "r2c123", "r3c123"], [[5, 5], [5, 6], [6, 5]]]
+//! magic_square = true
+//! consecutive = [["r1c1", "r1c2"]]
+//!
+//! [[cages]]
+//! cells = ["r1c1", "r2c1"]
+//! sum = 10
+//!
+//! [[lines]]
+//! kind = "thermometer"
+//! cells = ["r5c1", "r5c2", "r5c3"]
+//!
+//! [[dots]]
+//! kind = "black"
+//! cells = ["r9c8", "r9c9"]
+//! ```
+//!
+//! Lines are of the kinds `thermometer`, `palindrome` and `whispers`, and
+//! dots are `white` for consecutive values or `black` for one value being
+//! double the other. The houses of the classic rules which apply are listed
+//! by `houses`, which are `rows`, `columns` and `squares` if absent, such
+//! that `houses = ["rows", "columns"]` declares a Latin square.
+//!
+//! Descriptors are a format of the command line tool only, so the library
+//! does not read them; applications compose a [`RuleSet`] of its constraints
+//! instead.
+
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::Path;
+use sudoku_solver::moves;
+use sudoku_solver::variants::{
+    Cage, Cell, Consecutive, Dot, DotKind, Houses, MagicSquare, Palindrome, Region, RuleSet,
+    Thermometer, Whispers,
+};
+
+/// Load the rules declared by a descriptor file.
+pub fn load(path: &Path) -> io::Result<RuleSet> {
+    let text = fs::read_to_string(path)?;
+    let descriptor = match path
+        .extension()
+        .is_some_and(|extension| extension == "toml")
+    {
+        true => text
+            .parse::<toml::Table>()
+            .map_err(|err| err.message().to_string())
+            .and_then(|table| serde_json::to_value(table).map_err(|err| err.to_string())),
+        false => serde_json::from_str(&text).map_err(|err| format!("invalid JSON: {}", err)),
+    };
+    descriptor
+        .and_then(|descriptor| parse(&descriptor))
+        .map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        })
+}
+
+/// Build the rules declared by a descriptor, being the declared houses of
+/// the classic rules along with the declared constraints.
+pub fn parse(descriptor: &Value) -> Result<RuleSet, String> {
+    let object = descriptor
+        .as_object()
+        .ok_or("expected an object of constraints")?;
+    let houses = match object.get("houses") {
+        Some(value) => houses(value).map_err(|err| format!("houses: {}", err))?,
+        None => Houses::ALL,
+    };
+    let mut rules = RuleSet::base(houses);
+    for (key, value) in object {
+        let context = |err: &str| format!("{}: {}", key, err);
+        match key.as_str() {
+            "houses" => {}
+            "diagonals" => {
+                for diagonal in items(value).map_err(|err| context(&err))? {
+                    match diagonal.as_str() {
+                        Some("main") => rules.add(Region::main_diagonal()),
+                        Some("anti") => rules.add(Region::anti_diagonal()),
+                        _ => return Err(context("expected \"main\" or \"anti\"")),
+                    }
+                }
+            }
+            "regions" => {
+                for region in items(value).map_err(|err| context(&err))? {
+                    rules.add(
+                        cells(region)
+                            .and_then(|cells| Ok(Region::new(cells)?))
+                            .map_err(|err| context(&err))?,
+                    );
+                }
+            }
+            "cages" => {
+                for cage in items(value).map_err(|err| context(&err))? {
+                    let sum = match cage.get("sum") {
+                        None => None,
+                        Some(sum) => Some(
+                            sum.as_u64()
+                                .and_then(|sum| u32::try_from(sum).ok())
+                                .filter(|sum| (1..=45).contains(sum))
+                                .ok_or_else(|| context("expected a sum from 1 to 45"))?,
+                        ),
+                    };
+                    let cells = field(cage, "cells").and_then(cells);
+                    rules.add(
+                        cells
+                            .and_then(|cells| Ok(Cage::new(cells, sum)?))
+                            .map_err(|err| context(&err))?,
+                    );
+                }
+            }
+            "lines" => {
+                for line in items(value).map_err(|err| context(&err))? {
+                    let cells = field(line, "cells")
+                        .and_then(cells)
+                        .map_err(|err| context(&err))?;
+                    let added = match line.get("kind").and_then(Value::as_str) {
+                        Some("thermometer") => Thermometer::new(cells).map(|line| rules.add(line)),
+                        Some("palindrome") => Palindrome::new(cells).map(|line| rules.add(line)),
+                        Some("whispers") => Whispers::new(cells).map(|line| rules.add(line)),
+                        _ => Err("expected a kind of thermometer, palindrome or whispers"),
+                    };
+                    added.map_err(context)?;
+                }
+            }
+            "dots" => {
+                for dot in items(value).map_err(|err| context(&err))? {
+                    let kind = match dot.get("kind").and_then(Value::as_str) {
+                        Some("white") => DotKind::White,
+                        Some("black") => DotKind::Black,
+                        _ => return Err(context("expected a kind of white or black")),
+                    };
+                    let cells = field(dot, "cells")
+                        .and_then(pair)
+                        .map_err(|err| context(&err))?;
+                    rules.add(Dot::new(cells, kind).map_err(context)?);
+                }
+            }
+            "consecutive" => {
+                let marked = items(value)
+                    .and_then(|marked| marked.iter().map(pair).collect::<Result<Vec<_>, _>>())
+                    .map_err(|err| context(&err))?;
+                rules.add(Consecutive::new(marked).map_err(context)?);
+            }
+            "magic_square" => match value.as_bool() {
+                Some(true) => rules.add(MagicSquare::center()),
+                Some(false) => {}
+                None => return Err(context("expected true or false")),
+            },
+            _ => return Err(format!("unknown constraint {}", key)),
+        }
+    }
+    Ok(rules)
+}
+
+/// The kinds of houses listed in an array.
+fn houses(value: &Value) -> Result<Houses, String> {
+    let mut houses = Houses {
+        rows: false,
+        columns: false,
+        squares: false,
+    };
+    for item in items(value)? {
+        match item.as_str() {
+            Some("rows") => houses.rows = true,
+            Some("columns") => houses.columns = true,
+            Some("squares") => houses.squares = true,
+            _ => return Err("expected \"rows\", \"columns\" or \"squares\"".to_string()),
+        }
+    }
+    Ok(houses)
+}
+
+/// The items of an array.
+fn items(value: &Value) -> Result<&[Value], String> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| "expected an array".to_string())
+}
+
+/// A field of an object.
+fn field<'a>(value: &'a Value, name: &str) -> Result<&'a Value, String> {
+    value
+        .get(name)
+        .ok_or_else(|| format!("missing field {}", name))
+}
+
+/// The cells of an array of cells, groups of cells and coordinates, in
+/// order.
+fn cells(value: &Value) -> Result<Vec<Cell>, String> {
+    let mut cells = Vec::new();
+    for item in items(value)? {
+        match item {
+            Value::String(spec) => cells.extend(moves::parse_cells(spec)?),
+            Value::Array(_) => cells.push(coordinates(item)?),
+            _ => return Err("expected cells such as r1c2 or [1, 2]".to_string()),
+        }
+    }
+    Ok(cells)
+}
+
+/// The cell at coordinates given as its row and column from 1 to 9.
+fn coordinates(value: &Value) -> Result<Cell, String> {
+    let coordinate = |value: &Value| {
+        value
+            .as_u64()
+            .filter(|coordinate| (1..=9).contains(coordinate))
+            .map(|coordinate| coordinate as usize - 1)
+    };
+    match items(value)? {
+        [row, col] => match (coordinate(row), coordinate(col)) {
+            (Some(row), Some(col)) => Ok((row, col)),
+            _ => Err("expected coordinates from 1 to 9".to_string()),
+        },
+        _ => Err("expected coordinates of a row and column such as [1, 2]".to_string()),
+    }
+}
+
+/// The two cells of an array of cells.
+fn pair(value: &Value) -> Result<(Cell, Cell), String> {
+    match cells(value)?.as_slice() {
+        [first, second] => Ok((*first, *second)),
+        _ => Err("expected two cells".to_string()),
+    }
+}
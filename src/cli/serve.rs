@@ -0,0 +1,621 @@
+use super::jobs::Jobs;
+use super::limits::{Limits, Rejection};
+use super::metrics;
+use super::queue;
+use super::socket;
+use super::solve::parse_timeout;
+use clap::Parser;
+use serde_json::{json, Value};
+use std::io::{self, prelude::*};
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::slice;
+use std::sync::OnceLock;
+use std::thread::{self, Scope};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sudoku_solver::generate;
+use sudoku_solver::logic::{self, Hint, HintLevel, Step, Technique};
+use sudoku_solver::random::Rng;
+use sudoku_solver::rating::{Difficulty, Rating};
+use sudoku_solver::{Options, Puzzle, Solver, NODE_LIMIT_EXCEEDED, TIME_LIMIT_EXCEEDED};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Maximum number of puzzles of a batch handled within a single request.
+const BATCH_SIZE: usize = 1000;
+
+/// Maximum number of puzzles generated when looking for one of a requested
+/// difficulty or technique.
+const GENERATE_ATTEMPTS: usize = 1000;
+
+/// Serve an HTTP API for solving, rating, generating and hinting puzzles.
+/// Each endpoint accepts a POST request with a JSON object as body, and
+/// responds with a JSON object:
+///
+/// - /solve: {"puzzle"} to {"solution", "brute_forces"}
+/// - /rate: {"puzzle"} to {"difficulty", "score", "hardest", "steps"}
+/// - /generate: {"difficulty"? or "technique"?, "seed"?} to {"puzzle", "difficulty"}
+/// - /hint: {"puzzle", "level"?} to the next step, including its "description",
+///   or only its "house", "cell" or "value" at the region, cell or digit level
+///
+/// - /batch: {"puzzles", "operation"?} to {"results"}, being the responses to
+///   each of up to 1000 puzzles for the solve, rate or hint operation, which
+///   take the other fields of the request as well and share its --timeout
+///
+/// Failures are responded to with an {"error"} object.
+///
+/// Solves are streamed over a WebSocket on /stream, on which each message is
+/// a {"puzzle", "delay"?} object. In response, a "start" event is sent,
+/// followed by a "step" event for each step of the logical solve along with
+/// the "grid" after it, and a "solved" or "error" event at the end. Steps are
+/// sent "delay" milliseconds apart, such that they can be animated as they
+/// arrive. Each connection counts as a request being handled by its client
+/// until it is closed, and connections beyond --max-connections are refused
+/// with 503.
+///
+/// Larger batches are submitted as a job using POST /jobs with the same body
+/// as /batch, which responds with its {"id"} and processes it in the
+/// background. Using GET, /jobs/<id> responds with its {"status"} of queued,
+/// running or done and the number of puzzles {"completed"} out of {"total"},
+/// and /jobs/<id>/results with its {"results"} once done. Jobs are processed
+/// by a few threads, and refused with 503 while too many are queued.
+///
+/// Metrics of the requests are exposed in the text format of Prometheus on
+/// /metrics using GET: the number of requests by endpoint and status code,
+/// histograms of their latency, the number of solves which exceeded
+/// --timeout or --max-nodes, and the number of steps of each technique in
+/// rated and hinted puzzles.
+///
+/// Requests are handled on --jobs threads, such that slow requests do not
+/// hold up others. To keep a single client from occupying all of them, the
+/// number of requests of each client, identified by its IP address, may be
+/// limited using --rate-limit and --max-per-client, which are responded to
+/// with 429 when exceeded. Bodies larger than --max-body bytes are refused
+/// with 413, while --timeout and --max-nodes bound the search for each puzzle
+/// to solve, rate, hint or generate.
+///
+/// Using --stdio, the same methods are served as newline-delimited JSON-RPC
+/// 2.0 on standard input and output instead, such that editors and GUIs can
+/// keep a single process running. The parameters and results of the methods
+/// are the request and response objects of the endpoints.
+#[derive(Parser)]
+#[command(verbatim_doc_comment)]
+pub struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080", conflicts_with = "stdio")]
+    address: String,
+    /// Serve JSON-RPC requests on standard input and output instead of HTTP
+    #[arg(long)]
+    stdio: bool,
+    /// Give up on a request to solve, rate or hint a puzzle after this many
+    /// seconds of brute-forcing it, or to generate a puzzle after this many
+    /// seconds
+    #[arg(long, value_name = "SECONDS", value_parser = parse_timeout)]
+    timeout: Option<Duration>,
+    /// Give up on a puzzle to solve after making this many guesses
+    #[arg(long, value_name = "N")]
+    max_nodes: Option<u32>,
+    #[command(flatten)]
+    jobs: Jobs,
+    /// Number of requests each client may make per minute, in bursts of at
+    /// most as many requests [default: unlimited]
+    #[arg(long, value_name = "N", conflicts_with = "stdio")]
+    rate_limit: Option<NonZeroU32>,
+    /// Number of requests of each client which may be handled at once
+    /// [default: unlimited]
+    #[arg(long, value_name = "N", conflicts_with = "stdio")]
+    max_per_client: Option<NonZeroUsize>,
+    /// Maximum size of the body of a request in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = 64 * 1024, conflicts_with = "stdio")]
+    max_body: u64,
+    /// Number of WebSocket connections of all clients which may be open at
+    /// once
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 256,
+        conflicts_with = "stdio"
+    )]
+    max_connections: usize,
+}
+
+/// Options of solving puzzles, limiting their search as configured.
+static OPTIONS: OnceLock<Options> = OnceLock::new();
+
+/// Error code of JSON-RPC for a request which is not valid JSON.
+const PARSE_ERROR: i64 = -32700;
+/// Error code of JSON-RPC for a request which is not a valid request object.
+const INVALID_REQUEST: i64 = -32600;
+/// Error code of JSON-RPC for an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// Error code of JSON-RPC for a method failing on its parameters.
+const INVALID_PARAMS: i64 = -32602;
+
+/// A failed request, with its HTTP status code and reason.
+pub struct Failure(pub u16, pub String);
+
+impl From<&str> for Failure {
+    fn from(reason: &str) -> Self {
+        Failure(400, reason.to_string())
+    }
+}
+
+pub fn run(args: Args) -> super::Result {
+    let _ = OPTIONS.set(Options {
+        #[cfg(feature = "parallel")]
+        parallel: false,
+        max_guesses: args.max_nodes,
+        timeout: args.timeout,
+    });
+    if args.stdio {
+        return serve_stdio();
+    }
+    let server = Server::http(&args.address).map_err(io::Error::other)?;
+    eprintln!("Listening on http://{}", args.address);
+
+    let limits = Limits::new(
+        args.rate_limit.map(NonZeroU32::get),
+        args.max_per_client.map(NonZeroUsize::get),
+        args.max_connections,
+    );
+    thread::scope(|scope| {
+        for _ in 0..args.jobs.count() {
+            scope.spawn(|| {
+                for request in server.incoming_requests() {
+                    respond(request, &limits, args.max_body, scope);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Respond to a request, unless the limits of its client are exceeded.
+/// WebSocket connections are served on a thread of their own, such that they
+/// do not occupy a thread handling requests.
+fn respond<'scope>(
+    mut request: Request,
+    limits: &'scope Limits,
+    max_body: u64,
+    scope: &'scope Scope<'scope, '_>,
+) {
+    let start = Instant::now();
+    let endpoint = endpoint(request.url());
+    let mut retry_after = None;
+    // Metrics are exempt from the limits, such that monitoring continues
+    // while a client is limited.
+    let admitted = match request.remote_addr() {
+        Some(addr) if endpoint != "metrics" => limits.admit(addr.ip()).map(Some),
+        _ => Ok(None),
+    };
+    let (status, body, content_type) = match admitted {
+        Err(Rejection::RateLimited(wait)) => {
+            retry_after = Some(wait.as_secs() + 1);
+            error(429, "rate limit exceeded")
+        }
+        Err(Rejection::TooManyActive) => error(429, "too many requests being handled"),
+        Ok(admission) => match endpoint {
+            "stream" => match (socket::accept_key(&request), limits.connect()) {
+                (Ok(key), Some(connection)) => {
+                    metrics::request(endpoint, 101, start.elapsed());
+                    let socket = socket::upgrade(request, key, max_body);
+                    scope.spawn(move || {
+                        socket::session(socket);
+                        drop((admission, connection));
+                    });
+                    return;
+                }
+                (Err(Failure(status, reason)), _) => error(status, &reason),
+                (Ok(_), None) => error(503, "too many open connections"),
+            },
+            "metrics" if *request.method() == Method::Get => {
+                (200, metrics::render(), "text/plain; version=0.0.4")
+            }
+            "metrics" => error(405, "only GET requests are accepted"),
+            _ => match handle(&mut request, max_body) {
+                Ok((status, body)) => (status, body.to_string(), "application/json"),
+                Err(Failure(status, reason)) => error(status, &reason),
+            },
+        },
+    };
+    let content_type = Header::from_bytes("Content-Type", content_type).unwrap();
+    let mut response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Some(seconds) = retry_after {
+        response.add_header(Header::from_bytes("Retry-After", seconds.to_string()).unwrap());
+    }
+    if let Err(err) = request.respond(response) {
+        eprintln!("error: failed to respond: {}", err);
+    }
+    metrics::request(endpoint, status, start.elapsed());
+}
+
+/// Status code, body and content type of a failed request.
+fn error(status: u16, reason: &str) -> (u16, String, &'static str) {
+    let body = json!({ "error": reason }).to_string();
+    (status, body, "application/json")
+}
+
+/// Handler of an endpoint or method, taking the request object and the
+/// budget of the request.
+pub type Handler = fn(&Value, Budget) -> Result<Value, Failure>;
+
+/// Find the handler of an endpoint or method by its name.
+fn handler(name: &str) -> Option<Handler> {
+    match name {
+        "solve" => Some(solve),
+        "rate" => Some(rate),
+        "generate" => Some(generate),
+        "hint" => Some(hint),
+        "batch" => Some(batch),
+        _ => None,
+    }
+}
+
+/// Name of the endpoint of a URL for metrics, which is "unknown" for URLs
+/// other than the endpoints, such that they do not add labels.
+fn endpoint(url: &str) -> &'static str {
+    match url.strip_prefix('/') {
+        Some("solve") => "solve",
+        Some("rate") => "rate",
+        Some("generate") => "generate",
+        Some("hint") => "hint",
+        Some("metrics") => "metrics",
+        Some("batch") => "batch",
+        Some("stream") => "stream",
+        Some(path) if path == "jobs" || path.starts_with("jobs/") => "jobs",
+        _ => "unknown",
+    }
+}
+
+/// Handle a request to one of the endpoints.
+fn handle(request: &mut Request, max_body: u64) -> Result<(u16, Value), Failure> {
+    let budget = Budget::start();
+    let path = request.url().strip_prefix('/').unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').collect();
+    let get = *request.method() == Method::Get;
+    let (endpoint, status): (Handler, u16) = match segments.as_slice() {
+        ["jobs", id] if get => return Ok((200, queue::status(id)?)),
+        ["jobs", id, "results"] if get => return Ok((200, queue::results(id)?)),
+        ["jobs", _] | ["jobs", _, "results"] => {
+            return Err(Failure(405, "only GET requests are accepted".to_string()))
+        }
+        ["jobs"] => (submit, 202),
+        [name] => match handler(name) {
+            Some(handler) => (handler, 200),
+            None => return Err(Failure(404, "unknown endpoint".to_string())),
+        },
+        _ => return Err(Failure(404, "unknown endpoint".to_string())),
+    };
+    if *request.method() != Method::Post {
+        return Err(Failure(405, "only POST requests are accepted".to_string()));
+    }
+
+    let too_large = || Failure(413, "request body is too large".to_string());
+    if request
+        .body_length()
+        .is_some_and(|length| length as u64 > max_body)
+    {
+        return Err(too_large());
+    }
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(max_body + 1)
+        .read_to_string(&mut body)
+        .map_err(|err| Failure(400, err.to_string()))?;
+    if body.len() as u64 > max_body {
+        return Err(too_large());
+    }
+    let body = match body.trim() {
+        "" => Value::Object(Default::default()),
+        body => serde_json::from_str(body).map_err(|err| Failure(400, err.to_string()))?,
+    };
+    Ok((status, endpoint(&body, budget)?))
+}
+
+/// Serve JSON-RPC requests on standard input, one on each line, writing the
+/// responses to standard output until the input ends.
+fn serve_stdio() -> super::Result {
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(Value::Array(batch)) if !batch.is_empty() => {
+                let responses: Vec<Value> = batch.iter().filter_map(call).collect();
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            Ok(request @ Value::Object(_)) => call(&request),
+            Ok(_) => Some(rpc_error(Value::Null, INVALID_REQUEST, "invalid request")),
+            Err(err) => Some(rpc_error(Value::Null, PARSE_ERROR, &err.to_string())),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Call the method of a JSON-RPC request, returning its response, or `None`
+/// for a notification, which has no identifier and gets no response.
+fn call(request: &Value) -> Option<Value> {
+    let id = request.get("id").cloned();
+    let method = match (request.get("jsonrpc"), request.get("method")) {
+        (Some(version), Some(Value::String(method))) if version == "2.0" => method,
+        _ => {
+            let id = id.unwrap_or(Value::Null);
+            return Some(rpc_error(id, INVALID_REQUEST, "invalid request"));
+        }
+    };
+    let params = match request.get("params") {
+        None => Value::Object(Default::default()),
+        Some(params) => params.clone(),
+    };
+    let outcome = match handler(method) {
+        Some(handler) => {
+            handler(&params, Budget::start()).map_err(|Failure(_, reason)| (INVALID_PARAMS, reason))
+        }
+        None => Err((METHOD_NOT_FOUND, "unknown method".to_string())),
+    };
+    let id = id?;
+    Some(match outcome {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => rpc_error(id, code, &message),
+    })
+}
+
+/// Construct a JSON-RPC error response.
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Limits of the time and guesses spent on a request, as configured using
+/// --timeout and --max-nodes, of which the time counts from the start of the
+/// request. The puzzles of a batch share the budget of the batch, while each
+/// puzzle of a job or stream has a budget of its own.
+#[derive(Clone, Copy)]
+pub struct Budget {
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    /// Start the budget of a request.
+    pub fn start() -> Self {
+        let timeout = OPTIONS.get().and_then(|options| options.timeout);
+        Self {
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+
+    /// Whether the time of the budget has run out.
+    fn exceeded(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Options of solving puzzles within what is left of the budget.
+    pub fn options(&self) -> Options {
+        let mut options = OPTIONS.get().cloned().unwrap_or_default();
+        options.timeout = self
+            .deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        options
+    }
+}
+
+/// Record a solve of an endpoint which failed by exceeding its limits.
+pub fn count_timeout(endpoint: &'static str) -> impl Fn(&&'static str) {
+    move |err| {
+        if *err == TIME_LIMIT_EXCEEDED || *err == NODE_LIMIT_EXCEEDED {
+            metrics::timeout(endpoint);
+        }
+    }
+}
+
+/// Parse the puzzle contained in a request.
+pub fn puzzle(body: &Value) -> Result<Puzzle, Failure> {
+    let puzzle = body
+        .get("puzzle")
+        .and_then(Value::as_str)
+        .ok_or("missing puzzle string")?;
+    Ok(puzzle.parse()?)
+}
+
+fn solve(body: &Value, budget: Budget) -> Result<Value, Failure> {
+    let solution = Solver::solve_with_options(&puzzle(body)?, &budget.options())
+        .inspect_err(count_timeout("solve"))?;
+    Ok(json!({
+        "solution": solution.row_representation(),
+        "brute_forces": solution.brute_forces,
+    }))
+}
+
+fn rate(body: &Value, budget: Budget) -> Result<Value, Failure> {
+    let steps = logic::solve_with_options(&puzzle(body)?, &budget.options())
+        .inspect_err(count_timeout("rate"))?;
+    metrics::techniques(&steps);
+    let rating = Rating::from_steps(&steps);
+    Ok(json!({
+        "difficulty": rating.difficulty.name(),
+        "score": rating.score,
+        "hardest": rating.hardest.map(|technique| technique.name()),
+        "steps": rating.steps,
+    }))
+}
+
+fn generate(body: &Value, budget: Budget) -> Result<Value, Failure> {
+    let seed = match body.get("seed") {
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64),
+        Some(seed) => seed.as_u64().ok_or("seed is not an unsigned integer")?,
+    };
+    let mut rng = Rng::new(seed);
+    let puzzle = match (body.get("difficulty"), body.get("technique")) {
+        (None, None) => {
+            let puzzle = generate::generate(&mut rng);
+            if budget.exceeded() {
+                metrics::timeout("generate");
+                return Err(TIME_LIMIT_EXCEEDED.into());
+            }
+            puzzle
+        }
+        (Some(difficulty), None) => {
+            let difficulty: Difficulty = difficulty
+                .as_str()
+                .ok_or("difficulty is not a string")?
+                .parse()?;
+            attempt(budget, || {
+                generate::generate_with_difficulty(&mut rng, difficulty, 1)
+            })?
+        }
+        (None, Some(technique)) => {
+            let technique: Technique = technique
+                .as_str()
+                .ok_or("technique is not a string")?
+                .parse()?;
+            attempt(budget, || {
+                generate::generate_with_technique(&mut rng, technique, 1)
+            })?
+        }
+        (Some(_), Some(_)) => return Err("difficulty and technique are exclusive".into()),
+    };
+    let steps = logic::solve_with_options(&puzzle, &budget.options())
+        .inspect_err(count_timeout("generate"))?;
+    Ok(json!({
+        "puzzle": puzzle.to_string(),
+        "difficulty": Rating::from_steps(&steps).difficulty.name(),
+    }))
+}
+
+/// Make attempts at generating a puzzle until one succeeds, giving up after
+/// [`GENERATE_ATTEMPTS`] attempts or when the budget has run out.
+fn attempt(
+    budget: Budget,
+    mut generate: impl FnMut() -> Result<Puzzle, &'static str>,
+) -> Result<Puzzle, Failure> {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match generate() {
+            Ok(puzzle) => return Ok(puzzle),
+            Err(err) if attempts == GENERATE_ATTEMPTS => return Err(err.into()),
+            Err(_) if budget.exceeded() => {
+                metrics::timeout("generate");
+                return Err(TIME_LIMIT_EXCEEDED.into());
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// Parse a batch into the handler of its operation and a request for each of
+/// its puzzles, consisting of the puzzle along with the other fields of the
+/// batch.
+fn requests(body: &Value) -> Result<(Handler, Vec<Value>), Failure> {
+    let operation = match body.get("operation") {
+        None => "solve",
+        Some(operation) => operation.as_str().ok_or("operation is not a string")?,
+    };
+    let handler = match operation {
+        "solve" | "rate" | "hint" => handler(operation).unwrap(),
+        _ => return Err("operation is not one of solve, rate or hint".into()),
+    };
+    let puzzles = body
+        .get("puzzles")
+        .and_then(Value::as_array)
+        .ok_or("missing puzzles array")?;
+    let mut fields = body.as_object().cloned().unwrap_or_default();
+    fields.remove("operation");
+    fields.remove("puzzles");
+    let requests = puzzles
+        .iter()
+        .map(|puzzle| {
+            let mut request = fields.clone();
+            request.insert("puzzle".to_string(), puzzle.clone());
+            Value::Object(request)
+        })
+        .collect();
+    Ok((handler, requests))
+}
+
+fn batch(body: &Value, budget: Budget) -> Result<Value, Failure> {
+    let (handler, requests) = requests(body)?;
+    if requests.len() > BATCH_SIZE {
+        let reason = format!(
+            "batch has more than {} puzzles, submit it as a job instead",
+            BATCH_SIZE
+        );
+        return Err(Failure(413, reason));
+    }
+    // Each puzzle gets what is left of the budget of the batch, and those
+    // after it has run out fail without being started.
+    let mut exceeded = false;
+    let results: Vec<Value> = requests
+        .iter()
+        .map(|request| match budget.exceeded() {
+            true => {
+                exceeded = true;
+                json!({ "error": TIME_LIMIT_EXCEEDED })
+            }
+            false => queue::respond(handler, request, budget),
+        })
+        .collect();
+    if exceeded {
+        metrics::timeout("batch");
+    }
+    Ok(json!({ "results": results }))
+}
+
+fn submit(body: &Value, _: Budget) -> Result<Value, Failure> {
+    let (handler, requests) = requests(body)?;
+    let id = queue::submit(handler, requests)?;
+    Ok(json!({ "id": id, "status": "queued" }))
+}
+
+fn hint(body: &Value, budget: Budget) -> Result<Value, Failure> {
+    let step = logic::hint_with_options(&puzzle(body)?, &budget.options())
+        .inspect_err(count_timeout("hint"))?;
+    metrics::techniques(slice::from_ref(&step));
+    let level = match body.get("level") {
+        None => return Ok(step_json(&step)),
+        Some(level) => level
+            .as_str()
+            .ok_or("level is not a string")?
+            .parse::<HintLevel>()?,
+    };
+    let cell = |(row, col): (usize, usize)| json!({ "row": row + 1, "column": col + 1 });
+    Ok(match step.hint(level)? {
+        Hint::Region(house) => json!({ "level": level.name(), "house": house.to_string() }),
+        Hint::Cell(coords) => json!({ "level": level.name(), "cell": cell(coords) }),
+        Hint::Digit {
+            cell: coords,
+            digit,
+            placement,
+        } => json!({
+            "level": level.name(),
+            "cell": cell(coords),
+            "value": digit,
+            "placement": placement,
+        }),
+        Hint::Explanation(step) => step_json(&step),
+    })
+}
+
+/// Represent a step as JSON, with rows and columns numbered from one.
+pub fn step_json(step: &Step) -> Value {
+    let cell = |(row, col): (usize, usize)| json!({ "row": row + 1, "column": col + 1 });
+    let candidate = |&((row, col), value): &((usize, usize), u8)| json!({ "row": row + 1, "column": col + 1, "value": value });
+    json!({
+        "technique": step.technique.name(),
+        "description": step.to_string(),
+        "houses": step.houses.iter().map(|house| house.to_string()).collect::<Vec<_>>(),
+        "cells": step.pattern.iter().copied().map(cell).collect::<Vec<_>>(),
+        "digits": step.digits,
+        "placements": step.placements.iter().map(candidate).collect::<Vec<_>>(),
+        "eliminations": step.eliminations.iter().map(candidate).collect::<Vec<_>>(),
+    })
+}
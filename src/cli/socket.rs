@@ -0,0 +1,146 @@
+//! WebSocket endpoint of the `serve` subcommand, streaming the steps of
+//! logical solves as they are found, such that web interfaces can animate the
+//! reasoning of the solver.
+
+use super::serve::{self, Budget, Failure};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::thread;
+use std::time::Duration;
+use sudoku_solver::logic::Grid;
+use sudoku_solver::{Puzzle, Solver};
+use tiny_http::{Header, Request, Response};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::{Role, WebSocketConfig};
+use tungstenite::{Message, WebSocket};
+
+/// Maximum delay between steps in milliseconds a client may request.
+const MAX_DELAY: u64 = 5000;
+
+/// Check that a request is a WebSocket handshake, returning the key with
+/// which to accept it.
+pub fn accept_key(request: &Request) -> Result<String, Failure> {
+    let header = |name: &'static str| {
+        request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv(name))
+            .map(|header| header.value.as_str())
+    };
+    let upgrade = header("Upgrade").is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+    if !upgrade {
+        return Err(Failure(426, "expected a WebSocket handshake".to_string()));
+    }
+    if header("Sec-WebSocket-Version") != Some("13") {
+        return Err(Failure(426, "unsupported WebSocket version".to_string()));
+    }
+    let key = header("Sec-WebSocket-Key").ok_or("missing WebSocket key")?;
+    Ok(derive_accept_key(key.as_bytes()))
+}
+
+/// Complete the handshake of a request, returning the connection.
+pub fn upgrade(request: Request, key: String, max_body: u64) -> WebSocket<impl Read + Write> {
+    let response = Response::empty(101)
+        .with_header(Header::from_bytes("Connection", "Upgrade").unwrap())
+        .with_header(Header::from_bytes("Sec-WebSocket-Accept", key).unwrap());
+    let stream = request.upgrade("websocket", response);
+    let config = WebSocketConfig::default().max_message_size(Some(max_body as usize));
+    WebSocket::from_raw_socket(stream, Role::Server, Some(config))
+}
+
+/// Stream the solves of the puzzles received on a connection until it is
+/// closed. Each message is a {"puzzle", "delay"?} object, in response to
+/// which a "start" event is sent, followed by a "step" event for each step
+/// with the grid after it, and a "solved" or "error" event at the end. The
+/// steps are sent "delay" milliseconds apart, for pacing an animation.
+pub fn session(mut socket: WebSocket<impl Read + Write>) {
+    loop {
+        let request = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) | Err(_) => return,
+            Ok(_) => continue,
+        };
+        let mut send = |event: Value| socket.send(Message::text(event.to_string()));
+        let result = match parse(&request) {
+            Ok((puzzle, delay)) => stream(&puzzle, delay, &mut send),
+            Err(Failure(_, reason)) => send(json!({ "event": "error", "error": reason })),
+        };
+        if result.is_err() {
+            return;
+        }
+    }
+}
+
+/// Parse the puzzle and delay of a message.
+fn parse(request: &str) -> Result<(Puzzle, Duration), Failure> {
+    let body: Value = serde_json::from_str(request).map_err(|err| Failure(400, err.to_string()))?;
+    let puzzle = serve::puzzle(&body)?;
+    let delay = match body.get("delay") {
+        None => 0,
+        Some(delay) => delay
+            .as_u64()
+            .ok_or("delay is not an unsigned integer")?
+            .min(MAX_DELAY),
+    };
+    Ok((puzzle, Duration::from_millis(delay)))
+}
+
+/// Solve a puzzle logically, sending the events of the solve. Guesses are
+/// taken from the solution when no technique applies, which is searched
+/// within the limits of the server.
+fn stream<E>(
+    puzzle: &Puzzle,
+    delay: Duration,
+    send: &mut impl FnMut(Value) -> Result<(), E>,
+) -> Result<(), E> {
+    send(json!({ "event": "start", "puzzle": puzzle.to_string() }))?;
+    let mut grid = match Grid::new(puzzle) {
+        Ok(grid) => grid,
+        Err(err) => return send(json!({ "event": "error", "error": err })),
+    };
+    let mut solution = None;
+    let mut index = 0;
+    while !grid.is_solved() {
+        let step = match grid.next_step() {
+            Some(step) => step,
+            None => {
+                let solution = match &mut solution {
+                    Some(solution) => solution,
+                    // The budget starts here, as the delays between steps
+                    // do not count towards the time spent solving.
+                    None => match Solver::solve_with_options(puzzle, &Budget::start().options()) {
+                        Ok(found) => solution.insert(found),
+                        Err(err) => {
+                            serve::count_timeout("stream")(&err);
+                            return send(json!({ "event": "error", "error": err }));
+                        }
+                    },
+                };
+                match grid.brute_force_step(solution) {
+                    Some(step) => step,
+                    None => {
+                        let err = "no unfilled cell was found";
+                        return send(json!({ "event": "error", "error": err }));
+                    }
+                }
+            }
+        };
+        if let Err(err) = grid.apply(&step) {
+            return send(json!({ "event": "error", "error": err }));
+        }
+        if index > 0 && !delay.is_zero() {
+            thread::sleep(delay);
+        }
+        let mut event = serve::step_json(&step);
+        event["event"] = json!("step");
+        event["index"] = json!(index);
+        event["grid"] = json!(grid.to_puzzle().to_string());
+        send(event)?;
+        index += 1;
+    }
+    send(json!({
+        "event": "solved",
+        "solution": grid.to_puzzle().to_string(),
+        "steps": index,
+    }))
+}
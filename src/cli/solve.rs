@@ -0,0 +1,1012 @@
+use super::checkpoint::{Checkpoint, State};
+use super::color::{self, ColorChoice};
+#[cfg(feature = "sqlite")]
+use super::database::Database;
+use super::format::Notation;
+use super::input::{self, Failures, Inputs, Line};
+use super::jobs::{self, Jobs};
+#[cfg(feature = "mmap")]
+use super::mapped::Mapped;
+use super::progress::Progress;
+use super::report::Report;
+use super::rules;
+use super::streams::{self, Compression};
+#[cfg(feature = "arrow")]
+use super::table::{Row, TableFormat, TableWriter};
+use super::usage::Usage;
+use super::verbosity::{self, Verbosity};
+use super::{Error, Result};
+use clap::{Parser, ValueEnum};
+use serde_json::{json, Value};
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use sudoku_solver::logic::{self, Step, Technique};
+use sudoku_solver::rating::{Difficulty, Rating};
+use sudoku_solver::snapshot::Snapshot;
+use sudoku_solver::variants::RuleSet;
+use sudoku_solver::{Options, Puzzle, Solution, Solver, NODE_LIMIT_EXCEEDED, TIME_LIMIT_EXCEEDED};
+
+/// Solve all puzzles in a file, showing statistics afterwards. Puzzles which
+/// are invalid or unsolvable are reported along with their line number and
+/// skipped.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Solve this puzzle given on the command line instead of reading puzzles
+    /// from files, writing its solution in the pretty format
+    #[arg(long, value_name = "PUZZLE",
+        conflicts_with_all = ["paths", "skip", "limit", "sample", "format", "tiers", "checkpoint", "report", "rejects"])]
+    puzzle: Option<String>,
+    /// File to write the solved puzzles to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Also write the puzzles which failed to this file, each preceded by a
+    /// comment with its location and the reason, such that they can be
+    /// inspected or fed back in as input
+    #[arg(long, value_name = "FILE", conflicts_with = "checkpoint")]
+    rejects: Option<PathBuf>,
+    /// Format of the output
+    #[arg(long, value_enum, default_value_t = OutputFormat::Line)]
+    format: OutputFormat,
+    /// Show each puzzle next to its solution in the pretty format
+    #[arg(long)]
+    with_puzzle: bool,
+    /// When to color the clues and solved cells in the pretty format
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+    /// Also verify that every puzzle has a unique solution, treating puzzles
+    /// with multiple solutions as failures
+    #[arg(long)]
+    check_unique: bool,
+    /// Also check every solution independently of the solver before writing
+    /// it, treating solutions which do not keep the givens or do not hold
+    /// every value in every house as failures
+    #[arg(long)]
+    verify: bool,
+    /// Show the values and candidates of the grid at the point where solving
+    /// failed below the failure of each invalid or unsolvable puzzle
+    #[arg(long)]
+    snapshot: bool,
+    /// Give up on a puzzle after brute-forcing it for this many seconds
+    #[arg(long, value_name = "SECONDS", value_parser = parse_timeout)]
+    timeout: Option<Duration>,
+    /// Give up on a puzzle after making this many brute-force guesses
+    #[arg(long, value_name = "N")]
+    max_nodes: Option<u32>,
+    /// Also rate every puzzle, showing the number of puzzles solvable purely
+    /// logically per difficulty tier in the statistics, which is slower
+    #[arg(long)]
+    tiers: bool,
+    /// Notation of the steps shown when very verbose
+    #[arg(long, value_enum, default_value_t = Notation::Default)]
+    notation: Notation,
+    /// Periodically record the progress in this file, such that an
+    /// interrupted run can be resumed using --resume
+    #[arg(long, value_name = "FILE")]
+    checkpoint: Option<PathBuf>,
+    /// Resume an interrupted run from its checkpoint, continuing the output
+    /// where it left off, which requires the same inputs and options
+    #[arg(long, requires = "checkpoint")]
+    resume: bool,
+    /// Also record the results in this SQLite database, adding puzzles which
+    /// are not in it yet and updating the results of those which are
+    #[cfg(feature = "sqlite")]
+    #[arg(long, value_name = "FILE", conflicts_with = "puzzle")]
+    database: Option<PathBuf>,
+    /// Also write a JSON report of the run to this file, with the result of
+    /// every puzzle, aggregate statistics, the configuration and the timing,
+    /// or a Markdown report with tables of the statistics if the file ends
+    /// in .md
+    #[arg(long, value_name = "FILE")]
+    report: Option<PathBuf>,
+    /// Show this many of the hardest puzzles next to their solutions in a
+    /// Markdown report, which are the hardest by rating score when rating
+    /// and otherwise by the number of guesses
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "report")]
+    report_hardest: usize,
+    /// Solve under the rules of a variant declared in this JSON or TOML
+    /// descriptor, such as diagonals, killer cages, thermometers and dots,
+    /// which apply to every puzzle on top of the classic rules. Puzzles are
+    /// not rated, and the time and guess limits do not apply
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["timeout", "max_nodes", "tiers", "snapshot"])]
+    rules: Option<PathBuf>,
+    /// Memory-map the input files instead of reading them line by line,
+    /// which requires records of equal width: lines of exactly 81 values
+    /// without labels, or packed binary records. Only solutions in the line
+    /// format are written
+    #[cfg(feature = "mmap")]
+    #[arg(long, conflicts_with_all = ["format", "with_puzzle", "tiers", "checkpoint", "rules", "snapshot", "puzzle", "rejects", "follow"])]
+    mmap: bool,
+    /// Flush the output after the result of every puzzle instead of when the
+    /// buffer is full, such that following stages of a pipeline see results
+    /// as they are produced. Output files are then written to directly
+    #[arg(long)]
+    line_buffered: bool,
+    /// Keep running after reaching the end of the input files, solving the
+    /// puzzles on lines appended to them and in files created in the input
+    /// directories as they appear, until interrupted. Compressed files are
+    /// solved once they stop growing. Implies --line-buffered
+    #[arg(long, conflicts_with_all = ["sample", "checkpoint", "puzzle", "report"])]
+    follow: bool,
+    #[command(flatten)]
+    jobs: Jobs,
+}
+
+/// Format in which the solutions are written.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// The solution of each puzzle as a line of 81 values
+    Line,
+    /// Each puzzle as given followed by a comma and its solution on a line,
+    /// such that solutions need not be joined back to their puzzles
+    Pair,
+    /// Each puzzle as given followed by a tab and its solution on a line
+    PairTab,
+    /// A JSON object on each line with the label, input, solution,
+    /// statistics, solve time and error of each puzzle
+    Jsonl,
+    /// Comma-separated values with a header, with the label, input,
+    /// solution, statistics, solve time, difficulty and error of each puzzle
+    Csv,
+    /// The solution of each puzzle as a framed grid, followed by an empty line
+    Pretty,
+    /// Apache Parquet file with the same columns as the CSV format, along
+    /// with the rating score and hardest technique
+    #[cfg(feature = "arrow")]
+    Parquet,
+    /// Apache Arrow IPC file with the same columns as the Parquet format
+    #[cfg(feature = "arrow")]
+    Arrow,
+}
+
+impl Args {
+    /// Whether the output is flushed after the result of every puzzle.
+    fn line_buffered(&self) -> bool {
+        self.line_buffered || self.follow
+    }
+}
+
+impl OutputFormat {
+    /// Whether the difficulty of each puzzle is written, which requires
+    /// rating every puzzle.
+    fn rated(self) -> bool {
+        match self {
+            OutputFormat::Csv => true,
+            #[cfg(feature = "arrow")]
+            OutputFormat::Parquet | OutputFormat::Arrow => true,
+            _ => false,
+        }
+    }
+
+    /// Columnar file format, if this is one.
+    #[cfg(feature = "arrow")]
+    fn table(self) -> Option<TableFormat> {
+        match self {
+            OutputFormat::Parquet => Some(TableFormat::Parquet),
+            OutputFormat::Arrow => Some(TableFormat::Arrow),
+            _ => None,
+        }
+    }
+}
+
+/// Error of a puzzle with multiple solutions when checking uniqueness.
+pub const NOT_UNIQUE: &str = "puzzle has multiple solutions";
+
+/// Result of solving the puzzle on a line.
+struct Solved {
+    line: Line,
+    puzzle: Option<Puzzle>,
+    solution: std::result::Result<Solution, &'static str>,
+    elapsed: Duration,
+    /// Rating of the puzzle, which is only computed if it is needed.
+    rating: Option<Rating>,
+    /// Steps solving the puzzle the way a human would, which are only
+    /// computed when rating or when very verbose.
+    steps: Vec<Step>,
+    /// Limit which the logical solve exceeded, such that the puzzle is not
+    /// rated.
+    rating_limit: Option<&'static str>,
+    /// The grid at the point where solving failed, if requested.
+    snapshot: Option<Snapshot>,
+}
+
+/// Aggregate statistics of the solved puzzles, shown after solving.
+#[derive(Default)]
+struct Aggregate {
+    solved: u64,
+    brute_forces: u64,
+    guesses: u64,
+    max_guesses: u32,
+    elapsed: Duration,
+    max_elapsed: Duration,
+    /// Number of puzzles per number of guesses, in buckets of powers of two
+    /// such that bucket `n > 0` holds the puzzles with `2^(n-1)` up to
+    /// `2^n - 1` guesses.
+    histogram: Vec<u64>,
+    /// Number of puzzles solvable purely logically per difficulty tier.
+    logical: [u64; 5],
+    /// Number of rated puzzles which require brute force.
+    brute_forced: u64,
+    /// Usage of the techniques by the puzzles solved step by step.
+    usage: Usage,
+}
+
+impl Aggregate {
+    fn add(
+        &mut self,
+        solution: &Solution,
+        elapsed: Duration,
+        rating: Option<&Rating>,
+        steps: &[Step],
+    ) {
+        self.solved += 1;
+        self.brute_forces += solution.brute_forces as u64;
+        self.guesses += solution.guesses as u64;
+        self.max_guesses = self.max_guesses.max(solution.guesses);
+        self.elapsed += elapsed;
+        self.max_elapsed = self.max_elapsed.max(elapsed);
+        let bucket = (u32::BITS - solution.guesses.leading_zeros()) as usize;
+        if self.histogram.len() <= bucket {
+            self.histogram.resize(bucket + 1, 0);
+        }
+        self.histogram[bucket] += 1;
+        match rating {
+            Some(rating) if rating.hardest == Some(Technique::BruteForce) => self.brute_forced += 1,
+            Some(rating) => self.logical[rating.difficulty as usize] += 1,
+            None => {}
+        }
+        // Steps are only computed when rating or when very verbose, and
+        // complete puzzles take none.
+        if rating.is_some() || !steps.is_empty() {
+            self.usage.add(steps);
+        }
+    }
+
+    /// Write the statistics to standard error.
+    fn print(&self, tiers: bool, notation: Notation) {
+        eprintln!("Total brute-force fills: {}", self.brute_forces);
+        if self.solved == 0 {
+            return;
+        }
+        eprintln!(
+            "Guesses: average {:.2}, max {}",
+            self.guesses as f64 / self.solved as f64,
+            self.max_guesses
+        );
+        eprintln!(
+            "Solve time: average {:?}, max {:?}",
+            self.elapsed / self.solved as u32,
+            self.max_elapsed
+        );
+        // The histogram ends at the last bucket holding puzzles, and the
+        // empty buckets before the first one are left out as well.
+        eprintln!("Guesses histogram:");
+        let first = self.histogram.iter().position(|count| *count > 0);
+        let buckets = self.histogram.iter().enumerate();
+        for (bucket, count) in buckets.skip(first.unwrap_or(0)) {
+            let (low, high) = match bucket {
+                0 => (0, 0),
+                n => (1u64 << (n - 1), (1u64 << n) - 1),
+            };
+            if low == high {
+                eprintln!("  {}: {}", low, count);
+            } else {
+                eprintln!("  {}-{}: {}", low, high, count);
+            }
+        }
+        if tiers {
+            eprintln!("Solvable logically:");
+            for (difficulty, count) in Difficulty::ALL.into_iter().zip(self.logical) {
+                eprintln!("  {}: {}", difficulty, count);
+            }
+            eprintln!("Requiring brute force: {}", self.brute_forced);
+        }
+        if !self.usage.is_empty() {
+            self.usage.print(notation);
+        }
+    }
+
+    /// The statistics as a JSON object for reports.
+    fn to_json(&self, tiers: bool) -> Value {
+        let mut object = json!({
+            "solved": self.solved,
+            "brute_forces": self.brute_forces,
+            "guesses": self.guesses,
+            "max_guesses": self.max_guesses,
+            "elapsed_us": self.elapsed.as_micros() as u64,
+            "max_elapsed_us": self.max_elapsed.as_micros() as u64,
+            "guesses_histogram": self.histogram,
+        });
+        if self.solved > 0 {
+            object["average_guesses"] = json!(self.guesses as f64 / self.solved as f64);
+            object["average_elapsed_us"] =
+                json!((self.elapsed / self.solved as u32).as_micros() as u64);
+        }
+        if tiers {
+            let logical: serde_json::Map<String, Value> = Difficulty::ALL
+                .into_iter()
+                .zip(self.logical)
+                .map(|(difficulty, count)| (difficulty.name().to_string(), json!(count)))
+                .collect();
+            object["logical"] = Value::Object(logical);
+            object["brute_forced"] = json!(self.brute_forced);
+        }
+        if !self.usage.is_empty() {
+            object["techniques"] = self.usage.to_json();
+        }
+        object
+    }
+}
+
+/// Configuration of a run as a JSON object for reports.
+fn configuration(args: &Args, mapped: bool) -> Value {
+    json!({
+        "format": args.format.to_possible_value().map(|value| value.get_name().to_string()),
+        "check_unique": args.check_unique,
+        "verify": args.verify,
+        "timeout_s": args.timeout.map(|timeout| timeout.as_secs_f64()),
+        "max_nodes": args.max_nodes,
+        "tiers": args.tiers,
+        "rules": args.rules.as_ref().map(|rules| rules.display().to_string()),
+        "mmap": mapped,
+        "rejects": args.rejects.as_ref().map(|rejects| rejects.display().to_string()),
+        "line_buffered": args.line_buffered(),
+        "jobs": args.jobs.count(),
+    })
+}
+
+pub fn run(args: Args) -> Result {
+    let rules = args.rules.as_deref().map(rules::load).transpose()?;
+    if let Some(puzzle) = &args.puzzle {
+        return run_inline(&args, puzzle, rules.as_ref());
+    }
+    let files = args.inputs.files()?;
+    let output = args.output.as_deref();
+    let mut checkpoint = args.checkpoint.as_deref().map(Checkpoint::new);
+    if checkpoint.is_some() && streams::output_compression(output) != Compression::None {
+        return Err(Error::Fatal(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "checkpoints require an uncompressed output",
+        )));
+    }
+    #[cfg(feature = "arrow")]
+    if args.format.table().is_some() && (checkpoint.is_some() || streams::appending()) {
+        return Err(Error::Fatal(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "parquet and arrow outputs cannot be checkpointed or appended to",
+        )));
+    }
+    #[cfg(feature = "arrow")]
+    if args.format.table().is_some() && args.follow {
+        return Err(Error::Fatal(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "parquet and arrow outputs cannot be followed",
+        )));
+    }
+    let resumed = match &checkpoint {
+        Some(checkpoint) if args.resume => checkpoint.load()?,
+        _ => None,
+    };
+    // The CSV header is not repeated when appending to an existing output.
+    let appending = streams::appending()
+        && streams::output_length(output).is_ok_and(|length| length.is_some_and(|l| l > 0));
+    // Checkpointed runs write to the output directly, such that the output
+    // up to the last checkpoint is kept when interrupted. Line-buffered runs
+    // do so as well, such that the results can be followed as they come.
+    let mut writer = match (resumed, &checkpoint) {
+        (Some(state), _) => streams::resume_output(output, state.output_length)?,
+        // Interrupted before the first checkpoint, so nothing is kept.
+        (None, Some(_)) if args.resume => streams::resume_output(output, Some(0))?,
+        (None, Some(_)) => streams::create_output_in_place(output)?,
+        (None, None) if args.line_buffered() => streams::create_output_in_place(output)?,
+        (None, None) => streams::create_output(output)?,
+    };
+    let resumed = resumed.map_or(0, |state| state.puzzles);
+    let mut rejects = match (&args.rejects, args.line_buffered()) {
+        (Some(path), true) => Some(streams::create_output_in_place(Some(path))?),
+        (Some(path), false) => Some(streams::create_output(Some(path))?),
+        (None, _) => None,
+    };
+
+    let options = Options {
+        #[cfg(feature = "parallel")]
+        parallel: false,
+        max_guesses: args.max_nodes,
+        timeout: args.timeout,
+    };
+    #[cfg(feature = "mmap")]
+    if args.mmap {
+        return run_mapped(&args, &files, writer, &options);
+    }
+    let mut failures = Failures::default();
+    let mut num_solved = Vec::new();
+    let mut aggregate = Aggregate::default();
+    let mut num_not_unique = 0;
+    let mut num_timed_out = 0;
+    let mut num_node_limited = 0;
+    let mut report = args
+        .report
+        .as_ref()
+        .map(|_| Report::new(args.report_hardest));
+
+    if args.format == OutputFormat::Csv && resumed == 0 && !appending {
+        writeln!(
+            writer,
+            "label,puzzle,solution,clues,guesses,backtracks,elapsed_us,difficulty,error"
+        )?;
+    }
+
+    #[cfg(feature = "arrow")]
+    let mut table = args.format.table().map(TableWriter::new).transpose()?;
+    #[cfg(feature = "sqlite")]
+    let mut database = args.database.as_deref().map(Database::open).transpose()?;
+    let color = args.color.enabled(output);
+    let mut progress = match args.follow {
+        true => Progress::unbounded(),
+        false => Progress::new(&files),
+    };
+    let follow = args
+        .follow
+        .then(|| args.inputs.follow(&files))
+        .transpose()?;
+    progress.exclude(resumed);
+    let mut num_processed = resumed;
+    let map = |solver: &mut Solver, line: Line| {
+        let start = Instant::now();
+        let puzzle = line.puzzle();
+        let mut solution = match &rules {
+            Some(rules) => puzzle.and_then(|puzzle| rules.solve(&puzzle)),
+            None => puzzle
+                .and_then(|puzzle| solver.load(&puzzle))
+                .and_then(|_| solver.run(&options)),
+        };
+        let elapsed = start.elapsed();
+        let snapshot = match (&solution, &puzzle) {
+            (Err(_), Ok(_)) if args.snapshot => Some(solver.snapshot()),
+            _ => None,
+        };
+        let puzzle = puzzle.ok();
+        if let (true, Ok(found), Some(puzzle)) = (args.verify, &solution, &puzzle) {
+            let verified = match &rules {
+                Some(rules) => rules.verify(puzzle, found),
+                None => found.verify(puzzle),
+            };
+            if let Err(err) = verified {
+                solution = Err(err);
+            }
+        }
+        if let (true, Ok(_), Some(puzzle)) = (args.check_unique, &solution, &puzzle) {
+            let count = match &rules {
+                Some(rules) => rules.count_solutions(puzzle, 2),
+                None => Solver::count_solutions(puzzle, 2),
+            };
+            if count > 1 {
+                solution = Err(NOT_UNIQUE);
+            }
+        }
+        // The logical solver only knows the classic rules, and puzzles of
+        // which the solve exceeded a limit are not rated either, as the
+        // logical solve would search them the same way. Its brute-force
+        // steps are bounded by the same limits.
+        let limited = matches!(solution, Err(TIME_LIMIT_EXCEEDED | NODE_LIMIT_EXCEEDED));
+        let rated = (args.tiers || args.format.rated()) && rules.is_none() && !limited;
+        let mut rating_limit = None;
+        let steps = match &puzzle {
+            Some(_) if rules.is_some() || limited => None,
+            Some(puzzle) if rated || verbosity::get() == Verbosity::VeryVerbose => {
+                match logic::solve_with_options(puzzle, &options) {
+                    Ok(steps) => Some(steps),
+                    Err(err @ (TIME_LIMIT_EXCEEDED | NODE_LIMIT_EXCEEDED)) => {
+                        rating_limit = Some(err);
+                        None
+                    }
+                    Err(_) => None,
+                }
+            }
+            _ => None,
+        };
+        let rating = match &steps {
+            Some(steps) if rated => Some(Rating::from_steps(steps)),
+            _ => None,
+        };
+        let steps = steps.unwrap_or_default();
+        Solved {
+            line,
+            puzzle,
+            solution,
+            elapsed,
+            rating,
+            steps,
+            snapshot,
+            rating_limit,
+        }
+    };
+    let consume = |solved: Solved| {
+        progress.inc();
+        match solved.rating_limit {
+            Some(TIME_LIMIT_EXCEEDED) => num_timed_out += 1,
+            Some(NODE_LIMIT_EXCEEDED) => num_node_limited += 1,
+            _ => {}
+        }
+        match &solved.solution {
+            Ok(solution) => {
+                input::tally(&mut num_solved, solved.line.location.file);
+                aggregate.add(
+                    solution,
+                    solved.elapsed,
+                    solved.rating.as_ref(),
+                    &solved.steps,
+                );
+                if verbosity::get() >= Verbosity::Verbose {
+                    eprintln!(
+                        "{}: solved in {:?} with {} guesses",
+                        solved.line.location, solved.elapsed, solution.guesses
+                    );
+                }
+                let steps = match verbosity::get() {
+                    Verbosity::VeryVerbose => solved.steps.as_slice(),
+                    _ => &[],
+                };
+                for step in steps {
+                    match args.notation {
+                        Notation::Default => eprintln!("  {}", step),
+                        Notation::Hodoku => eprintln!("  {}", step.hodoku()),
+                    }
+                }
+            }
+            Err(err) => {
+                match *err {
+                    NOT_UNIQUE => num_not_unique += 1,
+                    TIME_LIMIT_EXCEEDED => num_timed_out += 1,
+                    NODE_LIMIT_EXCEEDED => num_node_limited += 1,
+                    _ => {}
+                }
+                failures.report(&solved.line.location, err);
+                if let Some(snapshot) = &solved.snapshot {
+                    eprintln!("{}", snapshot);
+                }
+                if let Some(rejects) = &mut rejects {
+                    let location = &solved.line.location;
+                    writeln!(rejects, "# {}: {}", location, err)?;
+                    writeln!(rejects, "{}{}", location.prefix(), solved.line.text)?;
+                    if args.line_buffered() {
+                        rejects.flush()?;
+                    }
+                }
+            }
+        }
+        if let Some(report) = &mut report {
+            report.add(
+                &solved.line.location,
+                solved.puzzle.as_ref(),
+                &solved.solution,
+                solved.elapsed,
+                solved.rating.as_ref(),
+            );
+        }
+        #[cfg(feature = "arrow")]
+        if let Some(table) = &mut table {
+            table.push(row(&solved), &mut writer)?;
+        }
+        #[cfg(feature = "sqlite")]
+        if let (Some(database), Some(puzzle)) = (&mut database, &solved.puzzle) {
+            database.record_solution(
+                puzzle,
+                &solved.solution,
+                solved.elapsed,
+                solved.rating.as_ref(),
+            )?;
+        }
+        write(&mut writer, &solved, &args, color)?;
+        if args.line_buffered() {
+            writer.flush()?;
+        }
+        num_processed += 1;
+        match &mut checkpoint {
+            Some(checkpoint) if checkpoint.due() => {
+                writer.flush()?;
+                checkpoint.save(State {
+                    puzzles: num_processed,
+                    output_length: streams::output_length(output)?,
+                })
+            }
+            _ => Ok(()),
+        }
+    };
+    // Results are consumed as soon as the lines read so far are solved when
+    // following, instead of waiting for a whole chunk of lines to be appended.
+    let jobs = args.jobs.count();
+    match follow {
+        Some(batches) => jobs::map_batches(jobs, batches, Solver::new, map, consume)?,
+        None => {
+            let lines = files.lines().skip(resumed as usize);
+            jobs::map_ordered(jobs, lines, Solver::new, map, consume)?
+        }
+    }
+    progress.finish();
+    #[cfg(feature = "arrow")]
+    if let Some(table) = table {
+        table.finish(&mut writer)?;
+    }
+    writer.finish()?;
+    if let Some(rejects) = rejects {
+        rejects.finish()?;
+    }
+    #[cfg(feature = "sqlite")]
+    let recorded = database.map(Database::finish).transpose()?;
+    if let Some(checkpoint) = checkpoint {
+        checkpoint.remove()?;
+    }
+    if let (Some(path), Some(report)) = (&args.report, report) {
+        let mut aggregates = aggregate.to_json(args.tiers);
+        aggregates["failed"] = json!(failures.count());
+        aggregates["resumed"] = json!(resumed);
+        if args.check_unique {
+            aggregates["not_unique"] = json!(num_not_unique);
+        }
+        if args.timeout.is_some() {
+            aggregates["timed_out"] = json!(num_timed_out);
+        }
+        if args.max_nodes.is_some() {
+            aggregates["node_limited"] = json!(num_node_limited);
+        }
+        report.write(path, &files, configuration(&args, false), aggregates)?;
+    }
+
+    if verbosity::summary() {
+        files.print_name();
+        eprintln!(
+            "Output file: {}",
+            streams::display_name(args.output.as_ref(), "standard output")
+        );
+        if let Some(path) = &args.rejects {
+            eprintln!("Rejects file: {}", path.display());
+        }
+        if resumed > 0 {
+            eprintln!("Resumed after: {} puzzles", resumed);
+        }
+        files.summarize(&num_solved, "solved", failures.per_file(), "failed");
+        eprintln!("Total solved: {}", num_solved.iter().sum::<usize>());
+        eprintln!("Total failed: {}", failures.count());
+        if args.check_unique {
+            eprintln!("Total with multiple solutions: {}", num_not_unique);
+        }
+        if args.timeout.is_some() {
+            eprintln!("Total timed out: {}", num_timed_out);
+        }
+        if args.max_nodes.is_some() {
+            eprintln!("Total exceeding node limit: {}", num_node_limited);
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some((added, existing)) = recorded {
+            eprintln!(
+                "Database: {} new puzzles, {} already present",
+                added, existing
+            );
+        }
+        aggregate.print(args.tiers, args.notation);
+    }
+    failures.finish()
+}
+
+/// Solve a single puzzle given on the command line, writing its solution in
+/// the pretty format.
+fn run_inline(args: &Args, text: &str, rules: Option<&RuleSet>) -> Result {
+    let puzzle: Puzzle = text.parse().map_err(|err| {
+        Error::Fatal(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid puzzle: {}", err),
+        ))
+    })?;
+    let options = Options {
+        #[cfg(feature = "parallel")]
+        parallel: false,
+        max_guesses: args.max_nodes,
+        timeout: args.timeout,
+    };
+    let start = Instant::now();
+    let mut solver = Solver::new();
+    let mut solution = match rules {
+        Some(rules) => rules.solve(&puzzle),
+        None => solver.load(&puzzle).and_then(|_| solver.run(&options)),
+    };
+    let elapsed = start.elapsed();
+    if let (true, Ok(found)) = (args.verify, &solution) {
+        let verified = match rules {
+            Some(rules) => rules.verify(&puzzle, found),
+            None => found.verify(&puzzle),
+        };
+        if let Err(err) = verified {
+            solution = Err(err);
+        }
+    }
+    if let (true, Ok(_)) = (args.check_unique, &solution) {
+        let count = match rules {
+            Some(rules) => rules.count_solutions(&puzzle, 2),
+            None => Solver::count_solutions(&puzzle, 2),
+        };
+        if count > 1 {
+            solution = Err(NOT_UNIQUE);
+        }
+    }
+    let solution = match solution {
+        Ok(solution) => solution,
+        Err(err) => {
+            eprintln!("puzzle: {}", err);
+            if args.snapshot && rules.is_none() {
+                eprintln!("{}", solver.snapshot());
+            }
+            return Err(Error::Failures("1 puzzle failed".to_string()));
+        }
+    };
+
+    let output = args.output.as_deref();
+    let color = args.color.enabled(output);
+    let mut writer = streams::create_output(output)?;
+    let framed = color::framed(solution.cells(), &puzzle, color);
+    if args.with_puzzle {
+        let given = color::framed(puzzle.cells(), &puzzle, color);
+        for (left, right) in given.lines().zip(framed.lines()) {
+            writeln!(writer, "{}   {}", left, right)?;
+        }
+    } else {
+        writeln!(writer, "{}", framed)?;
+    }
+    writer.finish()?;
+    if verbosity::get() >= Verbosity::Verbose {
+        eprintln!("Solved in {:?} with {} guesses", elapsed, solution.guesses);
+    }
+    Ok(())
+}
+
+/// Solve the puzzles of memory-mapped inputs, writing their solutions in the
+/// line format without allocating for each puzzle.
+#[cfg(feature = "mmap")]
+fn run_mapped(
+    args: &Args,
+    files: &input::Files,
+    mut writer: streams::Output,
+    options: &Options,
+) -> Result {
+    #[cfg(feature = "sqlite")]
+    if args.database.is_some() {
+        return Err(Error::Fatal(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "memory-mapped inputs cannot be recorded in a database",
+        )));
+    }
+    let maps = files
+        .paths()
+        .map(|path| match path {
+            Some(path) => Mapped::open(path),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "standard input cannot be memory-mapped",
+            )),
+        })
+        .collect::<io::Result<Vec<Mapped>>>()?;
+    let records = maps.iter().enumerate().flat_map(|(file, map)| {
+        map.records()
+            .map(move |record| record.map(|(line, bytes)| (file, line, map, bytes)))
+    });
+
+    let mut failures = Failures::default();
+    let mut num_solved = Vec::new();
+    let mut aggregate = Aggregate::default();
+    let total = maps.iter().map(|map| map.len() as u64).sum();
+    let mut progress = Progress::with_total(files.selected(total));
+    let mut report = args
+        .report
+        .as_ref()
+        .map(|_| Report::new(args.report_hardest));
+    jobs::map_ordered(
+        args.jobs.count(),
+        files.select(records),
+        Solver::new,
+        |solver, (file, line, map, bytes)| {
+            let start = Instant::now();
+            let solution = map.puzzle(bytes).and_then(|puzzle| {
+                solver.load(&puzzle)?;
+                let solution = solver.run(options)?;
+                if args.verify {
+                    solution.verify(&puzzle)?;
+                }
+                if args.check_unique && Solver::count_solutions(&puzzle, 2) > 1 {
+                    return Err(NOT_UNIQUE);
+                }
+                Ok(solution)
+            });
+            (file, line, solution, start.elapsed())
+        },
+        |(file, line, solution, elapsed)| {
+            progress.inc();
+            if let Some(report) = &mut report {
+                let location = input::Location::record(files, file, line);
+                report.add(&location, None, &solution, elapsed, None);
+            }
+            let solution = match solution {
+                Ok(solution) => solution,
+                Err(err) => {
+                    failures.report(&input::Location::record(files, file, line), err);
+                    return Ok(());
+                }
+            };
+            input::tally(&mut num_solved, file);
+            aggregate.add(&solution, elapsed, None, &[]);
+            let mut record = [b'\n'; 82];
+            for (byte, value) in record.iter_mut().zip(solution.cells().iter().flatten()) {
+                *byte = b'0' + value;
+            }
+            writer.write_all(&record)?;
+            match args.line_buffered() {
+                true => writer.flush(),
+                false => Ok(()),
+            }
+        },
+    )?;
+    progress.finish();
+    writer.finish()?;
+    if let (Some(path), Some(report)) = (&args.report, report) {
+        let mut aggregates = aggregate.to_json(false);
+        aggregates["failed"] = json!(failures.count());
+        report.write(path, files, configuration(args, true), aggregates)?;
+    }
+
+    if verbosity::summary() {
+        files.print_name();
+        eprintln!(
+            "Output file: {}",
+            streams::display_name(args.output.as_ref(), "standard output")
+        );
+        files.summarize(&num_solved, "solved", failures.per_file(), "failed");
+        eprintln!("Total solved: {}", num_solved.iter().sum::<usize>());
+        eprintln!("Total failed: {}", failures.count());
+        aggregate.print(false, args.notation);
+    }
+    failures.finish()
+}
+
+/// Write the result of solving a puzzle in the requested format. Failures are
+/// only written in formats which can represent them.
+fn write(writer: &mut impl Write, solved: &Solved, args: &Args, color: bool) -> io::Result<()> {
+    match (args.format, &solved.solution) {
+        (OutputFormat::Line, Ok(solution)) => writeln!(
+            writer,
+            "{}{}",
+            solved.line.location.prefix(),
+            solution.row_representation()
+        ),
+        (OutputFormat::Pretty, Ok(solution)) => {
+            if let Some(label) = solved.line.location.label() {
+                writeln!(writer, "{}", label)?;
+            }
+            let Some(puzzle) = &solved.puzzle else {
+                return Ok(());
+            };
+            let solution = color::framed(solution.cells(), puzzle, color);
+            if args.with_puzzle {
+                let puzzle = color::framed(puzzle.cells(), puzzle, color);
+                for (left, right) in puzzle.lines().zip(solution.lines()) {
+                    writeln!(writer, "{}   {}", left, right)?;
+                }
+            } else {
+                writeln!(writer, "{}", solution)?;
+            }
+            writeln!(writer)
+        }
+        (OutputFormat::Pair | OutputFormat::PairTab, Ok(solution)) => writeln!(
+            writer,
+            "{}{}{}{}",
+            solved.line.location.prefix(),
+            solved.line.text,
+            if args.format == OutputFormat::Pair {
+                ','
+            } else {
+                '\t'
+            },
+            solution.row_representation()
+        ),
+        (
+            OutputFormat::Line | OutputFormat::Pair | OutputFormat::PairTab | OutputFormat::Pretty,
+            Err(_),
+        ) => Ok(()),
+        // Written by the table writer instead.
+        #[cfg(feature = "arrow")]
+        (OutputFormat::Parquet | OutputFormat::Arrow, _) => Ok(()),
+        (OutputFormat::Jsonl, solution) => {
+            let location = &solved.line.location;
+            let mut object = json!({
+                "line": location.line,
+                "input": solved.line.text,
+                "elapsed_us": solved.elapsed.as_micros() as u64,
+            });
+            if let Some(name) = location.name() {
+                object["file"] = json!(name);
+            }
+            if let Some(label) = location.label() {
+                object["label"] = json!(label);
+            }
+            match solution {
+                Ok(solution) => {
+                    object["solution"] = json!(solution.row_representation());
+                    object["brute_forces"] = json!(solution.brute_forces);
+                    object["guesses"] = json!(solution.guesses);
+                    object["backtracks"] = json!(solution.backtracks);
+                }
+                Err(err) => object["error"] = json!(err),
+            }
+            writeln!(writer, "{}", object)
+        }
+        (OutputFormat::Csv, solution) => {
+            let clues = solved.puzzle.map(|puzzle| puzzle.clues().to_string());
+            let (solution, guesses, backtracks, error) = match solution {
+                Ok(solution) => (
+                    solution.row_representation(),
+                    solution.guesses.to_string(),
+                    solution.backtracks.to_string(),
+                    "",
+                ),
+                Err(err) => (String::new(), String::new(), String::new(), *err),
+            };
+            let difficulty = solved.rating.map(|rating| rating.difficulty.to_string());
+            let fields = [
+                solved.line.location.label().unwrap_or(""),
+                solved.line.text.as_str(),
+                &solution,
+                clues.as_deref().unwrap_or(""),
+                &guesses,
+                &backtracks,
+                &solved.elapsed.as_micros().to_string(),
+                difficulty.as_deref().unwrap_or(""),
+                error,
+            ];
+            let fields: Vec<_> = fields.into_iter().map(csv_field).collect();
+            writeln!(writer, "{}", fields.join(","))
+        }
+    }
+}
+
+/// Row of a columnar output with the result of solving a puzzle.
+#[cfg(feature = "arrow")]
+fn row(solved: &Solved) -> Row<'_> {
+    let location = &solved.line.location;
+    let solution = solved.solution.as_ref().ok();
+    Row {
+        file: location.name(),
+        line: location.line as u64,
+        label: location.label(),
+        input: &solved.line.text,
+        solution: solution.map(Solution::row_representation),
+        clues: solved.puzzle.map(|puzzle| puzzle.clues() as u8),
+        brute_forces: solution.map(|solution| solution.brute_forces),
+        guesses: solution.map(|solution| solution.guesses),
+        backtracks: solution.map(|solution| solution.backtracks),
+        elapsed_us: solved.elapsed.as_micros() as u64,
+        difficulty: solved.rating.map(|rating| rating.difficulty.name()),
+        score: solved.rating.map(|rating| rating.score),
+        hardest: solved
+            .rating
+            .and_then(|rating| rating.hardest)
+            .map(Technique::name),
+        error: solved.solution.as_ref().err().copied(),
+    }
+}
+
+/// Parse a timeout given as a number of seconds.
+pub fn parse_timeout(s: &str) -> std::result::Result<Duration, String> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("invalid number of seconds '{}'", s))
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,141 @@
+use super::input::{Failures, Inputs, Line};
+use super::jobs::{self, Jobs};
+use super::progress::Progress;
+use super::{streams, verbosity, Error, Result};
+use clap::Parser;
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use sudoku_solver::random::Rng;
+use sudoku_solver::rating::{self, Difficulty};
+
+/// Split a collection of puzzles randomly into several files, such as into a
+/// train and a test set or into shards of equal size. Each puzzle goes to
+/// exactly one output, the sizes of the outputs follow their weights, and
+/// the puzzles keep their input order within each output. All puzzles are
+/// kept in memory.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Files to write the splits to, given once for each split, such as
+    /// `-o train.txt -o test.txt`
+    #[arg(short, long, required = true, num_args = 1..)]
+    output: Vec<PathBuf>,
+    /// Relative sizes of the splits, one for each output, such as `8,2` for
+    /// a train set of 80% and a test set of 20% [default: equal sizes]
+    #[arg(long, value_delimiter = ',')]
+    weights: Vec<u32>,
+    /// Split the puzzles of each difficulty tier separately, such that each
+    /// split has the same distribution of difficulties, which requires
+    /// rating every puzzle
+    #[arg(long)]
+    stratify: bool,
+    /// Seed of the random split, such that the same split is made again
+    /// [default: random, which is shown]
+    #[arg(long)]
+    seed: Option<u64>,
+    #[command(flatten)]
+    jobs: Jobs,
+}
+
+pub fn run(args: Args) -> Result {
+    let weights = match args.weights.as_slice() {
+        [] => vec![1; args.output.len()],
+        weights if weights.len() != args.output.len() => {
+            return Err(invalid("there must be a weight for each output"));
+        }
+        weights if weights.iter().all(|weight| *weight == 0) => {
+            return Err(invalid("at least one weight must be positive"));
+        }
+        weights => weights.to_vec(),
+    };
+    let seed = args.seed.unwrap_or_else(|| {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        if verbosity::summary() {
+            eprintln!("Seed: {}", seed);
+        }
+        seed
+    });
+    let files = args.inputs.files()?;
+
+    // The lines of each stratum, which is the difficulty tier when
+    // stratifying and otherwise the whole collection.
+    let mut strata: Vec<Vec<(usize, Line)>> =
+        (0..Difficulty::ALL.len()).map(|_| Vec::new()).collect();
+    let mut num_read = 0;
+    let mut failures = Failures::default();
+    let mut progress = Progress::new(&files);
+    jobs::map_ordered(
+        args.jobs.count(),
+        files.lines(),
+        || (),
+        |_, line| {
+            let stratum = line.puzzle().and_then(|puzzle| match args.stratify {
+                true => Ok(rating::rate(&puzzle)?.difficulty as usize),
+                false => Ok(0),
+            });
+            (line, stratum)
+        },
+        |(line, stratum)| {
+            progress.inc();
+            match stratum {
+                Ok(stratum) => {
+                    strata[stratum].push((num_read, line));
+                    num_read += 1;
+                }
+                Err(err) => failures.report(&line.location, err),
+            }
+            Ok(())
+        },
+    )?;
+    progress.finish();
+
+    // Each stratum is shuffled and divided by the weights, after which the
+    // lines of each split are put back in input order.
+    let mut rng = Rng::new(seed);
+    let mut splits: Vec<Vec<(usize, Line)>> = weights.iter().map(|_| Vec::new()).collect();
+    let total_weight: u64 = weights.iter().map(|weight| *weight as u64).sum();
+    for mut stratum in strata {
+        rng.shuffle(&mut stratum);
+        let len = stratum.len() as u64;
+        let mut lines = stratum.into_iter();
+        let mut cumulative = 0;
+        let mut taken = 0;
+        for (split, weight) in splits.iter_mut().zip(&weights) {
+            // Rounding the cumulative sizes keeps the total exact.
+            cumulative += *weight as u64;
+            let end = (len * cumulative + total_weight / 2) / total_weight;
+            split.extend(lines.by_ref().take((end - taken) as usize));
+            taken = end;
+        }
+    }
+
+    for (split, path) in splits.iter_mut().zip(&args.output) {
+        split.sort_by_key(|(idx, _)| *idx);
+        let mut writer = streams::create_output(Some(path))?;
+        for (_, line) in split.iter() {
+            writeln!(writer, "{}{}", line.location.prefix(), line.text)?;
+        }
+        writer.finish()?;
+    }
+
+    if verbosity::summary() {
+        eprintln!("Total read: {}", num_read);
+        for (split, path) in splits.iter().zip(&args.output) {
+            eprintln!(
+                "{}: {}",
+                streams::display_name(Some(path), "standard output"),
+                split.len()
+            );
+        }
+        eprintln!("Failed: {}", failures.count());
+    }
+    failures.finish()
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Fatal(io::Error::new(io::ErrorKind::InvalidInput, message))
+}
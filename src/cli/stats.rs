@@ -0,0 +1,142 @@
+use super::input::{Failures, Inputs};
+use super::Result;
+use clap::Parser;
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
+use sudoku_solver::rating::{self, Difficulty};
+use sudoku_solver::transform::{self, Symmetry};
+use sudoku_solver::{Options, Solver};
+
+/// Analyze a collection of puzzles, reporting the distribution of clue
+/// counts, difficulties and symmetries of the clue patterns, the puzzles
+/// which are not uniquely solvable, duplicates and the cost of solving.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+}
+
+/// Minimum, maximum and total of a measured quantity.
+struct Range<T> {
+    min: T,
+    max: T,
+    total: T,
+}
+
+impl<T: Copy + Ord + std::ops::AddAssign> Range<T> {
+    fn new(value: T) -> Self {
+        Self {
+            min: value,
+            max: value,
+            total: value,
+        }
+    }
+
+    fn add(range: &mut Option<Self>, value: T) {
+        match range {
+            None => *range = Some(Self::new(value)),
+            Some(range) => {
+                range.min = range.min.min(value);
+                range.max = range.max.max(value);
+                range.total += value;
+            }
+        }
+    }
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+
+    let mut num_puzzles = 0;
+    let mut num_duplicates = 0;
+    let mut num_unsolvable = 0;
+    let mut num_ambiguous = 0;
+    let mut clues: BTreeMap<usize, u64> = BTreeMap::new();
+    let mut difficulties: BTreeMap<Difficulty, u64> = BTreeMap::new();
+    let mut symmetries: BTreeMap<Symmetry, u64> = BTreeMap::new();
+    let mut num_asymmetric = 0;
+    let mut brute_forces: Option<Range<u64>> = None;
+    let mut elapsed: Option<Range<Duration>> = None;
+    let mut num_solved = 0;
+
+    let mut seen = HashSet::new();
+    let mut failures = Failures::default();
+    let mut solver = Solver::new();
+    let options = Options::default();
+
+    for line in files.lines() {
+        let line = line?;
+        let puzzle = match line.puzzle() {
+            Ok(puzzle) => puzzle,
+            Err(err) => {
+                failures.report(&line.location, err);
+                continue;
+            }
+        };
+
+        num_puzzles += 1;
+        if !seen.insert(puzzle.fingerprint()) {
+            num_duplicates += 1;
+        }
+        *clues.entry(puzzle.clues()).or_default() += 1;
+        let mut symmetric = false;
+        for symmetry in transform::symmetries(&puzzle) {
+            *symmetries.entry(symmetry).or_default() += 1;
+            symmetric = true;
+        }
+        if !symmetric {
+            num_asymmetric += 1;
+        }
+
+        match Solver::count_solutions(&puzzle, 2) {
+            0 => num_unsolvable += 1,
+            1 => {}
+            _ => num_ambiguous += 1,
+        }
+
+        let start = Instant::now();
+        if let Ok(solution) = solver.load(&puzzle).and_then(|_| solver.run(&options)) {
+            Range::add(&mut elapsed, start.elapsed());
+            Range::add(&mut brute_forces, solution.brute_forces as u64);
+            num_solved += 1;
+        }
+
+        if let Ok(rating) = rating::rate(&puzzle) {
+            *difficulties.entry(rating.difficulty).or_default() += 1;
+        }
+    }
+
+    println!("Puzzles: {}", num_puzzles);
+    println!("Invalid: {}", failures.count());
+    println!("Duplicates: {}", num_duplicates);
+    println!("No solution: {}", num_unsolvable);
+    println!("Multiple solutions: {}", num_ambiguous);
+    println!();
+    println!("Clues:");
+    for (clues, count) in &clues {
+        println!("  {}: {}", clues, count);
+    }
+    println!("Difficulty:");
+    for (difficulty, count) in &difficulties {
+        println!("  {}: {}", difficulty, count);
+    }
+    println!("Symmetry:");
+    for (symmetry, count) in &symmetries {
+        println!("  {}: {}", symmetry, count);
+    }
+    println!("  none: {}", num_asymmetric);
+    if let (Some(brute_forces), Some(elapsed)) = (brute_forces, elapsed) {
+        println!("Brute-force fills:");
+        println!("  min: {}", brute_forces.min);
+        println!("  max: {}", brute_forces.max);
+        println!(
+            "  average: {:.2}",
+            brute_forces.total as f64 / num_solved as f64
+        );
+        println!("Solve time:");
+        println!("  min: {:?}", elapsed.min);
+        println!("  max: {:?}", elapsed.max);
+        println!("  average: {:?}", elapsed.total / num_solved);
+    }
+    failures.finish()
+}
@@ -0,0 +1,405 @@
+//! Opening of the input and output streams of subcommands, where an absent
+//! path or a dash ('-') refers to standard input or output. Compressed
+//! inputs are decompressed transparently, and outputs are compressed based
+//! on their extension or the `--compress` option. Output files are replaced
+//! atomically once finished, and existing ones are only overwritten or
+//! appended to using the `--force` or `--append` options.
+
+use clap::ValueEnum;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Compression format of a stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Detect the compression of an input from its first bytes.
+    pub fn from_magic(bytes: &[u8]) -> Self {
+        match bytes {
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    /// Detect the compression of an output from its file extension.
+    fn from_extension(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Compression of outputs requested using `--compress`, overriding the
+/// detection from extensions.
+static OUTPUT_COMPRESSION: OnceLock<Compression> = OnceLock::new();
+
+/// Compress all outputs using the given format.
+pub fn set_output_compression(compression: Compression) {
+    let _ = OUTPUT_COMPRESSION.set(compression);
+}
+
+/// Whether a path refers to a standard stream instead of a file.
+fn is_standard(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// Open an input file for buffered reading, or standard input. Gzip and
+/// Zstandard compressed inputs are detected and decompressed.
+pub fn open_input(path: Option<&Path>) -> io::Result<Box<dyn BufRead>> {
+    let mut reader: Box<dyn BufRead> = match path {
+        Some(path) if !is_standard(path) => Box::new(BufReader::new(File::open(path)?)),
+        _ => Box::new(io::stdin().lock()),
+    };
+    match Compression::from_magic(reader.fill_buf()?) {
+        Compression::None => Ok(reader),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(reader),
+        ))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(BufReader::new(
+            zstd::stream::read::Decoder::with_buffer(reader)?,
+        ))),
+        #[allow(unreachable_patterns)]
+        compression => Err(unsupported(compression)),
+    }
+}
+
+/// How outputs are written when their file already exists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Refuse to overwrite existing files.
+    Create,
+    /// Replace existing files.
+    Overwrite,
+    /// Append to existing files.
+    Append,
+}
+
+/// Handling of existing output files requested using `--force` or `--append`.
+static OUTPUT_MODE: OnceLock<OutputMode> = OnceLock::new();
+
+/// Write all outputs using the given mode.
+pub fn set_output_mode(mode: OutputMode) {
+    let _ = OUTPUT_MODE.set(mode);
+}
+
+/// Mode in which outputs are written.
+fn output_mode() -> OutputMode {
+    OUTPUT_MODE.get().copied().unwrap_or(OutputMode::Create)
+}
+
+/// Whether output files are appended to.
+pub fn appending() -> bool {
+    output_mode() == OutputMode::Append
+}
+
+/// Temporary files of outputs which have not been finished yet, which are
+/// removed when the process is interrupted.
+static TEMPORARY_FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Whether an interrupt stops the run instead of exiting, see
+/// [`stop_on_interrupt`].
+static STOPPABLE: AtomicBool = AtomicBool::new(false);
+
+/// Whether the process has been interrupted while stoppable.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Remove the temporary files of unfinished outputs and exit when the process
+/// is interrupted, as their outputs are not dropped then.
+pub fn remove_temporary_files_on_interrupt() {
+    let _ = ctrlc::set_handler(|| {
+        // A second interrupt exits anyway, in case the run does not stop.
+        if STOPPABLE.load(Ordering::SeqCst) && !INTERRUPTED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if let Ok(files) = TEMPORARY_FILES.lock() {
+            for path in files.iter() {
+                let _ = fs::remove_file(path);
+            }
+        }
+        process::exit(130);
+    });
+}
+
+/// Let the first interrupt only be recorded instead of exiting, for runs
+/// which check [`interrupted`] to stop and finish their outputs normally.
+pub fn stop_on_interrupt() {
+    STOPPABLE.store(true, Ordering::SeqCst);
+}
+
+/// Whether the process has been interrupted since [`stop_on_interrupt`].
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Stop tracking a temporary file, which has been renamed or removed.
+fn forget_temporary_file(path: &Path) {
+    if let Ok(mut files) = TEMPORARY_FILES.lock() {
+        files.retain(|other| other != path);
+    }
+}
+
+/// Buffered output stream, which is possibly compressed. Files are written
+/// to a temporary file next to them first, which replaces the file only once
+/// the output is finished, such that an interrupted run leaves any existing
+/// file untouched instead of half-written. Dropping the output without
+/// finishing it discards what was written.
+pub struct Output {
+    writer: Writer,
+    /// Temporary file and the file it replaces when finished.
+    rename: Option<(PathBuf, PathBuf)>,
+}
+
+impl Output {
+    /// Finish the compression, flush the output and move a temporary file in
+    /// place of the output file.
+    pub fn finish(mut self) -> io::Result<()> {
+        let writer = std::mem::replace(&mut self.writer, Writer::Plain(Box::new(io::sink())));
+        writer.finish()?;
+        if let Some((temporary, path)) = self.rename.take() {
+            forget_temporary_file(&temporary);
+            fs::rename(&temporary, path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        if let Some((temporary, _)) = &self.rename {
+            forget_temporary_file(temporary);
+            let _ = fs::remove_file(temporary);
+        }
+    }
+}
+
+/// Output stream with its compression.
+enum Writer {
+    Plain(Box<dyn Write>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<Box<dyn Write>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, Box<dyn Write>>),
+}
+
+impl Writer {
+    /// Wrap a stream in the compression of an output.
+    fn new(writer: Box<dyn Write>, path: Option<&Path>) -> io::Result<Self> {
+        match output_compression(path) {
+            Compression::None => Ok(Writer::Plain(writer)),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Ok(Writer::Gzip(flate2::write::GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            ))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Ok(Writer::Zstd(zstd::stream::write::Encoder::new(writer, 0)?)),
+            #[allow(unreachable_patterns)]
+            compression => Err(unsupported(compression)),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Writer::Plain(mut writer) => writer.flush(),
+            #[cfg(feature = "gzip")]
+            Writer::Gzip(encoder) => encoder.finish()?.flush(),
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(encoder) => encoder.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Writer::Plain(writer) => writer.write(buf),
+            #[cfg(feature = "gzip")]
+            Writer::Gzip(encoder) => encoder.write(buf),
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Writer::Plain(writer) => writer.flush(),
+            #[cfg(feature = "gzip")]
+            Writer::Gzip(encoder) => encoder.flush(),
+            #[cfg(feature = "zstd")]
+            Writer::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Create an output file for buffered writing, or use standard output. The
+/// output is compressed if requested, or if the file has a `.gz` or `.zst`
+/// extension. An existing file is only replaced using `--force`, or appended
+/// to using `--append`, in which case it is written to directly.
+pub fn create_output(path: Option<&Path>) -> io::Result<Output> {
+    let (file, rename) = match path {
+        Some(path) if !is_standard(path) => match output_mode() {
+            OutputMode::Append => (open_append(path)?, None),
+            mode => {
+                if mode == OutputMode::Create && path.exists() {
+                    return Err(exists(path));
+                }
+                let temporary = temporary_path(path);
+                let file = OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&temporary)?;
+                if let Ok(mut files) = TEMPORARY_FILES.lock() {
+                    files.push(temporary.clone());
+                }
+                (file, Some((temporary, path.to_path_buf())))
+            }
+        },
+        _ => return standard_output(),
+    };
+    Ok(Output {
+        writer: Writer::new(Box::new(BufWriter::new(file)), path)?,
+        rename,
+    })
+}
+
+/// Create an output file which is written to directly instead of through a
+/// temporary file, such that what has been written is visible while the
+/// output is not yet finished. Existing files are handled as with
+/// [`create_output`].
+pub fn create_output_in_place(path: Option<&Path>) -> io::Result<Output> {
+    let file = match path {
+        Some(path) if !is_standard(path) => match output_mode() {
+            OutputMode::Append => open_append(path)?,
+            OutputMode::Overwrite => File::create(path)?,
+            OutputMode::Create => OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+                .map_err(|err| match err.kind() {
+                    io::ErrorKind::AlreadyExists => exists(path),
+                    _ => err,
+                })?,
+        },
+        _ => return standard_output(),
+    };
+    Ok(Output {
+        writer: Writer::new(Box::new(BufWriter::new(file)), path)?,
+        rename: None,
+    })
+}
+
+/// Continue writing to an uncompressed output file after truncating it to a
+/// length, discarding anything written after that point. The file is created
+/// if it does not exist. Standard output is simply written to.
+pub fn resume_output(path: Option<&Path>, length: Option<u64>) -> io::Result<Output> {
+    match (path, length) {
+        (Some(path), Some(length)) if !is_standard(path) => {
+            let file = open_append(path)?;
+            file.set_len(length)?;
+            Ok(Output {
+                writer: Writer::Plain(Box::new(BufWriter::new(file))),
+                rename: None,
+            })
+        }
+        _ => standard_output(),
+    }
+}
+
+/// Use standard output for buffered writing.
+fn standard_output() -> io::Result<Output> {
+    Ok(Output {
+        writer: Writer::new(Box::new(BufWriter::new(io::stdout().lock())), None)?,
+        rename: None,
+    })
+}
+
+/// Open an output file for appending, creating it if it does not exist.
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().append(true).create(true).open(path)
+}
+
+/// Path of the temporary file written in place of an output file, in the
+/// same directory such that it can be renamed.
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.{}.tmp", name, process::id()))
+}
+
+/// Error for an output file which already exists.
+fn exists(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!(
+            "{} already exists, use --force to overwrite it or --append to append to it",
+            path.display()
+        ),
+    )
+}
+
+/// Compression applied to an output.
+pub fn output_compression(path: Option<&Path>) -> Compression {
+    let extension = match path {
+        Some(path) if !is_standard(path) => Compression::from_extension(path),
+        _ => Compression::None,
+    };
+    OUTPUT_COMPRESSION.get().copied().unwrap_or(extension)
+}
+
+/// Length of an output file, or `None` for standard output.
+pub fn output_length(path: Option<&Path>) -> io::Result<Option<u64>> {
+    match path {
+        Some(path) if !is_standard(path) => Ok(Some(fs::metadata(path)?.len())),
+        _ => Ok(None),
+    }
+}
+
+/// Error for a compression format of which support has not been enabled.
+#[allow(dead_code)]
+fn unsupported(compression: Compression) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("support for {:?} compression is not enabled", compression),
+    )
+}
+
+/// Whether an output is standard output connected to a terminal, without
+/// compression.
+pub fn is_terminal(path: Option<&Path>) -> bool {
+    path.is_none_or(is_standard)
+        && OUTPUT_COMPRESSION
+            .get()
+            .is_none_or(|compression| *compression == Compression::None)
+        && io::stdout().is_terminal()
+}
+
+/// Name of an input or output for showing to the user.
+pub fn display_name(path: Option<&PathBuf>, standard: &str) -> String {
+    match path {
+        Some(path) if !is_standard(path) => path.display().to_string(),
+        _ => standard.to_string(),
+    }
+}
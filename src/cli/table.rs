@@ -0,0 +1,203 @@
+//! Columnar output as Parquet or Arrow IPC files, such that results of large
+//! batches can be analyzed directly using tools like pandas, Polars or
+//! DuckDB. Rows are encoded in batches, of which the encoded bytes are moved
+//! to the output as soon as they are available.
+
+use arrow_array::builder::{StringBuilder, UInt32Builder, UInt64Builder, UInt8Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+/// Number of rows encoded at once, which bounds the memory used.
+const BATCH_SIZE: usize = 8192;
+
+/// Columnar file format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableFormat {
+    /// Apache Parquet, compressed using Snappy.
+    Parquet,
+    /// Apache Arrow IPC file, also known as Feather.
+    Arrow,
+}
+
+/// Result of solving a puzzle, of which each field is a column.
+pub struct Row<'a> {
+    pub file: Option<&'a str>,
+    pub line: u64,
+    pub label: Option<&'a str>,
+    pub input: &'a str,
+    pub solution: Option<String>,
+    pub clues: Option<u8>,
+    pub brute_forces: Option<u8>,
+    pub guesses: Option<u32>,
+    pub backtracks: Option<u32>,
+    pub elapsed_us: u64,
+    pub difficulty: Option<&'static str>,
+    pub score: Option<u32>,
+    pub hardest: Option<&'static str>,
+    pub error: Option<&'a str>,
+}
+
+/// Writer of rows to a columnar file.
+pub struct TableWriter {
+    schema: SchemaRef,
+    encoder: Encoder,
+    columns: Columns,
+    rows: usize,
+}
+
+/// Encoder of batches into a buffer, which is drained into the output.
+enum Encoder {
+    Parquet(ArrowWriter<Vec<u8>>),
+    Arrow(FileWriter<Vec<u8>>),
+}
+
+/// Builders of the columns of the batch being collected.
+#[derive(Default)]
+struct Columns {
+    file: StringBuilder,
+    line: UInt64Builder,
+    label: StringBuilder,
+    input: StringBuilder,
+    solution: StringBuilder,
+    clues: UInt8Builder,
+    brute_forces: UInt8Builder,
+    guesses: UInt32Builder,
+    backtracks: UInt32Builder,
+    elapsed_us: UInt64Builder,
+    difficulty: StringBuilder,
+    score: UInt32Builder,
+    hardest: StringBuilder,
+    error: StringBuilder,
+}
+
+/// Schema of the columns, of which only those always known are not nullable.
+fn schema() -> SchemaRef {
+    let column = |name, data_type, nullable| Field::new(name, data_type, nullable);
+    Arc::new(Schema::new(vec![
+        column("file", DataType::Utf8, true),
+        column("line", DataType::UInt64, false),
+        column("label", DataType::Utf8, true),
+        column("input", DataType::Utf8, false),
+        column("solution", DataType::Utf8, true),
+        column("clues", DataType::UInt8, true),
+        column("brute_forces", DataType::UInt8, true),
+        column("guesses", DataType::UInt32, true),
+        column("backtracks", DataType::UInt32, true),
+        column("elapsed_us", DataType::UInt64, false),
+        column("difficulty", DataType::Utf8, true),
+        column("score", DataType::UInt32, true),
+        column("hardest", DataType::Utf8, true),
+        column("error", DataType::Utf8, true),
+    ]))
+}
+
+impl TableWriter {
+    pub fn new(format: TableFormat) -> io::Result<Self> {
+        let schema = schema();
+        let encoder = match format {
+            TableFormat::Parquet => {
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                Encoder::Parquet(
+                    ArrowWriter::try_new(Vec::new(), schema.clone(), Some(properties))
+                        .map_err(io::Error::other)?,
+                )
+            }
+            TableFormat::Arrow => {
+                Encoder::Arrow(FileWriter::try_new(Vec::new(), &schema).map_err(io::Error::other)?)
+            }
+        };
+        Ok(Self {
+            schema,
+            encoder,
+            columns: Columns::default(),
+            rows: 0,
+        })
+    }
+
+    /// Add a row, writing the batch to the output once it is full.
+    pub fn push(&mut self, row: Row, output: &mut impl Write) -> io::Result<()> {
+        let columns = &mut self.columns;
+        columns.file.append_option(row.file);
+        columns.line.append_value(row.line);
+        columns.label.append_option(row.label);
+        columns.input.append_value(row.input);
+        columns.solution.append_option(row.solution);
+        columns.clues.append_option(row.clues);
+        columns.brute_forces.append_option(row.brute_forces);
+        columns.guesses.append_option(row.guesses);
+        columns.backtracks.append_option(row.backtracks);
+        columns.elapsed_us.append_value(row.elapsed_us);
+        columns.difficulty.append_option(row.difficulty);
+        columns.score.append_option(row.score);
+        columns.hardest.append_option(row.hardest);
+        columns.error.append_option(row.error);
+        self.rows += 1;
+        if self.rows == BATCH_SIZE {
+            self.write_batch(output)?;
+        }
+        Ok(())
+    }
+
+    /// Encode the collected rows as batch, and move the encoded bytes to the
+    /// output.
+    fn write_batch(&mut self, output: &mut impl Write) -> io::Result<()> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+        let columns = &mut self.columns;
+        let arrays: Vec<ArrayRef> = vec![
+            Arc::new(columns.file.finish()),
+            Arc::new(columns.line.finish()),
+            Arc::new(columns.label.finish()),
+            Arc::new(columns.input.finish()),
+            Arc::new(columns.solution.finish()),
+            Arc::new(columns.clues.finish()),
+            Arc::new(columns.brute_forces.finish()),
+            Arc::new(columns.guesses.finish()),
+            Arc::new(columns.backtracks.finish()),
+            Arc::new(columns.elapsed_us.finish()),
+            Arc::new(columns.difficulty.finish()),
+            Arc::new(columns.score.finish()),
+            Arc::new(columns.hardest.finish()),
+            Arc::new(columns.error.finish()),
+        ];
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays).map_err(io::Error::other)?;
+        self.rows = 0;
+        let buffer = match &mut self.encoder {
+            Encoder::Parquet(writer) => {
+                writer.write(&batch).map_err(io::Error::other)?;
+                writer.flush().map_err(io::Error::other)?;
+                writer.inner_mut()
+            }
+            Encoder::Arrow(writer) => {
+                writer.write(&batch).map_err(io::Error::other)?;
+                writer.flush().map_err(io::Error::other)?;
+                writer.get_mut()
+            }
+        };
+        output.write_all(buffer)?;
+        buffer.clear();
+        Ok(())
+    }
+
+    /// Write the remaining rows and the footer of the file to the output.
+    pub fn finish(mut self, output: &mut impl Write) -> io::Result<()> {
+        self.write_batch(output)?;
+        let buffer = match self.encoder {
+            Encoder::Parquet(writer) => writer.into_inner().map_err(io::Error::other)?,
+            Encoder::Arrow(mut writer) => {
+                writer.finish().map_err(io::Error::other)?;
+                writer.into_inner().map_err(io::Error::other)?
+            }
+        };
+        output.write_all(&buffer)
+    }
+}
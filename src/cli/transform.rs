@@ -0,0 +1,115 @@
+use super::input::{Failures, Inputs};
+use super::{streams, verbosity};
+use clap::Parser;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use sudoku_solver::random::Rng;
+use sudoku_solver::transform::Transformation;
+
+/// Transform all puzzles in a file while preserving their validity, applying
+/// the requested transformations in order.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// File to write the transformed puzzles to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Transformation to apply, which can be repeated: rotate, transpose,
+    /// mirror-horizontal, mirror-vertical, swap-bands=A,B, swap-stacks=A,B
+    /// (bands and stacks numbered 1 to 3) or relabel=DIGITS (the new labels
+    /// of the values 1 to 9, e.g. relabel=912345678)
+    #[arg(short, long = "transformation", value_parser = parse_transformation)]
+    transformations: Vec<Transformation>,
+    /// Apply a different random transformation to each puzzle after the
+    /// requested transformations
+    #[arg(long)]
+    random: bool,
+    /// Seed for the random transformations [default: current time]
+    #[arg(long, requires = "random")]
+    seed: Option<u64>,
+}
+
+pub fn run(args: Args) -> super::Result {
+    let files = args.inputs.files()?;
+    let mut writer = streams::create_output(args.output.as_deref())?;
+
+    let transformation = args
+        .transformations
+        .iter()
+        .fold(Transformation::identity(), |chain, next| chain.then(next));
+    let seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    let mut rng = Rng::new(seed);
+    let mut failures = Failures::default();
+
+    for line in files.lines() {
+        let line = line?;
+        let puzzle = match line.puzzle() {
+            Ok(puzzle) => puzzle,
+            Err(err) => {
+                failures.report(&line.location, err);
+                continue;
+            }
+        };
+        let mut transformed = transformation.apply(&puzzle);
+        if args.random {
+            transformed = Transformation::random(&mut rng).apply(&transformed);
+        }
+        writeln!(writer, "{}{}", line.location.prefix(), transformed)?;
+    }
+    writer.finish()?;
+
+    if args.random && verbosity::summary() {
+        eprintln!("Seed: {}", seed);
+    }
+    failures.finish()
+}
+
+/// Parse a transformation given on the command line.
+fn parse_transformation(s: &str) -> Result<Transformation, String> {
+    let (name, argument) = match s.split_once('=') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (s, None),
+    };
+    let pair = || -> Result<(usize, usize), String> {
+        let invalid = || format!("expected two numbers from 1 to 3 for {}", name);
+        let (a, b) = argument
+            .and_then(|a| a.split_once(','))
+            .ok_or_else(invalid)?;
+        let index = |n: &str| match n.trim().parse::<usize>() {
+            Ok(n @ 1..=3) => Ok(n - 1),
+            _ => Err(invalid()),
+        };
+        Ok((index(a)?, index(b)?))
+    };
+    match (name, argument) {
+        ("rotate", None) => Ok(Transformation::rotate()),
+        ("transpose", None) => Ok(Transformation::transpose()),
+        ("mirror-horizontal", None) => Ok(Transformation::mirror_horizontal()),
+        ("mirror-vertical", None) => Ok(Transformation::mirror_vertical()),
+        ("swap-bands", _) => {
+            let (a, b) = pair()?;
+            Transformation::swap_bands(a, b).map_err(str::to_string)
+        }
+        ("swap-stacks", _) => {
+            let (a, b) = pair()?;
+            Transformation::swap_stacks(a, b).map_err(str::to_string)
+        }
+        ("relabel", Some(labels)) => {
+            let labels: Vec<u8> = labels
+                .chars()
+                .map(|c| c.to_digit(10).map_or(0, |d| d as u8))
+                .collect();
+            let labels = labels
+                .try_into()
+                .map_err(|_| "expected nine labels for relabel".to_string())?;
+            Transformation::relabel(labels).map_err(str::to_string)
+        }
+        _ => Err(format!("unknown transformation '{}'", s)),
+    }
+}
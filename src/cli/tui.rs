@@ -0,0 +1,327 @@
+use super::format;
+use super::{Error, Result};
+use clap::Parser;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant};
+use sudoku_solver::logic::{self, Grid, Step};
+use sudoku_solver::{Options, Puzzle, Solver};
+
+/// Enter, inspect and solve a puzzle interactively in the terminal. The
+/// candidates of each cell are shown while entering clues, hints show the
+/// next step a human would take, and the solver can be watched taking its
+/// steps one by one. The entered puzzle is written when quitting.
+#[derive(Parser)]
+pub struct Args {
+    /// Puzzle to start with, instead of an empty grid
+    puzzle: Option<Puzzle>,
+    /// Time between the steps of the solver when animating, in milliseconds
+    #[arg(long, value_name = "MS", default_value_t = 300)]
+    delay: u64,
+}
+
+/// Keys and what they do, shown next to the grid.
+const KEYS: [(&str, &str); 9] = [
+    ("arrows", "move"),
+    ("1-9", "enter clue"),
+    ("0 . del", "clear cell"),
+    ("h", "show hint"),
+    ("n", "take next step"),
+    ("space", "animate solver"),
+    ("r", "undo solver steps"),
+    ("c", "toggle candidates"),
+    ("q esc", "quit"),
+];
+
+/// State of the interface.
+struct App {
+    /// Clues entered by the user, of which empty cells are zero.
+    clues: [[u8; 9]; 9],
+    /// The clues along with the steps taken by the solver, or the reason the
+    /// clues are invalid.
+    grid: std::result::Result<Grid, &'static str>,
+    cursor: (usize, usize),
+    show_candidates: bool,
+    /// Next step as shown to the user, which is taken next.
+    hint: Option<Step>,
+    animating: bool,
+    /// Description of the last step taken, or of why it failed.
+    message: String,
+    quit: bool,
+}
+
+pub fn run(args: Args) -> Result {
+    if !io::stdout().is_terminal() {
+        return Err(Error::Fatal(io::Error::other(
+            "the interactive interface requires a terminal",
+        )));
+    }
+    let mut app = App::new(args.puzzle.as_ref());
+    let mut terminal = ratatui::try_init()?;
+    let result = app.run(&mut terminal, Duration::from_millis(args.delay));
+    ratatui::restore();
+    result?;
+    println!("{}", format::to_puzzle(&app.clues));
+    Ok(())
+}
+
+impl App {
+    fn new(puzzle: Option<&Puzzle>) -> Self {
+        let clues = puzzle.map_or([[0; 9]; 9], |puzzle| *puzzle.cells());
+        Self {
+            clues,
+            grid: Grid::new(&format::to_puzzle(&clues)),
+            cursor: (0, 0),
+            show_candidates: true,
+            hint: None,
+            animating: false,
+            message: String::new(),
+            quit: false,
+        }
+    }
+
+    /// Handle input and animate the solver until the user quits.
+    fn run(&mut self, terminal: &mut DefaultTerminal, delay: Duration) -> io::Result<()> {
+        let mut last_step = Instant::now();
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            let timeout = match self.animating {
+                true => delay.saturating_sub(last_step.elapsed()),
+                false => Duration::from_secs(1),
+            };
+            if event::poll(timeout)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle(key.code);
+                    }
+                }
+            }
+            if self.animating && last_step.elapsed() >= delay {
+                self.step();
+                last_step = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, key: KeyCode) {
+        let (row, col) = self.cursor;
+        match key {
+            KeyCode::Up => self.cursor = ((row + 8) % 9, col),
+            KeyCode::Down => self.cursor = ((row + 1) % 9, col),
+            KeyCode::Left => self.cursor = (row, (col + 8) % 9),
+            KeyCode::Right => self.cursor = (row, (col + 1) % 9),
+            KeyCode::Char(c @ '1'..='9') => self.enter(c as u8 - b'0'),
+            KeyCode::Char('0' | '.') | KeyCode::Backspace | KeyCode::Delete => self.enter(0),
+            KeyCode::Char('h') => match self.next_step() {
+                Ok(step) => self.hint = Some(step),
+                Err(reason) => self.message = reason.to_string(),
+            },
+            KeyCode::Char('n') => self.step(),
+            KeyCode::Char(' ') => self.animating = !self.animating,
+            KeyCode::Char('r') => self.reset(),
+            KeyCode::Char('c') => self.show_candidates = !self.show_candidates,
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            _ => {}
+        }
+    }
+
+    /// Change the clue of the cell under the cursor, undoing the steps taken
+    /// by the solver.
+    fn enter(&mut self, value: u8) {
+        self.clues[self.cursor.0][self.cursor.1] = value;
+        self.reset();
+    }
+
+    /// Undo the steps taken by the solver.
+    fn reset(&mut self) {
+        self.grid = Grid::new(&format::to_puzzle(&self.clues));
+        self.hint = None;
+        self.animating = false;
+        self.message.clear();
+    }
+
+    /// Find the next step, filling in a value of the solution if no
+    /// technique applies.
+    fn next_step(&self) -> std::result::Result<Step, &'static str> {
+        let grid = self.grid.as_ref().map_err(|reason| *reason)?;
+        if grid.is_solved() {
+            return Err("puzzle is solved");
+        }
+        match grid.next_step() {
+            Some(step) => Ok(step),
+            None => {
+                let solution = Solver::solve_with_options(&grid.to_puzzle(), &Options::default())?;
+                grid.brute_force_step(&solution)
+                    .ok_or("no unfilled cell was found")
+            }
+        }
+    }
+
+    /// Take the hinted or otherwise the next step.
+    fn step(&mut self) {
+        let step = match self.hint.take().map_or_else(|| self.next_step(), Ok) {
+            Ok(step) => step,
+            Err(reason) => {
+                self.message = reason.to_string();
+                self.animating = false;
+                return;
+            }
+        };
+        if let Ok(grid) = &mut self.grid {
+            match grid.apply(&step) {
+                Ok(()) => self.message = step.to_string(),
+                Err(reason) => self.message = reason.to_string(),
+            }
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let width = if self.show_candidates { 7 } else { 3 };
+        let [board, side] =
+            Layout::horizontal([Constraint::Length(9 * width + 4), Constraint::Min(30)])
+                .areas(frame.area());
+        frame.render_widget(
+            Paragraph::new(self.board(width)).block(Block::bordered().title(" Sudoku ")),
+            board,
+        );
+        frame.render_widget(
+            Paragraph::new(self.side())
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered()),
+            side,
+        );
+    }
+
+    /// Lines of the grid, of which each cell is `width` characters wide.
+    fn board(&self, width: u16) -> Vec<Line<'_>> {
+        let height = if self.show_candidates { 3 } else { 1 };
+        let separator = vec!["─".repeat(3 * width as usize); 3].join("┼");
+        let mut lines = Vec::new();
+        for row in 0..9 {
+            if row > 0 && row % 3 == 0 {
+                lines.push(Line::raw(separator.clone()));
+            }
+            for sub_row in 0..height {
+                let mut spans = Vec::new();
+                for col in 0..9 {
+                    if col > 0 && col % 3 == 0 {
+                        spans.push(Span::raw("│"));
+                    }
+                    spans.extend(self.cell((row, col), sub_row));
+                }
+                lines.push(Line::from(spans));
+            }
+        }
+        lines
+    }
+
+    /// Spans of a line of a cell, which shows either its value or, if
+    /// enabled, its candidates on three lines.
+    fn cell(&self, coords: (usize, usize), sub_row: usize) -> Vec<Span<'_>> {
+        let clue = self.clues[coords.0][coords.1];
+        let grid = self.grid.as_ref().ok();
+        let value = match clue {
+            0 => grid.and_then(|grid| grid.value(coords)),
+            clue => Some(clue),
+        };
+        let hint = self.hint.as_ref();
+
+        let mut style = Style::new();
+        if coords == self.cursor {
+            style = style.reversed();
+        } else if hint.is_some_and(|step| step.pattern.contains(&coords)) {
+            style = style.bg(Color::DarkGray);
+        }
+        let value_style = match clue {
+            0 => style.fg(Color::Cyan),
+            _ if grid.is_none() && self.conflicts(coords) => style.fg(Color::Red).bold(),
+            _ => style.bold(),
+        };
+        let placement = hint.and_then(|step| {
+            step.placements
+                .iter()
+                .find(|(cell, _)| *cell == coords)
+                .map(|(_, value)| *value)
+        });
+
+        if !self.show_candidates {
+            return vec![match (value, placement) {
+                (Some(value), _) => Span::styled(format!(" {} ", value), value_style),
+                (None, Some(value)) => Span::styled(format!(" {} ", value), style.fg(Color::Green)),
+                (None, None) => Span::styled(" · ", style.fg(Color::DarkGray)),
+            }];
+        }
+        if let Some(value) = value {
+            let text = match sub_row {
+                1 => format!("   {}   ", value),
+                _ => "       ".to_string(),
+            };
+            return vec![Span::styled(text, value_style)];
+        }
+        let mut spans = vec![Span::styled(" ", style)];
+        for digit in (1..=3).map(|idx| 3 * sub_row as u8 + idx) {
+            let candidate = grid.is_some_and(|grid| grid.has_candidate(coords, digit));
+            let eliminated = hint.is_some_and(|step| step.eliminations.contains(&(coords, digit)));
+            let span = match candidate {
+                true if placement == Some(digit) => {
+                    Span::styled(digit.to_string(), style.fg(Color::Green).bold())
+                }
+                true if eliminated => Span::styled(digit.to_string(), style.fg(Color::Red)),
+                true => Span::styled(digit.to_string(), style.fg(Color::DarkGray)),
+                false => Span::styled(" ", style),
+            };
+            spans.push(span);
+            spans.push(Span::styled(" ", style));
+        }
+        spans
+    }
+
+    /// Whether the clue of a cell conflicts with that of a peer.
+    fn conflicts(&self, coords: (usize, usize)) -> bool {
+        let clue = self.clues[coords.0][coords.1];
+        (0..9)
+            .flat_map(|row| (0..9).map(move |col| (row, col)))
+            .any(|other| logic::sees(coords, other) && self.clues[other.0][other.1] == clue)
+    }
+
+    /// Lines of the panel next to the grid, with the state of the puzzle,
+    /// the hint or last step, and the keys.
+    fn side(&self) -> Vec<Line<'_>> {
+        let clues = self.clues.iter().flatten().filter(|value| **value != 0);
+        let mut lines = vec![Line::raw(format!("Clues: {}", clues.count()))];
+        match &self.grid {
+            Ok(grid) => {
+                let filled = (0..81).filter(|idx| grid.value((idx / 9, idx % 9)).is_some());
+                lines.push(Line::raw(format!("Filled: {}/81", filled.count())));
+                if grid.is_solved() {
+                    lines.push(Line::from("Solved!".green().bold()));
+                }
+            }
+            Err(reason) => lines.push(Line::from(format!("Invalid: {}", reason).red())),
+        }
+        if self.animating {
+            lines.push(Line::from("Animating...".cyan()));
+        }
+        lines.push(Line::raw(""));
+        if let Some(step) = &self.hint {
+            lines.push(Line::from("Hint:".bold()));
+            lines.push(Line::raw(step.to_string()));
+        } else if !self.message.is_empty() {
+            lines.push(Line::raw(self.message.clone()));
+        }
+        lines.push(Line::raw(""));
+        for (key, action) in KEYS {
+            lines.push(Line::from(vec![
+                Span::raw(format!("{:>8}", key)).bold(),
+                Span::raw(format!("  {}", action)),
+            ]));
+        }
+        lines
+    }
+}
@@ -0,0 +1,83 @@
+//! Usage of the techniques across a collection of puzzles, such that
+//! collections can be characterized by the techniques they require.
+
+use super::format::Notation;
+use serde_json::{json, Value};
+use sudoku_solver::logic::{Step, Technique};
+
+/// Number of steps taken using each technique, and number of puzzles
+/// requiring each technique, over the puzzles solved step by step.
+#[derive(Default)]
+pub struct Usage {
+    puzzles: u64,
+    steps: [u64; Technique::ALL.len()],
+    requiring: [u64; Technique::ALL.len()],
+}
+
+impl Usage {
+    /// Count the techniques of the steps solving a puzzle.
+    pub fn add(&mut self, steps: &[Step]) {
+        self.puzzles += 1;
+        let mut used = [false; Technique::ALL.len()];
+        for step in steps {
+            self.steps[step.technique as usize] += 1;
+            used[step.technique as usize] = true;
+        }
+        for (requiring, used) in self.requiring.iter_mut().zip(used) {
+            *requiring += used as u64;
+        }
+    }
+
+    /// Whether any puzzle was solved step by step.
+    pub fn is_empty(&self) -> bool {
+        self.puzzles == 0
+    }
+
+    /// Write the usage of each technique which was used to standard error,
+    /// along with the share of the puzzles requiring it.
+    pub fn print(&self, notation: Notation) {
+        eprintln!("Technique usage:");
+        for (idx, technique) in Technique::ALL.into_iter().enumerate() {
+            if self.requiring[idx] == 0 {
+                continue;
+            }
+            eprintln!(
+                "  {}: {} steps in {} {} ({:.1}%)",
+                name(technique, notation),
+                self.steps[idx],
+                self.requiring[idx],
+                match self.requiring[idx] {
+                    1 => "puzzle",
+                    _ => "puzzles",
+                },
+                self.requiring[idx] as f64 / self.puzzles as f64 * 100.0
+            );
+        }
+    }
+
+    /// The usage of each technique which was used as a JSON object for
+    /// reports.
+    pub fn to_json(&self) -> Value {
+        let techniques = Technique::ALL
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| self.requiring[*idx] > 0)
+            .map(|(idx, technique)| {
+                let usage = json!({
+                    "steps": self.steps[idx],
+                    "puzzles": self.requiring[idx],
+                });
+                (technique.name().to_string(), usage)
+            })
+            .collect();
+        Value::Object(techniques)
+    }
+}
+
+/// Name of a technique in a notation.
+fn name(technique: Technique, notation: Notation) -> &'static str {
+    match notation {
+        Notation::Default => technique.name(),
+        Notation::Hodoku => technique.hodoku_name(),
+    }
+}
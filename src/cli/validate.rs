@@ -0,0 +1,129 @@
+use super::input::{self, Inputs};
+use super::jobs::{self, Jobs};
+use super::progress::Progress;
+use super::{verbosity, Error, Result};
+use clap::Parser;
+use std::io::{self, prelude::*};
+use sudoku_solver::analysis;
+use sudoku_solver::logic::House;
+use sudoku_solver::{Puzzle, Solver};
+
+/// Check that every puzzle in a file is well-formed and has exactly one
+/// solution. The reason for each invalid puzzle is printed along with its
+/// line number, and the exit status is nonzero if any puzzle is invalid.
+/// Using --ambiguous, the cells of which the digit differs across the
+/// solutions of puzzles with multiple solutions are listed as well.
+#[derive(Parser)]
+pub struct Args {
+    /// List the ambiguous cells of puzzles with multiple solutions, along with
+    /// the digits they take
+    #[arg(long)]
+    ambiguous: bool,
+    #[command(flatten)]
+    inputs: Inputs,
+    #[command(flatten)]
+    jobs: Jobs,
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let mut stdout = io::stdout().lock();
+
+    let mut num_valid = Vec::new();
+    let mut num_invalid = Vec::new();
+
+    let mut progress = Progress::new(&files);
+    jobs::map_ordered(
+        args.jobs.count(),
+        files.lines(),
+        || (),
+        |_, line| (line.location, diagnose(&line.text, args.ambiguous)),
+        |(location, reason)| {
+            progress.inc();
+            match reason {
+                None => input::tally(&mut num_valid, location.file),
+                Some(reason) => {
+                    input::tally(&mut num_invalid, location.file);
+                    writeln!(stdout, "{}: {}", location, reason)?;
+                }
+            }
+            Ok(())
+        },
+    )?;
+    progress.finish();
+    stdout.flush()?;
+
+    let total_invalid = num_invalid.iter().sum::<usize>();
+    if verbosity::summary() {
+        files.print_name();
+        files.summarize(&num_valid, "valid", &num_invalid, "invalid");
+        eprintln!("Total valid: {}", num_valid.iter().sum::<usize>());
+        eprintln!("Total invalid: {}", total_invalid);
+    }
+
+    if total_invalid > 0 {
+        return Err(Error::Failures(format!(
+            "{} invalid puzzles",
+            total_invalid
+        )));
+    }
+    Ok(())
+}
+
+/// Find the reason a puzzle is invalid, if any, optionally listing its
+/// ambiguous cells if it has multiple solutions.
+fn diagnose(line: &str, ambiguous: bool) -> Option<String> {
+    let puzzle: Puzzle = match line.parse() {
+        Ok(puzzle) => puzzle,
+        Err(_) => return Some(malformation(line)),
+    };
+    if let Some(conflict) = conflict(&puzzle) {
+        return Some(conflict);
+    }
+    match Solver::count_solutions(&puzzle, 2) {
+        0 => Some("puzzle has no solution".to_string()),
+        1 => None,
+        _ if ambiguous => {
+            let cells = analysis::ambiguous_cells(&puzzle).unwrap_or_default();
+            let cells: Vec<String> = cells.iter().map(|cell| cell.to_string()).collect();
+            Some(format!(
+                "puzzle has multiple solutions, ambiguous cells: {}",
+                cells.join(", ")
+            ))
+        }
+        _ => Some("puzzle has multiple solutions".to_string()),
+    }
+}
+
+/// Describe why a line could not be parsed as a puzzle.
+fn malformation(line: &str) -> String {
+    let invalid = line
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !c.is_ascii_digit() && *c != '.');
+    match invalid {
+        Some((idx, c)) => format!("invalid character {:?} at position {}", c, idx + 1),
+        None => format!("expected 81 cells, found {}", line.chars().count()),
+    }
+}
+
+/// Describe a value occurring more than once in a house, if any.
+fn conflict(puzzle: &Puzzle) -> Option<String> {
+    for house in House::all() {
+        let mut seen = [false; 9];
+        for (row, col) in house.cells() {
+            let value = puzzle.cells()[row][col];
+            if value == 0 {
+                continue;
+            }
+            if seen[value as usize - 1] {
+                return Some(format!(
+                    "value {} occurs more than once in {}",
+                    value, house
+                ));
+            }
+            seen[value as usize - 1] = true;
+        }
+    }
+    None
+}
@@ -0,0 +1,43 @@
+//! Amount of information shown on standard error, set using `-q` or `-v`.
+//! Failures are reported at every level, while the summary after processing
+//! is omitted when quiet, and details about each puzzle are added when
+//! verbose.
+
+use std::sync::OnceLock;
+
+/// Level of detail of the information shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only failures.
+    Quiet,
+    /// Failures and the summary.
+    Normal,
+    /// Also the solve time and number of guesses of each puzzle.
+    Verbose,
+    /// Also the steps taken to solve each puzzle.
+    VeryVerbose,
+}
+
+/// Verbosity requested on the command line.
+static VERBOSITY: OnceLock<Verbosity> = OnceLock::new();
+
+/// Set the verbosity from the number of `-q` and `-v` flags given.
+pub fn set(quiet: bool, verbose: u8) {
+    let verbosity = match (quiet, verbose) {
+        (true, _) => Verbosity::Quiet,
+        (false, 0) => Verbosity::Normal,
+        (false, 1) => Verbosity::Verbose,
+        (false, _) => Verbosity::VeryVerbose,
+    };
+    let _ = VERBOSITY.set(verbosity);
+}
+
+/// Current verbosity.
+pub fn get() -> Verbosity {
+    VERBOSITY.get().copied().unwrap_or(Verbosity::Normal)
+}
+
+/// Whether the summary after processing is shown.
+pub fn summary() -> bool {
+    get() > Verbosity::Quiet
+}
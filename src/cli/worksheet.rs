@@ -0,0 +1,582 @@
+use super::input::{Failures, Inputs};
+use super::{streams, verbosity, Error, Result};
+use clap::{Parser, ValueEnum};
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::io::{self, prelude::*};
+use std::path::{Path, PathBuf};
+use sudoku_solver::{rating, Options, Puzzle, Solution, Solver};
+
+/// Create a worksheet of puzzles as PDF or LaTeX, with several puzzles on
+/// each page and optionally their difficulty and an appendix with the
+/// solutions, or as web page on which the puzzles can be solved.
+#[derive(Parser)]
+pub struct Args {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// File to write the worksheet to [default: standard output]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Number of puzzles on each page
+    #[arg(long, value_name = "N", default_value_t = 6,
+        value_parser = clap::value_parser!(u32).range(1..=20))]
+    per_page: u32,
+    /// Label each puzzle with its difficulty
+    #[arg(long)]
+    difficulty: bool,
+    /// Append pages with the solutions of the puzzles, which are always
+    /// included in web pages for checking entries
+    #[arg(long)]
+    solutions: bool,
+    /// Size of the pages
+    #[arg(long, value_enum, default_value_t = Paper::A4)]
+    paper: Paper,
+    /// Format of the worksheet [default: latex if the output file ends in
+    /// .tex, html if it ends in .html or .htm, otherwise pdf]
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+    /// Write a complete LaTeX document, instead of pictures to include in
+    /// another document
+    #[arg(long)]
+    standalone: bool,
+}
+
+/// Format of a worksheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Portable Document Format, ready for printing
+    Pdf,
+    /// LaTeX with a TikZ picture for each puzzle
+    Latex,
+    /// Standalone web page on which the puzzles can be solved and checked
+    Html,
+}
+
+/// Web page on which puzzles are solved, in which the JSON array of puzzles
+/// replaces the placeholder.
+const HTML_TEMPLATE: &str = include_str!("worksheet.html");
+const HTML_PLACEHOLDER: &str = "/* PUZZLES */";
+
+/// Size of the pages of a worksheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Paper {
+    /// 210 by 297 millimeters
+    A4,
+    /// 8.5 by 11 inches
+    Letter,
+}
+
+impl Paper {
+    /// Width and height in points.
+    fn size(self) -> (f32, f32) {
+        match self {
+            Paper::A4 => (595.0, 842.0),
+            Paper::Letter => (612.0, 792.0),
+        }
+    }
+}
+
+/// Space around the contents of a page, in points.
+const MARGIN: f32 = 36.0;
+
+/// Space between puzzles, in points.
+const GAP: f32 = 24.0;
+
+/// Font size of the labels above puzzles and the headings of pages.
+const LABEL_SIZE: f32 = 11.0;
+
+/// Height reserved for the label above each puzzle, in points.
+const LABEL_HEIGHT: f32 = 18.0;
+
+/// Puzzle on a worksheet along with what is shown with it.
+struct Entry {
+    puzzle: Puzzle,
+    /// Name of the difficulty tier, if requested.
+    difficulty: Option<&'static str>,
+    solution: Option<Solution>,
+}
+
+pub fn run(args: Args) -> Result {
+    let files = args.inputs.files()?;
+    let format = args
+        .format
+        .unwrap_or_else(|| detect(args.output.as_deref()));
+    let mut failures = Failures::default();
+    let mut entries = Vec::new();
+    for line in files.lines() {
+        let line = line?;
+        let puzzle = match line.puzzle() {
+            Ok(puzzle) => puzzle,
+            Err(err) => {
+                failures.report(&line.location, err);
+                continue;
+            }
+        };
+        let difficulty = match args.difficulty {
+            false => None,
+            true => match rating::rate(&puzzle) {
+                Ok(rating) => Some(rating.difficulty.name()),
+                Err(err) => {
+                    failures.report(&line.location, err);
+                    continue;
+                }
+            },
+        };
+        let solution = match args.solutions || format == Format::Html {
+            false => None,
+            true => match Solver::solve_with_options(&puzzle, &Options::default()) {
+                Ok(solution) => Some(solution),
+                Err(err) => {
+                    failures.report(&line.location, err);
+                    continue;
+                }
+            },
+        };
+        entries.push(Entry {
+            puzzle,
+            difficulty,
+            solution,
+        });
+    }
+
+    if format == Format::Pdf && streams::is_terminal(args.output.as_deref()) {
+        return Err(Error::Fatal(io::Error::other(
+            "refusing to write a PDF to a terminal",
+        )));
+    }
+    let layout = Layout::new(args.paper.size(), args.per_page as usize);
+    let data = match format {
+        Format::Pdf => pdf(&entries, &layout, args.paper, args.solutions),
+        Format::Latex => latex(
+            &entries,
+            &layout,
+            args.paper,
+            args.solutions,
+            args.standalone,
+        ),
+        Format::Html => html(&entries),
+    };
+    let mut writer = streams::create_output(args.output.as_deref())?;
+    writer.write_all(&data)?;
+    writer.finish()?;
+
+    if verbosity::summary() {
+        eprintln!("Total puzzles: {}", entries.len());
+    }
+    failures.finish()
+}
+
+/// Guess the format of a worksheet from the extension of its file.
+fn detect(path: Option<&Path>) -> Format {
+    match path.and_then(Path::extension) {
+        Some(extension) if extension == "tex" => Format::Latex,
+        Some(extension) if extension == "html" || extension == "htm" => Format::Html,
+        _ => Format::Pdf,
+    }
+}
+
+/// Label of a puzzle, showing its difficulty unless it is in the appendix
+/// with solutions.
+fn title(number: usize, entry: &Entry, solution: bool) -> String {
+    match entry.difficulty {
+        Some(difficulty) if !solution => format!("Puzzle {} - {}", number, difficulty),
+        _ => format!("Puzzle {}", number),
+    }
+}
+
+/// Write a worksheet as PDF, with the solutions on pages after the puzzles.
+fn pdf(entries: &[Entry], layout: &Layout, paper: Paper, solutions: bool) -> Vec<u8> {
+    let mut pdf = Pdf::new(paper.size());
+    let sections = match solutions {
+        true => &[false, true][..],
+        false => &[false],
+    };
+    for &solution in sections {
+        for (page_idx, page) in entries.chunks(layout.per_page).enumerate() {
+            let mut content = String::new();
+            if solution && page_idx == 0 {
+                let (_, height) = paper.size();
+                pdf_label(
+                    &mut content,
+                    MARGIN,
+                    height - MARGIN / 2.0 - 8.0,
+                    "Solutions",
+                );
+            }
+            for (slot, entry) in page.iter().enumerate() {
+                let (x, y) = layout.position(slot);
+                let number = page_idx * layout.per_page + slot + 1;
+                let text = title(number, entry, solution);
+                pdf_label(&mut content, x, y + layout.size + 6.0, &text);
+                let solution = entry.solution.as_ref().filter(|_| solution);
+                pdf_grid(&mut content, x, y, layout.size, &entry.puzzle, solution);
+            }
+            pdf.page(content);
+        }
+    }
+    pdf.finish()
+}
+
+/// Write a worksheet as LaTeX, drawing each puzzle using TikZ. Puzzles are
+/// arranged as in a PDF, taking the same fraction of the width of the lines
+/// as they would of the width of a page. Unless standalone, the result is
+/// meant to be included in a document which loads the tikz package.
+fn latex(
+    entries: &[Entry],
+    layout: &Layout,
+    paper: Paper,
+    solutions: bool,
+    standalone: bool,
+) -> Vec<u8> {
+    let mut tex = String::new();
+    if standalone {
+        tex.push_str(concat!(
+            "\\documentclass{article}\n",
+            "\\usepackage[margin=0.5in]{geometry}\n",
+            "\\usepackage{tikz}\n",
+            "\\pagestyle{empty}\n",
+            "\\begin{document}\n",
+        ));
+    } else {
+        tex.push_str("% Requires \\usepackage{tikz}\n");
+    }
+
+    let (width, _) = paper.size();
+    let cell = layout.size / (width - 2.0 * MARGIN) / 9.0;
+    let font = match cell {
+        0.06.. => "\\Huge",
+        0.04.. => "\\Large",
+        _ => "\\normalsize",
+    };
+    let sections = match solutions {
+        true => &[false, true][..],
+        false => &[false],
+    };
+    for &solution in sections {
+        if solution {
+            tex.push_str("\\clearpage\n\\section*{Solutions}\n");
+        }
+        for (page_idx, page) in entries.chunks(layout.per_page).enumerate() {
+            if page_idx > 0 {
+                tex.push_str("\\clearpage\n");
+            }
+            tex.push_str("\\begin{center}\n");
+            for (slot, entry) in page.iter().enumerate() {
+                let number = page_idx * layout.per_page + slot + 1;
+                let text = title(number, entry, solution);
+                let solution = entry.solution.as_ref().filter(|_| solution);
+                tikz(&mut tex, cell, font, &text, &entry.puzzle, solution);
+                match (slot + 1) % layout.columns {
+                    0 => tex.push_str("\\par\\bigskip\n"),
+                    _ => tex.push_str("\\hfill\n"),
+                }
+            }
+            tex.push_str("\\end{center}\n");
+        }
+    }
+    if standalone {
+        tex.push_str("\\end{document}\n");
+    }
+    tex.into_bytes()
+}
+
+/// Draw a labeled puzzle as TikZ picture with cells of a fraction of the
+/// width of the lines. If a solution is given, its values are filled in, with
+/// the clues in bold.
+fn tikz(
+    tex: &mut String,
+    cell: f32,
+    font: &str,
+    label: &str,
+    puzzle: &Puzzle,
+    solution: Option<&Solution>,
+) {
+    writeln!(
+        tex,
+        "\\begin{{tikzpicture}}[x={:.4}\\linewidth, y={:.4}\\linewidth, font={}\\sffamily]",
+        cell, cell, font
+    )
+    .unwrap();
+    writeln!(
+        tex,
+        "  \\node[anchor=south west, inner xsep=0pt, font=\\normalsize\\bfseries] at (0,9) {{{}}};",
+        label
+    )
+    .unwrap();
+    tex.push_str("  \\draw[gray] (0,0) grid (9,9);\n");
+    tex.push_str("  \\draw[very thick, step=3] (0,0) grid (9,9);\n");
+    for row in 0..9 {
+        for col in 0..9 {
+            let value = match (puzzle.cells()[row][col], solution) {
+                (0, Some(solution)) => solution.cells()[row][col].to_string(),
+                (0, None) => continue,
+                (value, Some(_)) => format!("\\textbf{{{}}}", value),
+                (value, None) => value.to_string(),
+            };
+            writeln!(tex, "  \\node at ({}.5,{}.5) {{{}}};", col, 8 - row, value).unwrap();
+        }
+    }
+    tex.push_str("\\end{tikzpicture}\n");
+}
+
+/// Write a worksheet as web page, on which each puzzle can be solved by
+/// entering values and candidates, and the entries are checked against the
+/// embedded solution.
+fn html(entries: &[Entry]) -> Vec<u8> {
+    let puzzles: Vec<Value> = entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            json!({
+                "label": title(idx + 1, entry, false),
+                "puzzle": entry.puzzle.to_string(),
+                "solution": entry.solution.as_ref().map(Solution::row_representation),
+            })
+        })
+        .collect();
+    HTML_TEMPLATE
+        .replace(HTML_PLACEHOLDER, &Value::Array(puzzles).to_string())
+        .into_bytes()
+}
+
+/// Arrangement of the puzzles on a page, in columns and rows of equal size.
+struct Layout {
+    per_page: usize,
+    columns: usize,
+    /// Width and height of each puzzle, in points.
+    size: f32,
+    /// Top left corner of the area in which puzzles are placed.
+    origin: (f32, f32),
+    /// Width and height of the area of each puzzle, including its label.
+    slot: (f32, f32),
+}
+
+impl Layout {
+    /// Arrange a number of puzzles on a page, choosing the number of columns
+    /// such that the puzzles are as large as possible.
+    fn new((width, height): (f32, f32), per_page: usize) -> Self {
+        let (area_width, area_height) = (width - 2.0 * MARGIN, height - 2.0 * MARGIN);
+        let fit = |columns: usize| {
+            let rows = per_page.div_ceil(columns);
+            let size_width = (area_width - GAP * (columns - 1) as f32) / columns as f32;
+            let size_height = (area_height - GAP * (rows - 1) as f32) / rows as f32 - LABEL_HEIGHT;
+            size_width.min(size_height)
+        };
+        let columns = (1..=per_page)
+            .max_by(|a, b| fit(*a).total_cmp(&fit(*b)))
+            .unwrap_or(1);
+        let rows = per_page.div_ceil(columns);
+        let size = fit(columns);
+        let slot = (size + GAP, size + LABEL_HEIGHT + GAP);
+        let used = (columns as f32 * slot.0 - GAP, rows as f32 * slot.1 - GAP);
+        Self {
+            per_page,
+            columns,
+            size,
+            origin: ((width - used.0) / 2.0, height - (height - used.1) / 2.0),
+            slot,
+        }
+    }
+
+    /// Bottom left corner of the puzzle in a slot, counted row by row.
+    fn position(&self, slot: usize) -> (f32, f32) {
+        let (row, col) = (slot / self.columns, slot % self.columns);
+        (
+            self.origin.0 + col as f32 * self.slot.0,
+            self.origin.1 - row as f32 * self.slot.1 - LABEL_HEIGHT - self.size,
+        )
+    }
+}
+
+/// Draw text in a PDF with its baseline at a position.
+fn pdf_label(content: &mut String, x: f32, y: f32, text: &str) {
+    writeln!(
+        content,
+        "BT /F2 {} Tf {:.2} {:.2} Td ({}) Tj ET",
+        LABEL_SIZE,
+        x,
+        y,
+        escape(text)
+    )
+    .unwrap();
+}
+
+/// Draw a puzzle in a PDF with its bottom left corner at a position. If a solution
+/// is given, its values are filled in, with the clues in bold.
+fn pdf_grid(
+    content: &mut String,
+    x: f32,
+    y: f32,
+    size: f32,
+    puzzle: &Puzzle,
+    solution: Option<&Solution>,
+) {
+    let cell = size / 9.0;
+    for idx in 1..9 {
+        if idx % 3 == 0 {
+            continue;
+        }
+        let offset = idx as f32 * cell;
+        writeln!(
+            content,
+            "0.5 w {:.2} {:.2} m {:.2} {:.2} l S {:.2} {:.2} m {:.2} {:.2} l S",
+            x + offset,
+            y,
+            x + offset,
+            y + size,
+            x,
+            y + offset,
+            x + size,
+            y + offset
+        )
+        .unwrap();
+    }
+    for idx in [3.0, 6.0] {
+        let offset = idx * cell;
+        writeln!(
+            content,
+            "2 w {:.2} {:.2} m {:.2} {:.2} l S {:.2} {:.2} m {:.2} {:.2} l S",
+            x + offset,
+            y,
+            x + offset,
+            y + size,
+            x,
+            y + offset,
+            x + size,
+            y + offset
+        )
+        .unwrap();
+    }
+    writeln!(
+        content,
+        "2 w {:.2} {:.2} {:.2} {:.2} re S",
+        x, y, size, size
+    )
+    .unwrap();
+
+    // Digits of Helvetica are 0.556 em wide and 0.7 em high.
+    let font_size = cell * 0.6;
+    for row in 0..9 {
+        for col in 0..9 {
+            let (value, font) = match (puzzle.cells()[row][col], solution) {
+                (0, Some(solution)) => (solution.cells()[row][col], "F1"),
+                (value, _) => (value, "F2"),
+            };
+            if value == 0 {
+                continue;
+            }
+            let digit_x = x + col as f32 * cell + (cell - 0.556 * font_size) / 2.0;
+            let digit_y = y + (8 - row) as f32 * cell + (cell - 0.7 * font_size) / 2.0;
+            writeln!(
+                content,
+                "BT /{} {:.2} Tf {:.2} {:.2} Td ({}) Tj ET",
+                font, font_size, digit_x, digit_y, value
+            )
+            .unwrap();
+        }
+    }
+}
+
+/// Escape text for use in a string of a PDF.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+/// Minimal PDF document of pages with uncompressed contents, using the
+/// standard Helvetica fonts which need not be embedded.
+struct Pdf {
+    size: (f32, f32),
+    /// Bodies of the objects, of which the number is their index plus one.
+    objects: Vec<String>,
+    /// Object numbers of the pages.
+    pages: Vec<usize>,
+}
+
+/// Object numbers of the objects every document starts with.
+const CATALOG: usize = 1;
+const PAGES: usize = 2;
+
+impl Pdf {
+    fn new(size: (f32, f32)) -> Self {
+        Self {
+            size,
+            objects: vec![
+                format!("<< /Type /Catalog /Pages {} 0 R >>", PAGES),
+                String::new(),
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold >>".to_string(),
+            ],
+            pages: Vec::new(),
+        }
+    }
+
+    /// Add an object, returning its number.
+    fn add(&mut self, body: String) -> usize {
+        self.objects.push(body);
+        self.objects.len()
+    }
+
+    /// Add a page with content in the PDF drawing language.
+    fn page(&mut self, content: String) {
+        let content = self.add(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+        let page = self.add(format!(
+            "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            PAGES, self.size.0, self.size.1, content
+        ));
+        self.pages.push(page);
+    }
+
+    /// Serialize the document, which has an empty page if no page was added.
+    fn finish(mut self) -> Vec<u8> {
+        if self.pages.is_empty() {
+            self.page(String::new());
+        }
+        let kids: Vec<String> = self
+            .pages
+            .iter()
+            .map(|page| format!("{} 0 R", page))
+            .collect();
+        self.objects[PAGES - 1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            self.pages.len()
+        );
+
+        let mut data = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (idx, body) in self.objects.iter().enumerate() {
+            offsets.push(data.len());
+            writeln!(data, "{} 0 obj\n{}\nendobj", idx + 1, body).unwrap();
+        }
+        let xref = data.len();
+        writeln!(data, "xref\n0 {}\n0000000000 65535 f ", offsets.len() + 1).unwrap();
+        for offset in offsets {
+            writeln!(data, "{:010} 00000 n ", offset).unwrap();
+        }
+        writeln!(
+            data,
+            "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF",
+            self.objects.len() + 1,
+            CATALOG,
+            xref
+        )
+        .unwrap();
+        data.into_bytes()
+    }
+}
@@ -0,0 +1,169 @@
+//! Solving in `const` context, such that known puzzles can be solved at
+//! compile time and their solutions embedded as constants:
+//!
+//! ```
+//! use sudoku_solver::{constant, Puzzle, Solution};
+//!
+//! const PUZZLE: Puzzle = match Puzzle::from_cells([
+//!     [5, 3, 0, 0, 7, 0, 0, 0, 0],
+//!     [6, 0, 0, 1, 9, 5, 0, 0, 0],
+//!     [0, 9, 8, 0, 0, 0, 0, 6, 0],
+//!     [8, 0, 0, 0, 6, 0, 0, 0, 3],
+//!     [4, 0, 0, 8, 0, 3, 0, 0, 1],
+//!     [7, 0, 0, 0, 2, 0, 0, 0, 6],
+//!     [0, 6, 0, 0, 0, 0, 2, 8, 0],
+//!     [0, 0, 0, 4, 1, 9, 0, 0, 5],
+//!     [0, 0, 0, 0, 8, 0, 0, 7, 9],
+//! ]) {
+//!     Ok(puzzle) => puzzle,
+//!     Err(_) => panic!("invalid puzzle"),
+//! };
+//! const SOLUTION: Solution = match constant::solve(&PUZZLE) {
+//!     Ok(solution) => solution,
+//!     Err(_) => panic!("puzzle has no solution"),
+//! };
+//! assert_eq!(SOLUTION.cells()[0], [5, 3, 4, 6, 7, 8, 9, 1, 2]);
+//! ```
+//!
+//! As `const fn` cannot use iterators, traits or the trail of the solver,
+//! this is a separate and plainer search, which fills the empty cell with
+//! the fewest candidates first and backtracks over fixed-size arrays. It is
+//! therefore slower than [`Solver`](crate::Solver), and very hard puzzles
+//! may exceed the limits of the compiler on constant evaluation.
+
+use crate::{Puzzle, Solution};
+
+/// Mask of all values.
+const ALL: u16 = 0x1ff;
+
+/// Check that no value of a puzzle occurs twice in a row, column or square,
+/// usable in `const` context.
+pub const fn check(puzzle: &Puzzle) -> Result<(), &'static str> {
+    match house_values(puzzle) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// The values in each row, column and square of a puzzle as bitmasks,
+/// failing if a value occurs twice in one.
+#[allow(clippy::type_complexity)]
+const fn house_values(puzzle: &Puzzle) -> Result<([u16; 9], [u16; 9], [u16; 9]), &'static str> {
+    let mut rows = [0u16; 9];
+    let mut cols = [0u16; 9];
+    let mut squares = [0u16; 9];
+    let mut idx = 0;
+    while idx < 81 {
+        let (row, col) = (idx / 9, idx % 9);
+        let square = row / 3 * 3 + col / 3;
+        let value = puzzle.cells[row][col];
+        if value != 0 {
+            let bit = 1 << (value - 1);
+            if (rows[row] | cols[col] | squares[square]) & bit != 0 {
+                return Err("puzzle has conflicting values");
+            }
+            rows[row] |= bit;
+            cols[col] |= bit;
+            squares[square] |= bit;
+        }
+        idx += 1;
+    }
+    Ok((rows, cols, squares))
+}
+
+/// Solve a puzzle, usable in `const` context. A puzzle with a unique
+/// solution gets the solution found by [`Solver`](crate::Solver), although
+/// the statistics are those of this search. Of a puzzle with multiple
+/// solutions, another solution may be found.
+pub const fn solve(puzzle: &Puzzle) -> Result<Solution, &'static str> {
+    let mut cells = puzzle.cells;
+    let (mut rows, mut cols, mut squares) = match house_values(puzzle) {
+        Ok(values) => values,
+        Err(err) => return Err(err),
+    };
+
+    // The cells filled by the search, along with their untried candidates
+    // and whether filling them was a guess.
+    let mut filled = [0u8; 81];
+    let mut untried = [0u16; 81];
+    let mut guessed = [false; 81];
+    let mut depth = 0;
+    let mut guesses: u32 = 0;
+    let mut backtracks: u32 = 0;
+    loop {
+        // Find the empty cell with the fewest candidates.
+        let mut best = 81;
+        let mut best_mask = 0;
+        let mut best_count = 10;
+        let mut idx = 0;
+        while idx < 81 {
+            let (row, col) = (idx / 9, idx % 9);
+            if cells[row][col] == 0 {
+                let taken = rows[row] | cols[col] | squares[row / 3 * 3 + col / 3];
+                let mask = ALL & !taken;
+                let count = mask.count_ones();
+                if count < best_count {
+                    (best, best_mask, best_count) = (idx, mask, count);
+                    if count <= 1 {
+                        break;
+                    }
+                }
+            }
+            idx += 1;
+        }
+        if best == 81 {
+            break;
+        }
+        if best_count > 0 {
+            filled[depth] = best as u8;
+            untried[depth] = best_mask;
+            guessed[depth] = best_count > 1;
+            depth += 1;
+        }
+
+        // Fill the next untried candidate of the innermost cell, emptying
+        // the cells of which every candidate has been tried.
+        loop {
+            if depth == 0 {
+                return Err("puzzle has no solution");
+            }
+            let frame = depth - 1;
+            let idx = filled[frame] as usize;
+            let (row, col) = (idx / 9, idx % 9);
+            let square = row / 3 * 3 + col / 3;
+            let current = cells[row][col];
+            if current != 0 {
+                let bit = !(1 << (current - 1));
+                rows[row] &= bit;
+                cols[col] &= bit;
+                squares[square] &= bit;
+                cells[row][col] = 0;
+                if guessed[frame] {
+                    backtracks = backtracks.saturating_add(1);
+                }
+            }
+            let remaining = untried[frame];
+            if remaining == 0 {
+                depth -= 1;
+                continue;
+            }
+            let bit = remaining & remaining.wrapping_neg();
+            untried[frame] = remaining & !bit;
+            cells[row][col] = bit.trailing_zeros() as u8 + 1;
+            rows[row] |= bit;
+            cols[col] |= bit;
+            squares[square] |= bit;
+            if guessed[frame] {
+                guesses = guesses.saturating_add(1);
+            }
+            break;
+        }
+    }
+
+    Ok(Solution {
+        cells,
+        brute_forces: guesses.saturating_sub(backtracks) as u8,
+        guesses,
+        backtracks,
+    })
+}
@@ -0,0 +1,85 @@
+//! Small curated sets of puzzles for each difficulty tier, which are embedded
+//! in the library such that applications and tests have puzzles at hand
+//! without shipping files. Every puzzle has a unique solution and is rated
+//! as the tier of its set.
+
+use crate::Puzzle;
+
+/// Number of puzzles in each set.
+pub const SIZE: usize = 20;
+
+static EASY: [Puzzle; SIZE] = parse(include_str!("datasets/easy.txt"));
+static MEDIUM: [Puzzle; SIZE] = parse(include_str!("datasets/medium.txt"));
+static HARD: [Puzzle; SIZE] = parse(include_str!("datasets/hard.txt"));
+static EXPERT: [Puzzle; SIZE] = parse(include_str!("datasets/expert.txt"));
+static EXTREME: [Puzzle; SIZE] = parse(include_str!("datasets/extreme.txt"));
+
+/// Parse puzzles of 81 values and dots on each line at compile time, such
+/// that a malformed set fails the build.
+const fn parse(text: &str) -> [Puzzle; SIZE] {
+    let bytes = text.as_bytes();
+    let mut puzzles = [Puzzle { cells: [[0; 9]; 9] }; SIZE];
+    let mut count = 0;
+    let mut idx = 0;
+    let mut cell = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\n' => {
+                assert!(cell == 81, "puzzle in dataset is not 81 values long");
+                count += 1;
+                cell = 0;
+            }
+            byte @ (b'.' | b'0'..=b'9') => {
+                assert!(count < SIZE, "dataset has too many puzzles");
+                assert!(cell < 81, "puzzle in dataset is not 81 values long");
+                if byte != b'.' {
+                    puzzles[count].cells[cell / 9][cell % 9] = byte - b'0';
+                }
+                cell += 1;
+            }
+            _ => panic!("invalid character in dataset"),
+        }
+        idx += 1;
+    }
+    assert!(count == SIZE && cell == 0, "dataset has too few puzzles");
+    puzzles
+}
+
+/// Puzzles which only require singles.
+pub fn easy() -> &'static [Puzzle] {
+    &EASY
+}
+
+/// Puzzles which require candidates locked within a house.
+pub fn medium() -> &'static [Puzzle] {
+    &MEDIUM
+}
+
+/// Puzzles which require naked or hidden subsets.
+pub fn hard() -> &'static [Puzzle] {
+    &HARD
+}
+
+/// Puzzles which require fish or wings.
+pub fn expert() -> &'static [Puzzle] {
+    &EXPERT
+}
+
+/// Puzzles which none of the known techniques solve, such that
+/// brute-forcing is required.
+pub fn extreme() -> &'static [Puzzle] {
+    &EXTREME
+}
+
+/// Puzzles of a difficulty tier.
+#[cfg(feature = "rater")]
+pub fn of_difficulty(difficulty: crate::rating::Difficulty) -> &'static [Puzzle] {
+    use crate::rating::Difficulty;
+    match difficulty {
+        Difficulty::Easy => easy(),
+        Difficulty::Medium => medium(),
+        Difficulty::Hard => hard(),
+        Difficulty::Expert => expert(),
+        Difficulty::Extreme => extreme(),
+    }
+}
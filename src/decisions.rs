@@ -0,0 +1,353 @@
+//! Recording and replaying of the guesses made while brute-forcing a puzzle,
+//! such that hard-to-reproduce performance cases can be captured and shared
+//! as small text files. A recording lists every candidate filled in by
+//! guessing, along with the depth of the guess and whether filling it was
+//! consistent:
+//!
+//! ```text
+//! puzzle 8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..
+//! 1 r1c3=1 ok
+//! 2 r2c1=9 contradiction
+//! 2 r2c1=5 ok
+//! ...
+//! solved
+//! ```
+//!
+//! Replaying fills the recorded candidates in order instead of choosing them,
+//! which reproduces the search even after the heuristics of the solver have
+//! changed, and reports the first decision of which the outcome differs.
+//! Recordings can also be rendered as search tree using [`dot`], for
+//! visualizing the search with Graphviz.
+
+use crate::{check_cell, check_digit, Budget, Cell, Observe, Options, Puzzle, Solution, Solver};
+use std::fmt;
+use std::str::FromStr;
+
+/// A candidate filled in by guessing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Decision {
+    /// Number of guesses this guess is nested in, starting from one.
+    pub depth: usize,
+    pub cell: (usize, usize),
+    pub value: u8,
+    /// Whether filling the candidate was consistent, instead of leading to a
+    /// contradiction right away. Consistent guesses may still be reverted
+    /// later, when no guess nested in them leads to a solution.
+    pub consistent: bool,
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{} r{}c{}={} {}",
+            self.depth,
+            self.cell.0.saturating_add(1),
+            self.cell.1.saturating_add(1),
+            self.value,
+            match self.consistent {
+                true => "ok",
+                false => "contradiction",
+            }
+        )
+    }
+}
+
+impl FromStr for Decision {
+    type Err = &'static str;
+
+    /// Parse a decision as written by its `Display` implementation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace();
+        let (Some(depth), Some(fill), Some(outcome), None) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err("expected a depth, fill and outcome");
+        };
+        let depth = depth
+            .parse()
+            .ok()
+            .filter(|depth| (1..=9 * 9).contains(depth))
+            .ok_or("invalid depth of decision")?;
+        let fill = fill.as_bytes();
+        let (cell, value) = match fill {
+            [b'r', row @ b'1'..=b'9', b'c', col @ b'1'..=b'9', b'=', value @ b'1'..=b'9'] => {
+                (((row - b'1') as usize, (col - b'1') as usize), value - b'0')
+            }
+            _ => return Err("expected a fill such as r1c2=3"),
+        };
+        let consistent = match outcome {
+            "ok" => true,
+            "contradiction" => false,
+            _ => return Err("expected an outcome of ok or contradiction"),
+        };
+        Ok(Self {
+            depth,
+            cell,
+            value,
+            consistent,
+        })
+    }
+}
+
+/// The guesses made while brute-forcing a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recording {
+    pub puzzle: Puzzle,
+    pub decisions: Vec<Decision>,
+    /// Whether the search found a solution, instead of being exhausted or
+    /// stopped by a limit.
+    pub solved: bool,
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(fmt, "puzzle {}", self.puzzle)?;
+        for decision in &self.decisions {
+            writeln!(fmt, "{}", decision)?;
+        }
+        match self.solved {
+            true => writeln!(fmt, "solved"),
+            false => writeln!(fmt, "unsolved"),
+        }
+    }
+}
+
+impl FromStr for Recording {
+    type Err = String;
+
+    /// Parse a recording as written by its `Display` implementation,
+    /// ignoring empty lines and comments starting with `#`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+        let context = |number: usize, err: &str| format!("line {}: {}", number, err);
+        let puzzle = match lines.next() {
+            Some((number, line)) => line
+                .strip_prefix("puzzle ")
+                .ok_or("expected the puzzle")
+                .and_then(|puzzle| puzzle.trim().parse())
+                .map_err(|err| context(number, err))?,
+            None => return Err("recording is empty".to_string()),
+        };
+        let mut decisions = Vec::new();
+        for (number, line) in lines.by_ref() {
+            match line {
+                "solved" | "unsolved" => {
+                    if let Some((number, _)) = lines.next() {
+                        return Err(context(number, "unexpected line after the result"));
+                    }
+                    return Ok(Self {
+                        puzzle,
+                        decisions,
+                        solved: line == "solved",
+                    });
+                }
+                _ => decisions.push(line.parse().map_err(|err| context(number, err))?),
+            }
+        }
+        Err("recording has no result".to_string())
+    }
+}
+
+/// Observer collecting the guesses of a search as decisions.
+impl Observe for Vec<Decision> {
+    fn guess(&mut self, depth: usize, coords: (usize, usize), value: u8, consistent: bool) {
+        self.push(Decision {
+            depth,
+            cell: coords,
+            value,
+            consistent,
+        });
+    }
+}
+
+/// Solve a puzzle like [`Solver::solve_with_options`], recording the guesses
+/// made while brute-forcing it. The search is always sequential, such that
+/// the recording is deterministic.
+pub fn record(puzzle: &Puzzle, options: &Options) -> (Result<Solution, &'static str>, Recording) {
+    let mut solver = Solver::new();
+    let mut decisions = Vec::new();
+    let solved = solver.load(puzzle).and_then(|_| {
+        if solver.unfilled_cells > 0 {
+            let budget = Budget::new(options);
+            solver.brute_force(&Default::default(), &budget, &mut decisions)?;
+        }
+        Ok(solver.solution())
+    });
+    let recording = Recording {
+        puzzle: *puzzle,
+        solved: solved.is_ok(),
+        decisions,
+    };
+    (solved, recording)
+}
+
+/// A decision of which the outcome differs when replayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the decision in the recording, starting from zero, or `None`
+    /// if the result of the search differs.
+    pub index: Option<usize>,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(fmt, "decision {}: {}", index + 1, self.reason),
+            None => write!(fmt, "result: {}", self.reason),
+        }
+    }
+}
+
+/// Replay a recording, filling its decisions in order and checking that
+/// each has the recorded outcome, and that the search ends with the recorded
+/// result. Returns the solution if the replay solved the puzzle.
+pub fn replay(recording: &Recording) -> Result<Option<Solution>, Divergence> {
+    replay_counting(recording, &mut Vec::new())
+}
+
+/// Replay a recording like [`replay`], collecting the number of candidates
+/// of the cell of each decision right before it was filled.
+fn replay_counting(
+    recording: &Recording,
+    counts: &mut Vec<u32>,
+) -> Result<Option<Solution>, Divergence> {
+    let diverged = |index, reason| Divergence { index, reason };
+    let mut solver = Solver::new();
+    // A puzzle which cannot be loaded is never searched.
+    if let Err(reason) = solver.load(&recording.puzzle) {
+        return match (recording.decisions.is_empty(), recording.solved) {
+            (true, false) => Ok(None),
+            (false, _) => Err(diverged(Some(0), reason)),
+            (true, true) => Err(diverged(None, reason)),
+        };
+    }
+    // The length of the trail before each consistent guess the next one is
+    // nested in.
+    let mut marks = Vec::new();
+    for (index, decision) in recording.decisions.iter().enumerate() {
+        if decision.depth == 0 {
+            return Err(diverged(Some(index), "guess is nested at depth zero"));
+        }
+        if decision.depth > marks.len() + 1 {
+            return Err(diverged(
+                Some(index),
+                "guess is nested deeper than possible",
+            ));
+        }
+        if let Some(&mark) = marks.get(decision.depth - 1) {
+            solver.undo(mark);
+            marks.truncate(decision.depth - 1);
+        }
+        if let Err(reason) = check_cell(decision.cell).and(check_digit(decision.value)) {
+            return Err(diverged(Some(index), reason));
+        }
+        let (row, col) = decision.cell;
+        match solver.cells[row][col] {
+            Cell::Value(_) => return Err(diverged(Some(index), "cell is already filled")),
+            Cell::Candidates(cs) if !cs.contains(decision.value) => {
+                return Err(diverged(
+                    Some(index),
+                    "value is not a candidate of the cell",
+                ))
+            }
+            Cell::Candidates(cs) => counts.push(cs.len()),
+        }
+        marks.push(solver.trail.len());
+        solver.guesses = solver.guesses.saturating_add(1);
+        let consistent = solver.fill(decision.cell, decision.value).is_ok();
+        if !consistent {
+            solver.undo(marks.pop().unwrap());
+        }
+        if consistent != decision.consistent {
+            return Err(diverged(
+                Some(index),
+                match consistent {
+                    true => "guess was consistent instead of leading to a contradiction",
+                    false => "guess led to a contradiction instead of being consistent",
+                },
+            ));
+        }
+    }
+    let solved = recording
+        .decisions
+        .last()
+        .map_or(solver.unfilled_cells == 0, |last| {
+            last.consistent && solver.unfilled_cells == 0
+        });
+    match (solved, recording.solved) {
+        (true, true) => {
+            solver.brute_force_fills = marks.len() as u8;
+            Ok(Some(solver.solution()))
+        }
+        (false, false) => Ok(None),
+        (true, false) => Err(diverged(
+            None,
+            "puzzle was solved instead of remaining unsolved",
+        )),
+        (false, true) => Err(diverged(
+            None,
+            "puzzle remained unsolved instead of being solved",
+        )),
+    }
+}
+
+/// Render the search tree of a recording in the DOT language of Graphviz,
+/// replaying it to annotate each guess with the number of candidates its
+/// cell had. Each guess is a child of the consistent guess it is nested in,
+/// and is colored by its outcome: green on the path to the solution, yellow
+/// on the path at which a limit stopped the search, red if it led to a
+/// contradiction and gray if the search backtracked from it.
+pub fn dot(recording: &Recording) -> Result<String, Divergence> {
+    let mut counts = Vec::new();
+    let solved = replay_counting(recording, &mut counts)?.is_some();
+    // The consistent decisions each decision is nested in, of which those
+    // left at the end form the path at which the search ended.
+    let mut path: Vec<usize> = Vec::new();
+    let mut parents = Vec::with_capacity(recording.decisions.len());
+    for (index, decision) in recording.decisions.iter().enumerate() {
+        path.truncate(decision.depth - 1);
+        parents.push(path.last().copied());
+        if decision.consistent {
+            path.push(index);
+        }
+    }
+
+    let mut out = String::from("digraph search {\n");
+    out.push_str("  node [shape=box, style=filled, fontname=monospace];\n");
+    out.push_str(&format!(
+        "  root [label=\"{} clues\\n{}\", fillcolor=white];\n",
+        recording.puzzle.clues(),
+        if solved { "solved" } else { "unsolved" }
+    ));
+    for (index, decision) in recording.decisions.iter().enumerate() {
+        let (outcome, color) = match path.contains(&index) {
+            _ if !decision.consistent => ("contradiction", "lightcoral"),
+            true if solved => ("solution", "palegreen"),
+            true => ("stopped", "lightyellow"),
+            false => ("backtracked", "lightgray"),
+        };
+        out.push_str(&format!(
+            "  n{} [label=\"r{}c{}={}\\n{} candidates\\n{}\", fillcolor={}];\n",
+            index + 1,
+            decision.cell.0 + 1,
+            decision.cell.1 + 1,
+            decision.value,
+            counts[index],
+            outcome,
+            color
+        ));
+        let parent = match parents[index] {
+            Some(parent) => format!("n{}", parent + 1),
+            None => "root".to_string(),
+        };
+        out.push_str(&format!("  {} -> n{};\n", parent, index + 1));
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
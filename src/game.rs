@@ -0,0 +1,517 @@
+//! State of a game in progress, which keeps the givens of the puzzle apart
+//! from the digits and pencil marks entered by the player, along with the
+//! moves made such that they can be undone.
+//!
+//! Sessions can be saved and restored in a compact string form using their
+//! [`Display`](fmt::Display) and [`FromStr`] implementations, or using serde
+//! with the `serde` feature. The string form consists of fields separated by
+//! colons: `v1:<puzzle>:<entries>:<notes>:<moves>`. The puzzle and entries
+//! are 81 values with dots for empty cells. Pencil marks are listed as cells
+//! with their digits, such as `r1c2=135`, and moves as `r1c2=5` for entering
+//! a digit, `r1c2=.` for erasing it and `r1c2#5` for toggling a pencil mark,
+//! each separated by commas.
+
+use crate::analysis;
+use crate::logic::{self, cell_name, sees, Technique};
+use crate::{check_cell, check_digit, Options, Puzzle, Solver};
+use std::fmt;
+use std::str::FromStr;
+
+/// Version of the saved form of sessions, which is raised whenever the form
+/// changes such that saves of other versions are recognized.
+pub const SAVE_VERSION: u32 = 1;
+
+/// A move made by the player, recording what it replaced such that it can be
+/// undone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Move {
+    /// A digit was entered into a cell, or its entry was erased if `None`.
+    Entry {
+        cell: (usize, usize),
+        digit: Option<u8>,
+        previous: Option<u8>,
+    },
+    /// A pencil mark of a digit was added to or removed from a cell.
+    Note { cell: (usize, usize), digit: u8 },
+}
+
+/// Outcome of checking a move against the solution of the puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MoveCheck {
+    /// The digit is the one of the solution.
+    Correct,
+    /// The digit differs from the one of the solution, even if it does not
+    /// conflict with any of the digits filled in so far.
+    Incorrect,
+    /// The puzzle has multiple solutions, so no digit is the correct one.
+    PuzzleNotUnique,
+}
+
+/// Whether a puzzle can still be solved, and in how many ways.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Solvability {
+    /// No solution is left, such as after entering a wrong digit.
+    Unsolvable,
+    /// A single solution is left.
+    Unique,
+    /// Multiple solutions are left.
+    Multiple,
+}
+
+/// A game in progress of solving a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameSession {
+    puzzle: Puzzle,
+    /// Digits entered by the player, which are zero for empty cells.
+    entries: [[u8; 9]; 9],
+    /// Pencil marks of the player, as bitmask of digits per cell.
+    notes: [[u16; 9]; 9],
+    /// Moves made so far, excluding those which were undone.
+    moves: Vec<Move>,
+}
+
+/// Parse the name of a cell, such as `r1c2` for the cell in row 1 and
+/// column 2.
+fn parse_cell(name: &str) -> Result<(usize, usize), &'static str> {
+    match name.as_bytes() {
+        &[b'r', row @ b'1'..=b'9', b'c', col @ b'1'..=b'9'] => {
+            Ok(((row - b'1') as usize, (col - b'1') as usize))
+        }
+        _ => Err("invalid cell in saved game"),
+    }
+}
+
+/// Parse a list of values of all cells, with dots or zeroes for empty ones.
+fn parse_values(values: &str) -> Result<[[u8; 9]; 9], &'static str> {
+    Ok(values.parse::<Puzzle>()?.cells)
+}
+
+impl GameSession {
+    /// Start a game of a puzzle, of which the givens may not conflict.
+    pub fn new(puzzle: Puzzle) -> Result<Self, &'static str> {
+        let session = Self {
+            puzzle,
+            entries: [[0; 9]; 9],
+            notes: [[0; 9]; 9],
+            moves: Vec::new(),
+        };
+        if !session.conflicts().is_empty() {
+            return Err("givens of the puzzle conflict");
+        }
+        Ok(session)
+    }
+
+    /// The puzzle being played.
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    /// Whether a cell is given by the puzzle.
+    pub fn is_given(&self, cell: (usize, usize)) -> bool {
+        check_cell(cell).is_ok() && self.puzzle.cells[cell.0][cell.1] != 0
+    }
+
+    /// The digit entered by the player into a cell, if any.
+    pub fn entry(&self, cell: (usize, usize)) -> Option<u8> {
+        check_cell(cell).ok()?;
+        match self.entries[cell.0][cell.1] {
+            0 => None,
+            digit => Some(digit),
+        }
+    }
+
+    /// The digit of a cell, which is either given or entered.
+    pub fn value(&self, cell: (usize, usize)) -> Option<u8> {
+        check_cell(cell).ok()?;
+        match self.puzzle.cells[cell.0][cell.1] {
+            0 => self.entry(cell),
+            digit => Some(digit),
+        }
+    }
+
+    /// The digits pencil marked in a cell.
+    pub fn notes(&self, cell: (usize, usize)) -> impl Iterator<Item = u8> {
+        let notes = match check_cell(cell) {
+            Ok(()) => self.notes[cell.0][cell.1],
+            Err(_) => 0,
+        };
+        (1..=9).filter(move |digit| notes & (1 << (digit - 1)) != 0)
+    }
+
+    /// Whether a digit is pencil marked in a cell.
+    pub fn has_note(&self, cell: (usize, usize), digit: u8) -> bool {
+        check_cell(cell).is_ok()
+            && check_digit(digit).is_ok()
+            && self.notes[cell.0][cell.1] & (1 << (digit - 1)) != 0
+    }
+
+    /// The moves made so far, excluding those which were undone.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The givens and entered digits as a puzzle.
+    pub fn to_puzzle(&self) -> Puzzle {
+        let mut cells = self.puzzle.cells;
+        for (row, values) in cells.iter_mut().enumerate() {
+            for (col, value) in values.iter_mut().enumerate() {
+                if *value == 0 {
+                    *value = self.entries[row][col];
+                }
+            }
+        }
+        Puzzle { cells }
+    }
+
+    /// Enter a digit into a cell which is not given, replacing its previous
+    /// entry. Its pencil marks are kept, and shown again once erased.
+    pub fn enter(&mut self, cell: (usize, usize), digit: u8) -> Result<(), &'static str> {
+        check_digit(digit)?;
+        self.set_entry(cell, Some(digit))
+    }
+
+    /// Erase the entry of a cell which is not given.
+    pub fn erase(&mut self, cell: (usize, usize)) -> Result<(), &'static str> {
+        self.set_entry(cell, None)
+    }
+
+    fn set_entry(&mut self, cell: (usize, usize), digit: Option<u8>) -> Result<(), &'static str> {
+        check_cell(cell)?;
+        if self.is_given(cell) {
+            return Err("cannot change a given cell");
+        }
+        let previous = self.entry(cell);
+        self.entries[cell.0][cell.1] = digit.unwrap_or(0);
+        self.moves.push(Move::Entry {
+            cell,
+            digit,
+            previous,
+        });
+        Ok(())
+    }
+
+    /// Add a pencil mark of a digit to a cell, or remove it if present.
+    pub fn toggle_note(&mut self, cell: (usize, usize), digit: u8) -> Result<(), &'static str> {
+        check_cell(cell)?;
+        check_digit(digit)?;
+        if self.is_given(cell) {
+            return Err("cannot change a given cell");
+        }
+        self.notes[cell.0][cell.1] ^= 1 << (digit - 1);
+        self.moves.push(Move::Note { cell, digit });
+        Ok(())
+    }
+
+    /// Undo the last move, returning it if there was any.
+    pub fn undo(&mut self) -> Option<Move> {
+        let last = self.moves.pop()?;
+        match last {
+            Move::Entry { cell, previous, .. } => {
+                self.entries[cell.0][cell.1] = previous.unwrap_or(0);
+            }
+            Move::Note { cell, digit } => self.notes[cell.0][cell.1] ^= 1 << (digit - 1),
+        }
+        Some(last)
+    }
+
+    /// Check whether a digit in a cell is the one of the unique solution of
+    /// the puzzle, rather than only whether it conflicts with its peers. The
+    /// puzzle is solved for each check, which takes up to a few milliseconds.
+    pub fn check_move(&self, cell: (usize, usize), digit: u8) -> Result<MoveCheck, &'static str> {
+        check_cell(cell)?;
+        check_digit(digit)?;
+        if Solver::count_solutions(&self.puzzle, 2) > 1 {
+            return Ok(MoveCheck::PuzzleNotUnique);
+        }
+        let solution = Solver::solve_with_options(&self.puzzle, &Options::default())?;
+        match solution.cells()[cell.0][cell.1] == digit {
+            true => Ok(MoveCheck::Correct),
+            false => Ok(MoveCheck::Incorrect),
+        }
+    }
+
+    /// The earliest entry among those differing from the unique solution of
+    /// the puzzle, such that a player can be pointed to where they first went
+    /// wrong. Entries are ordered by the move which entered their digit.
+    pub fn first_mistake(&self) -> Result<Option<(usize, usize)>, &'static str> {
+        let check = analysis::check_grid(&self.puzzle, &self.to_puzzle())?;
+        let mut entered = [[0; 9]; 9];
+        for (idx, mv) in self.moves.iter().enumerate() {
+            if let Move::Entry { cell, .. } = mv {
+                entered[cell.0][cell.1] = idx;
+            }
+        }
+        Ok(check
+            .mistakes
+            .into_iter()
+            .min_by_key(|cell| entered[cell.0][cell.1]))
+    }
+
+    /// Points for entering a digit into a cell, such that games can reward
+    /// harder deductions consistently. Digits other than the one of the
+    /// unique solution earn nothing. Otherwise, the points are the score of
+    /// the hardest technique required to deduce the digit logically from the
+    /// givens and the correct entries so far, as used for rating puzzles.
+    pub fn score_move(&self, cell: (usize, usize), digit: u8) -> Result<u32, &'static str> {
+        check_cell(cell)?;
+        check_digit(digit)?;
+        if self.is_given(cell) {
+            return Err("cannot change a given cell");
+        }
+        if Solver::count_solutions(&self.puzzle, 2) > 1 {
+            return Err("puzzle has multiple solutions");
+        }
+        let solution = Solver::solve_with_options(&self.puzzle, &Options::default())?;
+        if solution.cells()[cell.0][cell.1] != digit {
+            return Ok(0);
+        }
+        let mut puzzle = self.to_puzzle();
+        for (row, values) in puzzle.cells.iter_mut().enumerate() {
+            for (col, value) in values.iter_mut().enumerate() {
+                if *value != solution.cells()[row][col] {
+                    *value = 0;
+                }
+            }
+        }
+        puzzle.cells[cell.0][cell.1] = 0;
+        let mut hardest = None;
+        for step in logic::solve(&puzzle)? {
+            hardest = hardest.max(Some(step.technique));
+            if step.placements.iter().any(|(coords, _)| *coords == cell) {
+                break;
+            }
+        }
+        Ok(hardest.map_or(0, Technique::score))
+    }
+
+    /// Whether the puzzle would remain solvable, and uniquely so, if a digit
+    /// were entered into a cell, keeping the other entries. The session is
+    /// not changed, such that moves can be vetted before making them.
+    pub fn solvability_after(
+        &self,
+        cell: (usize, usize),
+        digit: u8,
+    ) -> Result<Solvability, &'static str> {
+        check_cell(cell)?;
+        check_digit(digit)?;
+        if self.is_given(cell) {
+            return Err("cannot change a given cell");
+        }
+        let mut puzzle = self.to_puzzle();
+        puzzle.cells[cell.0][cell.1] = digit;
+        match Solver::count_solutions(&puzzle, 2) {
+            0 => Ok(Solvability::Unsolvable),
+            1 => Ok(Solvability::Unique),
+            _ => Ok(Solvability::Multiple),
+        }
+    }
+
+    /// The cells of which the digit also occurs in a peer.
+    pub fn conflicts(&self) -> Vec<(usize, usize)> {
+        let cells = || (0..9).flat_map(|row| (0..9).map(move |col| (row, col)));
+        cells()
+            .filter(|cell| {
+                let value = self.value(*cell);
+                value.is_some()
+                    && cells().any(|peer| sees(*cell, peer) && self.value(peer) == value)
+            })
+            .collect()
+    }
+
+    /// Restore a session by replaying its moves, checking that they result
+    /// in the saved entries and pencil marks.
+    fn restore(
+        puzzle: Puzzle,
+        entries: [[u8; 9]; 9],
+        notes: [[u16; 9]; 9],
+        moves: &[Move],
+    ) -> Result<Self, &'static str> {
+        let mut session = Self::new(puzzle)?;
+        for mv in moves {
+            match *mv {
+                Move::Entry {
+                    cell,
+                    digit,
+                    previous,
+                } => {
+                    check_cell(cell)?;
+                    if session.entry(cell) != previous {
+                        return Err("saved moves are inconsistent");
+                    }
+                    match digit {
+                        Some(digit) => session.enter(cell, digit)?,
+                        None => session.erase(cell)?,
+                    }
+                }
+                Move::Note { cell, digit } => session.toggle_note(cell, digit)?,
+            }
+        }
+        if session.entries != entries || session.notes != notes {
+            return Err("saved entries or pencil marks do not match the moves");
+        }
+        Ok(session)
+    }
+
+    /// Whether every cell has a digit.
+    pub fn is_complete(&self) -> bool {
+        (0..9).all(|row| (0..9).all(|col| self.value((row, col)).is_some()))
+    }
+
+    /// Whether every cell has a digit and none of them conflict, such that
+    /// the puzzle is solved.
+    pub fn is_solved(&self) -> bool {
+        self.is_complete() && self.conflicts().is_empty()
+    }
+}
+
+impl fmt::Display for GameSession {
+    /// Write the session in its compact saved form.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let entries = Puzzle {
+            cells: self.entries,
+        };
+        write!(fmt, "v{}:{}:{}:", SAVE_VERSION, self.puzzle, entries)?;
+        let notes: Vec<String> = (0..9)
+            .flat_map(|row| (0..9).map(move |col| (row, col)))
+            .filter(|cell| self.notes[cell.0][cell.1] != 0)
+            .map(|cell| {
+                let digits: String = self
+                    .notes(cell)
+                    .map(|digit| (b'0' + digit) as char)
+                    .collect();
+                format!("{}={}", cell_name(cell), digits)
+            })
+            .collect();
+        let moves: Vec<String> = self
+            .moves
+            .iter()
+            .map(|mv| match *mv {
+                Move::Entry {
+                    cell,
+                    digit: Some(digit),
+                    ..
+                } => format!("{}={}", cell_name(cell), digit),
+                Move::Entry { cell, .. } => format!("{}=.", cell_name(cell)),
+                Move::Note { cell, digit } => format!("{}#{}", cell_name(cell), digit),
+            })
+            .collect();
+        write!(fmt, "{}:{}", notes.join(","), moves.join(","))
+    }
+}
+
+impl FromStr for GameSession {
+    type Err = &'static str;
+
+    /// Parse a session from its compact saved form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.trim().split(':');
+        match fields.next() {
+            Some(version) if version == format!("v{}", SAVE_VERSION) => {}
+            Some(version) if version.starts_with('v') => {
+                return Err("unsupported version of saved game")
+            }
+            _ => return Err("saved game has no version"),
+        }
+        let mut field = || fields.next().ok_or("saved game is incomplete");
+        let puzzle = field()?.parse()?;
+        let entries = parse_values(field()?)?;
+        let mut notes = [[0; 9]; 9];
+        for note in field()?.split(',').filter(|note| !note.is_empty()) {
+            let (cell, digits) = note.split_once('=').ok_or("invalid note in saved game")?;
+            let cell = parse_cell(cell)?;
+            for digit in digits.bytes() {
+                match digit {
+                    b'1'..=b'9' => notes[cell.0][cell.1] |= 1 << (digit - b'1'),
+                    _ => return Err("invalid note in saved game"),
+                }
+            }
+        }
+        let mut moves = Vec::new();
+        let mut replayed = GameSession::new(puzzle)?;
+        for mv in field()?.split(',').filter(|mv| !mv.is_empty()) {
+            let (cell, digit, note) = match mv.split_once('=') {
+                Some((cell, digit)) => (cell, digit, false),
+                None => match mv.split_once('#') {
+                    Some((cell, digit)) => (cell, digit, true),
+                    None => return Err("invalid move in saved game"),
+                },
+            };
+            let cell = parse_cell(cell)?;
+            let digit = match digit.as_bytes() {
+                &[digit @ b'1'..=b'9'] => Some(digit - b'0'),
+                b"." if !note => None,
+                _ => return Err("invalid move in saved game"),
+            };
+            moves.push(match (note, digit) {
+                (true, Some(digit)) => Move::Note { cell, digit },
+                (_, digit) => Move::Entry {
+                    cell,
+                    digit,
+                    previous: replayed.entry(cell),
+                },
+            });
+            match digit {
+                Some(digit) if note => replayed.toggle_note(cell, digit)?,
+                Some(digit) => replayed.enter(cell, digit)?,
+                None => replayed.erase(cell)?,
+            }
+        }
+        if fields.next().is_some() {
+            return Err("saved game has too many fields");
+        }
+        Self::restore(puzzle, entries, notes, &moves)
+    }
+}
+
+/// Saved form of a session for serde, which is checked when restoring.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedSession {
+    version: u32,
+    /// Values of the puzzle, with dots for empty cells.
+    puzzle: String,
+    /// Digits entered by the player, with dots for empty cells.
+    entries: String,
+    /// Pencil marks of each cell in row-major order, as bitmask of digits.
+    notes: Vec<u16>,
+    moves: Vec<Move>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for GameSession {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SavedSession {
+            version: SAVE_VERSION,
+            puzzle: self.puzzle.to_string(),
+            entries: Puzzle {
+                cells: self.entries,
+            }
+            .to_string(),
+            notes: self.notes.iter().flatten().copied().collect(),
+            moves: self.moves.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for GameSession {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let saved = SavedSession::deserialize(deserializer)?;
+        if saved.version != SAVE_VERSION {
+            return Err(D::Error::custom("unsupported version of saved game"));
+        }
+        if saved.notes.len() != 81 || saved.notes.iter().any(|notes| *notes >= 1 << 9) {
+            return Err(D::Error::custom("invalid notes in saved game"));
+        }
+        let mut notes = [[0; 9]; 9];
+        for (idx, value) in saved.notes.into_iter().enumerate() {
+            notes[idx / 9][idx % 9] = value;
+        }
+        let puzzle = saved.puzzle.parse().map_err(D::Error::custom)?;
+        let entries = parse_values(&saved.entries).map_err(D::Error::custom)?;
+        Self::restore(puzzle, entries, notes, &saved.moves).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,140 @@
+//! Generation of random puzzles which have a unique solution, optionally of
+//! a specific difficulty or requiring a specific technique, and of packs of
+//! puzzles of increasing difficulty.
+
+use crate::incremental::Incremental;
+use crate::logic::Technique;
+use crate::random::Rng;
+use crate::rating::{self, Difficulty, Rating};
+use crate::transform::Transformation;
+use crate::{Options, Puzzle, Solver};
+use std::collections::HashSet;
+
+/// Number of distinct puzzles generated for each puzzle of a pack, among
+/// which those spreading the difficulty evenly are picked.
+const PACK_OVERSAMPLING: usize = 4;
+
+/// Generate a random completely filled grid.
+pub fn solution(rng: &mut Rng) -> Puzzle {
+    // The squares on the diagonal do not share any rows or columns, so they
+    // can be filled independently before solving for the remaining cells.
+    let mut puzzle = Puzzle { cells: [[0; 9]; 9] };
+    for sqr in 0..3 {
+        let mut values = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        rng.shuffle(&mut values);
+        for (idx, value) in values.into_iter().enumerate() {
+            puzzle.cells[3 * sqr + idx / 3][3 * sqr + idx % 3] = value;
+        }
+    }
+    let solution = Solver::solve_with_options(&puzzle, &Options::default())
+        .expect("grid with filled diagonal squares is solvable");
+    Transformation::random(rng).apply(&Puzzle {
+        cells: *solution.cells(),
+    })
+}
+
+/// Generate a random puzzle with a unique solution. Clues are removed from
+/// a random solution for as long as the solution stays unique, so no clue
+/// of the resulting puzzle can be removed.
+pub fn generate(rng: &mut Rng) -> Puzzle {
+    let solution = solution(rng);
+    let mut cells: [usize; 81] = core::array::from_fn(|idx| idx);
+    rng.shuffle(&mut cells);
+    // The clues are added in reverse order of their removal, such that the
+    // clue removed next is near the top of the incremental solver.
+    let mut puzzle = Incremental::new();
+    for idx in cells.iter().rev() {
+        let (row, col) = (idx / 9, idx % 9);
+        puzzle
+            .add_clue((row, col), solution.cells[row][col])
+            .expect("clues of a solution are consistent");
+    }
+    for idx in cells {
+        let coords = (idx / 9, idx % 9);
+        let value = puzzle.remove_clue(coords).expect("cell has a clue");
+        if puzzle.count_solutions(2) != 1 {
+            puzzle
+                .add_clue(coords, value)
+                .expect("clues of a solution are consistent");
+        }
+    }
+    puzzle.puzzle()
+}
+
+/// Generate a random puzzle with a unique solution of a specific difficulty,
+/// giving up after the given number of attempts. Puzzles of the harder
+/// difficulties are rare, so they may take many attempts.
+pub fn generate_with_difficulty(
+    rng: &mut Rng,
+    difficulty: Difficulty,
+    attempts: usize,
+) -> Result<Puzzle, &'static str> {
+    for _ in 0..attempts {
+        let puzzle = generate(rng);
+        if rating::rate(&puzzle)?.difficulty == difficulty {
+            return Ok(puzzle);
+        }
+    }
+    Err("no puzzle of the difficulty was generated")
+}
+
+/// Generate a random puzzle with a unique solution for practicing a
+/// technique: solving it logically requires the technique at least once and
+/// no harder technique. Gives up after the given number of attempts, as
+/// puzzles requiring some techniques are rare.
+pub fn generate_with_technique(
+    rng: &mut Rng,
+    technique: Technique,
+    attempts: usize,
+) -> Result<Puzzle, &'static str> {
+    for _ in 0..attempts {
+        let puzzle = generate(rng);
+        if rating::rate(&puzzle)?.hardest == Some(technique) {
+            return Ok(puzzle);
+        }
+    }
+    Err("no puzzle requiring the technique was generated")
+}
+
+/// Generate a pack of distinct puzzles with a unique solution and increasing
+/// difficulty, along with their ratings, such as for the levels of a game.
+/// Several times as many puzzles as requested are generated and sorted by
+/// difficulty and score, of which those at evenly spaced ranks are picked,
+/// such that the difficulty rises smoothly from the easiest to the hardest
+/// one. Gives up after the given number of attempts if too few distinct
+/// puzzles are found.
+pub fn generate_pack(
+    rng: &mut Rng,
+    count: usize,
+    attempts: usize,
+) -> Result<Vec<(Puzzle, Rating)>, &'static str> {
+    let too_large = "pack has too many puzzles";
+    let pool_size = count.checked_mul(PACK_OVERSAMPLING).ok_or(too_large)?;
+    let mut seen = HashSet::new();
+    let mut pool = Vec::new();
+    for _ in 0..attempts {
+        if pool.len() >= pool_size {
+            break;
+        }
+        let puzzle = generate(rng);
+        if seen.insert(puzzle.fingerprint()) {
+            pool.push((puzzle, rating::rate(&puzzle)?));
+        }
+    }
+    if pool.len() < count {
+        return Err("too few distinct puzzles were generated for the pack");
+    }
+    // The tier comes first, as scores of different tiers may overlap. Sorting
+    // is stable, and puzzles are generated randomly, so the order of equal
+    // ratings is random as well.
+    pool.sort_by_key(|(_, rating)| (rating.difficulty, rating.score));
+    (0..count)
+        .map(|idx| match count {
+            1 => Ok(pool[0]),
+            _ => idx
+                .checked_mul(pool.len() - 1)
+                .map(|rank| pool[rank / (count - 1)])
+                .ok_or(too_large),
+        })
+        .collect()
+}
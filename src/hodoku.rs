@@ -0,0 +1,201 @@
+//! Entries of Hodoku libraries, which describe a grid in which a technique
+//! applies along with its expected effects, such that the techniques of this
+//! crate can be cross-checked against Hodoku and SudokuWiki.
+//!
+//! Each entry is a line of fields separated by colons, of which the first is
+//! empty: `:<code>:<digits>:<grid>:<deleted>:<eliminations>:<placements>:`.
+//! The code identifies the technique, such as `0100` for pointing. The grid
+//! has 81 values with dots or zeroes for empty cells, of which values placed
+//! after the start are preceded by `+`. Deleted candidates, eliminations and
+//! placements are lists of candidates separated by spaces, each written as
+//! digit, row and column, such that `512` is the 5 in `r1c2`. Entries are
+//! written back in the same form, with dots for empty cells.
+
+use crate::logic::{Grid, Step, Technique};
+use crate::Puzzle;
+use std::fmt;
+use std::str::FromStr;
+
+/// Library code of a technique, if Hodoku has one. Full houses are naked
+/// singles in this crate, so they have no code of their own.
+pub fn code(technique: Technique) -> Option<&'static str> {
+    match technique {
+        Technique::NakedSingle => Some("0003"),
+        Technique::HiddenSingle => Some("0002"),
+        Technique::Pointing => Some("0100"),
+        Technique::Claiming => Some("0101"),
+        Technique::NakedPair => Some("0200"),
+        Technique::NakedTriple => Some("0201"),
+        Technique::HiddenPair => Some("0210"),
+        Technique::HiddenTriple => Some("0211"),
+        Technique::XWing => Some("0300"),
+        Technique::Swordfish => Some("0301"),
+        Technique::XYWing => Some("0800"),
+        Technique::BruteForce => None,
+    }
+}
+
+/// Technique of a library code, if it is known to this crate. Codes may
+/// carry a suffix after a hyphen distinguishing variants of an entry.
+pub fn technique(code: &str) -> Option<Technique> {
+    let code = code.split('-').next().unwrap_or_default();
+    match code {
+        "0000" => Some(Technique::NakedSingle),
+        code => Technique::ALL
+            .into_iter()
+            .find(|technique| self::code(*technique) == Some(code)),
+    }
+}
+
+/// A candidate of a cell, as written in library entries.
+pub type Candidate = ((usize, usize), u8);
+
+/// An entry of a Hodoku library.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// Library code of the technique.
+    pub code: String,
+    /// The technique, if it is known to this crate.
+    pub technique: Option<Technique>,
+    /// Digits of the pattern of the technique, as written in the library.
+    pub digits: String,
+    /// Values given at the start, without those placed after it.
+    pub givens: Puzzle,
+    /// Filled values of the grid, including those placed after the start.
+    pub puzzle: Puzzle,
+    /// Candidates which were removed before the technique applies.
+    pub deleted: Vec<Candidate>,
+    /// Candidates which the technique is expected to remove.
+    pub eliminations: Vec<Candidate>,
+    /// Values which the technique is expected to place.
+    pub placements: Vec<Candidate>,
+}
+
+/// Parse a list of candidates separated by spaces, each written as digit,
+/// row and column.
+fn candidates(field: &str) -> Result<Vec<Candidate>, &'static str> {
+    field
+        .split_whitespace()
+        .map(|candidate| match candidate.as_bytes() {
+            &[digit @ b'1'..=b'9', row @ b'1'..=b'9', col @ b'1'..=b'9'] => {
+                Ok((((row - b'1') as usize, (col - b'1') as usize), digit - b'0'))
+            }
+            _ => Err("invalid candidate in entry"),
+        })
+        .collect()
+}
+
+impl FromStr for Entry {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.trim().split(':');
+        if fields.next() != Some("") {
+            return Err("entry does not start with a colon");
+        }
+        let code = fields.next().ok_or("entry has no technique")?.trim();
+        let digits = fields.next().ok_or("entry has no digits")?;
+        let grid = fields.next().ok_or("entry has no grid")?;
+        let puzzle = grid.chars().filter(|c| *c != '+').collect::<String>();
+        // Placed values are left out of the givens, keeping only the
+        // characters of the others.
+        let mut givens = String::new();
+        let mut placed = false;
+        for c in grid.chars() {
+            match c {
+                '+' => placed = true,
+                _ if placed => {
+                    givens.push('.');
+                    placed = false;
+                }
+                c => givens.push(c),
+            }
+        }
+        Ok(Self {
+            code: code.to_string(),
+            technique: technique(code),
+            digits: digits.to_string(),
+            givens: givens.parse()?,
+            puzzle: puzzle.parse()?,
+            deleted: candidates(fields.next().unwrap_or_default())?,
+            eliminations: candidates(fields.next().unwrap_or_default())?,
+            placements: candidates(fields.next().unwrap_or_default())?,
+        })
+    }
+}
+
+/// Write a list of candidates separated by spaces, each written as digit,
+/// row and column.
+fn write_candidates(fmt: &mut fmt::Formatter, candidates: &[Candidate]) -> fmt::Result {
+    for (idx, ((row, col), digit)) in candidates.iter().enumerate() {
+        if idx > 0 {
+            write!(fmt, " ")?;
+        }
+        write!(fmt, "{}{}{}", digit, row + 1, col + 1)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Entry {
+    /// Write the entry as a line of a library, without a line break.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, ":{}:{}:", self.code, self.digits)?;
+        let givens = self.givens.cells().iter().flatten();
+        for (given, value) in givens.zip(self.puzzle.cells().iter().flatten()) {
+            match (given, value) {
+                (_, 0) => write!(fmt, ".")?,
+                (0, value) => write!(fmt, "+{}", value)?,
+                (_, value) => write!(fmt, "{}", value)?,
+            }
+        }
+        write!(fmt, ":")?;
+        write_candidates(fmt, &self.deleted)?;
+        write!(fmt, ":")?;
+        write_candidates(fmt, &self.eliminations)?;
+        write!(fmt, ":")?;
+        write_candidates(fmt, &self.placements)?;
+        write!(fmt, ":")
+    }
+}
+
+impl Entry {
+    /// The grid in which the technique applies, which is the puzzle with the
+    /// deleted candidates removed.
+    pub fn grid(&self) -> Result<Grid, &'static str> {
+        let mut grid = Grid::new(&self.puzzle)?;
+        for (coords, value) in &self.deleted {
+            grid.eliminate(*coords, *value);
+        }
+        Ok(grid)
+    }
+
+    /// Whether a step has exactly the expected effects.
+    pub fn matches(&self, step: &Step) -> bool {
+        let sorted = |candidates: &[Candidate]| {
+            let mut candidates = candidates.to_vec();
+            candidates.sort();
+            candidates
+        };
+        sorted(&step.eliminations) == sorted(&self.eliminations)
+            && sorted(&step.placements) == sorted(&self.placements)
+    }
+
+    /// Find the step of the technique with the expected effects. A technique
+    /// may apply in several places, so steps with other effects are taken
+    /// until the expected one is found. Otherwise, the first step found is
+    /// returned for comparison, if any.
+    pub fn find(&self) -> Option<Step> {
+        let mut grid = self.grid().ok()?;
+        let mut first = None;
+        while let Some(step) = grid.find(self.technique?) {
+            if self.matches(&step) {
+                return Some(step);
+            }
+            if grid.apply(&step).is_err() {
+                break;
+            }
+            first.get_or_insert(step);
+        }
+        first
+    }
+}
@@ -0,0 +1,123 @@
+//! Solving of a puzzle which changes clue by clue, as when generating or
+//! minimizing puzzles. The propagated state of the clues is kept between
+//! changes, such that adding a clue only propagates that clue instead of
+//! loading the whole puzzle again.
+
+use crate::stack::Stack;
+use crate::{check_cell, Options, Puzzle, Solution, Solver};
+
+/// A clue along with the length of the trail before it was added.
+#[derive(Clone, Copy, Debug)]
+struct Clue {
+    coords: (usize, usize),
+    value: u8,
+    mark: usize,
+}
+
+/// A puzzle to which clues are added and from which they are removed, while
+/// keeping the state of the solver. Clues are kept in the order they were
+/// added, such that removing the most recently added clue only reverts its
+/// changes. Removing an earlier clue reverts the clues after it as well and
+/// adds them again, so clues which are likely to be removed are best added
+/// last.
+#[derive(Clone, Debug, Default)]
+pub struct Incremental {
+    solver: Solver,
+    clues: Stack<Clue, { 9 * 9 }>,
+}
+
+impl Incremental {
+    /// Create an empty puzzle.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a puzzle with the clues of a puzzle added in order of rows.
+    pub fn from_puzzle(puzzle: &Puzzle) -> Result<Self, &'static str> {
+        let mut incremental = Self::new();
+        for (row, values) in puzzle.cells.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                if *value != 0 {
+                    incremental.add_clue((row, col), *value)?;
+                }
+            }
+        }
+        Ok(incremental)
+    }
+
+    /// The clues as a puzzle.
+    pub fn puzzle(&self) -> Puzzle {
+        let mut puzzle = Puzzle { cells: [[0; 9]; 9] };
+        for clue in self.clues.as_slice() {
+            puzzle.cells[clue.coords.0][clue.coords.1] = clue.value;
+        }
+        puzzle
+    }
+
+    /// Add a clue to an empty cell, propagating it. If the clue contradicts
+    /// the other clues, it is not added and an error is returned.
+    pub fn add_clue(&mut self, coords: (usize, usize), value: u8) -> Result<(), &'static str> {
+        if !(1..=9).contains(&value) {
+            return Err("value must be from 1 to 9");
+        }
+        check_cell(coords)?;
+        if self
+            .clues
+            .as_slice()
+            .iter()
+            .any(|clue| clue.coords == coords)
+        {
+            return Err("cell already has a clue");
+        }
+        let mark = self.solver.trail.len();
+        if let Err(err) = self.solver.fill(coords, value) {
+            self.solver.undo(mark);
+            return Err(err);
+        }
+        self.clues.push(Clue {
+            coords,
+            value,
+            mark,
+        });
+        Ok(())
+    }
+
+    /// Remove the clue of a cell, returning its value. The clues added after
+    /// it are added again in the same order.
+    pub fn remove_clue(&mut self, coords: (usize, usize)) -> Result<u8, &'static str> {
+        let clues = self.clues.as_slice();
+        let idx = clues
+            .iter()
+            .position(|clue| clue.coords == coords)
+            .ok_or("cell has no clue")?;
+        let removed = clues[idx];
+        let mut later: Stack<Clue, { 9 * 9 }> = Default::default();
+        while self.clues.len() > idx + 1 {
+            later.push(self.clues.pop().unwrap());
+        }
+        self.clues.pop();
+        self.solver.undo(removed.mark);
+        while let Some(clue) = later.pop() {
+            // A subset of consistent clues cannot contradict each other.
+            self.add_clue(clue.coords, clue.value)?;
+        }
+        Ok(removed.value)
+    }
+
+    /// Count the solutions of the puzzle, stopping once the limit is reached,
+    /// after which the state of the clues is restored.
+    pub fn count_solutions(&mut self, limit: usize) -> usize {
+        let mark = self.solver.trail.len();
+        let count = self.solver.count(limit);
+        self.solver.undo(mark);
+        self.solver.brute_force_fills = 0;
+        self.solver.guesses = 0;
+        count
+    }
+
+    /// Solve the puzzle, searching for its solution as configured by the
+    /// options, without changing the state of the clues.
+    pub fn solve(&self, options: &Options) -> Result<Solution, &'static str> {
+        self.solver.clone().run(options)
+    }
+}
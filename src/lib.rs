@@ -1,7 +1,104 @@
-use std::char;
-use std::collections::HashSet;
-use std::fmt;
-use std::mem;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "rater")]
+pub mod analysis;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+#[cfg(feature = "std")]
+pub mod bench;
+pub mod binary;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod cell_set;
+pub mod constant;
+#[cfg(feature = "datasets")]
+pub mod datasets;
+#[cfg(feature = "std")]
+pub mod decisions;
+#[cfg(feature = "rater")]
+pub mod game;
+#[cfg(feature = "generator")]
+pub mod generate;
+#[cfg(feature = "techniques")]
+pub mod hodoku;
+pub mod incremental;
+pub mod lockstep;
+#[cfg(feature = "techniques")]
+pub mod logic;
+#[cfg(feature = "techniques")]
+pub mod moves;
+#[cfg(feature = "std")]
+pub mod pool;
+#[cfg(feature = "std")]
+pub mod portfolio;
+pub mod random;
+#[cfg(feature = "rater")]
+pub mod rating;
+#[cfg(feature = "std")]
+pub mod render;
+mod singles;
+pub mod snapshot;
+mod stack;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub mod testing;
+pub mod transform;
+#[cfg(feature = "variants")]
+pub mod variants;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use cell_set::{CellSet, HOUSES, PEERS};
+use core::fmt;
+use core::str::FromStr;
+use core::sync::atomic::{AtomicBool, Ordering};
+use singles::Singles;
+use snapshot::{Contradiction, Snapshot};
+use stack::Stack;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Create a [`Puzzle`] from a string literal of 81 values and dots ('.') or
+/// zeroes for empty cells, which is checked at compile time, such that typos
+/// in test fixtures fail the build. Values occurring twice in a row, column
+/// or square are rejected as well. Whitespace is skipped, such that the rows
+/// of a grid can be written on separate lines:
+///
+/// ```
+/// use sudoku_solver::{sudoku, Puzzle};
+///
+/// const PUZZLE: Puzzle = sudoku!(
+///     "53..7....
+///      6..195...
+///      .98....6.
+///      8...6...3
+///      4..8.3..1
+///      7...2...6
+///      .6....28.
+///      ...419..5
+///      ....8..79"
+/// );
+/// assert_eq!(PUZZLE.clues(), 30);
+/// ```
+///
+/// A literal which is not a valid puzzle does not compile:
+///
+/// ```compile_fail
+/// let puzzle = sudoku_solver::sudoku!("55..7....");
+/// ```
+#[macro_export]
+macro_rules! sudoku {
+    ($literal:expr) => {{
+        const PUZZLE: $crate::Puzzle = match $crate::Puzzle::from_literal($literal) {
+            Ok(puzzle) => match $crate::constant::check(&puzzle) {
+                Ok(()) => puzzle,
+                Err(err) => panic!("{}", err),
+            },
+            Err(err) => panic!("{}", err),
+        };
+        PUZZLE
+    }};
+}
 
 /// Convert the coordinates of a cell in a sudoku grid to the coordinates
 /// of the square it is located in.
@@ -9,32 +106,188 @@ fn cell_to_square(coords: (usize, usize)) -> (usize, usize) {
     (coords.0 / 3, coords.1 / 3)
 }
 
+/// Convert the coordinates of a cell in a sudoku grid to the indices of the
+/// row, column and square it is located in, as used for indexing houses.
+fn cell_to_houses(coords: (usize, usize)) -> [usize; 3] {
+    let square = cell_to_square(coords);
+    [coords.0, 9 + coords.1, 18 + 3 * square.0 + square.1]
+}
+
 /// Convert the index of an element in the range 0..9 to the corresponding
 /// coordinates in a 3x3 dimensional grid.
 fn index_to_3x3_coords(idx: usize) -> (usize, usize) {
     (idx / 3, idx % 3)
 }
 
+/// A sudoku puzzle of which the empty cells are represented by zeroes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Puzzle {
+    cells: [[u8; 9]; 9],
+}
+
+impl Puzzle {
+    /// Parse a puzzle represented by 81 values and dots ('.') or zeroes for
+    /// non-filled cells.
+    fn from_chars(chars: impl IntoIterator<Item = char>) -> Result<Self, &'static str> {
+        let mut cells = [[0; 9]; 9];
+        let mut len = 0;
+        for (idx, c) in chars.into_iter().enumerate() {
+            if idx >= 9 * 9 {
+                return Err("invalid puzzle size");
+            }
+            match c.to_digit(10) {
+                Some(value @ 1..=9) => cells[idx / 9][idx % 9] = value as u8,
+                Some(0) => {}
+                _ if c == '.' => {}
+                _ => return Err("invalid character in puzzle"),
+            }
+            len += 1;
+        }
+        if len != 9 * 9 {
+            return Err("invalid puzzle size");
+        }
+        Ok(Self { cells })
+    }
+
+    /// Create a puzzle from the values of its grid, of which empty cells are
+    /// zero. Usable in `const` context.
+    pub const fn from_cells(cells: [[u8; 9]; 9]) -> Result<Self, &'static str> {
+        let mut idx = 0;
+        while idx < 9 * 9 {
+            if cells[idx / 9][idx % 9] > 9 {
+                return Err("invalid value in puzzle");
+            }
+            idx += 1;
+        }
+        Ok(Self { cells })
+    }
+
+    /// Parse a puzzle like [`FromStr`], but usable in `const` context, as
+    /// done by [`sudoku!`]. Whitespace is skipped, such that the rows of a
+    /// grid can be written on separate lines.
+    pub const fn from_literal(literal: &str) -> Result<Self, &'static str> {
+        let bytes = literal.as_bytes();
+        let mut cells = [[0; 9]; 9];
+        let mut len = 0;
+        let mut idx = 0;
+        while idx < bytes.len() {
+            let byte = bytes[idx];
+            idx += 1;
+            if byte.is_ascii_whitespace() {
+                continue;
+            }
+            if len >= 9 * 9 {
+                return Err("invalid puzzle size");
+            }
+            match byte {
+                b'1'..=b'9' => cells[len / 9][len % 9] = byte - b'0',
+                b'0' | b'.' => {}
+                _ => return Err("invalid character in puzzle"),
+            }
+            len += 1;
+        }
+        if len != 9 * 9 {
+            return Err("invalid puzzle size");
+        }
+        Ok(Self { cells })
+    }
+
+    /// The values of the grid, of which empty cells are zero.
+    pub const fn cells(&self) -> &[[u8; 9]; 9] {
+        &self.cells
+    }
+
+    /// Number of filled cells, which are the clues of the puzzle.
+    pub fn clues(&self) -> usize {
+        self.cells
+            .iter()
+            .flatten()
+            .filter(|value| **value != 0)
+            .count()
+    }
+
+    /// A 128-bit fingerprint of the puzzle, for detecting duplicates among
+    /// large collections without keeping all puzzles in memory. Distinct
+    /// puzzles have the same fingerprint with negligible probability.
+    pub fn fingerprint(&self) -> u128 {
+        // Finalizer of SplitMix64, which is a bijection with good avalanche.
+        fn mix(mut z: u64) -> u64 {
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }
+
+        // Pack the values into four bits each, then hash the packed words
+        // into two independent lanes.
+        let mut words = [0u64; 6];
+        for (idx, value) in self.cells.iter().flatten().enumerate() {
+            words[idx / 16] |= (*value as u64) << (4 * (idx % 16));
+        }
+        let (mut high, mut low) = (0x243f_6a88_85a3_08d3, 0x1319_8a2e_0370_7344);
+        for word in words {
+            high = mix(high ^ word);
+            low = mix(low ^ word.rotate_left(32)).wrapping_add(high);
+        }
+        (high as u128) << 64 | low as u128
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<Vec<char>> for Puzzle {
+    type Error = &'static str;
+
+    /// Parse a puzzle represented by a 81 length vector of values
+    /// and dots ('.') or zeroes for non-filled cells.
+    fn try_from(puzzle: Vec<char>) -> Result<Self, Self::Error> {
+        Self::from_chars(puzzle)
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_chars(s.chars())
+    }
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for value in self.cells.iter().flatten() {
+            match value {
+                0 => write!(fmt, ".")?,
+                v => write!(fmt, "{}", v)?,
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct Solution {
     cells: [[u8; 9]; 9],
     pub brute_forces: u8,
+    /// Number of candidates filled in by guessing, including those which
+    /// were reverted later.
+    pub guesses: u32,
+    /// Number of guesses which were reverted because they did not lead to a
+    /// solution.
+    pub backtracks: u32,
 }
 
 impl fmt::Display for Solution {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         for (row_idx, row) in self.cells.iter().enumerate() {
             if row_idx % 3 == 0 {
-                write!(fmt, "+-------+-------+-------+\n")?;
+                writeln!(fmt, "+-------+-------+-------+")?;
             }
             for (col_idx, value) in row.iter().enumerate() {
                 if col_idx % 3 == 0 {
                     write!(fmt, "| ")?;
                 }
-                write!(fmt, "{}", value.to_string())?;
-                write!(fmt, " ")?;
+                write!(fmt, "{} ", value)?;
             }
-            write!(fmt, "|\n")?;
+            writeln!(fmt, "|")?;
         }
         write!(fmt, "+-------+-------+-------+")?;
         Ok(())
@@ -42,121 +295,622 @@ impl fmt::Display for Solution {
 }
 
 impl Solution {
+    /// The values of the solved grid.
+    pub const fn cells(&self) -> &[[u8; 9]; 9] {
+        &self.cells
+    }
+
+    /// Check that this is a solution of a puzzle independently of the solver,
+    /// such that changes to the solver cannot go unnoticed: every given of
+    /// the puzzle must be kept, and every row, column and square must hold
+    /// each value exactly once.
+    pub fn verify(&self, puzzle: &Puzzle) -> Result<(), &'static str> {
+        for (given, value) in puzzle
+            .cells
+            .iter()
+            .flatten()
+            .zip(self.cells.iter().flatten())
+        {
+            if *given != 0 && given != value {
+                return Err("solution does not keep the givens");
+            }
+        }
+        for house in 0..9 {
+            let (mut row, mut col, mut square) = (0u16, 0u16, 0u16);
+            for idx in 0..9 {
+                row |= 1 << self.cells[house][idx];
+                col |= 1 << self.cells[idx][house];
+                square |= 1 << self.cells[house / 3 * 3 + idx / 3][house % 3 * 3 + idx % 3];
+            }
+            // Bit zero is set by empty cells, and the others by the values.
+            let all = 0b11_1111_1110;
+            if row != all {
+                return Err("solution has a row without every value");
+            }
+            if col != all {
+                return Err("solution has a column without every value");
+            }
+            if square != all {
+                return Err("solution has a square without every value");
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
     pub fn row_representation(&self) -> String {
         self.cells
             .iter()
-            .flat_map(|row| row.map(|cell| char::from_digit(cell.into(), 10).unwrap()))
+            .flat_map(|row| row.map(|cell| char::from(b'0' + cell)))
             .collect()
     }
 }
 
-#[derive(Clone, Debug)]
+impl From<&Solution> for Puzzle {
+    /// The solved grid as a puzzle without empty cells.
+    fn from(solution: &Solution) -> Self {
+        Puzzle {
+            cells: solution.cells,
+        }
+    }
+}
+
+/// Check that the coordinates of a cell are within the grid.
+pub(crate) fn check_cell(cell: (usize, usize)) -> Result<(), &'static str> {
+    match cell.0 < 9 && cell.1 < 9 {
+        true => Ok(()),
+        false => Err("cell is outside of the grid"),
+    }
+}
+
+/// Check that a digit is in the range 1 to 9.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub(crate) fn check_digit(digit: u8) -> Result<(), &'static str> {
+    match (1..=9).contains(&digit) {
+        true => Ok(()),
+        false => Err("digit is not between 1 and 9"),
+    }
+}
+
+/// Set of candidate values of a cell, stored as a bitmask in which bit `n - 1`
+/// is set if value `n` is a candidate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Candidates(u16);
+
+impl Default for Candidates {
+    fn default() -> Self {
+        Self(0b1_1111_1111)
+    }
+}
+
+impl Candidates {
+    /// Number of candidates in the set.
+    fn len(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Whether a value is a candidate.
+    fn contains(self, value: u8) -> bool {
+        self.0 & (1 << (value - 1)) != 0
+    }
+
+    /// Add a value to the set.
+    fn insert(&mut self, value: u8) {
+        self.0 |= 1 << (value - 1);
+    }
+
+    /// Remove a value from the set, returning whether it was present.
+    fn remove(&mut self, value: u8) -> bool {
+        let present = self.contains(value);
+        self.0 &= !(1 << (value - 1));
+        present
+    }
+
+    /// The lowest candidate in the set, if any.
+    fn first(self) -> Option<u8> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.0.trailing_zeros() as u8 + 1)
+        }
+    }
+
+    /// Iterate over the candidates in ascending order.
+    fn iter(self) -> impl Iterator<Item = u8> {
+        let mut bits = self;
+        core::iter::from_fn(move || {
+            let value = bits.first()?;
+            bits.0 &= bits.0 - 1;
+            Some(value)
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 enum Cell {
     Value(u8),
-    Candidates(HashSet<u8>),
+    Candidates(Candidates),
 }
 
 impl Default for Cell {
     fn default() -> Self {
-        Self::Candidates([1, 2, 3, 4, 5, 6, 7, 8, 9].iter().cloned().collect())
+        Self::Candidates(Default::default())
     }
 }
 
-#[derive(Debug)]
-enum Group {
-    All,
-    Row,
-    Column,
-    Square,
-    None,
+/// Options for tuning how the solver searches for a solution.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Explore the candidates of the first brute-force guess in parallel,
+    /// returning the first branch which leads to a solution. This trades CPU
+    /// time for latency on very hard puzzles.
+    #[cfg(feature = "parallel")]
+    pub parallel: bool,
+    /// Maximum number of guesses made while brute-forcing, after which
+    /// solving fails with [`NODE_LIMIT_EXCEEDED`].
+    pub max_guesses: Option<u32>,
+    /// Maximum time spent brute-forcing, after which solving fails with
+    /// [`TIME_LIMIT_EXCEEDED`].
+    #[cfg(feature = "std")]
+    pub timeout: Option<core::time::Duration>,
 }
 
-#[derive(Clone, Debug)]
-struct Occurrences<T> {
-    row: [[T; 9]; 9],
-    col: [[T; 9]; 9],
-    sqr: [[[T; 9]; 3]; 3],
+/// Number of puzzles of a batch solved together on a thread.
+#[cfg(feature = "parallel")]
+const BATCH_CHUNK: usize = 16 * lockstep::LANES;
+
+/// Error of solving when the maximum number of guesses is exceeded.
+pub const NODE_LIMIT_EXCEEDED: &str = "node limit exceeded";
+
+/// Error of solving when the timeout is exceeded.
+pub const TIME_LIMIT_EXCEEDED: &str = "time limit exceeded";
+
+/// Error of a puzzle of a batch which was not started before the time budget
+/// of the batch ran out.
+pub const SKIPPED: &str = "skipped as time budget ran out";
+
+/// Limits on the search of a single solve, derived from the options.
+#[derive(Clone, Copy, Debug)]
+struct Budget {
+    max_guesses: u32,
+    #[cfg(feature = "std")]
+    deadline: Option<std::time::Instant>,
 }
 
-impl Default for Occurrences<u8> {
-    fn default() -> Self {
+impl Budget {
+    fn new(options: &Options) -> Self {
         Self {
-            row: [[9; 9]; 9],
-            col: [[9; 9]; 9],
-            sqr: [[[9; 9]; 3]; 3],
+            max_guesses: options.max_guesses.unwrap_or(u32::MAX),
+            #[cfg(feature = "std")]
+            deadline: options
+                .timeout
+                .map(|timeout| std::time::Instant::now() + timeout),
         }
     }
-}
 
-impl Default for Occurrences<bool> {
-    fn default() -> Self {
-        Self {
-            row: [[false; 9]; 9],
-            col: [[false; 9]; 9],
-            sqr: [[[false; 9]; 3]; 3],
+    /// Fail if the number of guesses or the time spent exceeds the budget.
+    fn check(&self, guesses: u32) -> Result<(), &'static str> {
+        if guesses > self.max_guesses {
+            return Err(NODE_LIMIT_EXCEEDED);
+        }
+        #[cfg(feature = "std")]
+        if self
+            .deadline
+            .is_some_and(|deadline| std::time::Instant::now() >= deadline)
+        {
+            return Err(TIME_LIMIT_EXCEEDED);
         }
+        Ok(())
     }
 }
 
+/// A reversible change to the state of the solver. Changes are recorded on a
+/// trail so that guesses made during brute-forcing can be undone in place.
+/// Coordinates are stored compactly to keep the trail small.
+#[derive(Clone, Copy, Debug)]
+enum Change {
+    /// A value was filled into a cell which formerly had these candidates.
+    Fill((u8, u8), u8, Candidates),
+    /// A candidate was removed from a cell.
+    RemoveCandidate((u8, u8), u8),
+}
+
+/// Upper bound on the number of changes on the trail: every cell is filled
+/// at most once, and every candidate of every cell is removed at most once.
+const TRAIL_CAPACITY: usize = 9 * 9 + 9 * 9 * 9;
+
+/// Compact coordinates for storing on the trail.
+fn compact(coords: (usize, usize)) -> (u8, u8) {
+    (coords.0 as u8, coords.1 as u8)
+}
+
+/// Expand coordinates stored on the trail.
+fn expand(coords: (u8, u8)) -> (usize, usize) {
+    (coords.0 as usize, coords.1 as usize)
+}
+
+/// Observer of the guesses made while brute-forcing, which is told of every
+/// candidate filled in by guessing, at the depth of the guess starting from
+/// one, and whether filling it was consistent. Regular solving observes
+/// nothing, which compiles away.
+pub(crate) trait Observe {
+    fn guess(&mut self, depth: usize, coords: (usize, usize), value: u8, consistent: bool);
+}
+
+impl Observe for () {
+    fn guess(&mut self, _: usize, _: (usize, usize), _: u8, _: bool) {}
+}
+
+/// A guess made while brute-forcing, along with the candidates of the cell
+/// that have not been tried yet and the trail length before the guess.
+#[derive(Clone, Copy, Debug)]
+struct Guess {
+    coords: (usize, usize),
+    remaining: Candidates,
+    mark: usize,
+}
+
 #[derive(Clone, Debug)]
 pub struct Solver {
     cells: [[Cell; 9]; 9],
-    value_occurrences: Occurrences<bool>,
-    candidate_occurrences: Occurrences<u8>,
+    /// Bitmasks of the values filled into each row, column and square.
+    house_values: [u16; 27],
+    /// The cells which have each value as a candidate.
+    candidate_cells: [CellSet; 9],
     unfilled_cells: u8,
     brute_force_fills: u8,
+    guesses: u32,
+    trail: Stack<Change, TRAIL_CAPACITY>,
 }
 
 impl Default for Solver {
     fn default() -> Self {
         Self {
             cells: Default::default(),
-            value_occurrences: Default::default(),
-            candidate_occurrences: Default::default(),
+            house_values: [0; 27],
+            candidate_cells: [CellSet::ALL; 9],
             unfilled_cells: 9 * 9,
             brute_force_fills: 0,
+            guesses: 0,
+            trail: Default::default(),
         }
     }
 }
 
 impl Solver {
     /// Load a puzzle represented by a 81 length vector of values
-    /// and dots ('.') for non-filled cells.
+    /// and dots ('.') or zeroes for non-filled cells.
+    #[cfg(feature = "std")]
     pub fn solve(puzzle: Vec<char>) -> Result<Solution, &'static str> {
-        if puzzle.len() != 9 * 9 {
-            return Err("invalid puzzle size");
+        Self::solve_with_options(&Puzzle::try_from(puzzle)?, &Default::default())
+    }
+
+    /// Solve a puzzle, searching for its solution as configured by the options.
+    /// Solving is deterministic: the same puzzle always results in the same
+    /// solution and number of brute-force fills, regardless of the options.
+    /// Only the number of guesses and backtracks differ when exploring in
+    /// parallel, as they then only count those of the successful branch.
+    pub fn solve_with_options(
+        puzzle: &Puzzle,
+        options: &Options,
+    ) -> Result<Solution, &'static str> {
+        let mut solver = Self::new();
+        solver.load(puzzle)?;
+        solver.run(options)
+    }
+
+    /// Solve a puzzle like [`Solver::solve_with_options`], attaching a
+    /// snapshot of the grid to the error if solving fails. The snapshot is of
+    /// the point where a contradiction was found while loading the puzzle,
+    /// such as a cell without candidates, or, if the search was exhausted,
+    /// of the propagated puzzle in which every guess leads to one. If a limit
+    /// of the options was exceeded, it is of the point where the search
+    /// stopped.
+    // The snapshot is returned by value, as it cannot be boxed without `std`.
+    #[allow(clippy::result_large_err)]
+    pub fn solve_with_snapshot(
+        puzzle: &Puzzle,
+        options: &Options,
+    ) -> Result<Solution, Contradiction> {
+        let mut solver = Self::new();
+        solver
+            .load(puzzle)
+            .and_then(|_| solver.run(options))
+            .map_err(|error| Contradiction {
+                error,
+                snapshot: solver.snapshot(),
+            })
+    }
+
+    /// Create a solver with an empty grid.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Reset the solver to an empty grid in place, such that it can be reused
+    /// for another puzzle.
+    pub fn reset(&mut self) {
+        self.cells = Default::default();
+        self.house_values = [0; 27];
+        self.candidate_cells = [CellSet::ALL; 9];
+        self.unfilled_cells = 9 * 9;
+        self.brute_force_fills = 0;
+        self.guesses = 0;
+        self.trail.clear();
+    }
+
+    /// Reset the solver and load in the values supplied by a puzzle. The
+    /// givens are placed without maintaining any candidates, after which the
+    /// candidates of the empty cells are derived at once from the values in
+    /// their houses, and only then are the singles filled in. This skips
+    /// removing candidates from cells which turn out to be given, which makes
+    /// loading puzzles with many givens considerably cheaper.
+    pub fn load(&mut self, puzzle: &Puzzle) -> Result<(), &'static str> {
+        self.reset();
+        // Most puzzles are solved by singles alone, which is done without
+        // maintaining the candidates at all. Others continue from the cells
+        // filled by the singles, while contradictions are left to be found
+        // again below, for reporting them as usual.
+        let stuck;
+        let puzzle = match singles::solve(puzzle) {
+            Singles::Solved(solution) => {
+                self.cells = solution.map(|row| row.map(Cell::Value));
+                self.house_values = [Candidates::default().0; 27];
+                self.candidate_cells = [CellSet::EMPTY; 9];
+                self.unfilled_cells = 0;
+                return Ok(());
+            }
+            Singles::Stuck(cells) => {
+                stuck = Puzzle { cells };
+                &stuck
+            }
+            Singles::Contradiction => puzzle,
+        };
+        let conflicts = [
+            "fill results in row conflict",
+            "fill results in column conflict",
+            "fill results in square conflict",
+        ];
+        for (row, values) in puzzle.cells.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                if *value == 0 {
+                    continue;
+                }
+                let bit = 1 << (value - 1);
+                let houses = cell_to_houses((row, col));
+                for (house, conflict) in houses.into_iter().zip(conflicts) {
+                    if self.house_values[house] & bit != 0 {
+                        return Err(conflict);
+                    }
+                }
+                for house in houses {
+                    self.house_values[house] |= bit;
+                }
+                self.cells[row][col] = Cell::Value(*value);
+                self.unfilled_cells -= 1;
+            }
         }
 
-        // Load in values supplied by the puzzle.
-        let mut grid: Solver = Default::default();
-        for (idx, c) in puzzle.iter().enumerate() {
-            if let Some(value) = c.to_digit(10) {
-                grid.fill((idx / 9, idx % 9), value as u8)?;
-            } else if *c != '.' {
-                return Err("invalid character in puzzle");
+        // The candidates of the empty cells are the values which are in none
+        // of their houses.
+        self.candidate_cells = [CellSet::EMPTY; 9];
+        for row in 0..9 {
+            for col in 0..9 {
+                if let Cell::Candidates(cs) = &mut self.cells[row][col] {
+                    let [r, c, s] = cell_to_houses((row, col));
+                    let filled = self.house_values[r] | self.house_values[c] | self.house_values[s];
+                    *cs = Candidates(Candidates::default().0 & !filled);
+                    for candidate in cs.iter() {
+                        self.candidate_cells[candidate as usize - 1].insert((row, col));
+                    }
+                }
+            }
+        }
+
+        // Fill in the naked and hidden singles, which propagates the values
+        // further as usual.
+        for row in 0..9 {
+            for col in 0..9 {
+                if let Cell::Candidates(cs) = self.cells[row][col] {
+                    match cs.first() {
+                        None => return Err("no candidate left for cell"),
+                        Some(single) if cs.len() == 1 => self.fill((row, col), single)?,
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+        for value in 1..=9 {
+            for (house, cells) in HOUSES.iter().enumerate() {
+                if self.house_values[house] & (1 << (value - 1)) != 0 {
+                    continue;
+                }
+                let cells = self.candidate_cells[value as usize - 1].intersection(*cells);
+                match cells.first() {
+                    None => return Err("no cell left for value"),
+                    Some(single) if cells.len() == 1 => self.fill(single, value)?,
+                    Some(_) => {}
+                }
             }
         }
+        Ok(())
+    }
 
+    /// Solve the loaded puzzle, searching for its solution as configured by
+    /// the options.
+    pub fn run(&mut self, options: &Options) -> Result<Solution, &'static str> {
         // Brute-force any remaining unfilled cells.
-        let brute_force = grid.unfilled_cells > 0;
+        let brute_force = self.unfilled_cells > 0;
         if brute_force {
-            grid = grid.brute_force()?;
+            let budget = Budget::new(options);
+            #[cfg(feature = "parallel")]
+            if options.parallel {
+                *self = self.brute_force_parallel(&budget)?;
+            } else {
+                self.brute_force(&AtomicBool::new(false), &budget, &mut ())?;
+            }
+            #[cfg(not(feature = "parallel"))]
+            self.brute_force(&AtomicBool::new(false), &budget, &mut ())?;
         }
 
-        Ok(Solution {
-            cells: grid.cells.map(|row| {
+        Ok(self.solution())
+    }
+
+    /// The values and candidates of the grid in its current state.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            values: self.cells.map(|row| {
                 row.map(|cell| match cell {
                     Cell::Value(v) => v,
                     Cell::Candidates(_) => 0,
                 })
             }),
-            brute_forces: grid.brute_force_fills,
-        })
+            candidates: self.cells.map(|row| {
+                row.map(|cell| match cell {
+                    Cell::Value(_) => 0,
+                    Cell::Candidates(cs) => cs.0,
+                })
+            }),
+        }
+    }
+
+    /// The filled values of the grid as solution, along with the statistics
+    /// of the search.
+    fn solution(&self) -> Solution {
+        Solution {
+            cells: self.cells.map(|row| {
+                row.map(|cell| match cell {
+                    Cell::Value(v) => v,
+                    Cell::Candidates(_) => 0,
+                })
+            }),
+            brute_forces: self.brute_force_fills,
+            guesses: self.guesses,
+            backtracks: self.guesses.saturating_sub(self.brute_force_fills.into()),
+        }
+    }
+
+    /// Count the solutions of a puzzle, stopping once the limit is reached.
+    /// A puzzle is uniquely solvable if this returns one for a limit of two.
+    pub fn count_solutions(puzzle: &Puzzle, limit: usize) -> usize {
+        let mut solver = Self::new();
+        match solver.load(puzzle) {
+            Ok(()) => solver.count(limit),
+            Err(_) => 0,
+        }
+    }
+
+    /// Count the solutions of the loaded puzzle, stopping once the limit is
+    /// reached. Every branch of the search is explored instead of stopping at
+    /// the first solution, which leaves the grid in an unspecified state.
+    pub fn count(&mut self, limit: usize) -> usize {
+        self.for_each_solution(limit, |_| {})
+    }
+
+    /// Count the solutions of the loaded puzzle like [`Solver::count`],
+    /// calling a closure with the values of each solution found.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn for_each_solution(
+        &mut self,
+        limit: usize,
+        mut found: impl FnMut(&[[u8; 9]; 9]),
+    ) -> usize {
+        let mut stack: Stack<Guess, { 9 * 9 }> = Default::default();
+        let mut count = 0;
+        while count < limit {
+            if self.unfilled_cells == 0 {
+                found(&self.solution().cells);
+                count += 1;
+                if count == limit || self.next_guess(&mut stack, &mut ()).is_err() {
+                    break;
+                }
+                continue;
+            }
+            let Some(coords) = self.highest_entropy_cell() else {
+                break;
+            };
+            if let Cell::Candidates(cs) = self.cells[coords.0][coords.1] {
+                stack.push(Guess {
+                    coords,
+                    remaining: cs,
+                    mark: self.trail.len(),
+                });
+            }
+            if self.next_guess(&mut stack, &mut ()).is_err() {
+                break;
+            }
+        }
+        count
+    }
+
+    /// Solve many puzzles across a thread pool, returning the results in the
+    /// same order as the puzzles.
+    #[cfg(feature = "parallel")]
+    pub fn solve_batch(
+        puzzles: impl IntoIterator<Item = Puzzle>,
+    ) -> Vec<Result<Solution, &'static str>> {
+        Self::solve_batch_with_options(puzzles, &Default::default())
+    }
+
+    /// Solve many puzzles like [`Solver::solve_batch`], searching for their
+    /// solutions as configured by the options.
+    #[cfg(feature = "parallel")]
+    pub fn solve_batch_with_options(
+        puzzles: impl IntoIterator<Item = Puzzle>,
+        options: &Options,
+    ) -> Vec<Result<Solution, &'static str>> {
+        // Chunks of puzzles are solved using lockstep solving where it pays
+        // off, which is the case for most puzzles of typical collections.
+        puzzles
+            .into_iter()
+            .collect::<Vec<Puzzle>>()
+            .par_chunks(BATCH_CHUNK)
+            .flat_map_iter(|chunk| lockstep::solve_batch(chunk, options))
+            .collect()
+    }
+
+    /// Solve many puzzles within a total time budget, returning the results
+    /// in the same order as the puzzles, such that latency-bound services can
+    /// return partial results instead of missing their deadline. Puzzles
+    /// which are still being solved when the budget runs out fail with
+    /// [`TIME_LIMIT_EXCEEDED`], and those which were not started yet fail
+    /// with [`SKIPPED`]. A timeout in the options still applies to each
+    /// puzzle. With the `parallel` feature, the puzzles are solved across a
+    /// thread pool.
+    #[cfg(feature = "std")]
+    pub fn solve_batch_within(
+        puzzles: impl IntoIterator<Item = Puzzle>,
+        options: &Options,
+        budget: core::time::Duration,
+    ) -> Vec<Result<Solution, &'static str>> {
+        let deadline = std::time::Instant::now() + budget;
+        let solve = |solver: &mut Self, puzzle: &Puzzle| {
+            let remaining = deadline
+                .checked_duration_since(std::time::Instant::now())
+                .filter(|remaining| !remaining.is_zero())
+                .ok_or(SKIPPED)?;
+            let options = Options {
+                timeout: Some(options.timeout.map_or(remaining, |t| t.min(remaining))),
+                ..options.clone()
+            };
+            solver.load(puzzle)?;
+            solver.run(&options)
+        };
+        let puzzles: Vec<Puzzle> = puzzles.into_iter().collect();
+        #[cfg(feature = "parallel")]
+        return puzzles.par_iter().map_init(Self::new, solve).collect();
+        #[cfg(not(feature = "parallel"))]
+        {
+            let mut solver = Self::new();
+            puzzles
+                .iter()
+                .map(|puzzle| solve(&mut solver, puzzle))
+                .collect()
+        }
     }
 
     /// Fill a value in the grid at specific coordinates.
     fn fill(&mut self, coords: (usize, usize), value: u8) -> Result<(), &'static str> {
-        let square = cell_to_square(coords);
         match &mut self.cells[coords.0][coords.1] {
             Cell::Value(v) => {
                 if *v != value {
@@ -164,51 +918,44 @@ impl Solver {
                 }
             }
             Cell::Candidates(cs) => {
-                if mem::replace(
-                    &mut self.value_occurrences.row[coords.0][value as usize - 1],
-                    true,
-                ) {
-                    return Err("fill results in row conflict");
-                }
-                if mem::replace(
-                    &mut self.value_occurrences.col[coords.1][value as usize - 1],
-                    true,
-                ) {
-                    return Err("fill results in column conflict");
+                let bit = 1 << (value - 1);
+                let houses = cell_to_houses(coords);
+                let conflicts = [
+                    "fill results in row conflict",
+                    "fill results in column conflict",
+                    "fill results in square conflict",
+                ];
+                for (house, conflict) in houses.into_iter().zip(conflicts) {
+                    if self.house_values[house] & bit != 0 {
+                        return Err(conflict);
+                    }
                 }
-                if mem::replace(
-                    &mut self.value_occurrences.sqr[square.0][square.1][value as usize - 1],
-                    true,
-                ) {
-                    return Err("fill results in square conflict");
+                for house in houses {
+                    self.house_values[house] |= bit;
                 }
 
-                let former_candidates = mem::take(cs);
+                let former_candidates = *cs;
                 self.cells[coords.0][coords.1] = Cell::Value(value);
                 self.unfilled_cells -= 1;
+                for candidate in former_candidates.iter() {
+                    self.candidate_cells[candidate as usize - 1].remove(coords);
+                }
+                self.trail
+                    .push(Change::Fill(compact(coords), value, former_candidates));
 
                 // Remove candidates of filled in value in the row, column and square.
-                for idx in 0..9 {
-                    self.remove_candidate((coords.0, idx), value, Group::Row)?;
-                    self.remove_candidate((idx, coords.1), value, Group::Column)?;
-
-                    let relative = index_to_3x3_coords(idx);
-                    let absolute = (relative.0 + square.0 * 3, relative.1 + square.1 * 3);
-                    self.remove_candidate(absolute, value, Group::Square)?;
+                let peers = self.candidate_cells[value as usize - 1]
+                    .intersection(PEERS[9 * coords.0 + coords.1]);
+                for peer in peers.iter() {
+                    self.remove_candidate(peer, value)?;
                 }
 
-                // Decrement occurrences as a result of the formerly present candidates
-                // being replaced by a value and thus removed from the grid.
-                for candidate in former_candidates {
-                    self.decrement_occurrences(
-                        coords,
-                        candidate,
-                        if candidate == value {
-                            Group::All
-                        } else {
-                            Group::None
-                        },
-                    )?;
+                // The other formerly present candidates have been removed from the grid
+                // by being replaced by a value, which may leave them with a single cell.
+                for candidate in former_candidates.iter() {
+                    if candidate != value {
+                        self.fill_hidden_singles(coords, candidate)?;
+                    }
                 }
             }
         }
@@ -220,95 +967,232 @@ impl Solver {
         &mut self,
         coords: (usize, usize),
         candidate: u8,
-        unique_occurence_ignore: Group,
     ) -> Result<(), &'static str> {
         if let Cell::Candidates(cs) = &mut self.cells[coords.0][coords.1] {
-            if cs.remove(&candidate) {
-                if cs.len() == 1 {
-                    let leftover = *cs.iter().next().unwrap();
-                    self.fill(coords, leftover)?;
+            if cs.remove(candidate) {
+                self.candidate_cells[candidate as usize - 1].remove(coords);
+                self.trail
+                    .push(Change::RemoveCandidate(compact(coords), candidate));
+                // Cells left with a single candidate are filled right away,
+                // except while loading, when they may not be filled yet.
+                match cs.first() {
+                    None => return Err("no candidate left for cell"),
+                    Some(leftover) if cs.len() == 1 => self.fill(coords, leftover)?,
+                    Some(_) => {}
                 }
-                self.decrement_occurrences(coords, candidate, unique_occurence_ignore)?;
+                self.fill_hidden_singles(coords, candidate)?;
             }
         }
         Ok(())
     }
 
-    /// Decrement occurrence of a value in the row, column and square as a result
-    /// of a candidate being removed from a cell.
-    fn decrement_occurrences(
+    /// After a candidate no longer occurs in a cell, check the row, column and
+    /// square of the cell. If such a house does not contain the value yet and
+    /// only a single cell is left which has it as candidate, fill it in there.
+    fn fill_hidden_singles(
         &mut self,
         coords: (usize, usize),
         candidate: u8,
-        unique_occurrence_ignore: Group,
     ) -> Result<(), &'static str> {
-        let square = cell_to_square(coords);
-        let candidate_idx = candidate as usize - 1;
-
-        self.candidate_occurrences.row[coords.0][candidate_idx] -= 1;
-        self.candidate_occurrences.col[coords.1][candidate_idx] -= 1;
-        self.candidate_occurrences.sqr[square.0][square.1][candidate_idx] -= 1;
-
-        if !matches!(unique_occurrence_ignore, Group::All) {
-            if !matches!(unique_occurrence_ignore, Group::Row) {
-                if self.candidate_occurrences.row[coords.0][candidate_idx] == 1 {
-                    for col in 0..9 {
-                        if let Cell::Candidates(cs) = &self.cells[coords.0][col] {
-                            if cs.contains(&candidate) {
-                                self.fill((coords.0, col), candidate)?;
-                            }
-                        }
-                    }
-                }
+        let bit = 1 << (candidate - 1);
+        for house in cell_to_houses(coords) {
+            if self.house_values[house] & bit != 0 {
+                continue;
             }
-            if !matches!(unique_occurrence_ignore, Group::Column) {
-                if self.candidate_occurrences.col[coords.1][candidate_idx] == 1 {
-                    for row in 0..9 {
-                        if let Cell::Candidates(cs) = &self.cells[row][coords.1] {
-                            if cs.contains(&candidate) {
-                                self.fill((row, coords.1), candidate)?;
-                            }
-                        }
+            let cells = self.candidate_cells[candidate as usize - 1].intersection(HOUSES[house]);
+            match cells.first() {
+                None => return Err("no cell left for value"),
+                Some(single) if cells.len() == 1 => self.fill(single, candidate)?,
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Revert all changes recorded on the trail after the specified mark.
+    fn undo(&mut self, mark: usize) {
+        while self.trail.len() > mark {
+            match self.trail.pop().unwrap() {
+                Change::Fill(coords, value, former_candidates) => {
+                    let coords = expand(coords);
+                    for house in cell_to_houses(coords) {
+                        self.house_values[house] &= !(1 << (value - 1));
                     }
+                    self.cells[coords.0][coords.1] = Cell::Candidates(former_candidates);
+                    for candidate in former_candidates.iter() {
+                        self.candidate_cells[candidate as usize - 1].insert(coords);
+                    }
+                    self.unfilled_cells += 1;
                 }
-            }
-            if !matches!(unique_occurrence_ignore, Group::Square) {
-                if self.candidate_occurrences.sqr[square.0][square.1][candidate_idx] == 1 {
-                    for row in 0..3 {
-                        for col in 0..3 {
-                            let absolute_row = 3 * square.0 + row;
-                            let absolute_col = 3 * square.1 + col;
-                            if let Cell::Candidates(cs) = &self.cells[absolute_row][absolute_col] {
-                                if cs.contains(&candidate) {
-                                    self.fill((absolute_row, absolute_col), candidate)?;
-                                }
-                            }
-                        }
+                Change::RemoveCandidate(coords, candidate) => {
+                    let coords = expand(coords);
+                    if let Cell::Candidates(cs) = &mut self.cells[coords.0][coords.1] {
+                        cs.insert(candidate);
+                        self.candidate_cells[candidate as usize - 1].insert(coords);
                     }
                 }
             }
         }
+    }
 
+    /// Apply brute-force by repeatedly guessing the least constraining
+    /// candidate of the cell with the least candidates (highest entropy).
+    /// Guesses are kept on an explicit stack and reverted using the trail when
+    /// they do not lead to a solution. Returns errors only if no branch can
+    /// result in a valid solution, if the budget is exceeded or if the search
+    /// is cancelled.
+    fn brute_force(
+        &mut self,
+        cancelled: &AtomicBool,
+        budget: &Budget,
+        observer: &mut impl Observe,
+    ) -> Result<(), &'static str> {
+        let mut stack: Stack<Guess, { 9 * 9 }> = Default::default();
+        while self.unfilled_cells > 0 {
+            budget.check(self.guesses)?;
+            let coords = self
+                .highest_entropy_cell()
+                .ok_or("no unfilled cell was found")?;
+            match self.cells[coords.0][coords.1] {
+                Cell::Value(_) => return Err("unfilled cell already filled in"),
+                Cell::Candidates(cs) => stack.push(Guess {
+                    coords,
+                    remaining: cs,
+                    mark: self.trail.len(),
+                }),
+            }
+            self.next_guess(&mut stack, observer)?;
+            if cancelled.load(Ordering::Relaxed) {
+                return Err("search was cancelled");
+            }
+        }
+        self.brute_force_fills = stack.len() as u8;
         Ok(())
     }
 
-    /// Recursively apply brute-force by testing all candidates of the cell
-    /// with the least candidates (highest entropy). Returns errors only if
-    /// no branch can result in a valid solution.
-    fn brute_force(self) -> Result<Self, &'static str> {
-        if self.unfilled_cells == 0 {
-            return Ok(self);
+    /// Apply brute-force like [`Solver::brute_force`], but explore the
+    /// candidates of the first guess in parallel. A branch finding a solution
+    /// cancels the branches after it, and the first successful branch in
+    /// guessing order wins, such that the result equals that of a sequential
+    /// search. The budget applies to each branch separately.
+    #[cfg(feature = "parallel")]
+    fn brute_force_parallel(&self, budget: &Budget) -> Result<Self, &'static str> {
+        let coords = self
+            .highest_entropy_cell()
+            .ok_or("no unfilled cell was found")?;
+        let mut remaining = match self.cells[coords.0][coords.1] {
+            Cell::Value(_) => return Err("unfilled cell already filled in"),
+            Cell::Candidates(cs) => cs,
+        };
+        let mut candidates: Stack<u8, 9> = Default::default();
+        while let Some(candidate) = self.least_constraining(coords, remaining) {
+            remaining.remove(candidate);
+            candidates.push(candidate);
         }
 
-        let mut highest_entropy: Option<(usize, usize, u8)> = None;
+        let cancelled: [AtomicBool; 9] = Default::default();
+        let exceeded = std::sync::OnceLock::new();
+        candidates
+            .as_slice()
+            .par_iter()
+            .enumerate()
+            .find_map_first(|(idx, &candidate)| {
+                let mut branch = self.fork();
+                branch.fill(coords, candidate).ok()?;
+                if let Err(err) = branch.brute_force(&cancelled[idx], budget, &mut ()) {
+                    if err == NODE_LIMIT_EXCEEDED || err == TIME_LIMIT_EXCEEDED {
+                        let _ = exceeded.set(err);
+                    }
+                    return None;
+                }
+                for later in &cancelled[idx + 1..] {
+                    later.store(true, Ordering::Relaxed);
+                }
+                branch.brute_force_fills += 1;
+                branch.guesses = branch.guesses.saturating_add(1);
+                Some(branch)
+            })
+            .ok_or_else(|| exceeded.get().copied().unwrap_or("all branches exhausted"))
+    }
+
+    /// Copy the state of the solver without its trail, for exploring a branch
+    /// which never has to be undone past its starting point. The copy lives
+    /// on the stack of the calling thread and only the grid state is copied.
+    #[cfg(feature = "parallel")]
+    fn fork(&self) -> Self {
+        Self {
+            cells: self.cells,
+            house_values: self.house_values,
+            candidate_cells: self.candidate_cells,
+            unfilled_cells: self.unfilled_cells,
+            brute_force_fills: self.brute_force_fills,
+            guesses: self.guesses,
+            trail: Default::default(),
+        }
+    }
+
+    /// Fill the next untried candidate of the innermost guess on the stack,
+    /// backtracking to outer guesses whenever all candidates of a guess have
+    /// been exhausted.
+    fn next_guess(
+        &mut self,
+        stack: &mut Stack<Guess, { 9 * 9 }>,
+        observer: &mut impl Observe,
+    ) -> Result<(), &'static str> {
+        loop {
+            let depth = stack.len();
+            let Some(guess) = stack.last_mut() else {
+                break;
+            };
+            self.undo(guess.mark);
+            while let Some(candidate) = self.least_constraining(guess.coords, guess.remaining) {
+                guess.remaining.remove(candidate);
+                self.guesses = self.guesses.saturating_add(1);
+                let filled = self.fill(guess.coords, candidate).is_ok();
+                observer.guess(depth, guess.coords, candidate, filled);
+                if filled {
+                    return Ok(());
+                }
+                self.undo(guess.mark);
+            }
+            stack.pop();
+        }
+        Err("all branches exhausted")
+    }
+
+    /// Pick the candidate which occurs the least among the candidates of the
+    /// peers of a cell, as filling it in eliminates the fewest options
+    /// elsewhere. Ties are broken by picking the lowest value.
+    fn least_constraining(&self, coords: (usize, usize), candidates: Candidates) -> Option<u8> {
+        let peers = PEERS[9 * coords.0 + coords.1];
+        candidates.iter().min_by_key(|candidate| {
+            self.candidate_cells[*candidate as usize - 1]
+                .intersection(peers)
+                .len()
+        })
+    }
+
+    /// Find the unfilled cell with the least candidates (highest entropy).
+    /// Ties are broken by preferring the cell which constrains the most
+    /// unfilled peers, as guessing there prunes the search the most.
+    fn highest_entropy_cell(&self) -> Option<(usize, usize)> {
+        let mut highest_entropy: Option<(usize, usize, u32, u8)> = None;
         for row in 0..9 {
             for col in 0..9 {
-                if let Cell::Candidates(cs) = &self.cells[row][col] {
-                    let current_entropy = (row, col, cs.len() as u8);
+                if let Cell::Candidates(cs) = self.cells[row][col] {
+                    let entropy = cs.len();
+                    if let Some(former) = highest_entropy {
+                        if entropy > former.2 {
+                            continue;
+                        }
+                    }
+                    let current_entropy = (row, col, entropy, self.unfilled_peers((row, col)));
                     match highest_entropy {
                         None => highest_entropy = Some(current_entropy),
                         Some(former) => {
-                            if current_entropy.2 < former.2 {
+                            if current_entropy.2 < former.2
+                                || (current_entropy.2 == former.2 && current_entropy.3 > former.3)
+                            {
                                 highest_entropy = Some(current_entropy);
                             }
                         }
@@ -316,28 +1200,29 @@ impl Solver {
                 }
             }
         }
+        highest_entropy.map(|(row, col, _, _)| (row, col))
+    }
 
-        match highest_entropy {
-            None => return Err("no unfilled cell was found"),
-            Some(highest_entropy) => {
-                let coords = (highest_entropy.0, highest_entropy.1);
-                match &self.cells[coords.0][coords.1] {
-                    Cell::Value(_) => return Err("unfilled cell already filled in"),
-                    Cell::Candidates(cs) => {
-                        for candidate in cs {
-                            let mut branch = self.clone();
-                            if let Ok(_) = branch.fill(coords, *candidate) {
-                                branch.brute_force_fills += 1;
-                                if let Ok(branch) = branch.brute_force() {
-                                    return Ok(branch);
-                                }
-                            }
-                        }
-                    }
-                }
+    /// Count the unfilled cells sharing a row, column or square with a cell.
+    fn unfilled_peers(&self, coords: (usize, usize)) -> u8 {
+        let square = cell_to_square(coords);
+        let mut count = 0;
+        for idx in 0..9 {
+            if idx != coords.1 && matches!(self.cells[coords.0][idx], Cell::Candidates(_)) {
+                count += 1;
+            }
+            if idx != coords.0 && matches!(self.cells[idx][coords.1], Cell::Candidates(_)) {
+                count += 1;
+            }
+            let relative = index_to_3x3_coords(idx);
+            let absolute = (relative.0 + square.0 * 3, relative.1 + square.1 * 3);
+            if absolute.0 != coords.0
+                && absolute.1 != coords.1
+                && matches!(self.cells[absolute.0][absolute.1], Cell::Candidates(_))
+            {
+                count += 1;
             }
         }
-
-        Err("all branches exhausted")
+        count
     }
 }
@@ -0,0 +1,397 @@
+//! Solving of many easy puzzles at once, advancing a group of puzzles in
+//! lockstep. The candidates of the puzzles are stored side by side for every
+//! cell, such that each step of propagation is the same operation applied to
+//! every puzzle of the group, which the compiler turns into SIMD
+//! instructions. Only naked and hidden singles are applied, without any
+//! branching on the state of a single puzzle, so puzzles needing a guess are
+//! left to the regular solver. With the `simd` feature on x86-64, the steps
+//! are written using SSE2 instructions directly, of which each eliminates a
+//! value from a cell of eight puzzles at once.
+
+#[cfg(feature = "std")]
+use crate::{Options, Solver};
+use crate::{Puzzle, Solution};
+
+/// Number of puzzles advanced in lockstep.
+pub const LANES: usize = 16;
+
+/// Mask of all candidates of a cell.
+const ALL: u16 = 0x1ff;
+
+/// The cells of each house by their index: the nine rows, followed by the
+/// nine columns and the nine squares.
+const HOUSE_CELLS: [[u8; 9]; 27] = house_cells();
+
+const fn house_cells() -> [[u8; 9]; 27] {
+    let mut houses = [[0; 9]; 27];
+    let mut idx = 0;
+    while idx < 9 {
+        let mut cell = 0;
+        while cell < 9 {
+            houses[idx][cell] = (9 * idx + cell) as u8;
+            houses[9 + idx][cell] = (9 * cell + idx) as u8;
+            let row = idx / 3 * 3 + cell / 3;
+            let col = idx % 3 * 3 + cell % 3;
+            houses[18 + idx][cell] = (9 * row + col) as u8;
+            cell += 1;
+        }
+        idx += 1;
+    }
+    houses
+}
+
+/// Candidates of the puzzles of a group, stored for each cell as the
+/// candidates of that cell in every puzzle.
+struct Group {
+    cells: [[u16; LANES]; 81],
+    /// Whether each puzzle contradicts itself.
+    failed: [bool; LANES],
+}
+
+impl Group {
+    /// Load up to [`LANES`] puzzles, of which unused lanes are left solved
+    /// as contradictions.
+    fn new(puzzles: &[Puzzle]) -> Self {
+        let mut group = Self {
+            cells: [[ALL; LANES]; 81],
+            failed: [true; LANES],
+        };
+        for (lane, puzzle) in puzzles.iter().enumerate() {
+            group.failed[lane] = false;
+            for (idx, value) in puzzle.cells.iter().flatten().enumerate() {
+                if *value != 0 {
+                    group.cells[idx][lane] = 1 << (value - 1);
+                }
+            }
+        }
+        group
+    }
+
+    /// Apply naked singles to every puzzle once, removing the values of
+    /// solved cells from their peers. Returns whether anything changed.
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+    fn naked_singles(&mut self) -> bool {
+        let mut changed = [false; LANES];
+        for house in &HOUSE_CELLS {
+            let mut solved = [0u16; LANES];
+            let mut conflict = [false; LANES];
+            for cell in house {
+                let cell = &self.cells[*cell as usize];
+                for lane in 0..LANES {
+                    let single = cell[lane] & cell[lane].wrapping_sub(1) == 0;
+                    let value = if single { cell[lane] } else { 0 };
+                    conflict[lane] |= solved[lane] & value != 0;
+                    solved[lane] |= value;
+                }
+            }
+            for cell in house {
+                let cell = &mut self.cells[*cell as usize];
+                for lane in 0..LANES {
+                    let single = cell[lane] & cell[lane].wrapping_sub(1) == 0;
+                    let pruned = if single {
+                        cell[lane]
+                    } else {
+                        cell[lane] & !solved[lane]
+                    };
+                    changed[lane] |= pruned != cell[lane];
+                    conflict[lane] |= pruned == 0;
+                    cell[lane] = pruned;
+                }
+            }
+            for (failed, conflict) in self.failed.iter_mut().zip(conflict) {
+                *failed |= conflict;
+            }
+        }
+        self.any(changed)
+    }
+
+    /// Apply hidden singles to every puzzle once, placing the values which
+    /// fit in a single cell of a house. Returns whether anything changed.
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+    fn hidden_singles(&mut self) -> bool {
+        let mut changed = [false; LANES];
+        for house in &HOUSE_CELLS {
+            let mut once = [0u16; LANES];
+            let mut twice = [0u16; LANES];
+            for cell in house {
+                let cell = &self.cells[*cell as usize];
+                for lane in 0..LANES {
+                    twice[lane] |= once[lane] & cell[lane];
+                    once[lane] |= cell[lane];
+                }
+            }
+            let mut conflict = [false; LANES];
+            for lane in 0..LANES {
+                conflict[lane] = once[lane] != ALL;
+                once[lane] &= !twice[lane];
+            }
+            for cell in house {
+                let cell = &mut self.cells[*cell as usize];
+                for lane in 0..LANES {
+                    let hidden = cell[lane] & once[lane];
+                    let placed = if hidden != 0 { hidden } else { cell[lane] };
+                    // A cell being the only place of two values contradicts.
+                    conflict[lane] |= placed & placed.wrapping_sub(1) != 0 && hidden != 0;
+                    changed[lane] |= placed != cell[lane];
+                    cell[lane] = placed;
+                }
+            }
+            for (failed, conflict) in self.failed.iter_mut().zip(conflict) {
+                *failed |= conflict;
+            }
+        }
+        self.any(changed)
+    }
+
+    /// Apply naked singles to every puzzle once, like the portable version,
+    /// on vectors of the candidates of eight puzzles.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    fn naked_singles(&mut self) -> bool {
+        use simd::*;
+
+        // SAFETY: SSE2 is part of the x86-64 baseline, and the lanes of each
+        // cell are loaded and stored by `simd` in bounds.
+        unsafe {
+            let (zero, ones) = (_mm_setzero_si128(), _mm_set1_epi16(-1));
+            let mut changed = [zero; HALVES];
+            let mut conflict = [zero; HALVES];
+            for house in &HOUSE_CELLS {
+                let mut solved = [zero; HALVES];
+                for cell in house {
+                    for (half, solved) in solved.iter_mut().enumerate() {
+                        let cell = load(&self.cells[*cell as usize], half);
+                        let single =
+                            _mm_cmpeq_epi16(_mm_and_si128(cell, _mm_add_epi16(cell, ones)), zero);
+                        let value = _mm_and_si128(cell, single);
+                        conflict[half] =
+                            _mm_or_si128(conflict[half], _mm_and_si128(*solved, value));
+                        *solved = _mm_or_si128(*solved, value);
+                    }
+                }
+                for cell in house {
+                    for (half, solved) in solved.iter().enumerate() {
+                        let cell = &mut self.cells[*cell as usize];
+                        let candidates = load(cell, half);
+                        let single = _mm_cmpeq_epi16(
+                            _mm_and_si128(candidates, _mm_add_epi16(candidates, ones)),
+                            zero,
+                        );
+                        let pruned =
+                            _mm_andnot_si128(_mm_andnot_si128(single, *solved), candidates);
+                        changed[half] =
+                            _mm_or_si128(changed[half], _mm_xor_si128(pruned, candidates));
+                        conflict[half] =
+                            _mm_or_si128(conflict[half], _mm_cmpeq_epi16(pruned, zero));
+                        store(cell, half, pruned);
+                    }
+                }
+            }
+            self.fail(conflict);
+            self.any(nonzero(changed))
+        }
+    }
+
+    /// Apply hidden singles to every puzzle once, like the portable version,
+    /// on vectors of the candidates of eight puzzles.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    fn hidden_singles(&mut self) -> bool {
+        use simd::*;
+
+        // SAFETY: SSE2 is part of the x86-64 baseline, and the lanes of each
+        // cell are loaded and stored by `simd` in bounds.
+        unsafe {
+            let (zero, ones) = (_mm_setzero_si128(), _mm_set1_epi16(-1));
+            let all = _mm_set1_epi16(ALL as i16);
+            let mut changed = [zero; HALVES];
+            let mut conflict = [zero; HALVES];
+            for house in &HOUSE_CELLS {
+                let mut once = [zero; HALVES];
+                let mut twice = [zero; HALVES];
+                for cell in house {
+                    for half in 0..HALVES {
+                        let cell = load(&self.cells[*cell as usize], half);
+                        twice[half] = _mm_or_si128(twice[half], _mm_and_si128(once[half], cell));
+                        once[half] = _mm_or_si128(once[half], cell);
+                    }
+                }
+                for half in 0..HALVES {
+                    let missing = _mm_andnot_si128(_mm_cmpeq_epi16(once[half], all), ones);
+                    conflict[half] = _mm_or_si128(conflict[half], missing);
+                    once[half] = _mm_andnot_si128(twice[half], once[half]);
+                }
+                for cell in house {
+                    for (half, once) in once.iter().enumerate() {
+                        let cell = &mut self.cells[*cell as usize];
+                        let candidates = load(cell, half);
+                        let hidden = _mm_and_si128(candidates, *once);
+                        let none = _mm_cmpeq_epi16(hidden, zero);
+                        let placed = _mm_or_si128(hidden, _mm_and_si128(none, candidates));
+                        // A cell being the only place of two values contradicts.
+                        let multiple = _mm_and_si128(placed, _mm_add_epi16(placed, ones));
+                        conflict[half] =
+                            _mm_or_si128(conflict[half], _mm_andnot_si128(none, multiple));
+                        changed[half] =
+                            _mm_or_si128(changed[half], _mm_xor_si128(placed, candidates));
+                        store(cell, half, placed);
+                    }
+                }
+            }
+            self.fail(conflict);
+            self.any(nonzero(changed))
+        }
+    }
+
+    /// Mark the puzzles of the lanes which are nonzero in any of the vectors
+    /// as failed.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    fn fail(&mut self, conflict: [simd::__m128i; simd::HALVES]) {
+        for (failed, conflict) in self.failed.iter_mut().zip(simd::nonzero(conflict)) {
+            *failed |= conflict;
+        }
+    }
+
+    /// Whether any puzzle which has not failed has a flag set.
+    fn any(&self, flags: [bool; LANES]) -> bool {
+        (0..LANES).any(|lane| flags[lane] && !self.failed[lane])
+    }
+
+    /// The solution of a puzzle, if its every cell has a single candidate.
+    fn solution(&self, lane: usize) -> Option<Solution> {
+        if self.failed[lane] {
+            return None;
+        }
+        let mut cells = [[0; 9]; 9];
+        for (idx, candidates) in self.cells.iter().enumerate() {
+            let candidates = candidates[lane];
+            if candidates.count_ones() != 1 {
+                return None;
+            }
+            cells[idx / 9][idx % 9] = candidates.trailing_zeros() as u8 + 1;
+        }
+        Some(Solution {
+            cells,
+            brute_forces: 0,
+            guesses: 0,
+            backtracks: 0,
+        })
+    }
+}
+
+/// Vectors of the candidates of a cell in eight puzzles, each cell holding
+/// two of them.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd {
+    pub use core::arch::x86_64::*;
+
+    use super::LANES;
+
+    /// Number of vectors holding the lanes of a cell.
+    pub const HALVES: usize = LANES / 8;
+
+    /// Load the candidates of a half of the lanes of a cell.
+    ///
+    /// # Safety
+    ///
+    /// The half must be less than [`HALVES`].
+    #[inline(always)]
+    pub unsafe fn load(cell: &[u16; LANES], half: usize) -> __m128i {
+        _mm_loadu_si128(cell.as_ptr().add(8 * half).cast())
+    }
+
+    /// Store the candidates of a half of the lanes of a cell.
+    ///
+    /// # Safety
+    ///
+    /// The half must be less than [`HALVES`].
+    #[inline(always)]
+    pub unsafe fn store(cell: &mut [u16; LANES], half: usize, candidates: __m128i) {
+        _mm_storeu_si128(cell.as_mut_ptr().add(8 * half).cast(), candidates)
+    }
+
+    /// Whether each lane is nonzero in the vectors of its half.
+    #[inline(always)]
+    pub fn nonzero(vectors: [__m128i; HALVES]) -> [bool; LANES] {
+        let mut lanes = [false; LANES];
+        for (half, vector) in vectors.into_iter().enumerate() {
+            // SAFETY: SSE2 is part of the x86-64 baseline.
+            let zero = unsafe { _mm_movemask_epi8(_mm_cmpeq_epi16(vector, _mm_setzero_si128())) };
+            for lane in 0..8 {
+                lanes[8 * half + lane] = zero >> (2 * lane) & 1 == 0;
+            }
+        }
+        lanes
+    }
+}
+
+/// Solve up to [`LANES`] puzzles in lockstep using naked and hidden singles,
+/// returning the solution of each puzzle which is solved by singles alone.
+/// Such solutions equal those of [`Solver`](crate::Solver), which needs no
+/// guess for them either. Puzzles which need a guess or are invalid give
+/// `None`, and are left to the regular solver. Does not allocate. Fails if
+/// more than [`LANES`] puzzles are given.
+pub fn solve_singles(puzzles: &[Puzzle]) -> Result<[Option<Solution>; LANES], &'static str> {
+    if puzzles.len() > LANES {
+        return Err("too many puzzles for a group");
+    }
+    let mut group = Group::new(puzzles);
+    loop {
+        let naked = group.naked_singles();
+        let hidden = group.hidden_singles();
+        if !naked && !hidden {
+            break;
+        }
+    }
+    Ok(core::array::from_fn(|lane| group.solution(lane)))
+}
+
+/// Solve many puzzles, returning the results in the same order as the
+/// puzzles. Groups of puzzles are first solved in lockstep, and those which
+/// need a guess are solved by [`Solver`] afterwards, giving the same results
+/// as solving each puzzle by [`Solver`]. Lockstep solving only pays off when
+/// at least a quarter of the puzzles are easy, so it is used only while that
+/// many puzzles of recent groups were solved by it, and tried again every so
+/// often otherwise.
+#[cfg(feature = "std")]
+pub fn solve_batch(puzzles: &[Puzzle], options: &Options) -> Vec<Result<Solution, &'static str>> {
+    /// Number of groups over which the share of easy puzzles is measured.
+    const WINDOW: usize = 8;
+    /// Number of groups after which lockstep solving is tried again.
+    const RETRY: usize = 64;
+
+    let mut solver = Solver::new();
+    let mut solve = |puzzle: &Puzzle| {
+        solver.load(puzzle)?;
+        solver.run(options)
+    };
+    let mut results = Vec::with_capacity(puzzles.len());
+    let mut skipped = 0;
+    // Number of groups, puzzles and solved puzzles of the current window.
+    let (mut window, mut tried, mut solved) = (0, 0, 0);
+    for group in puzzles.chunks(LANES) {
+        if skipped > 0 {
+            skipped -= 1;
+            results.extend(group.iter().map(&mut solve));
+            continue;
+        }
+        let Ok(solutions) = solve_singles(group) else {
+            results.extend(group.iter().map(&mut solve));
+            continue;
+        };
+        window += 1;
+        tried += group.len();
+        solved += solutions
+            .iter()
+            .filter(|solution| solution.is_some())
+            .count();
+        if window == WINDOW {
+            if 4 * solved < tried {
+                skipped = RETRY;
+            }
+            (window, tried, solved) = (0, 0, 0);
+        }
+        for (puzzle, solution) in group.iter().zip(solutions) {
+            results.push(solution.map_or_else(|| solve(puzzle), Ok));
+        }
+    }
+    results
+}
@@ -0,0 +1,1243 @@
+//! Solving of puzzles step by step using the techniques a human would use,
+//! which is the basis for rating puzzles and giving hints.
+
+#[cfg(feature = "rater")]
+use crate::rating::Difficulty;
+use crate::{check_cell, check_digit, Options, Puzzle, Solution, Solver};
+use std::fmt;
+use std::iter;
+use std::str::FromStr;
+
+/// A technique for making progress in a puzzle, in order of difficulty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    Pointing,
+    Claiming,
+    NakedPair,
+    HiddenPair,
+    NakedTriple,
+    HiddenTriple,
+    XWing,
+    Swordfish,
+    XYWing,
+    /// Filling in a value of the solution when no technique applies.
+    BruteForce,
+}
+
+impl Technique {
+    /// All techniques, in order of difficulty.
+    pub const ALL: [Technique; 12] = [
+        Technique::NakedSingle,
+        Technique::HiddenSingle,
+        Technique::Pointing,
+        Technique::Claiming,
+        Technique::NakedPair,
+        Technique::HiddenPair,
+        Technique::NakedTriple,
+        Technique::HiddenTriple,
+        Technique::XWing,
+        Technique::Swordfish,
+        Technique::XYWing,
+        Technique::BruteForce,
+    ];
+
+    /// Human-readable name of the technique.
+    pub fn name(self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "Naked Single",
+            Technique::HiddenSingle => "Hidden Single",
+            Technique::Pointing => "Pointing",
+            Technique::Claiming => "Claiming",
+            Technique::NakedPair => "Naked Pair",
+            Technique::HiddenPair => "Hidden Pair",
+            Technique::NakedTriple => "Naked Triple",
+            Technique::HiddenTriple => "Hidden Triple",
+            Technique::XWing => "X-Wing",
+            Technique::Swordfish => "Swordfish",
+            Technique::XYWing => "XY-Wing",
+            Technique::BruteForce => "Brute Force",
+        }
+    }
+
+    /// Name of the technique as used by Hodoku and SudokuWiki, which differs
+    /// for locked candidates.
+    pub fn hodoku_name(self) -> &'static str {
+        match self {
+            Technique::Pointing => "Locked Candidates Type 1 (Pointing)",
+            Technique::Claiming => "Locked Candidates Type 2 (Claiming)",
+            technique => technique.name(),
+        }
+    }
+
+    /// Score of applying the technique once, used for rating puzzles.
+    pub fn score(self) -> u32 {
+        match self {
+            Technique::NakedSingle => 4,
+            Technique::HiddenSingle => 14,
+            Technique::Pointing | Technique::Claiming => 50,
+            Technique::NakedPair => 60,
+            Technique::HiddenPair => 70,
+            Technique::NakedTriple => 80,
+            Technique::HiddenTriple => 100,
+            Technique::XWing => 140,
+            Technique::Swordfish => 150,
+            Technique::XYWing => 160,
+            Technique::BruteForce => 10000,
+        }
+    }
+
+    /// The difficulty of puzzles requiring the technique.
+    #[cfg(feature = "rater")]
+    pub fn difficulty(self) -> Difficulty {
+        match self {
+            Technique::NakedSingle | Technique::HiddenSingle => Difficulty::Easy,
+            Technique::Pointing | Technique::Claiming => Difficulty::Medium,
+            Technique::NakedPair
+            | Technique::HiddenPair
+            | Technique::NakedTriple
+            | Technique::HiddenTriple => Difficulty::Hard,
+            Technique::XWing | Technique::Swordfish | Technique::XYWing => Difficulty::Expert,
+            Technique::BruteForce => Difficulty::Extreme,
+        }
+    }
+}
+
+impl FromStr for Technique {
+    type Err = &'static str;
+
+    /// Parse the name of a technique, ignoring case, spaces and hyphens, such
+    /// that both "X-Wing" and "xwing" are accepted. Hodoku names are accepted
+    /// too.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalize = |name: &str| {
+            name.chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .map(|c| c.to_ascii_lowercase())
+                .collect::<String>()
+        };
+        let s = normalize(s);
+        Technique::ALL
+            .into_iter()
+            .find(|technique| {
+                normalize(technique.name()) == s || normalize(technique.hodoku_name()) == s
+            })
+            .ok_or("unknown technique")
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.name())
+    }
+}
+
+/// A row, column or square of the grid, indexed from zero. Squares are
+/// numbered in row-major order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum House {
+    Row(usize),
+    Column(usize),
+    Square(usize),
+}
+
+impl House {
+    /// All houses: the rows, followed by the columns and the squares.
+    pub fn all() -> impl Iterator<Item = House> {
+        (0..9)
+            .map(House::Row)
+            .chain((0..9).map(House::Column))
+            .chain((0..9).map(House::Square))
+    }
+
+    /// The coordinates of the cells in the house.
+    pub fn cells(self) -> [(usize, usize); 9] {
+        core::array::from_fn(|idx| match self {
+            House::Row(row) => (row, idx),
+            House::Column(col) => (idx, col),
+            House::Square(sqr) => (sqr / 3 * 3 + idx / 3, sqr % 3 * 3 + idx % 3),
+        })
+    }
+
+    /// Whether the house contains a cell.
+    pub fn contains(self, coords: (usize, usize)) -> bool {
+        match self {
+            House::Row(row) => coords.0 == row,
+            House::Column(col) => coords.1 == col,
+            House::Square(sqr) => coords.0 / 3 * 3 + coords.1 / 3 == sqr,
+        }
+    }
+}
+
+impl fmt::Display for House {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            House::Row(row) => write!(fmt, "row {}", row + 1),
+            House::Column(col) => write!(fmt, "column {}", col + 1),
+            House::Square(sqr) => write!(fmt, "box {}", sqr + 1),
+        }
+    }
+}
+
+/// A single step towards the solution, placing values and/or eliminating
+/// candidates as justified by a pattern found using a technique.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub technique: Technique,
+    /// The cells whose candidates form the pattern.
+    pub pattern: Vec<(usize, usize)>,
+    /// The houses in which the pattern was found.
+    pub houses: Vec<House>,
+    /// The values involved in the pattern.
+    pub digits: Vec<u8>,
+    /// Values to fill into cells.
+    pub placements: Vec<((usize, usize), u8)>,
+    /// Candidates to remove from cells.
+    pub eliminations: Vec<((usize, usize), u8)>,
+}
+
+impl Step {
+    fn new(technique: Technique) -> Self {
+        Self {
+            technique,
+            pattern: Vec::new(),
+            houses: Vec::new(),
+            digits: Vec::new(),
+            placements: Vec::new(),
+            eliminations: Vec::new(),
+        }
+    }
+
+    /// Reveal part of the step as hint of a level of detail, such that
+    /// assistance can be given progressively. Fails for steps which neither
+    /// place nor eliminate a digit.
+    pub fn hint(&self, level: HintLevel) -> Result<Hint, &'static str> {
+        let (cell, digit, placement) = match (self.placements.first(), self.eliminations.first()) {
+            (Some((cell, digit)), _) => (*cell, *digit, true),
+            (None, Some((cell, digit))) => (*cell, *digit, false),
+            (None, None) => return Err("step neither places nor eliminates a digit"),
+        };
+        check_cell(cell)?;
+        Ok(match level {
+            HintLevel::Region => Hint::Region(
+                self.houses
+                    .first()
+                    .copied()
+                    .unwrap_or(House::Square(cell.0 / 3 * 3 + cell.1 / 3)),
+            ),
+            HintLevel::Cell => Hint::Cell(cell),
+            HintLevel::Digit => Hint::Digit {
+                cell,
+                digit,
+                placement,
+            },
+            HintLevel::Explanation => Hint::Explanation(self.clone()),
+        })
+    }
+
+    /// The events of the step, being its placements followed by its
+    /// eliminations enclosed by its start and end. The step is numbered by
+    /// its index in the solve.
+    pub fn events(&self, index: usize) -> impl Iterator<Item = SolveEvent> + '_ {
+        let technique = self.technique;
+        let fills = self
+            .placements
+            .iter()
+            .map(move |&(cell, digit)| SolveEvent::Fill {
+                step: index,
+                technique,
+                cell,
+                digit,
+            });
+        let eliminations =
+            self.eliminations
+                .iter()
+                .map(move |&(cell, digit)| SolveEvent::Eliminate {
+                    step: index,
+                    technique,
+                    cell,
+                    digit,
+                });
+        iter::once(SolveEvent::StepStart {
+            step: index,
+            technique,
+        })
+        .chain(fills)
+        .chain(eliminations)
+        .chain(iter::once(SolveEvent::StepEnd { step: index }))
+    }
+
+    /// Describe the step in the notation of Hodoku and SudokuWiki, such that
+    /// it can be compared to the steps of those tools.
+    pub fn hodoku(&self) -> HodokuStep<'_> {
+        HodokuStep(self)
+    }
+}
+
+impl fmt::Display for Step {
+    /// Describe the step in the common notation for cells, where `r1c2` is
+    /// the cell in row 1 and column 2, `r1c2=5` places a 5 in that cell and
+    /// `r1c2<>5` removes 5 as candidate from it.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let join = |items: Vec<String>, separator| items.join(separator);
+        write!(fmt, "{}", self.technique)?;
+        if !self.houses.is_empty() {
+            let houses = self.houses.iter().map(House::to_string).collect();
+            write!(fmt, " in {}", join(houses, " and "))?;
+        }
+        let cells = self.pattern.iter().map(|c| cell_name(*c)).collect();
+        let digits = self.digits.iter().map(u8::to_string).collect();
+        write!(fmt, " ({} with {}):", join(cells, ", "), join(digits, "/"))?;
+        let effects: Vec<String> = self
+            .placements
+            .iter()
+            .map(|(coords, value)| format!("{}={}", cell_name(*coords), value))
+            .chain(
+                self.eliminations
+                    .iter()
+                    .map(|(coords, value)| format!("{}<>{}", cell_name(*coords), value)),
+            )
+            .collect();
+        write!(fmt, " {}", join(effects, ", "))
+    }
+}
+
+/// An event of a logical solve, such that the solve can be animated cell by
+/// cell. The events of each step are enclosed by its start and end, and are
+/// numbered by the step in order of the solve, starting from zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SolveEvent {
+    /// Start of the events of a step.
+    StepStart { step: usize, technique: Technique },
+    /// A value is filled into a cell.
+    Fill {
+        step: usize,
+        technique: Technique,
+        cell: (usize, usize),
+        digit: u8,
+    },
+    /// A candidate is removed from a cell.
+    Eliminate {
+        step: usize,
+        technique: Technique,
+        cell: (usize, usize),
+        digit: u8,
+    },
+    /// End of the events of a step.
+    StepEnd { step: usize },
+}
+
+impl SolveEvent {
+    /// The step to which the event belongs.
+    pub fn step(&self) -> usize {
+        match *self {
+            SolveEvent::StepStart { step, .. }
+            | SolveEvent::Fill { step, .. }
+            | SolveEvent::Eliminate { step, .. }
+            | SolveEvent::StepEnd { step } => step,
+        }
+    }
+}
+
+/// Level of detail of a hint, from revealing only where to look up to
+/// revealing the full reasoning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HintLevel {
+    /// The house in which progress can be made.
+    Region,
+    /// The cell in which progress can be made.
+    Cell,
+    /// The digit to place into or eliminate from that cell.
+    Digit,
+    /// The step, including the technique and pattern justifying it.
+    Explanation,
+}
+
+impl HintLevel {
+    /// All levels, from least to most revealing.
+    pub const ALL: [HintLevel; 4] = [
+        HintLevel::Region,
+        HintLevel::Cell,
+        HintLevel::Digit,
+        HintLevel::Explanation,
+    ];
+
+    /// Name of the level.
+    pub fn name(self) -> &'static str {
+        match self {
+            HintLevel::Region => "region",
+            HintLevel::Cell => "cell",
+            HintLevel::Digit => "digit",
+            HintLevel::Explanation => "explanation",
+        }
+    }
+}
+
+impl FromStr for HintLevel {
+    type Err = &'static str;
+
+    /// Parse the name of a level, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HintLevel::ALL
+            .into_iter()
+            .find(|level| level.name().eq_ignore_ascii_case(s))
+            .ok_or("unknown hint level")
+    }
+}
+
+/// Part of a step revealed as hint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Hint {
+    /// The house in which progress can be made.
+    Region(House),
+    /// The cell in which progress can be made.
+    Cell((usize, usize)),
+    /// A digit to place into a cell, or to eliminate from it if not a
+    /// placement.
+    Digit {
+        cell: (usize, usize),
+        digit: u8,
+        placement: bool,
+    },
+    /// The full step.
+    Explanation(Step),
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Hint::Region(house) => write!(fmt, "Look at {}", house),
+            Hint::Cell(cell) => write!(fmt, "Look at {}", cell_name(*cell)),
+            Hint::Digit {
+                cell,
+                digit,
+                placement: true,
+            } => write!(fmt, "{}={}", cell_name(*cell), digit),
+            Hint::Digit { cell, digit, .. } => write!(fmt, "{}<>{}", cell_name(*cell), digit),
+            Hint::Explanation(step) => write!(fmt, "{}", step),
+        }
+    }
+}
+
+/// A step displayed in the notation of Hodoku, such as
+/// `Locked Candidates Type 1 (Pointing): 5 in b1 => r1c47<>5`. Cells in the
+/// same row or column are combined, such as `r1c47` for `r1c4` and `r1c7`.
+pub struct HodokuStep<'a>(&'a Step);
+
+impl fmt::Display for HodokuStep<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let step = self.0;
+        let join = |digits: &[u8], separator| {
+            let digits: Vec<String> = digits.iter().map(u8::to_string).collect();
+            digits.join(separator)
+        };
+        write!(fmt, "{}: ", step.technique.hodoku_name())?;
+        match step.technique {
+            Technique::NakedSingle | Technique::HiddenSingle | Technique::BruteForce => {}
+            Technique::Pointing | Technique::Claiming => {
+                let house = hodoku_houses(&step.houses[..1]);
+                write!(fmt, "{} in {} => ", join(&step.digits, ","), house)?;
+            }
+            Technique::NakedPair
+            | Technique::HiddenPair
+            | Technique::NakedTriple
+            | Technique::HiddenTriple => {
+                let cells = hodoku_cells(&step.pattern);
+                write!(fmt, "{} in {} => ", join(&step.digits, ","), cells)?;
+            }
+            Technique::XWing | Technique::Swordfish => {
+                let (bases, covers) = step.houses.split_at(step.houses.len() / 2);
+                let (bases, covers) = (hodoku_houses(bases), hodoku_houses(covers));
+                write!(fmt, "{} {} {} => ", join(&step.digits, ","), bases, covers)?;
+            }
+            Technique::XYWing => {
+                // The digit which is eliminated comes last.
+                let mut digits = step.digits.clone();
+                if let Some((_, z)) = step.eliminations.first() {
+                    digits.retain(|digit| digit != z);
+                    digits.push(*z);
+                }
+                let cells = hodoku_cells(&step.pattern);
+                write!(fmt, "{} in {} => ", join(&digits, "/"), cells)?;
+            }
+        }
+        let mut effects: Vec<String> = step
+            .placements
+            .iter()
+            .map(|(coords, value)| format!("{}={}", cell_name(*coords), value))
+            .collect();
+        for value in 1..=9 {
+            let cells: Vec<_> = step
+                .eliminations
+                .iter()
+                .filter(|(_, eliminated)| *eliminated == value)
+                .map(|(coords, _)| *coords)
+                .collect();
+            if !cells.is_empty() {
+                effects.push(format!("{}<>{}", hodoku_cells(&cells), value));
+            }
+        }
+        write!(fmt, "{}", effects.join(", "))
+    }
+}
+
+/// Combined names of cells in the notation of Hodoku, such as `r1c47` for
+/// cells in the same row and `r14c7` for cells in the same column.
+fn hodoku_cells(cells: &[(usize, usize)]) -> String {
+    let mut cells = cells.to_vec();
+    cells.sort();
+    cells.dedup();
+    fn digits(idxs: impl Iterator<Item = usize>) -> String {
+        idxs.map(|idx| idx.saturating_add(1).to_string()).collect()
+    }
+    if cells.len() > 1 && cells.iter().all(|coords| coords.1 == cells[0].1) {
+        let rows = digits(cells.iter().map(|coords| coords.0));
+        return format!("r{}c{}", rows, cells[0].1.saturating_add(1));
+    }
+    let mut groups = Vec::new();
+    for row in cells.chunk_by(|a, b| a.0 == b.0) {
+        let cols = digits(row.iter().map(|coords| coords.1));
+        groups.push(format!("r{}c{}", row[0].0.saturating_add(1), cols));
+    }
+    groups.join(",")
+}
+
+/// Combined names of houses of the same kind in the notation of Hodoku, such
+/// as `r25` for rows 2 and 5 or `b1` for the first box.
+fn hodoku_houses(houses: &[House]) -> String {
+    let mut name = String::new();
+    for house in houses {
+        let (kind, idx) = match house {
+            House::Row(row) => ('r', row),
+            House::Column(col) => ('c', col),
+            House::Square(sqr) => ('b', sqr),
+        };
+        if name.is_empty() {
+            name.push(kind);
+        }
+        name.push(char::from(b'1' + *idx as u8));
+    }
+    name
+}
+
+/// Name of a cell in the common notation, where `r1c2` is the cell in row 1
+/// and column 2.
+pub fn cell_name(coords: (usize, usize)) -> String {
+    format!(
+        "r{}c{}",
+        coords.0.saturating_add(1),
+        coords.1.saturating_add(1)
+    )
+}
+
+/// Whether two distinct cells share a row, column or square.
+pub fn sees(a: (usize, usize), b: (usize, usize)) -> bool {
+    a != b && (a.0 == b.0 || a.1 == b.1 || (a.0 / 3 == b.0 / 3 && a.1 / 3 == b.1 / 3))
+}
+
+/// Iterate over the values in a bitmask of values.
+fn digits(mask: u16) -> impl Iterator<Item = u8> {
+    (1..=9).filter(move |value| mask & (1 << (value - 1)) != 0)
+}
+
+/// Iterate over all combinations of `n` items out of `len`, as bitmasks.
+fn combinations(len: usize, n: u32) -> impl Iterator<Item = u32> {
+    (0..1u32 << len).filter(move |combination| combination.count_ones() == n)
+}
+
+/// A partially solved grid, tracking the candidates of unfilled cells like
+/// the pencil marks a human would make.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid {
+    values: [[u8; 9]; 9],
+    candidates: [[u16; 9]; 9],
+}
+
+impl Grid {
+    /// Create a grid with the values of a puzzle filled in, and all
+    /// candidates conflicting with them removed.
+    pub fn new(puzzle: &Puzzle) -> Result<Self, &'static str> {
+        let mut grid = Self {
+            values: [[0; 9]; 9],
+            candidates: [[0b1_1111_1111; 9]; 9],
+        };
+        for (row, values) in puzzle.cells.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                if *value != 0 {
+                    grid.place((row, col), *value)?;
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    /// The value of a cell, if filled.
+    pub fn value(&self, coords: (usize, usize)) -> Option<u8> {
+        check_cell(coords).ok()?;
+        match self.values[coords.0][coords.1] {
+            0 => None,
+            value => Some(value),
+        }
+    }
+
+    /// The candidates of a cell, which are none if the cell is filled.
+    pub fn candidates(&self, coords: (usize, usize)) -> impl Iterator<Item = u8> {
+        match check_cell(coords) {
+            Ok(()) => digits(self.candidates[coords.0][coords.1]),
+            Err(_) => digits(0),
+        }
+    }
+
+    /// Whether a value is a candidate of a cell.
+    pub fn has_candidate(&self, coords: (usize, usize), value: u8) -> bool {
+        check_cell(coords).is_ok()
+            && check_digit(value).is_ok()
+            && self.candidates[coords.0][coords.1] & (1 << (value - 1)) != 0
+    }
+
+    /// Whether all cells have been filled.
+    pub fn is_solved(&self) -> bool {
+        self.values.iter().flatten().all(|value| *value != 0)
+    }
+
+    /// The filled values of the grid as a puzzle.
+    pub fn to_puzzle(&self) -> Puzzle {
+        Puzzle { cells: self.values }
+    }
+
+    /// Fill a value into a cell, removing it as candidate from its peers.
+    pub fn place(&mut self, coords: (usize, usize), value: u8) -> Result<(), &'static str> {
+        check_cell(coords)?;
+        check_digit(value)?;
+        if self.value(coords).is_some() {
+            return Err("cannot change already filled in cell");
+        }
+        if !self.has_candidate(coords, value) {
+            return Err("value conflicts with a peer");
+        }
+        self.values[coords.0][coords.1] = value;
+        self.candidates[coords.0][coords.1] = 0;
+        for row in 0..9 {
+            for col in 0..9 {
+                if sees(coords, (row, col)) {
+                    self.eliminate((row, col), value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove a candidate from a cell, which does nothing if the cell is
+    /// outside of the grid or the value is not between 1 and 9.
+    pub fn eliminate(&mut self, coords: (usize, usize), value: u8) {
+        if check_cell(coords).is_ok() && check_digit(value).is_ok() {
+            self.candidates[coords.0][coords.1] &= !(1 << (value - 1));
+        }
+    }
+
+    /// Apply the placements and eliminations of a step.
+    pub fn apply(&mut self, step: &Step) -> Result<(), &'static str> {
+        for (coords, value) in &step.eliminations {
+            self.eliminate(*coords, *value);
+        }
+        for (coords, value) in &step.placements {
+            self.place(*coords, *value)?;
+        }
+        Ok(())
+    }
+
+    /// Find the next step using the easiest technique which makes progress.
+    /// Brute-forcing is not attempted, so `None` is returned if no technique
+    /// applies.
+    pub fn next_step(&self) -> Option<Step> {
+        Technique::ALL
+            .into_iter()
+            .find_map(|technique| self.find(technique))
+    }
+
+    /// Find a step using a specific technique, if it applies anywhere.
+    pub fn find(&self, technique: Technique) -> Option<Step> {
+        match technique {
+            Technique::NakedSingle => self.naked_single(),
+            Technique::HiddenSingle => self.hidden_single(),
+            Technique::Pointing => self.pointing(),
+            Technique::Claiming => self.claiming(),
+            Technique::NakedPair => self.naked_subset(Technique::NakedPair, 2),
+            Technique::HiddenPair => self.hidden_subset(Technique::HiddenPair, 2),
+            Technique::NakedTriple => self.naked_subset(Technique::NakedTriple, 3),
+            Technique::HiddenTriple => self.hidden_subset(Technique::HiddenTriple, 3),
+            Technique::XWing => self.fish(Technique::XWing, 2),
+            Technique::Swordfish => self.fish(Technique::Swordfish, 3),
+            Technique::XYWing => self.xy_wing(),
+            Technique::BruteForce => None,
+        }
+    }
+
+    /// Fill in the value of the solution in the unfilled cell with the least
+    /// candidates, for when no technique applies.
+    pub fn brute_force_step(&self, solution: &Solution) -> Option<Step> {
+        let coords = (0..9)
+            .flat_map(|row| (0..9).map(move |col| (row, col)))
+            .filter(|coords| self.value(*coords).is_none())
+            .min_by_key(|coords| self.candidates[coords.0][coords.1].count_ones())?;
+        let value = solution.cells[coords.0][coords.1];
+        let mut step = Step::new(Technique::BruteForce);
+        step.pattern.push(coords);
+        step.digits.push(value);
+        step.placements.push((coords, value));
+        Some(step)
+    }
+
+    /// The cells of a house having a value as candidate.
+    fn cells_with(&self, house: House, value: u8) -> Vec<(usize, usize)> {
+        house
+            .cells()
+            .into_iter()
+            .filter(|coords| self.has_candidate(*coords, value))
+            .collect()
+    }
+
+    /// A cell with only a single candidate left.
+    fn naked_single(&self) -> Option<Step> {
+        for row in 0..9 {
+            for col in 0..9 {
+                let candidates = self.candidates[row][col];
+                if candidates.count_ones() == 1 {
+                    let value = candidates.trailing_zeros() as u8 + 1;
+                    let mut step = Step::new(Technique::NakedSingle);
+                    step.pattern.push((row, col));
+                    step.digits.push(value);
+                    step.placements.push(((row, col), value));
+                    return Some(step);
+                }
+            }
+        }
+        None
+    }
+
+    /// A value which is a candidate of only a single cell in a house.
+    fn hidden_single(&self) -> Option<Step> {
+        for house in House::all() {
+            for value in 1..=9 {
+                if let [coords] = self.cells_with(house, value)[..] {
+                    let mut step = Step::new(Technique::HiddenSingle);
+                    step.pattern.push(coords);
+                    step.houses.push(house);
+                    step.digits.push(value);
+                    step.placements.push((coords, value));
+                    return Some(step);
+                }
+            }
+        }
+        None
+    }
+
+    /// The candidates of a value within a square all lie in a single row or
+    /// column, so the value can be removed from the rest of that line.
+    fn pointing(&self) -> Option<Step> {
+        for sqr in 0..9 {
+            for value in 1..=9 {
+                let cells = self.cells_with(House::Square(sqr), value);
+                if cells.len() < 2 {
+                    continue;
+                }
+                let line = if cells.iter().all(|coords| coords.0 == cells[0].0) {
+                    House::Row(cells[0].0)
+                } else if cells.iter().all(|coords| coords.1 == cells[0].1) {
+                    House::Column(cells[0].1)
+                } else {
+                    continue;
+                };
+                let step = self.locked_candidates(
+                    Technique::Pointing,
+                    cells,
+                    House::Square(sqr),
+                    line,
+                    value,
+                );
+                if step.is_some() {
+                    return step;
+                }
+            }
+        }
+        None
+    }
+
+    /// The candidates of a value within a row or column all lie in a single
+    /// square, so the value can be removed from the rest of that square.
+    fn claiming(&self) -> Option<Step> {
+        for line in (0..9).map(House::Row).chain((0..9).map(House::Column)) {
+            for value in 1..=9 {
+                let cells = self.cells_with(line, value);
+                if cells.len() < 2 {
+                    continue;
+                }
+                let square = House::Square(cells[0].0 / 3 * 3 + cells[0].1 / 3);
+                if !cells.iter().all(|coords| square.contains(*coords)) {
+                    continue;
+                }
+                let step = self.locked_candidates(Technique::Claiming, cells, line, square, value);
+                if step.is_some() {
+                    return step;
+                }
+            }
+        }
+        None
+    }
+
+    /// Remove a value from the cells of the target house outside of the
+    /// source house, in which all its candidates are locked.
+    fn locked_candidates(
+        &self,
+        technique: Technique,
+        cells: Vec<(usize, usize)>,
+        source: House,
+        target: House,
+        value: u8,
+    ) -> Option<Step> {
+        let eliminations: Vec<_> = self
+            .cells_with(target, value)
+            .into_iter()
+            .filter(|coords| !source.contains(*coords))
+            .map(|coords| (coords, value))
+            .collect();
+        if eliminations.is_empty() {
+            return None;
+        }
+        let mut step = Step::new(technique);
+        step.pattern = cells;
+        step.houses = vec![source, target];
+        step.digits.push(value);
+        step.eliminations = eliminations;
+        Some(step)
+    }
+
+    /// A group of `n` cells in a house which together have only `n`
+    /// candidates, which can thus be removed from the other cells.
+    fn naked_subset(&self, technique: Technique, n: u32) -> Option<Step> {
+        for house in House::all() {
+            let unfilled: Vec<_> = house
+                .cells()
+                .into_iter()
+                .filter(|coords| self.value(*coords).is_none())
+                .collect();
+            for combination in combinations(unfilled.len(), n) {
+                let subset: Vec<_> = (0..unfilled.len())
+                    .filter(|idx| combination & (1 << idx) != 0)
+                    .map(|idx| unfilled[idx])
+                    .collect();
+                let union = subset.iter().fold(0, |union, coords| {
+                    union | self.candidates[coords.0][coords.1]
+                });
+                if union.count_ones() != n {
+                    continue;
+                }
+                let eliminations: Vec<_> = unfilled
+                    .iter()
+                    .filter(|coords| !subset.contains(coords))
+                    .flat_map(|coords| {
+                        digits(self.candidates[coords.0][coords.1] & union)
+                            .map(move |value| (*coords, value))
+                    })
+                    .collect();
+                if eliminations.is_empty() {
+                    continue;
+                }
+                let mut step = Step::new(technique);
+                step.pattern = subset;
+                step.houses.push(house);
+                step.digits = digits(union).collect();
+                step.eliminations = eliminations;
+                return Some(step);
+            }
+        }
+        None
+    }
+
+    /// A group of `n` values in a house which are candidates of only `n`
+    /// cells, so all other candidates can be removed from those cells.
+    fn hidden_subset(&self, technique: Technique, n: u32) -> Option<Step> {
+        for house in House::all() {
+            let values: Vec<u8> = (1..=9)
+                .filter(|value| !self.cells_with(house, *value).is_empty())
+                .collect();
+            for combination in combinations(values.len(), n) {
+                let subset: Vec<u8> = (0..values.len())
+                    .filter(|idx| combination & (1 << idx) != 0)
+                    .map(|idx| values[idx])
+                    .collect();
+                let mut cells: Vec<_> = subset
+                    .iter()
+                    .flat_map(|value| self.cells_with(house, *value))
+                    .collect();
+                cells.sort();
+                cells.dedup();
+                if cells.len() != n as usize {
+                    continue;
+                }
+                let mask = subset
+                    .iter()
+                    .fold(0, |mask, value| mask | (1 << (value - 1)));
+                let eliminations: Vec<_> = cells
+                    .iter()
+                    .flat_map(|coords| {
+                        digits(self.candidates[coords.0][coords.1] & !mask)
+                            .map(move |value| (*coords, value))
+                    })
+                    .collect();
+                if eliminations.is_empty() {
+                    continue;
+                }
+                let mut step = Step::new(technique);
+                step.pattern = cells;
+                step.houses.push(house);
+                step.digits = subset;
+                step.eliminations = eliminations;
+                return Some(step);
+            }
+        }
+        None
+    }
+
+    /// A value whose candidates in `n` rows (or columns) all lie within `n`
+    /// columns (or rows), so it can be removed from the rest of those.
+    fn fish(&self, technique: Technique, n: u32) -> Option<Step> {
+        for value in 1..=9 {
+            for by_row in [true, false] {
+                let line = |idx| {
+                    if by_row {
+                        House::Row(idx)
+                    } else {
+                        House::Column(idx)
+                    }
+                };
+                let cover_idx = |coords: (usize, usize)| if by_row { coords.1 } else { coords.0 };
+
+                let bases: Vec<(usize, Vec<(usize, usize)>)> = (0..9)
+                    .map(|idx| (idx, self.cells_with(line(idx), value)))
+                    .filter(|(_, cells)| (2..=n as usize).contains(&cells.len()))
+                    .collect();
+                for combination in combinations(bases.len(), n) {
+                    let chosen: Vec<_> = (0..bases.len())
+                        .filter(|idx| combination & (1 << idx) != 0)
+                        .map(|idx| &bases[idx])
+                        .collect();
+                    let covers = chosen
+                        .iter()
+                        .flat_map(|(_, cells)| cells.iter())
+                        .fold(0u16, |covers, coords| covers | (1 << cover_idx(*coords)));
+                    if covers.count_ones() != n {
+                        continue;
+                    }
+                    let base_idxs: Vec<usize> = chosen.iter().map(|(idx, _)| *idx).collect();
+                    let cover_idxs: Vec<usize> =
+                        (0..9).filter(|idx| covers & (1 << idx) != 0).collect();
+                    let cover = |idx| {
+                        if by_row {
+                            House::Column(idx)
+                        } else {
+                            House::Row(idx)
+                        }
+                    };
+                    let eliminations: Vec<_> = cover_idxs
+                        .iter()
+                        .flat_map(|idx| self.cells_with(cover(*idx), value))
+                        .filter(|coords| {
+                            !base_idxs.contains(&if by_row { coords.0 } else { coords.1 })
+                        })
+                        .map(|coords| (coords, value))
+                        .collect();
+                    if eliminations.is_empty() {
+                        continue;
+                    }
+                    let mut step = Step::new(technique);
+                    step.pattern = chosen
+                        .iter()
+                        .flat_map(|(_, cells)| cells.iter().copied())
+                        .collect();
+                    step.houses = base_idxs.iter().map(|idx| line(*idx)).collect();
+                    step.houses.extend(cover_idxs.iter().map(|idx| cover(*idx)));
+                    step.digits.push(value);
+                    step.eliminations = eliminations;
+                    return Some(step);
+                }
+            }
+        }
+        None
+    }
+
+    /// A pivot cell with candidates `xy` seeing two pincer cells with
+    /// candidates `xz` and `yz`: either pincer must be `z`, so `z` can be
+    /// removed from all cells seeing both pincers.
+    fn xy_wing(&self) -> Option<Step> {
+        let bivalue: Vec<(usize, usize)> = (0..9)
+            .flat_map(|row| (0..9).map(move |col| (row, col)))
+            .filter(|coords| self.candidates[coords.0][coords.1].count_ones() == 2)
+            .collect();
+        for pivot in &bivalue {
+            let pivot_mask = self.candidates[pivot.0][pivot.1];
+            for a in bivalue.iter().filter(|a| sees(*pivot, **a)) {
+                let a_mask = self.candidates[a.0][a.1];
+                let shared = a_mask & pivot_mask;
+                if shared.count_ones() != 1 {
+                    continue;
+                }
+                let z_mask = a_mask & !pivot_mask;
+                for b in bivalue.iter().filter(|b| sees(*pivot, **b) && *b != a) {
+                    let b_mask = self.candidates[b.0][b.1];
+                    if b_mask != (pivot_mask & !shared) | z_mask {
+                        continue;
+                    }
+                    let z = z_mask.trailing_zeros() as u8 + 1;
+                    let eliminations: Vec<_> = (0..9)
+                        .flat_map(|row| (0..9).map(move |col| (row, col)))
+                        .filter(|coords| {
+                            coords != pivot
+                                && sees(*a, *coords)
+                                && sees(*b, *coords)
+                                && self.has_candidate(*coords, z)
+                        })
+                        .map(|coords| (coords, z))
+                        .collect();
+                    if eliminations.is_empty() {
+                        continue;
+                    }
+                    let mut step = Step::new(Technique::XYWing);
+                    step.pattern = vec![*pivot, *a, *b];
+                    step.digits = digits(pivot_mask | z_mask).collect();
+                    step.eliminations = eliminations;
+                    return Some(step);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Find the next step towards the solution of a partially filled puzzle,
+/// using the easiest technique which makes progress. If no technique applies,
+/// the step fills in a value of the solution instead.
+pub fn hint(puzzle: &Puzzle) -> Result<Step, &'static str> {
+    hint_with_options(puzzle, &Options::default())
+}
+
+/// Find the next step towards the solution of a partially filled puzzle like
+/// [`hint`], searching for the solution within the limits of the options
+/// when no technique applies.
+pub fn hint_with_options(puzzle: &Puzzle, options: &Options) -> Result<Step, &'static str> {
+    let grid = Grid::new(puzzle)?;
+    if grid.is_solved() {
+        return Err("puzzle is already solved");
+    }
+    match grid.next_step() {
+        Some(step) => Ok(step),
+        None => {
+            let solution = Solver::solve_with_options(puzzle, options)?;
+            grid.brute_force_step(&solution)
+                .ok_or("no unfilled cell was found")
+        }
+    }
+}
+
+/// Find the next step towards the solution of a partially filled puzzle like
+/// [`hint`], revealing only part of it depending on the level of detail.
+pub fn hint_at_level(puzzle: &Puzzle, level: HintLevel) -> Result<Hint, &'static str> {
+    hint(puzzle)?.hint(level)
+}
+
+/// Solve a puzzle step by step, always applying the easiest technique which
+/// makes progress. When no technique applies, a value of the solution is
+/// filled in as a brute-force step.
+pub fn solve(puzzle: &Puzzle) -> Result<Vec<Step>, &'static str> {
+    solve_with_options(puzzle, &Options::default())
+}
+
+/// Solve a puzzle step by step like [`solve`], searching for the solution
+/// within the limits of the options when no technique applies, such that
+/// pathological puzzles fail with the error of the exceeded limit.
+pub fn solve_with_options(puzzle: &Puzzle, options: &Options) -> Result<Vec<Step>, &'static str> {
+    let mut grid = Grid::new(puzzle)?;
+    let mut solution = None;
+    let mut steps = Vec::new();
+    while !grid.is_solved() {
+        let step = match grid.next_step() {
+            Some(step) => step,
+            None => {
+                let solution = match &solution {
+                    Some(solution) => solution,
+                    None => solution.insert(Solver::solve_with_options(puzzle, options)?),
+                };
+                grid.brute_force_step(solution)
+                    .ok_or("no unfilled cell was found")?
+            }
+        };
+        grid.apply(&step)?;
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+/// Find the positions in the logical solve of a puzzle in which a technique
+/// is the key next step, as examples for tutorials and quizzes. Each
+/// position is a partially filled grid in which the technique is the easiest
+/// one making progress without relying on earlier eliminations, returned
+/// along with the step taken using it.
+pub fn examples(
+    puzzle: &Puzzle,
+    technique: Technique,
+) -> Result<Vec<(Puzzle, Step)>, &'static str> {
+    let mut grid = Grid::new(puzzle)?;
+    let mut examples: Vec<(Puzzle, Step)> = Vec::new();
+    for step in solve(puzzle)? {
+        let position = grid.to_puzzle();
+        let seen = examples.last().is_some_and(|(last, _)| *last == position);
+        if step.technique == technique && !seen {
+            let key = hint(&position)?;
+            if key.technique == technique {
+                examples.push((position, key));
+            }
+        }
+        grid.apply(&step)?;
+    }
+    Ok(examples)
+}
+
+/// Solve a puzzle like [`solve`], returning the events of all steps in order
+/// such that user interfaces can animate the solve.
+pub fn solve_events(puzzle: &Puzzle) -> Result<Vec<SolveEvent>, &'static str> {
+    let steps = solve(puzzle)?;
+    Ok(steps
+        .iter()
+        .enumerate()
+        .flat_map(|(index, step)| step.events(index))
+        .collect())
+}
+
+/// Why a candidate was removed from a cell during a logical solve. Steps are
+/// numbered by their index in the solve, and are `None` for the givens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Elimination {
+    /// The cell itself was filled with another value.
+    Filled { step: Option<usize> },
+    /// A peer of the cell was filled with the value.
+    Peer {
+        peer: (usize, usize),
+        step: Option<usize>,
+    },
+    /// A step of a technique removed the candidate.
+    Technique { step: usize, technique: Technique },
+}
+
+impl fmt::Display for Elimination {
+    /// Describe why the candidate was removed, numbering steps from one.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Elimination::Filled { .. } => write!(fmt, "the cell is filled with another value"),
+            Elimination::Peer { peer, .. } => write!(fmt, "{} has the value", cell_name(*peer)),
+            Elimination::Technique { step, technique } => {
+                write!(fmt, "removed by {} in step {}", technique, step + 1)
+            }
+        }
+    }
+}
+
+/// The logical solve of a puzzle along with why each candidate was removed,
+/// such that questions like "why can't this cell be 7?" can be answered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Provenance {
+    steps: Vec<Step>,
+    /// Cause of the removal of each candidate of each cell, if removed.
+    causes: [[[Option<Elimination>; 9]; 9]; 9],
+}
+
+impl Provenance {
+    /// Solve a puzzle logically like [`solve`], recording the first cause of
+    /// the removal of each candidate.
+    pub fn new(puzzle: &Puzzle) -> Result<Self, &'static str> {
+        let mut provenance = Self {
+            steps: solve(puzzle)?,
+            causes: [[[None; 9]; 9]; 9],
+        };
+        let mut grid = Grid::new(&Puzzle { cells: [[0; 9]; 9] })?;
+        for (row, values) in puzzle.cells.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                if *value != 0 {
+                    provenance.place(&mut grid, (row, col), *value, None)?;
+                }
+            }
+        }
+        for index in 0..provenance.steps.len() {
+            let step = provenance.steps[index].clone();
+            for (coords, value) in &step.eliminations {
+                if grid.has_candidate(*coords, *value) {
+                    provenance.causes[coords.0][coords.1][*value as usize - 1] =
+                        Some(Elimination::Technique {
+                            step: index,
+                            technique: step.technique,
+                        });
+                    grid.eliminate(*coords, *value);
+                }
+            }
+            for (coords, value) in &step.placements {
+                provenance.place(&mut grid, *coords, *value, Some(index))?;
+            }
+        }
+        Ok(provenance)
+    }
+
+    /// Fill a value into a cell of the grid, recording the removal of the
+    /// other candidates of the cell and of the value from its peers.
+    fn place(
+        &mut self,
+        grid: &mut Grid,
+        coords: (usize, usize),
+        value: u8,
+        step: Option<usize>,
+    ) -> Result<(), &'static str> {
+        for other in grid.candidates(coords).filter(|other| *other != value) {
+            self.causes[coords.0][coords.1][other as usize - 1] =
+                Some(Elimination::Filled { step });
+        }
+        for row in 0..9 {
+            for col in 0..9 {
+                if sees(coords, (row, col)) && grid.has_candidate((row, col), value) {
+                    self.causes[row][col][value as usize - 1] =
+                        Some(Elimination::Peer { peer: coords, step });
+                }
+            }
+        }
+        grid.place(coords, value)
+    }
+
+    /// The steps of the logical solve.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Why a value is not a candidate of a cell, if it was removed. Only
+    /// the value of the solution of a cell is never removed.
+    pub fn cause(&self, coords: (usize, usize), value: u8) -> Option<Elimination> {
+        let causes = self.causes.get(coords.0)?.get(coords.1)?;
+        *causes.get(usize::from(value).checked_sub(1)?)?
+    }
+
+    /// The candidates removed from a cell up to but excluding a step, along
+    /// with why, such as for explaining the pencil marks partway through.
+    pub fn removed_before(
+        &self,
+        coords: (usize, usize),
+        step: usize,
+    ) -> impl Iterator<Item = (u8, Elimination)> + '_ {
+        (1..=9).filter_map(move |value| {
+            let cause = self.cause(coords, value)?;
+            let index = match cause {
+                Elimination::Filled { step } | Elimination::Peer { step, .. } => step,
+                Elimination::Technique { step, .. } => Some(step),
+            };
+            index
+                .is_none_or(|index| index < step)
+                .then_some((value, cause))
+        })
+    }
+}
@@ -1,54 +1,11 @@
-use core::panic;
-use std::fs::File;
-use std::io::{prelude::*, BufReader};
-use std::path::Path;
-use std::{env, fs};
-use sudoku_solver::Solver;
+mod cli;
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        panic!("No input file specified!")
-    }
-    if args.len() > 3 {
-        panic!("Too many arguments specified!")
-    }
-
-    let input = args.get(1).unwrap();
-    let output = args.get(2);
-
-    let input_file = File::open(input).unwrap();
-    let mut output_file = if let Some(output) = output {
-        if Path::new(output).exists() {
-            fs::remove_file(output).unwrap();
-        }
-        Some(File::create(output).unwrap())
-    } else {
-        None
-    };
-
-    let mut num_solved = 0;
-    let mut num_brute_forces = 0;
+use cli::Cli;
+use std::process;
 
-    let reader = BufReader::new(input_file);
-    for line in reader.lines() {
-        let puzzle: Vec<char> = line.unwrap().chars().collect();
-        let solution = Solver::solve(puzzle).unwrap();
-
-        num_solved += 1;
-        num_brute_forces += solution.brute_forces;
-
-        if let Some(output_file) = &mut output_file {
-            let _ = output_file
-                .write((solution.row_representation() + "\n").as_bytes())
-                .unwrap();
-        }
-    }
-
-    println!("Input file: {}", input);
-    if let Some(output) = output {
-        println!("Output file: {}", output);
+fn main() {
+    if let Err(err) = Cli::parse_with_config().and_then(Cli::run) {
+        eprintln!("error: {}", err);
+        process::exit(err.exit_code());
     }
-    println!("Total solved: {}", num_solved);
-    println!("Total brute-force fills: {}", num_brute_forces)
 }
@@ -0,0 +1,227 @@
+//! Lists of moves in the common notation for cells, such as `r1c3=5, r4c7<>2`
+//! to place a 5 in `r1c3` and remove 2 as candidate from `r4c7`, such that
+//! solve logs of this crate and of other tools can be replayed and verified.
+//!
+//! Cells in the same row or column may be combined as in the notation of
+//! Hodoku, such as `r1c47<>5` for `r1c4` and `r1c7`, and groups of cells may
+//! be joined by commas, such as `r1c4,r2c5<>3`. Eliminations may remove
+//! several candidates at once, such as `r1c3<>25`.
+
+use crate::logic::{cell_name, Grid};
+use crate::{check_cell, Options, Solver};
+use std::fmt;
+use std::str::FromStr;
+
+/// A single placement or elimination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    /// Fill a value into a cell.
+    Place((usize, usize), u8),
+    /// Remove a candidate from a cell.
+    Eliminate((usize, usize), u8),
+}
+
+impl Move {
+    /// The cell of the move.
+    pub fn cell(self) -> (usize, usize) {
+        match self {
+            Move::Place(cell, _) | Move::Eliminate(cell, _) => cell,
+        }
+    }
+
+    /// The value placed or eliminated by the move.
+    pub fn digit(self) -> u8 {
+        match self {
+            Move::Place(_, digit) | Move::Eliminate(_, digit) => digit,
+        }
+    }
+
+    /// Apply the move to a grid. Eliminating a candidate which was already
+    /// removed has no effect, as logs need not track every candidate.
+    pub fn apply(self, grid: &mut Grid) -> Result<(), &'static str> {
+        match self {
+            Move::Place(cell, digit) => grid.place(cell, digit),
+            Move::Eliminate(cell, digit) if grid.value(cell) == Some(digit) => {
+                Err("cannot eliminate the value of a filled in cell")
+            }
+            Move::Eliminate(cell, digit) => {
+                grid.eliminate(cell, digit);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    /// Write the move in the common notation, such as `r1c3=5` or `r4c7<>2`.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Move::Place(cell, digit) => write!(fmt, "{}={}", cell_name(*cell), digit),
+            Move::Eliminate(cell, digit) => write!(fmt, "{}<>{}", cell_name(*cell), digit),
+        }
+    }
+}
+
+impl FromStr for Move {
+    type Err = &'static str;
+
+    /// Parse a single move of a single cell and digit.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse(s)?.as_slice() {
+            [single] => Ok(*single),
+            _ => Err("expected a single move"),
+        }
+    }
+}
+
+/// Parse a list of moves separated by commas. Moves combining several cells
+/// or digits are expanded into a move for each, in the order written.
+pub fn parse(list: &str) -> Result<Vec<Move>, &'static str> {
+    let mut moves = Vec::new();
+    let mut cells = Vec::new();
+    for token in list.split(',') {
+        let token: String = token.chars().filter(|c| !c.is_whitespace()).collect();
+        if token.is_empty() {
+            return Err("empty move");
+        }
+        let (spec, digits, place) = if let Some((spec, digits)) = token.split_once("<>") {
+            (spec, digits, false)
+        } else if let Some((spec, digits)) = token.split_once('=') {
+            (spec, digits, true)
+        } else {
+            // A group of cells which shares the effect of the next token.
+            cells.extend(parse_cells(&token)?);
+            continue;
+        };
+        cells.extend(parse_cells(spec)?);
+        let digits = parse_digits(digits)?;
+        if place && digits.len() != 1 {
+            return Err("placement must have a single digit");
+        }
+        for cell in cells.drain(..) {
+            for digit in &digits {
+                moves.push(match place {
+                    true => Move::Place(cell, *digit),
+                    false => Move::Eliminate(cell, *digit),
+                });
+            }
+        }
+    }
+    if !cells.is_empty() {
+        return Err("cells without placement or elimination");
+    }
+    Ok(moves)
+}
+
+/// Parse a script of moves, such as a solve log, of which each line holds a
+/// list of moves. Only the effects of logged steps are read, which follow the
+/// last colon or `=>` of a line, as in `Naked Single: r1c4=2` or
+/// `Locked Candidates Type 1 (Pointing): 5 in b1 => r1c47<>5`. Lines
+/// without any move, such as headers, and lines starting with `#` are
+/// skipped.
+pub fn parse_script(script: &str) -> Result<Vec<Move>, &'static str> {
+    let mut moves = Vec::new();
+    for line in script.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        let effects = line.rsplit(':').next().unwrap_or_default();
+        let effects = effects.rsplit("=>").next().unwrap_or_default();
+        if !effects.contains('=') && !effects.contains("<>") {
+            continue;
+        }
+        moves.extend(parse(effects)?);
+    }
+    Ok(moves)
+}
+
+/// Parse a group of cells such as `r1c47`, combining every row with every
+/// column.
+pub fn parse_cells(spec: &str) -> Result<Vec<(usize, usize)>, &'static str> {
+    let spec = spec.to_ascii_lowercase();
+    let (rows, cols) = spec
+        .strip_prefix('r')
+        .and_then(|spec| spec.split_once('c'))
+        .ok_or("invalid cell, expected one such as r1c2")?;
+    let rows = parse_digits(rows)?;
+    let cols = parse_digits(cols)?;
+    let mut cells = Vec::new();
+    for row in &rows {
+        for col in &cols {
+            cells.push((*row as usize - 1, *col as usize - 1));
+        }
+    }
+    Ok(cells)
+}
+
+/// Parse a non-empty sequence of digits from 1 to 9.
+fn parse_digits(digits: &str) -> Result<Vec<u8>, &'static str> {
+    if digits.is_empty() {
+        return Err("missing digit");
+    }
+    digits
+        .bytes()
+        .map(|byte| match byte {
+            b'1'..=b'9' => Ok(byte - b'0'),
+            _ => Err("invalid digit, expected 1 to 9"),
+        })
+        .collect()
+}
+
+/// A move which could not be replayed, along with the reason.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReplayError {
+    /// Index of the move in the list, starting from zero.
+    pub index: usize,
+    pub failed: Move,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "move {} ({}): {}",
+            self.index + 1,
+            self.failed,
+            self.reason
+        )
+    }
+}
+
+/// Replay moves on a grid in order. Each move must be possible in the grid,
+/// and if its values have a unique solution, placements must agree with it
+/// and eliminations must not remove its values, such that a log of wrong
+/// deductions is rejected at its first wrong move. The moves before the
+/// failing one remain applied.
+pub fn replay(grid: &mut Grid, moves: &[Move]) -> Result<(), ReplayError> {
+    let puzzle = grid.to_puzzle();
+    let solution = match Solver::count_solutions(&puzzle, 2) {
+        1 => Solver::solve_with_options(&puzzle, &Options::default()).ok(),
+        _ => None,
+    };
+    for (index, current) in moves.iter().enumerate() {
+        let error = |reason| ReplayError {
+            index,
+            failed: *current,
+            reason,
+        };
+        check_cell(current.cell()).map_err(error)?;
+        if let Some(solution) = &solution {
+            let (row, col) = current.cell();
+            let correct = solution.cells()[row][col] == current.digit();
+            match current {
+                Move::Place(..) if !correct => {
+                    return Err(error("placement differs from the solution"));
+                }
+                Move::Eliminate(..) if correct => {
+                    return Err(error("elimination removes the value of the solution"));
+                }
+                _ => {}
+            }
+        }
+        current.apply(grid).map_err(error)?;
+    }
+    Ok(())
+}
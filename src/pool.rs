@@ -0,0 +1,209 @@
+//! A pool of worker threads solving puzzles from a bounded queue, such that
+//! server applications can embed the solver with backpressure instead of
+//! spawning a thread for every request.
+
+use crate::{Options, Puzzle, Solution, Solver};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Error of submitting a job when the queue of the pool is full.
+pub const QUEUE_FULL: &str = "queue of solver pool is full";
+
+/// Configuration of a [`SolverPool`].
+#[derive(Clone, Debug)]
+pub struct PoolOptions {
+    /// Number of worker threads, which defaults to the available parallelism.
+    pub threads: usize,
+    /// Number of jobs which may wait in the queue, beyond which submitting
+    /// blocks or fails.
+    pub queue_capacity: usize,
+    /// Options of jobs submitted without their own, such as to limit the
+    /// guesses or time spent on each puzzle. A timeout starts once a worker
+    /// takes the job from the queue.
+    pub options: Options,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            queue_capacity: 64,
+            options: Options::default(),
+        }
+    }
+}
+
+/// Result of a job, which is received once a worker has solved its puzzle.
+pub type Pending = Receiver<Result<Solution, &'static str>>;
+
+/// A puzzle waiting to be solved, along with where to send its result.
+struct Job {
+    puzzle: Puzzle,
+    options: Options,
+    result: SyncSender<Result<Solution, &'static str>>,
+}
+
+/// A fixed number of worker threads solving the puzzles submitted to a
+/// bounded queue. Dropping the pool lets the workers finish the jobs in the
+/// queue, and waits for them.
+pub struct SolverPool {
+    queue: Option<SyncSender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    options: Options,
+}
+
+impl SolverPool {
+    /// Start the worker threads of a pool.
+    pub fn new(pool_options: PoolOptions) -> Self {
+        let (queue, jobs) = mpsc::sync_channel::<Job>(pool_options.queue_capacity);
+        let jobs = Arc::new(Mutex::new(jobs));
+        let workers = (0..pool_options.threads.max(1))
+            .map(|_| {
+                let jobs = Arc::clone(&jobs);
+                thread::spawn(move || work(&jobs))
+            })
+            .collect();
+        Self {
+            queue: Some(queue),
+            workers,
+            options: pool_options.options,
+        }
+    }
+
+    /// Submit a puzzle to be solved with the options of the pool, blocking
+    /// while the queue is full.
+    pub fn submit(&self, puzzle: Puzzle) -> Pending {
+        self.submit_with_options(puzzle, self.options.clone())
+    }
+
+    /// Submit a puzzle to be solved with its own options, blocking while the
+    /// queue is full.
+    pub fn submit_with_options(&self, puzzle: Puzzle, options: Options) -> Pending {
+        let (job, pending) = job(puzzle, options);
+        if let Err(mpsc::SendError(job)) = self.queue().send(job) {
+            let _ = job.result.send(Err("solver pool has stopped"));
+        }
+        pending
+    }
+
+    /// Submit a puzzle to be solved with the options of the pool, failing
+    /// with [`QUEUE_FULL`] instead of blocking while the queue is full, such
+    /// that a server can reject requests under load.
+    pub fn try_submit(&self, puzzle: Puzzle) -> Result<Pending, &'static str> {
+        self.try_submit_with_options(puzzle, self.options.clone())
+    }
+
+    /// Submit a puzzle to be solved with its own options, failing with
+    /// [`QUEUE_FULL`] instead of blocking while the queue is full.
+    pub fn try_submit_with_options(
+        &self,
+        puzzle: Puzzle,
+        options: Options,
+    ) -> Result<Pending, &'static str> {
+        let (job, pending) = job(puzzle, options);
+        match self.queue().try_send(job) {
+            Ok(()) => Ok(pending),
+            Err(TrySendError::Full(_)) => Err(QUEUE_FULL),
+            Err(TrySendError::Disconnected(_)) => Err("solver pool has stopped"),
+        }
+    }
+
+    /// Number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    fn queue(&self) -> &SyncSender<Job> {
+        self.queue
+            .as_ref()
+            .expect("queue is open until the pool is dropped")
+    }
+}
+
+impl Drop for SolverPool {
+    fn drop(&mut self) {
+        // Closing the queue stops the workers once it is empty.
+        self.queue = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Results of a stream of puzzles, each along with the index of its puzzle in
+/// the order of sending, starting from zero.
+pub type Results = Receiver<(usize, Result<Solution, &'static str>)>;
+
+/// Solve a stream of puzzles on worker threads, such that pipelines can
+/// overlap parsing, solving and writing. Puzzles sent into the returned
+/// sender are solved with the options of the pool, and their results come out
+/// of the returned receiver as they complete, which may differ from the order
+/// of sending. Both channels hold at most the capacity of the queue, such
+/// that sending blocks while the results are not received. The workers stop
+/// once the sender is dropped and the remaining puzzles are solved, or once
+/// the receiver is dropped.
+pub fn solve_stream(pool_options: PoolOptions) -> (SyncSender<Puzzle>, Results) {
+    let (sender, puzzles) = mpsc::sync_channel::<Puzzle>(pool_options.queue_capacity);
+    let (results, receiver) = mpsc::sync_channel(pool_options.queue_capacity);
+    // The index is taken along with the puzzle, so the puzzles are numbered in
+    // the order of sending.
+    let puzzles = Arc::new(Mutex::new((puzzles, 0)));
+    for _ in 0..pool_options.threads.max(1) {
+        let puzzles = Arc::clone(&puzzles);
+        let results = results.clone();
+        let options = pool_options.options.clone();
+        thread::spawn(move || {
+            let mut solver = Solver::new();
+            loop {
+                let next = match puzzles.lock() {
+                    Ok(mut puzzles) => puzzles.0.recv().map(|puzzle| {
+                        puzzles.1 += 1;
+                        (puzzles.1 - 1, puzzle)
+                    }),
+                    Err(_) => return,
+                };
+                let Ok((idx, puzzle)) = next else {
+                    return;
+                };
+                let result = solver.load(&puzzle).and_then(|_| solver.run(&options));
+                if results.send((idx, result)).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+    (sender, receiver)
+}
+
+/// Create a job along with the receiver of its result.
+fn job(puzzle: Puzzle, options: Options) -> (Job, Pending) {
+    let (result, pending) = mpsc::sync_channel(1);
+    let job = Job {
+        puzzle,
+        options,
+        result,
+    };
+    (job, pending)
+}
+
+/// Solve jobs from the queue until it is closed and empty, reusing a single
+/// solver for all of them.
+fn work(jobs: &Mutex<Receiver<Job>>) {
+    let mut solver = Solver::new();
+    loop {
+        // The lock is released before solving, so the others can take jobs.
+        let job = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            Err(_) => return,
+        };
+        let Ok(job) = job else {
+            return;
+        };
+        let result = solver
+            .load(&job.puzzle)
+            .and_then(|_| solver.run(&job.options));
+        // The submitter may no longer be waiting for the result.
+        let _ = job.result.send(result);
+    }
+}
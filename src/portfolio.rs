@@ -0,0 +1,100 @@
+//! Portfolio solving, which races several configurations of the search on
+//! the same puzzle and takes the first to finish, as no single order of
+//! guessing is fastest on every pathological puzzle.
+//!
+//! The configurations are transformations of the puzzle, such as rotations
+//! and relabelings of its values. The solver breaks ties between cells and
+//! values by their position and label, so each transformation leads the
+//! search down a different path, while the solution of a transformed puzzle
+//! maps back to one of the original.
+
+use crate::random::Rng;
+use crate::transform::Transformation;
+use crate::{Budget, Options, Puzzle, Solution, Solver};
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+
+/// A number of configurations for racing on separate threads, starting with
+/// the identity followed by random transformations. The same seed gives the
+/// same configurations.
+pub fn configurations(count: usize, seed: u64) -> Vec<Transformation> {
+    let mut rng = Rng::new(seed);
+    (0..count)
+        .map(|idx| match idx {
+            0 => Transformation::identity(),
+            _ => Transformation::random(&mut rng),
+        })
+        .collect()
+}
+
+/// Solve a puzzle by racing each configuration on its own thread, returning
+/// the index of the first configuration to finish along with its solution,
+/// after cancelling the others. The options apply to each configuration
+/// separately, except for exploring in parallel, which is not done. Puzzles
+/// with multiple solutions may be solved differently by each configuration,
+/// so which solution is returned then depends on timing. If no configuration
+/// finds a solution, the error of the first is returned.
+pub fn solve_portfolio(
+    puzzle: &Puzzle,
+    options: &Options,
+    configurations: &[Transformation],
+) -> Result<(usize, Solution), &'static str> {
+    if configurations.is_empty() {
+        return Err("portfolio has no configurations");
+    }
+    let budget = Budget::new(options);
+    let cancelled = AtomicBool::new(false);
+    let winner = OnceLock::new();
+    let errors: Vec<&'static str> = thread::scope(|scope| {
+        let racers: Vec<_> = configurations
+            .iter()
+            .enumerate()
+            .map(|(idx, transformation)| {
+                let (budget, cancelled, winner) = (&budget, &cancelled, &winner);
+                scope.spawn(move || {
+                    let solution = race(&transformation.apply(puzzle), budget, cancelled)?;
+                    let cells = transformation.inverse().apply(&Puzzle {
+                        cells: solution.cells,
+                    });
+                    let solution = Solution {
+                        cells: cells.cells,
+                        ..solution
+                    };
+                    if winner.set((idx, solution)).is_ok() {
+                        cancelled.store(true, Ordering::Relaxed);
+                    }
+                    Ok(())
+                })
+            })
+            .collect();
+        racers
+            .into_iter()
+            .filter_map(|racer| match racer.join() {
+                Ok(result) => result.err(),
+                Err(_) => Some("configuration of portfolio panicked"),
+            })
+            .collect()
+    });
+    match winner.into_inner() {
+        Some(winner) => Ok(winner),
+        None => Err(errors
+            .first()
+            .copied()
+            .unwrap_or("no configuration finished")),
+    }
+}
+
+/// Solve a puzzle until solved or cancelled.
+fn race(
+    puzzle: &Puzzle,
+    budget: &Budget,
+    cancelled: &AtomicBool,
+) -> Result<Solution, &'static str> {
+    let mut solver = Solver::new();
+    solver.load(puzzle)?;
+    if solver.unfilled_cells > 0 {
+        solver.brute_force(cancelled, budget, &mut ())?;
+    }
+    Ok(solver.solution())
+}
@@ -0,0 +1,43 @@
+//! Small pseudo-random number generator, such that randomized tasks like
+//! transforming or generating puzzles are reproducible from a seed without
+//! depending on an external crate. It is not suitable for cryptography.
+
+/// Pseudo-random number generator based on SplitMix64.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a generator which always produces the same numbers for a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Generate the next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Generate a random number in the range `0..bound`, or zero if the
+    /// bound is zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % (bound as u64).max(1)) as usize
+    }
+
+    /// Generate a random boolean.
+    pub fn coin(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    /// Shuffle a slice in place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.below(idx + 1));
+        }
+    }
+}
@@ -0,0 +1,520 @@
+//! Rating of the difficulty of puzzles, based on the techniques needed to
+//! solve them step by step.
+
+use crate::cell_set::{CellSet, HOUSES, PEERS};
+use crate::logic::{self, Step, Technique};
+use crate::{cell_to_houses, Puzzle};
+use std::fmt;
+use std::str::FromStr;
+
+/// Difficulty tier of a puzzle, as determined by the hardest technique
+/// required to solve it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    /// Only singles are required.
+    Easy,
+    /// Candidates locked within a house are required.
+    Medium,
+    /// Naked or hidden subsets are required.
+    Hard,
+    /// Fish or wings are required.
+    Expert,
+    /// None of the known techniques suffice, so brute-forcing is required.
+    Extreme,
+}
+
+impl Difficulty {
+    /// All difficulties, from easiest to hardest.
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+        Difficulty::Extreme,
+    ];
+
+    /// Human-readable name of the difficulty.
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+            Difficulty::Extreme => "Extreme",
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = &'static str;
+
+    /// Parse the name of a difficulty, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(s))
+            .ok_or("unknown difficulty")
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.name())
+    }
+}
+
+/// The rated difficulty of a puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    /// Sum of the scores of the techniques of all steps.
+    pub score: u32,
+    /// The hardest technique required, or `None` if the puzzle was complete.
+    pub hardest: Option<Technique>,
+    /// Number of steps taken to solve the puzzle.
+    pub steps: usize,
+}
+
+impl Rating {
+    /// Rate a puzzle by the steps taken to solve it.
+    pub fn from_steps(steps: &[Step]) -> Self {
+        let hardest = steps.iter().map(|step| step.technique).max();
+        Self {
+            difficulty: hardest.map_or(Difficulty::Easy, Technique::difficulty),
+            score: steps.iter().map(|step| step.technique.score()).sum(),
+            hardest,
+            steps: steps.len(),
+        }
+    }
+}
+
+/// Rate the difficulty of a puzzle.
+pub fn rate(puzzle: &Puzzle) -> Result<Rating, &'static str> {
+    let steps = logic::solve(puzzle)?;
+    Ok(Rating::from_steps(&steps))
+}
+
+/// A difficulty scale fitted to puzzles which people have labeled, such as
+/// the scale of an existing app. Puzzles are scored by the weights of the
+/// techniques of their steps, and get the level whose range of scores holds
+/// their score.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Calibration {
+    /// Weight of each technique, in the order of [`Technique::ALL`].
+    weights: [u32; Technique::ALL.len()],
+    /// Labels of the levels, from easiest to hardest.
+    labels: Vec<String>,
+    /// Lowest score of each level but the first.
+    thresholds: Vec<u32>,
+}
+
+/// Factors by which the weight of a technique is tried to be scaled while
+/// fitting, as numerator and denominator.
+const FACTORS: [(u32, u32); 4] = [(1, 2), (4, 5), (5, 4), (2, 1)];
+
+/// Largest weight of a technique when fitting.
+const MAX_WEIGHT: u32 = 1_000_000;
+
+/// Maximum number of rounds of adjusting the weights when fitting.
+const MAX_ROUNDS: usize = 50;
+
+impl Calibration {
+    /// Make a calibration from the weight of each technique in the order of
+    /// [`Technique::ALL`], the labels of the levels from easiest to hardest,
+    /// and the lowest score of each level but the first.
+    pub fn new(
+        weights: [u32; Technique::ALL.len()],
+        labels: Vec<String>,
+        thresholds: Vec<u32>,
+    ) -> Result<Self, &'static str> {
+        if labels.is_empty() {
+            return Err("no levels");
+        }
+        if thresholds.len() + 1 != labels.len() {
+            return Err("expected a threshold for every level but the first");
+        }
+        if thresholds.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err("thresholds of harder levels are lower");
+        }
+        Ok(Self {
+            weights,
+            labels,
+            thresholds,
+        })
+    }
+
+    /// Fit a calibration to sample puzzles, given by their steps along with
+    /// the index of their label among the labels of the levels, which are
+    /// from easiest to hardest. Starting from the scores of the techniques,
+    /// their weights are adjusted as long as that brings the levels of the
+    /// samples closer to their labels, with the thresholds placed between
+    /// the scores of the samples such that the levels are closest. Every
+    /// level needs samples, as its threshold would be arbitrary otherwise.
+    pub fn fit(labels: Vec<String>, samples: &[(Vec<Step>, usize)]) -> Result<Self, &'static str> {
+        if labels.is_empty() {
+            return Err("no levels");
+        }
+        if samples.iter().any(|(_, label)| *label >= labels.len()) {
+            return Err("label of sample out of range");
+        }
+        if (0..labels.len()).any(|level| samples.iter().all(|(_, label)| *label != level)) {
+            return Err("a level has no samples");
+        }
+        let samples: Vec<([u32; Technique::ALL.len()], usize)> = samples
+            .iter()
+            .map(|(steps, label)| {
+                let mut counts = [0u32; Technique::ALL.len()];
+                for step in steps {
+                    counts[step.technique as usize] += 1;
+                }
+                (counts, *label)
+            })
+            .collect();
+        let mut weights = Technique::ALL.map(Technique::score);
+        let (mut thresholds, mut distance) = fit_thresholds(&weights, &samples, labels.len());
+        for _ in 0..MAX_ROUNDS {
+            let mut improved = false;
+            for technique in 0..weights.len() {
+                if samples.iter().all(|(counts, _)| counts[technique] == 0) {
+                    continue;
+                }
+                for (numerator, denominator) in FACTORS {
+                    let mut candidate = weights;
+                    candidate[technique] = (weights[technique].saturating_mul(numerator)
+                        / denominator)
+                        .clamp(1, MAX_WEIGHT);
+                    if candidate[technique] == weights[technique] {
+                        continue;
+                    }
+                    let (fitted, fitted_distance) =
+                        fit_thresholds(&candidate, &samples, labels.len());
+                    if fitted_distance < distance {
+                        weights = candidate;
+                        thresholds = fitted;
+                        distance = fitted_distance;
+                        improved = true;
+                    }
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        Self::new(weights, labels, thresholds)
+    }
+
+    /// Weight of each technique, in the order of [`Technique::ALL`].
+    pub fn weights(&self) -> &[u32; Technique::ALL.len()] {
+        &self.weights
+    }
+
+    /// Labels of the levels, from easiest to hardest.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Lowest score of each level but the first.
+    pub fn thresholds(&self) -> &[u32] {
+        &self.thresholds
+    }
+
+    /// Score of a puzzle by the steps taken to solve it.
+    pub fn score(&self, steps: &[Step]) -> u32 {
+        steps
+            .iter()
+            .map(|step| self.weights[step.technique as usize])
+            .fold(0, u32::saturating_add)
+    }
+
+    /// Index of the level of a score among the labels.
+    pub fn level(&self, score: u32) -> usize {
+        self.thresholds
+            .partition_point(|&threshold| threshold <= score)
+    }
+
+    /// Label of the level of a score.
+    pub fn label(&self, score: u32) -> &str {
+        &self.labels[self.level(score)]
+    }
+}
+
+/// Place the thresholds between the levels for the given weights, such that
+/// the total distance between the levels of the samples and their labels is
+/// smallest, returning the thresholds along with that distance.
+fn fit_thresholds(
+    weights: &[u32; Technique::ALL.len()],
+    samples: &[([u32; Technique::ALL.len()], usize)],
+    levels: usize,
+) -> (Vec<u32>, u64) {
+    let mut scored: Vec<(u32, usize)> = samples
+        .iter()
+        .map(|(counts, label)| {
+            let score = counts
+                .iter()
+                .zip(weights)
+                .map(|(count, weight)| count.saturating_mul(*weight))
+                .fold(0, u32::saturating_add);
+            (score, *label)
+        })
+        .collect();
+    scored.sort_unstable();
+    // Samples of equal scores are of the same level, so they are grouped
+    // with the number of samples of each label.
+    let mut groups: Vec<(u32, Vec<u64>)> = Vec::new();
+    for (score, label) in scored {
+        match groups.last_mut() {
+            Some((last, counts)) if *last == score => counts[label] += 1,
+            _ => {
+                let mut counts = vec![0; levels];
+                counts[label] += 1;
+                groups.push((score, counts));
+            }
+        }
+    }
+    // The smallest distance of the groups so far with the last one being of
+    // each level, along with the level of the group before it. Levels do not
+    // decrease as scores increase.
+    let mut distances = vec![0u64; levels];
+    let mut previous = Vec::with_capacity(groups.len());
+    for (_, counts) in &groups {
+        let mut best = (u64::MAX, 0);
+        let mut chosen = Vec::with_capacity(levels);
+        for (level, total) in distances.iter_mut().enumerate() {
+            if *total < best.0 {
+                best = (*total, level);
+            }
+            let distance = counts
+                .iter()
+                .enumerate()
+                .map(|(label, count)| count * label.abs_diff(level) as u64)
+                .sum::<u64>();
+            *total = best.0 + distance;
+            chosen.push(best.1);
+        }
+        previous.push(chosen);
+    }
+    let (distance, mut level) = distances
+        .iter()
+        .enumerate()
+        .map(|(level, distance)| (*distance, level))
+        .min()
+        .unwrap_or((0, 0));
+    let mut assigned = vec![0; groups.len()];
+    for (group, chosen) in previous.iter().enumerate().rev() {
+        assigned[group] = level;
+        level = chosen[level];
+    }
+    // The threshold of a level lies halfway between the highest score below
+    // it and the lowest score of it or a harder level. Levels which no
+    // sample ends up at start just above the highest score, such that the
+    // thresholds stay in order.
+    let above = groups
+        .last()
+        .map_or(0, |(score, _)| score.saturating_add(1));
+    let thresholds = (1..levels)
+        .map(
+            |level| match assigned.iter().position(|&other| other >= level) {
+                Some(0) => groups[0].0,
+                Some(group) => {
+                    let (below, lowest) = (groups[group - 1].0, groups[group].0);
+                    below + (lowest - below).div_ceil(2)
+                }
+                None => above,
+            },
+        )
+        .collect();
+    (thresholds, distance)
+}
+
+/// A quick estimate of the difficulty of a puzzle, as made by [`estimate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Estimate {
+    /// The estimated difficulty, which is exact for easy and medium puzzles.
+    /// Harder puzzles are all estimated as hard, as the cheap measures below
+    /// do not tell them apart reliably.
+    pub difficulty: Difficulty,
+    /// Number of clues.
+    pub clues: usize,
+    /// Total number of candidates of the empty cells, once the values of the
+    /// clues are removed from their peers.
+    pub candidates: u32,
+    /// Number of empty cells which a first pass of naked and hidden singles
+    /// fills.
+    pub singles: usize,
+    /// Number of cells left empty by singles and locked candidates, which is
+    /// zero for easy and medium puzzles. Harder puzzles tend to leave more.
+    pub remaining: usize,
+}
+
+impl Estimate {
+    /// Share of the empty cells which a first pass of singles fills.
+    pub fn saturation(&self) -> f64 {
+        match self.clues {
+            81 => 1.0,
+            clues => self.singles as f64 / (81 - clues) as f64,
+        }
+    }
+}
+
+/// Estimate the difficulty of a puzzle without rating it, for triaging large
+/// collections before rating the puzzles of interest. Only naked and hidden
+/// singles and locked candidates are applied, which is much cheaper than
+/// rating as nothing is searched and no steps are recorded.
+pub fn estimate(puzzle: &Puzzle) -> Result<Estimate, &'static str> {
+    let mut grid = Grid::new(puzzle)?;
+    let clues = 81 - grid.empty.len() as usize;
+    let candidates = grid.places.iter().map(|places| places.len()).sum();
+    let singles = grid.singles_once();
+
+    grid.singles()?;
+    let mut difficulty = Difficulty::Easy;
+    while !grid.empty.is_empty() && grid.locked_candidates() {
+        difficulty = Difficulty::Medium;
+        grid.singles()?;
+    }
+    if !grid.empty.is_empty() {
+        difficulty = Difficulty::Hard;
+    }
+    Ok(Estimate {
+        difficulty,
+        clues,
+        candidates,
+        singles,
+        remaining: grid.empty.len() as usize,
+    })
+}
+
+/// The state of a puzzle being estimated, keeping the places left for each
+/// value instead of the candidates of each cell.
+struct Grid {
+    /// The empty cells.
+    empty: CellSet,
+    /// The empty cells in which each value can be placed.
+    places: [CellSet; 9],
+    /// The values placed in each house as bitmasks.
+    house_values: [u16; 27],
+}
+
+impl Grid {
+    /// Place the clues of a puzzle.
+    fn new(puzzle: &Puzzle) -> Result<Self, &'static str> {
+        let mut grid = Self {
+            empty: CellSet::ALL,
+            places: [CellSet::ALL; 9],
+            house_values: [0; 27],
+        };
+        for (row, values) in puzzle.cells.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                if *value == 0 {
+                    continue;
+                }
+                if !grid.places[*value as usize - 1].contains((row, col)) {
+                    return Err("puzzle has conflicting values");
+                }
+                grid.place((row, col), *value);
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Place a value in a cell, removing it from the places of its peers.
+    fn place(&mut self, coords: (usize, usize), value: u8) {
+        self.empty.remove(coords);
+        for places in &mut self.places {
+            places.remove(coords);
+        }
+        let places = &mut self.places[value as usize - 1];
+        *places = places.difference(PEERS[9 * coords.0 + coords.1]);
+        for house in cell_to_houses(coords) {
+            self.house_values[house] |= 1 << (value - 1);
+        }
+    }
+
+    /// The candidates of an empty cell as a bitmask.
+    fn candidates(&self, coords: (usize, usize)) -> u16 {
+        (0..9)
+            .filter(|value| self.places[*value].contains(coords))
+            .fold(0, |mask, value| mask | 1 << value)
+    }
+
+    /// Number of empty cells which are a naked single or the only place of a
+    /// value in a house, without placing them.
+    fn singles_once(&self) -> usize {
+        let mut singles = CellSet::EMPTY;
+        for coords in self.empty.iter() {
+            if self.candidates(coords).count_ones() == 1 {
+                singles.insert(coords);
+            }
+        }
+        for places in &self.places {
+            for house in &HOUSES {
+                let cells = places.intersection(*house);
+                if cells.len() == 1 {
+                    singles.insert(cells.first().unwrap());
+                }
+            }
+        }
+        singles.len() as usize
+    }
+
+    /// Place naked and hidden singles until there are none left, failing if
+    /// a cell or a value in a house has no place left.
+    fn singles(&mut self) -> Result<(), &'static str> {
+        loop {
+            let mut placed = false;
+            for coords in self.empty.iter() {
+                let candidates = self.candidates(coords);
+                match candidates.count_ones() {
+                    0 => return Err("no candidate left for cell"),
+                    1 => {
+                        self.place(coords, candidates.trailing_zeros() as u8 + 1);
+                        placed = true;
+                    }
+                    _ => {}
+                }
+            }
+            for value in 1..=9 {
+                for (house, cells) in HOUSES.iter().enumerate() {
+                    if self.house_values[house] & (1 << (value - 1)) != 0 {
+                        continue;
+                    }
+                    let cells = self.places[value as usize - 1].intersection(*cells);
+                    match cells.first() {
+                        None => return Err("no cell left for value"),
+                        Some(single) if cells.len() == 1 => {
+                            self.place(single, value);
+                            placed = true;
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+            if !placed {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Remove the values of which the places in a house all lie in another
+    /// house from the rest of that other house. Returns whether any place
+    /// was removed.
+    fn locked_candidates(&mut self) -> bool {
+        let mut removed = false;
+        for places in &mut self.places {
+            for house in &HOUSES {
+                let cells = places.intersection(*house);
+                if cells.len() < 2 {
+                    continue;
+                }
+                for other in &HOUSES {
+                    if other != house && cells.intersection(*other) == cells {
+                        let pruned = places.difference(other.difference(*house));
+                        removed |= pruned != *places;
+                        *places = pruned;
+                    }
+                }
+            }
+        }
+        removed
+    }
+}
@@ -0,0 +1,325 @@
+//! Rendering of puzzles as SVG and PNG images, such that they can be embedded
+//! in applications and documents. Digits are drawn as strokes rather than
+//! with a font, so rendering needs no files and looks the same everywhere.
+//! Both formats draw the same shapes, while PNG requires the `image` feature.
+
+use crate::{Puzzle, Solution};
+use std::f32::consts::PI;
+use std::fmt::Write as _;
+
+/// Smallest size of a cell in pixels, below which digits are illegible.
+pub const MIN_CELL_SIZE: u32 = 8;
+
+/// Largest size of a cell in pixels, beyond which images take tens of
+/// megabytes and long to rasterize.
+pub const MAX_CELL_SIZE: u32 = 256;
+
+/// Color of the clues of the puzzle and the thick lines.
+const BLACK: [u8; 3] = [0x00, 0x00, 0x00];
+
+/// Color of the thin lines between cells.
+const GRAY: [u8; 3] = [0x80, 0x80, 0x80];
+
+/// Color of the values filled in from the solution.
+const BLUE: [u8; 3] = [0x1f, 0x5f, 0xbf];
+
+/// Height of a digit relative to the size of a cell.
+const DIGIT_HEIGHT: f32 = 0.6;
+
+/// Width of a digit relative to its height.
+const DIGIT_WIDTH: f32 = 0.6;
+
+/// Width of the strokes of a digit relative to its height.
+const STROKE_WIDTH: f32 = 0.1;
+
+/// Render a puzzle as SVG image with cells of `cell_size` pixels, which is
+/// clamped between `MIN_CELL_SIZE` and `MAX_CELL_SIZE`. If a solution is
+/// given, the values it fills in are drawn in a different color than the
+/// clues.
+pub fn svg(puzzle: &Puzzle, solution: Option<&Solution>, cell_size: u32) -> String {
+    let (size, shapes) = draw(
+        puzzle,
+        solution,
+        cell_size.clamp(MIN_CELL_SIZE, MAX_CELL_SIZE),
+    );
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+        size
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r##"<rect width="{0}" height="{0}" fill="#ffffff"/>"##,
+        size
+    )
+    .unwrap();
+    for shape in shapes {
+        match shape {
+            Shape::Rect(x, y, width, height, color) => writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x,
+                y,
+                width,
+                height,
+                hex(color)
+            )
+            .unwrap(),
+            Shape::Stroke(polylines, width, color) => {
+                let mut path = String::new();
+                for points in &polylines {
+                    for (idx, (x, y)) in points.iter().enumerate() {
+                        let command = if idx == 0 { 'M' } else { 'L' };
+                        write!(path, "{}{:.2},{:.2}", command, x, y).unwrap();
+                    }
+                }
+                writeln!(
+                    svg,
+                    r#"<path d="{}" fill="none" stroke="{}" stroke-width="{:.2}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                    path,
+                    hex(color),
+                    width
+                )
+                .unwrap();
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render a puzzle as PNG image with cells of `cell_size` pixels, which is
+/// clamped between `MIN_CELL_SIZE` and `MAX_CELL_SIZE`. If a solution is
+/// given, the values it fills in are drawn in a different color than the
+/// clues.
+#[cfg(feature = "image")]
+pub fn png(puzzle: &Puzzle, solution: Option<&Solution>, cell_size: u32) -> Vec<u8> {
+    let (size, shapes) = draw(
+        puzzle,
+        solution,
+        cell_size.clamp(MIN_CELL_SIZE, MAX_CELL_SIZE),
+    );
+    let mut canvas = Canvas::new(size, size);
+    for shape in &shapes {
+        match shape {
+            &Shape::Rect(x, y, width, height, color) => canvas.fill(x, y, width, height, color),
+            Shape::Stroke(polylines, width, color) => canvas.stroke(polylines, *width, *color),
+        }
+    }
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, canvas.width, canvas.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&canvas.pixels))
+        .expect("encoding to memory cannot fail");
+    data
+}
+
+/// Shape making up the image of a puzzle, in pixels.
+enum Shape {
+    /// Rectangle given by its left, top, width and height.
+    Rect(u32, u32, u32, u32, [u8; 3]),
+    /// Polylines with round joints and ends, given by their points and width.
+    Stroke(Vec<Vec<(f32, f32)>>, f32, [u8; 3]),
+}
+
+/// Color in hexadecimal notation, as in `#1f5fbf`.
+fn hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// Draw the grid and values of a puzzle, returning the size of the square
+/// image along with its shapes in the order they are painted.
+fn draw(puzzle: &Puzzle, solution: Option<&Solution>, cell_size: u32) -> (u32, Vec<Shape>) {
+    let margin = cell_size / 2;
+    let size = 9 * cell_size + 2 * margin;
+    let mut shapes = Vec::new();
+
+    let thin = (cell_size / 40).max(1);
+    let thick = (cell_size / 15).max(2);
+    for idx in 0..=9 {
+        let (width, color) = match idx % 3 {
+            0 => (thick, BLACK),
+            _ => (thin, GRAY),
+        };
+        let offset = margin + idx * cell_size - width / 2;
+        let length = 9 * cell_size + thick;
+        let start = margin - thick / 2;
+        shapes.push(Shape::Rect(offset, start, width, length, color));
+        shapes.push(Shape::Rect(start, offset, length, width, color));
+    }
+
+    for row in 0..9 {
+        for col in 0..9 {
+            let (value, color) = match (puzzle.cells()[row][col], solution) {
+                (0, Some(solution)) => (solution.cells()[row][col], BLUE),
+                (value, _) => (value, BLACK),
+            };
+            if value == 0 {
+                continue;
+            }
+            let height = DIGIT_HEIGHT * cell_size as f32;
+            let x = (margin + col as u32 * cell_size) as f32
+                + (cell_size as f32 - DIGIT_WIDTH * height) / 2.0;
+            let y = (margin + row as u32 * cell_size) as f32 + (cell_size as f32 - height) / 2.0;
+            let strokes: Vec<Vec<(f32, f32)>> = glyph(value)
+                .into_iter()
+                .map(|stroke| {
+                    stroke
+                        .into_iter()
+                        .map(|(px, py)| (x + px * height, y + py * height))
+                        .collect()
+                })
+                .collect();
+            shapes.push(Shape::Stroke(strokes, STROKE_WIDTH * height, color));
+        }
+    }
+    (size, shapes)
+}
+
+/// Points on an elliptical arc from one angle to another in degrees, where
+/// zero degrees points right and angles increase clockwise.
+fn arc(center: (f32, f32), radius: (f32, f32), from: f32, to: f32) -> Vec<(f32, f32)> {
+    let steps = ((to - from).abs() / 10.0).ceil().max(1.0) as usize;
+    (0..=steps)
+        .map(|step| {
+            let angle = (from + (to - from) * step as f32 / steps as f32) * PI / 180.0;
+            (
+                center.0 + radius.0 * angle.cos(),
+                center.1 + radius.1 * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+/// Strokes of a digit as polylines in a box of `DIGIT_WIDTH` by 1, with the
+/// origin at the top left.
+fn glyph(digit: u8) -> Vec<Vec<(f32, f32)>> {
+    match digit {
+        1 => vec![
+            vec![(0.12, 0.2), (0.32, 0.05), (0.32, 0.95)],
+            vec![(0.12, 0.95), (0.52, 0.95)],
+        ],
+        2 => {
+            let mut stroke = arc((0.3, 0.3), (0.25, 0.25), 200.0, 390.0);
+            stroke.extend([(0.05, 0.95), (0.57, 0.95)]);
+            vec![stroke]
+        }
+        3 => vec![
+            arc((0.3, 0.27), (0.22, 0.22), 200.0, 450.0),
+            arc((0.3, 0.72), (0.25, 0.23), -90.0, 160.0),
+        ],
+        4 => vec![vec![(0.45, 0.95), (0.45, 0.05), (0.04, 0.68), (0.58, 0.68)]],
+        5 => {
+            let mut stroke = vec![(0.55, 0.05), (0.12, 0.05), (0.08, 0.47)];
+            stroke.extend(arc((0.3, 0.68), (0.26, 0.27), 215.0, 520.0));
+            vec![stroke]
+        }
+        6 => vec![
+            arc((0.3, 0.7), (0.25, 0.25), 0.0, 360.0),
+            arc((0.55, 0.7), (0.5, 0.65), 180.0, 250.0),
+        ],
+        7 => vec![vec![(0.04, 0.05), (0.56, 0.05), (0.2, 0.95)]],
+        8 => vec![
+            arc((0.3, 0.27), (0.2, 0.22), 0.0, 360.0),
+            arc((0.3, 0.72), (0.25, 0.23), 0.0, 360.0),
+        ],
+        9 => glyph(6)
+            .into_iter()
+            .map(|stroke| {
+                stroke
+                    .into_iter()
+                    .map(|(x, y)| (DIGIT_WIDTH - x, 1.0 - y))
+                    .collect()
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Image of RGB pixels, initially white.
+#[cfg(feature = "image")]
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[cfg(feature = "image")]
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0xff; 3 * width as usize * height as usize],
+        }
+    }
+
+    /// Blend a color into a pixel, covering the given fraction of it.
+    fn blend(&mut self, x: u32, y: u32, color: [u8; 3], coverage: f32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let idx = 3 * (y as usize * self.width as usize + x as usize);
+        for (pixel, channel) in self.pixels[idx..idx + 3].iter_mut().zip(color) {
+            let blended = *pixel as f32 * (1.0 - coverage) + channel as f32 * coverage;
+            *pixel = blended.round() as u8;
+        }
+    }
+
+    /// Fill a rectangle with a color.
+    fn fill(&mut self, x: u32, y: u32, width: u32, height: u32, color: [u8; 3]) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    /// Draw polylines with round joints and ends, anti-aliased by covering
+    /// each pixel according to its distance to the nearest line.
+    fn stroke(&mut self, polylines: &[Vec<(f32, f32)>], width: f32, color: [u8; 3]) {
+        let segments: Vec<_> = polylines
+            .iter()
+            .flat_map(|points| points.windows(2).map(|pair| (pair[0], pair[1])))
+            .collect();
+        let reach = width / 2.0 + 1.0;
+        let points = polylines.iter().flatten();
+        let min_x = points.clone().map(|p| p.0).fold(f32::MAX, f32::min) - reach;
+        let max_x = points.clone().map(|p| p.0).fold(f32::MIN, f32::max) + reach;
+        let min_y = points.clone().map(|p| p.1).fold(f32::MAX, f32::min) - reach;
+        let max_y = points.map(|p| p.1).fold(f32::MIN, f32::max) + reach;
+
+        for py in min_y.max(0.0) as u32..=max_y as u32 {
+            for px in min_x.max(0.0) as u32..=max_x as u32 {
+                let center = (px as f32 + 0.5, py as f32 + 0.5);
+                let distance = segments
+                    .iter()
+                    .map(|&(a, b)| distance(center, a, b))
+                    .fold(f32::MAX, f32::min);
+                let coverage = (width / 2.0 - distance + 0.5).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    self.blend(px, py, color, coverage);
+                }
+            }
+        }
+    }
+}
+
+/// Distance from a point to the line segment between two points.
+#[cfg(feature = "image")]
+fn distance(point: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    };
+    let (x, y) = (a.0 + t * dx, a.1 + t * dy);
+    ((point.0 - x).powi(2) + (point.1 - y).powi(2)).sqrt()
+}
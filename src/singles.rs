@@ -0,0 +1,130 @@
+//! Fast path for puzzles which are solved by naked and hidden singles alone,
+//! as most puzzles of real-world collections are. Such puzzles are solved in
+//! a tight loop over the empty cells which only keeps the values in each
+//! house, without the candidate bookkeeping and trail of the solver. Other
+//! puzzles continue from the cells filled by the singles.
+
+use crate::{cell_to_houses, Puzzle};
+
+/// Mask of all values.
+const ALL: u16 = 0x1ff;
+
+/// Outcome of applying singles to a puzzle.
+pub(crate) enum Singles {
+    /// The singles filled every cell, giving the solution.
+    Solved([[u8; 9]; 9]),
+    /// The singles got stuck, having filled the cells of this grid.
+    Stuck([[u8; 9]; 9]),
+    /// The puzzle contradicts itself.
+    Contradiction,
+}
+
+/// Apply naked and hidden singles to a puzzle until they get stuck. As the
+/// singles follow from the givens, a solution is the one the solver finds,
+/// and a grid they got stuck on leads the solver to the same state as the
+/// puzzle itself, with less work.
+pub(crate) fn solve(puzzle: &Puzzle) -> Singles {
+    let mut cells = puzzle.cells;
+    let mut house_values = [0u16; 27];
+    // The empty cells, of which the first `len` are still empty.
+    let mut empty = [(0u8, 0u8); 81];
+    let mut len = 0;
+    for (row, values) in cells.iter().enumerate() {
+        for (col, value) in values.iter().enumerate() {
+            if *value == 0 {
+                empty[len] = (row as u8, col as u8);
+                len += 1;
+                continue;
+            }
+            let bit = 1 << (value - 1);
+            for house in cell_to_houses((row, col)) {
+                if house_values[house] & bit != 0 {
+                    return Singles::Contradiction;
+                }
+                house_values[house] |= bit;
+            }
+        }
+    }
+
+    let mut candidates = [0u16; 81];
+    while len > 0 {
+        let mut progress = false;
+
+        // Fill the cells with a single candidate left.
+        let mut idx = 0;
+        while idx < len {
+            let (row, col) = (empty[idx].0 as usize, empty[idx].1 as usize);
+            let houses = cell_to_houses((row, col));
+            let mask = ALL
+                & !houses
+                    .iter()
+                    .fold(0, |mask, house| mask | house_values[*house]);
+            match mask.count_ones() {
+                0 => return Singles::Contradiction,
+                1 => {
+                    cells[row][col] = mask.trailing_zeros() as u8 + 1;
+                    for house in houses {
+                        house_values[house] |= mask;
+                    }
+                    len -= 1;
+                    empty[idx] = empty[len];
+                    progress = true;
+                }
+                _ => {
+                    candidates[idx] = mask;
+                    idx += 1;
+                }
+            }
+        }
+
+        // Fill the values which have a single cell left in a house. The
+        // candidates are updated first, as cells filled later in the pass
+        // above may have removed some.
+        let mut once = [0u16; 27];
+        let mut twice = [0u16; 27];
+        for idx in 0..len {
+            let houses = cell_to_houses((empty[idx].0 as usize, empty[idx].1 as usize));
+            candidates[idx] &= !houses
+                .iter()
+                .fold(0, |mask, house| mask | house_values[*house]);
+            for house in houses {
+                twice[house] |= once[house] & candidates[idx];
+                once[house] |= candidates[idx];
+            }
+        }
+        let mut idx = 0;
+        while idx < len {
+            let (row, col) = (empty[idx].0 as usize, empty[idx].1 as usize);
+            let houses = cell_to_houses((row, col));
+            let hidden = houses.iter().fold(0, |mask, house| {
+                mask | (once[*house] & !twice[*house] & candidates[idx])
+            });
+            if hidden == 0 {
+                idx += 1;
+                continue;
+            }
+            // Two values having this cell as their only place, or a value
+            // placed in a peer during this pass, contradict.
+            if hidden.count_ones() > 1
+                || houses
+                    .iter()
+                    .any(|house| house_values[*house] & hidden != 0)
+            {
+                return Singles::Contradiction;
+            }
+            cells[row][col] = hidden.trailing_zeros() as u8 + 1;
+            for house in houses {
+                house_values[house] |= hidden;
+            }
+            len -= 1;
+            empty[idx] = empty[len];
+            candidates[idx] = candidates[len];
+            progress = true;
+        }
+
+        if !progress {
+            return Singles::Stuck(cells);
+        }
+    }
+    Singles::Solved(cells)
+}
@@ -0,0 +1,123 @@
+//! Snapshots of the state of the solver, such that failures to solve a puzzle
+//! can be inspected. Solving using
+//! [`Solver::solve_with_snapshot`](crate::Solver::solve_with_snapshot)
+//! attaches the values and candidates of the grid at the point of the
+//! contradiction to the error:
+//!
+//! ```
+//! use sudoku_solver::{Puzzle, Solver};
+//!
+//! let puzzle: Puzzle = "12345678.........9..............................................................."
+//!     .parse()
+//!     .unwrap();
+//! let contradiction = Solver::solve_with_snapshot(&puzzle, &Default::default()).unwrap_err();
+//! assert_eq!(contradiction.error, "no candidate left for cell");
+//! assert_eq!(contradiction.snapshot.dead_cells().next(), Some((0, 8)));
+//! println!("{}", contradiction.snapshot);
+//! ```
+
+use crate::check_cell;
+use core::fmt;
+
+/// The values and candidates of a grid at some point while solving.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub(crate) values: [[u8; 9]; 9],
+    /// Bitmasks of the candidates of the empty cells, in which bit `n - 1` is
+    /// set if value `n` is a candidate.
+    pub(crate) candidates: [[u16; 9]; 9],
+}
+
+impl Snapshot {
+    /// The value filled into a cell, if any.
+    pub fn value(&self, coords: (usize, usize)) -> Option<u8> {
+        check_cell(coords).ok()?;
+        match self.values[coords.0][coords.1] {
+            0 => None,
+            value => Some(value),
+        }
+    }
+
+    /// The candidates of a cell in ascending order, which are none for filled
+    /// cells.
+    pub fn candidates(&self, coords: (usize, usize)) -> impl Iterator<Item = u8> {
+        let mask = match check_cell(coords) {
+            Ok(()) => self.candidates[coords.0][coords.1],
+            Err(_) => 0,
+        };
+        (1..=9).filter(move |value| mask & (1 << (value - 1)) != 0)
+    }
+
+    /// The empty cells without any candidate left, at which the solver
+    /// usually found its contradiction.
+    pub fn dead_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..9 * 9)
+            .map(|idx| (idx / 9, idx % 9))
+            .filter(|&(row, col)| self.values[row][col] == 0 && self.candidates[row][col] == 0)
+    }
+
+    /// Width of a cell when displayed.
+    fn width(&self, coords: (usize, usize)) -> usize {
+        match self.value(coords) {
+            Some(_) => 1,
+            None => (self.candidates[coords.0][coords.1].count_ones() as usize).max(1),
+        }
+    }
+}
+
+impl fmt::Display for Snapshot {
+    /// The grid with the candidates of each empty cell written in place of
+    /// its value, and a dash for empty cells without any candidate.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let widths: [usize; 9] =
+            core::array::from_fn(|col| (0..9).map(|row| self.width((row, col))).max().unwrap());
+        let line = |fmt: &mut fmt::Formatter| {
+            for band in widths.chunks(3) {
+                write!(fmt, "+")?;
+                for _ in 0..1 + band.iter().map(|width| width + 1).sum::<usize>() {
+                    write!(fmt, "-")?;
+                }
+            }
+            write!(fmt, "+")
+        };
+        for row in 0..9 {
+            if row % 3 == 0 {
+                line(fmt)?;
+                writeln!(fmt)?;
+            }
+            for (col, width) in widths.iter().enumerate() {
+                if col % 3 == 0 {
+                    write!(fmt, "| ")?;
+                }
+                match self.value((row, col)) {
+                    Some(value) => write!(fmt, "{}", value)?,
+                    None if self.candidates[row][col] == 0 => write!(fmt, "-")?,
+                    None => {
+                        for candidate in self.candidates((row, col)) {
+                            write!(fmt, "{}", candidate)?;
+                        }
+                    }
+                }
+                for _ in self.width((row, col))..*width + 1 {
+                    write!(fmt, " ")?;
+                }
+            }
+            writeln!(fmt, "|")?;
+        }
+        line(fmt)
+    }
+}
+
+/// An error of solving along with a snapshot of the grid at the point where
+/// the solver gave up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Contradiction {
+    pub error: &'static str,
+    pub snapshot: Snapshot,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.error)
+    }
+}
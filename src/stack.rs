@@ -0,0 +1,70 @@
+use core::fmt;
+use core::mem::MaybeUninit;
+
+/// Stack with a fixed capacity which is stored inline, such that it can be
+/// used without an allocator. Pushing beyond the capacity panics, so the
+/// capacity should be chosen to fit the worst case.
+#[derive(Clone, Copy)]
+pub(crate) struct Stack<T: Copy, const N: usize> {
+    items: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T: Copy, const N: usize> Default for Stack<T, N> {
+    fn default() -> Self {
+        Self {
+            items: [MaybeUninit::uninit(); N],
+            len: 0,
+        }
+    }
+}
+
+impl<T: Copy + fmt::Debug, const N: usize> fmt::Debug for Stack<T, N> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+impl<T: Copy, const N: usize> Stack<T, N> {
+    /// Number of items on the stack.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Remove all items from the stack.
+    pub(crate) fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Push an item onto the stack.
+    pub(crate) fn push(&mut self, item: T) {
+        self.items[self.len] = MaybeUninit::new(item);
+        self.len += 1;
+    }
+
+    /// Pop the topmost item off the stack, if any.
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: all items below `len` have been initialized by `push`.
+        Some(unsafe { self.items[self.len].assume_init() })
+    }
+
+    /// A mutable reference to the topmost item on the stack, if any.
+    pub(crate) fn last_mut(&mut self) -> Option<&mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: all items below `len` have been initialized by `push`.
+        Some(unsafe { self.items[self.len - 1].assume_init_mut() })
+    }
+
+    /// The items on the stack, from bottom to top.
+    pub(crate) fn as_slice(&self) -> &[T] {
+        // SAFETY: all items below `len` have been initialized by `push`, and
+        // `MaybeUninit<T>` has the same layout as `T`.
+        unsafe { core::slice::from_raw_parts(self.items.as_ptr() as *const T, self.len) }
+    }
+}
@@ -0,0 +1,189 @@
+//! Random puzzles for property testing code which consumes this crate, with
+//! implementations of `arbitrary::Arbitrary` for fuzzing with the
+//! `arbitrary` feature and proptest strategies with the `proptest` feature.
+//! Besides valid puzzles, near-valid puzzles are generated which differ from
+//! a valid one by a single flaw, as those exercise the error handling of
+//! code the most.
+
+use crate::generate;
+use crate::random::Rng;
+use crate::Puzzle;
+
+/// Minimum number of clues of a valid puzzle, below which no puzzle has a
+/// unique solution.
+const MIN_CLUES: usize = 17;
+
+/// A flaw of a near-valid puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Flaw {
+    /// A value of a clue is repeated in a cell sharing a house with it.
+    Conflict,
+    /// A clue was changed to a value which none of its peers hold, which
+    /// does not conflict directly but leaves the puzzle without a solution
+    /// most of the time.
+    WrongClue,
+    /// Too few clues are kept for the solution to be unique.
+    Ambiguous,
+}
+
+impl Flaw {
+    /// All flaws.
+    pub const ALL: [Flaw; 3] = [Flaw::Conflict, Flaw::WrongClue, Flaw::Ambiguous];
+}
+
+/// A random valid puzzle, being a random solution of which a random number
+/// of cells, leaving at least 17 clues, is emptied. The puzzle has a
+/// solution, which need not be unique.
+pub fn valid_puzzle(rng: &mut Rng) -> Puzzle {
+    let clues = MIN_CLUES + rng.below(9 * 9 - MIN_CLUES + 1);
+    with_clues(rng, clues)
+}
+
+/// A random solution of which all but a number of clues is emptied.
+fn with_clues(rng: &mut Rng, clues: usize) -> Puzzle {
+    let mut puzzle = generate::solution(rng);
+    let mut cells: Vec<usize> = (0..9 * 9).collect();
+    rng.shuffle(&mut cells);
+    for cell in &cells[clues..] {
+        puzzle.cells[cell / 9][cell % 9] = 0;
+    }
+    puzzle
+}
+
+/// A random near-valid puzzle, being a random valid puzzle with a flaw.
+pub fn near_valid_puzzle(rng: &mut Rng, flaw: Flaw) -> Puzzle {
+    if flaw == Flaw::Ambiguous {
+        let clues = rng.below(MIN_CLUES);
+        return with_clues(rng, clues);
+    }
+    // Leave a cell empty to put a conflicting value in.
+    let clues = MIN_CLUES + rng.below(9 * 9 - MIN_CLUES);
+    let mut puzzle = with_clues(rng, clues);
+    let filled: Vec<(usize, usize)> = (0..9 * 9)
+        .map(|idx| (idx / 9, idx % 9))
+        .filter(|(row, col)| puzzle.cells[*row][*col] != 0)
+        .collect();
+    let (row, col) = filled[rng.below(filled.len())];
+    let value = puzzle.cells[row][col];
+    match flaw {
+        Flaw::Conflict => {
+            // The value is repeated in an empty peer, or in a filled one if
+            // every peer is filled.
+            let mut targets: Vec<(usize, usize)> = peers((row, col))
+                .filter(|(row, col)| puzzle.cells[*row][*col] == 0)
+                .collect();
+            if targets.is_empty() {
+                targets = peers((row, col)).collect();
+            }
+            let (row, col) = targets[rng.below(targets.len())];
+            puzzle.cells[row][col] = value;
+        }
+        Flaw::WrongClue => {
+            let mut taken = 1 << value;
+            for (row, col) in peers((row, col)) {
+                taken |= 1 << puzzle.cells[row][col];
+            }
+            let free: Vec<u8> = (1..=9).filter(|value| taken & 1 << value == 0).collect();
+            // A clue of which every other value is held by a peer is given
+            // the value of a peer instead.
+            puzzle.cells[row][col] = match free.is_empty() {
+                true => value % 9 + 1,
+                false => free[rng.below(free.len())],
+            };
+        }
+        Flaw::Ambiguous => unreachable!(),
+    }
+    puzzle
+}
+
+/// The cells sharing a row, column or square with a cell.
+fn peers(coords: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+    (0..9 * 9)
+        .map(|idx| (idx / 9, idx % 9))
+        .filter(move |cell| crate::logic::sees(coords, *cell))
+}
+
+/// A random puzzle, which is valid three times out of four and otherwise
+/// near-valid with a random flaw.
+pub fn puzzle(rng: &mut Rng) -> Puzzle {
+    match rng.below(4) {
+        0 => {
+            let flaw = Flaw::ALL[rng.below(Flaw::ALL.len())];
+            near_valid_puzzle(rng, flaw)
+        }
+        _ => valid_puzzle(rng),
+    }
+}
+
+/// A valid puzzle, for fuzzing or property testing only with valid puzzles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ValidPuzzle(pub Puzzle);
+
+/// A near-valid puzzle along with its flaw, for fuzzing or property testing
+/// only with flawed puzzles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NearValidPuzzle(pub Puzzle, pub Flaw);
+
+#[cfg(feature = "arbitrary")]
+mod fuzzing {
+    use super::{Flaw, NearValidPuzzle, ValidPuzzle};
+    use crate::random::Rng;
+    use crate::Puzzle;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // The puzzles are derived from a seed taken from the input, as the
+    // bytes of the input cannot be turned into valid puzzles directly.
+
+    impl<'a> Arbitrary<'a> for Puzzle {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(super::puzzle(&mut Rng::new(u.arbitrary()?)))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for ValidPuzzle {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self(super::valid_puzzle(&mut Rng::new(u.arbitrary()?))))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for Flaw {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.choose(&Flaw::ALL).copied()
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for NearValidPuzzle {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let flaw = u.arbitrary()?;
+            let puzzle = super::near_valid_puzzle(&mut Rng::new(u.arbitrary()?), flaw);
+            Ok(Self(puzzle, flaw))
+        }
+    }
+}
+
+/// Proptest strategies generating puzzles, such as
+/// `proptest!(|(puzzle in testing::strategy::valid_puzzles())| ...)`.
+#[cfg(feature = "proptest")]
+pub mod strategy {
+    use super::Flaw;
+    use crate::random::Rng;
+    use crate::Puzzle;
+    use proptest::prelude::*;
+
+    /// Random puzzles, which are valid three times out of four and
+    /// otherwise near-valid with a random flaw.
+    pub fn puzzles() -> impl Strategy<Value = Puzzle> {
+        any::<u64>().prop_map(|seed| super::puzzle(&mut Rng::new(seed)))
+    }
+
+    /// Random valid puzzles.
+    pub fn valid_puzzles() -> impl Strategy<Value = Puzzle> {
+        any::<u64>().prop_map(|seed| super::valid_puzzle(&mut Rng::new(seed)))
+    }
+
+    /// Random near-valid puzzles along with their flaw.
+    pub fn near_valid_puzzles() -> impl Strategy<Value = (Puzzle, Flaw)> {
+        (prop::sample::select(Flaw::ALL.to_vec()), any::<u64>())
+            .prop_map(|(flaw, seed)| (super::near_valid_puzzle(&mut Rng::new(seed), flaw), flaw))
+    }
+}
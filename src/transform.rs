@@ -0,0 +1,541 @@
+//! Transformations of puzzles which preserve their validity, such as
+//! rotations, mirroring and relabeling of values. A transformed puzzle has
+//! the same number of solutions and is equally difficult to solve.
+
+use crate::random::Rng;
+use crate::Puzzle;
+#[cfg(feature = "std")]
+use crate::Solver;
+use core::fmt;
+use core::str::FromStr;
+
+/// A validity-preserving transformation, consisting of a permutation of the
+/// cells and a relabeling of the values. Transformations can be chained
+/// using [`Transformation::then`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Transformation {
+    /// For each cell of the result, the index of the cell it is taken from.
+    cells: [u8; 81],
+    /// For each value, the value it is replaced by. Index zero is unused.
+    labels: [u8; 10],
+}
+
+impl Default for Transformation {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transformation {
+    /// The transformation which leaves puzzles unchanged.
+    pub fn identity() -> Self {
+        Self::from_coords(|row, col| (row, col))
+    }
+
+    /// Build a transformation permuting the cells, for which the closure maps
+    /// each cell of the result to the cell it is taken from.
+    fn from_coords(source: impl Fn(usize, usize) -> (usize, usize)) -> Self {
+        Self {
+            cells: core::array::from_fn(|idx| {
+                let (row, col) = source(idx / 9, idx % 9);
+                (9 * row + col) as u8
+            }),
+            labels: core::array::from_fn(|value| value as u8),
+        }
+    }
+
+    /// Rotate the grid a quarter turn clockwise.
+    pub fn rotate() -> Self {
+        Self::from_coords(|row, col| (8 - col, row))
+    }
+
+    /// Mirror the grid along its main diagonal.
+    pub fn transpose() -> Self {
+        Self::from_coords(|row, col| (col, row))
+    }
+
+    /// Mirror the grid from left to right.
+    pub fn mirror_horizontal() -> Self {
+        Self::from_coords(|row, col| (row, 8 - col))
+    }
+
+    /// Mirror the grid from top to bottom.
+    pub fn mirror_vertical() -> Self {
+        Self::from_coords(|row, col| (8 - row, col))
+    }
+
+    /// Swap two bands, being groups of three rows, indexed from zero.
+    pub fn swap_bands(a: usize, b: usize) -> Result<Self, &'static str> {
+        if a >= 3 || b >= 3 {
+            return Err("band index out of range");
+        }
+        let swap = move |band| match band {
+            _ if band == a => b,
+            _ if band == b => a,
+            _ => band,
+        };
+        Ok(Self::from_coords(|row, col| {
+            (3 * swap(row / 3) + row % 3, col)
+        }))
+    }
+
+    /// Swap two stacks, being groups of three columns, indexed from zero.
+    pub fn swap_stacks(a: usize, b: usize) -> Result<Self, &'static str> {
+        Ok(Self::transpose()
+            .then(&Self::swap_bands(a, b)?)
+            .then(&Self::transpose()))
+    }
+
+    /// Replace each value by another, such that value `v` becomes
+    /// `labels[v - 1]`. The labels must be a permutation of 1 to 9.
+    pub fn relabel(labels: [u8; 9]) -> Result<Self, &'static str> {
+        let mut seen = [false; 9];
+        for label in labels {
+            if !(1..=9).contains(&label) || seen[label as usize - 1] {
+                return Err("labels are not a permutation of 1 to 9");
+            }
+            seen[label as usize - 1] = true;
+        }
+        let mut transformation = Self::identity();
+        transformation.labels[1..].copy_from_slice(&labels);
+        Ok(transformation)
+    }
+
+    /// A random transformation, uniformly chosen from all combinations of
+    /// transposition, permutations of bands, stacks, and rows and columns
+    /// within them, and relabeling of values.
+    pub fn random(rng: &mut Rng) -> Self {
+        let permutation = |rng: &mut Rng| {
+            let mut outer = [0, 1, 2];
+            rng.shuffle(&mut outer);
+            let mut lines = [0; 9];
+            for (idx, group) in outer.into_iter().enumerate() {
+                let mut inner = [0, 1, 2];
+                rng.shuffle(&mut inner);
+                for (offset, line) in inner.into_iter().enumerate() {
+                    lines[3 * idx + offset] = 3 * group + line;
+                }
+            }
+            lines
+        };
+        let rows = permutation(rng);
+        let cols = permutation(rng);
+        let mut transformation = Self::from_coords(|row, col| (rows[row], cols[col]));
+        if rng.coin() {
+            transformation = Self::transpose().then(&transformation);
+        }
+        let mut labels = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        rng.shuffle(&mut labels);
+        transformation.labels[1..].copy_from_slice(&labels);
+        transformation
+    }
+
+    /// Chain another transformation to be applied after this one.
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            cells: next.cells.map(|idx| self.cells[idx as usize]),
+            labels: self.labels.map(|value| next.labels[value as usize]),
+        }
+    }
+
+    /// The transformation undoing this one.
+    pub fn inverse(&self) -> Self {
+        let mut inverse = Self::identity();
+        for (idx, source) in self.cells.iter().enumerate() {
+            inverse.cells[*source as usize] = idx as u8;
+        }
+        for (value, label) in self.labels.iter().enumerate() {
+            inverse.labels[*label as usize] = value as u8;
+        }
+        inverse
+    }
+
+    /// Apply the transformation to a puzzle.
+    pub fn apply(&self, puzzle: &Puzzle) -> Puzzle {
+        let mut cells = [[0; 9]; 9];
+        for (idx, source) in self.cells.iter().enumerate() {
+            let source = *source as usize;
+            cells[idx / 9][idx % 9] = self.labels[puzzle.cells[source / 9][source % 9] as usize];
+        }
+        Puzzle { cells }
+    }
+}
+
+/// A symmetry of the pattern of clues of a puzzle, regardless of their values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Symmetry {
+    /// Unchanged by a half turn, which is the most common symmetry.
+    HalfTurn,
+    /// Unchanged by a quarter turn, and thus also by a half turn.
+    QuarterTurn,
+    /// Unchanged by mirroring from left to right.
+    Horizontal,
+    /// Unchanged by mirroring from top to bottom.
+    Vertical,
+    /// Unchanged by mirroring along the main diagonal.
+    Diagonal,
+    /// Unchanged by mirroring along the anti-diagonal.
+    AntiDiagonal,
+}
+
+impl Symmetry {
+    /// All symmetries.
+    pub const ALL: [Symmetry; 6] = [
+        Symmetry::HalfTurn,
+        Symmetry::QuarterTurn,
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Diagonal,
+        Symmetry::AntiDiagonal,
+    ];
+
+    /// Name of the symmetry.
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::HalfTurn => "half-turn",
+            Symmetry::QuarterTurn => "quarter-turn",
+            Symmetry::Horizontal => "horizontal",
+            Symmetry::Vertical => "vertical",
+            Symmetry::Diagonal => "diagonal",
+            Symmetry::AntiDiagonal => "anti-diagonal",
+        }
+    }
+
+    /// The transformation under which the pattern is unchanged.
+    pub fn transformation(self) -> Transformation {
+        match self {
+            Symmetry::HalfTurn => Transformation::rotate().then(&Transformation::rotate()),
+            Symmetry::QuarterTurn => Transformation::rotate(),
+            Symmetry::Horizontal => Transformation::mirror_horizontal(),
+            Symmetry::Vertical => Transformation::mirror_vertical(),
+            Symmetry::Diagonal => Transformation::transpose(),
+            Symmetry::AntiDiagonal => Transformation::from_coords(|row, col| (8 - col, 8 - row)),
+        }
+    }
+
+    /// Whether the pattern of clues of a puzzle has the symmetry, such that
+    /// each clue is mapped onto a clue.
+    pub fn holds(self, puzzle: &Puzzle) -> bool {
+        let transformed = self.transformation().apply(puzzle);
+        (0..81).all(|idx| {
+            (puzzle.cells[idx / 9][idx % 9] != 0) == (transformed.cells[idx / 9][idx % 9] != 0)
+        })
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.name())
+    }
+}
+
+/// A group of transformations, of which those mapping a puzzle onto itself
+/// make solutions of the puzzle equivalent, such as to count its essentially
+/// different solutions using [`count_essentially_different`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Group {
+    /// Rotations and mirroring of the grid.
+    Geometric,
+    /// Relabeling of the values.
+    Relabeling,
+    /// Rotations and mirroring of the grid combined with relabeling of the
+    /// values.
+    GeometricRelabeling,
+}
+
+impl Group {
+    /// All groups.
+    pub const ALL: [Group; 3] = [
+        Group::Geometric,
+        Group::Relabeling,
+        Group::GeometricRelabeling,
+    ];
+
+    /// Name of the group.
+    pub fn name(self) -> &'static str {
+        match self {
+            Group::Geometric => "geometric",
+            Group::Relabeling => "relabeling",
+            Group::GeometricRelabeling => "geometric-relabeling",
+        }
+    }
+}
+
+impl FromStr for Group {
+    type Err = &'static str;
+
+    /// Parse the name of a group, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Group::ALL
+            .into_iter()
+            .find(|group| group.name().eq_ignore_ascii_case(s))
+            .ok_or("unknown group, expected geometric, relabeling or geometric-relabeling")
+    }
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.name())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Group {
+    /// The permutations of the cells of the group, being the eight
+    /// rotations and reflections of the square for geometric groups.
+    fn permutations(self) -> Vec<Transformation> {
+        let half_turn = Transformation::rotate().then(&Transformation::rotate());
+        match self {
+            Group::Relabeling => vec![Transformation::identity()],
+            Group::Geometric | Group::GeometricRelabeling => vec![
+                Transformation::identity(),
+                Transformation::rotate(),
+                half_turn,
+                half_turn.then(&Transformation::rotate()),
+                Transformation::mirror_horizontal(),
+                Transformation::mirror_vertical(),
+                Transformation::transpose(),
+                Symmetry::AntiDiagonal.transformation(),
+            ],
+        }
+    }
+
+    /// Whether the group relabels values.
+    fn relabels(self) -> bool {
+        self != Group::Geometric
+    }
+
+    /// The relabeling of the values which maps a puzzle permuted by a
+    /// permutation of the group back onto the puzzle, as far as it is forced
+    /// by the clues, with zero for values it leaves free. Returns `None` if
+    /// no element of the group with that permutation maps the puzzle onto
+    /// itself.
+    fn stabilizing_labels(self, puzzle: &Puzzle, permutation: &Transformation) -> Option<[u8; 10]> {
+        let permuted = permutation.apply(puzzle);
+        let mut labels = [0; 10];
+        let mut used = [false; 10];
+        for (from, to) in permuted
+            .cells
+            .iter()
+            .flatten()
+            .zip(puzzle.cells.iter().flatten())
+        {
+            match (*from as usize, *to as usize) {
+                (0, 0) => {}
+                (0, _) | (_, 0) => return None,
+                (from, to) if labels[from] == 0 && !used[to] => {
+                    labels[from] = to as u8;
+                    used[to] = true;
+                }
+                (from, to) if labels[from] as usize != to => return None,
+                _ => {}
+            }
+        }
+        let relabeled = (1..10).any(|value| labels[value] != 0 && labels[value] as usize != value);
+        match relabeled && !self.relabels() {
+            true => None,
+            false => Some(labels),
+        }
+    }
+}
+
+/// The smallest image of a solution grid under the elements of a group
+/// mapping the puzzle onto itself, given as permutations along with the
+/// labels they force. Values left free are relabeled in order of their first
+/// appearance if the group relabels, and kept otherwise.
+#[cfg(feature = "std")]
+fn smallest_image(
+    grid: &[[u8; 9]; 9],
+    stabilizer: &[(Transformation, [u8; 10])],
+    relabels: bool,
+) -> [u8; 81] {
+    let mut best = [u8::MAX; 81];
+    for (permutation, forced) in stabilizer {
+        let mut labels = match relabels {
+            true => *forced,
+            false => core::array::from_fn(|value| value as u8),
+        };
+        let mut used = [false; 10];
+        for label in labels {
+            used[label as usize] = true;
+        }
+        let mut next = 1;
+        let mut image = [0; 81];
+        for (value, source) in image.iter_mut().zip(permutation.cells) {
+            let original = grid[source as usize / 9][source as usize % 9] as usize;
+            if labels[original] == 0 {
+                while used[next] {
+                    next += 1;
+                }
+                labels[original] = next as u8;
+                used[next] = true;
+            }
+            *value = labels[original];
+        }
+        best = best.min(image);
+    }
+    best
+}
+
+/// Count the solutions of a puzzle like [`Solver::count_solutions`], along
+/// with the number of essentially different ones among them. Solutions are
+/// equivalent if an element of the group which maps the puzzle onto itself
+/// maps one onto the other, such that the empty grid has far fewer
+/// essentially different completions than completions. Only the solutions
+/// found before reaching the limit are grouped.
+#[cfg(feature = "std")]
+pub fn count_essentially_different(puzzle: &Puzzle, group: Group, limit: usize) -> (usize, usize) {
+    let stabilizer: Vec<(Transformation, [u8; 10])> = group
+        .permutations()
+        .into_iter()
+        .filter_map(|permutation| {
+            let labels = group.stabilizing_labels(puzzle, &permutation)?;
+            Some((permutation, labels))
+        })
+        .collect();
+    let mut solver = Solver::new();
+    if solver.load(puzzle).is_err() {
+        return (0, 0);
+    }
+    let mut images = std::collections::HashSet::new();
+    let count = solver.for_each_solution(limit, |grid| {
+        images.insert(smallest_image(grid, &stabilizer, group.relabels()));
+    });
+    (count, images.len())
+}
+
+/// The symmetries of the pattern of clues of a puzzle, for classifying
+/// collections and validating generated puzzles.
+pub fn symmetries(puzzle: &Puzzle) -> impl Iterator<Item = Symmetry> + '_ {
+    Symmetry::ALL
+        .into_iter()
+        .filter(|symmetry| symmetry.holds(puzzle))
+}
+
+/// The canonical form of a puzzle, which is the same for all puzzles which
+/// are transformations of each other, such that duplicates up to rotation,
+/// mirroring, permutation of rows and columns and relabeling are found by
+/// comparing canonical forms. It is the smallest of all transformations of
+/// the puzzle when read row by row, with empty cells as zero and values
+/// relabeled in order of their first appearance.
+pub fn canonical(puzzle: &Puzzle) -> Puzzle {
+    let mut search = Canonical {
+        grid: [[0; 9]; 9],
+        best: [u8::MAX; 81],
+    };
+    for transposed in [false, true] {
+        search.grid = match transposed {
+            false => puzzle.cells,
+            true => core::array::from_fn(|row| core::array::from_fn(|col| puzzle.cells[col][row])),
+        };
+        for first in 0..9 {
+            let mut rows = [0; 9];
+            rows[0] = first;
+            search.columns(&mut rows, &mut [0; 9], &mut [0; 81], 0, [0; 10]);
+        }
+    }
+    let mut cells = [[0; 9]; 9];
+    for (idx, value) in search.best.into_iter().enumerate() {
+        cells[idx / 9][idx % 9] = value;
+    }
+    Puzzle { cells }
+}
+
+/// Search for the canonical form among the orders of the rows and columns of
+/// a grid, which is possibly transposed. The columns are ordered by the first
+/// row, after which the other rows are ordered, pruning orders as soon as the
+/// form is larger than the best form so far.
+struct Canonical {
+    grid: [[u8; 9]; 9],
+    best: [u8; 81],
+}
+
+impl Canonical {
+    /// Choose the column at a position of the first row, given the labels
+    /// assigned so far.
+    fn columns(
+        &mut self,
+        rows: &mut [usize; 9],
+        cols: &mut [usize; 9],
+        form: &mut [u8; 81],
+        pos: usize,
+        labels: [u8; 10],
+    ) {
+        if pos == 9 {
+            self.rows(rows, cols, form, 1, labels);
+            return;
+        }
+        for col in 0..9 {
+            if !allowed(&cols[..pos], col) {
+                continue;
+            }
+            let mut labels = labels;
+            form[pos] = label(&mut labels, self.grid[rows[0]][col]);
+            // The best form may have changed since the prefix was compared.
+            if form[..=pos] > self.best[..=pos] {
+                continue;
+            }
+            cols[pos] = col;
+            self.columns(rows, cols, form, pos + 1, labels);
+        }
+    }
+
+    /// Choose the row at a position once the order of the columns is chosen,
+    /// keeping the form if it is smaller than the best once complete.
+    fn rows(
+        &mut self,
+        rows: &mut [usize; 9],
+        cols: &[usize; 9],
+        form: &mut [u8; 81],
+        pos: usize,
+        labels: [u8; 10],
+    ) {
+        if pos == 9 {
+            if form[..] < self.best[..] {
+                self.best = *form;
+            }
+            return;
+        }
+        for row in 0..9 {
+            if !allowed(&rows[..pos], row) {
+                continue;
+            }
+            let mut labels = labels;
+            for (value, col) in form[9 * pos..9 * pos + 9].iter_mut().zip(cols) {
+                *value = label(&mut labels, self.grid[row][*col]);
+            }
+            if form[..9 * pos + 9] > self.best[..9 * pos + 9] {
+                continue;
+            }
+            rows[pos] = row;
+            self.rows(rows, cols, form, pos + 1, labels);
+        }
+    }
+}
+
+/// Whether a line can follow the chosen lines, such that bands and stacks
+/// stay together. The first line of a band or stack chooses it, after which
+/// the others are taken from it.
+fn allowed(chosen: &[usize], line: usize) -> bool {
+    match chosen.len() % 3 {
+        0 => chosen.iter().all(|other| other / 3 != line / 3),
+        offset => {
+            let group = &chosen[chosen.len() - offset..];
+            group[0] / 3 == line / 3 && !group.contains(&line)
+        }
+    }
+}
+
+/// The label of a value when relabeling values in order of their first
+/// appearance, assigning it if the value has not appeared yet. Index zero of
+/// the labels holds the number of labels assigned.
+fn label(labels: &mut [u8; 10], value: u8) -> u8 {
+    if value == 0 {
+        return 0;
+    }
+    if labels[value as usize] == 0 {
+        labels[0] += 1;
+        labels[value as usize] = labels[0];
+    }
+    labels[value as usize]
+}
@@ -0,0 +1,794 @@
+//! Variants of sudoku which add rules to the classic ones, such as
+//! consecutive sudoku. Each rule is a constraint which removes the candidates
+//! contradicting it, and the constraints of a puzzle are composed into a rule
+//! set. A rule set is solved by applying its constraints until none removes
+//! a candidate and guessing when stuck, which is slower than the solver of
+//! classic puzzles, as that one is specialized to the classic rules.
+
+use crate::{check_cell, check_digit, Puzzle, Solution};
+use std::fmt;
+
+/// Coordinates of a cell, being its zero-based row and column.
+pub type Cell = (usize, usize);
+
+/// Mask of all candidates of a cell.
+const ALL: u16 = 0x1ff;
+
+/// The candidates of every cell while solving a variant. The candidates of a
+/// cell are a bitmask, of which bit `n - 1` is set if `n` is a candidate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Candidates {
+    cells: [[u16; 9]; 9],
+}
+
+impl Candidates {
+    /// Candidates of a puzzle, being all values for the empty cells and the
+    /// value of the filled cells.
+    pub fn new(puzzle: &Puzzle) -> Self {
+        let mut cells = [[ALL; 9]; 9];
+        for (row, values) in puzzle.cells.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                if *value != 0 {
+                    cells[row][col] = 1 << (value - 1);
+                }
+            }
+        }
+        Self { cells }
+    }
+
+    /// The candidates of a cell as a bitmask, which is empty for cells
+    /// outside of the grid.
+    pub fn get(&self, cell: Cell) -> u16 {
+        match check_cell(cell) {
+            Ok(()) => self.cells[cell.0][cell.1],
+            Err(_) => 0,
+        }
+    }
+
+    /// Whether a value is a candidate of a cell.
+    pub fn has(&self, cell: Cell, value: u8) -> bool {
+        check_digit(value).is_ok() && self.get(cell) & 1 << (value - 1) != 0
+    }
+
+    /// The value of a cell if it has a single candidate left.
+    pub fn value(&self, cell: Cell) -> Option<u8> {
+        let mask = self.get(cell);
+        (mask.count_ones() == 1).then(|| mask.trailing_zeros() as u8 + 1)
+    }
+
+    /// Keep only the candidates of a cell which are in a bitmask, returning
+    /// whether any were removed. Fails if no candidate is left.
+    pub fn restrict(&mut self, cell: Cell, mask: u16) -> Result<bool, &'static str> {
+        check_cell(cell)?;
+        let old = self.get(cell);
+        let new = old & mask;
+        if new == 0 {
+            return Err("no candidate left for cell");
+        }
+        self.cells[cell.0][cell.1] = new;
+        Ok(new != old)
+    }
+
+    /// Remove a candidate of a cell, returning whether it was a candidate.
+    /// Fails if no candidate is left.
+    pub fn remove(&mut self, cell: Cell, value: u8) -> Result<bool, &'static str> {
+        check_digit(value)?;
+        self.restrict(cell, !(1 << (value - 1)))
+    }
+
+    /// Whether every cell has a single candidate.
+    pub fn is_solved(&self) -> bool {
+        self.cells
+            .iter()
+            .flatten()
+            .all(|mask| mask.count_ones() == 1)
+    }
+}
+
+/// A rule of a variant, which removes the candidates contradicting it.
+pub trait Constraint: fmt::Debug + Send + Sync {
+    /// Remove the candidates which contradict the rule, returning whether
+    /// any were removed. Fails if the rule can no longer be satisfied.
+    /// Candidates are only removed, and once no more are removed, a grid of
+    /// which every cell has a single candidate must satisfy the rule.
+    fn prune(&self, candidates: &mut Candidates) -> Result<bool, &'static str>;
+}
+
+/// Cells which all have different values, such as a row of the classic
+/// rules. A region of nine cells holds every value exactly once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    cells: Vec<Cell>,
+}
+
+impl Region {
+    /// Create a region of at most nine distinct cells of the grid.
+    pub fn new(cells: Vec<Cell>) -> Result<Self, &'static str> {
+        if cells.len() > 9 {
+            return Err("region has more than nine cells");
+        }
+        check_cells(&cells)?;
+        Ok(Self { cells })
+    }
+
+    /// The main diagonal, from the top left to the bottom right cell, as of
+    /// diagonal sudoku.
+    pub fn main_diagonal() -> Self {
+        Self {
+            cells: (0..9).map(|idx| (idx, idx)).collect(),
+        }
+    }
+
+    /// The anti-diagonal, from the top right to the bottom left cell, as of
+    /// diagonal sudoku.
+    pub fn anti_diagonal() -> Self {
+        Self {
+            cells: (0..9).map(|idx| (idx, 8 - idx)).collect(),
+        }
+    }
+
+    /// The cells of the region.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+}
+
+impl Constraint for Region {
+    fn prune(&self, candidates: &mut Candidates) -> Result<bool, &'static str> {
+        let mut changed = false;
+        // Values of cells with a single candidate are removed from the other
+        // cells, which may in turn leave them with a single candidate.
+        let mut solved = 0;
+        loop {
+            let mut values = 0;
+            for cell in &self.cells {
+                if let Some(value) = candidates.value(*cell) {
+                    let bit = 1 << (value - 1);
+                    if values & bit != 0 {
+                        return Err("value occurs twice in region");
+                    }
+                    values |= bit;
+                }
+            }
+            if values == solved {
+                break;
+            }
+            solved = values;
+            for cell in &self.cells {
+                if candidates.get(*cell).count_ones() > 1 {
+                    changed |= candidates.restrict(*cell, !values)?;
+                }
+            }
+        }
+        // In a full region, a value which fits in a single cell is placed.
+        if self.cells.len() == 9 {
+            for value in 1..=9 {
+                let mut places = self
+                    .cells
+                    .iter()
+                    .filter(|cell| candidates.has(**cell, value));
+                match (places.next(), places.next()) {
+                    (None, _) => return Err("no cell left for value in region"),
+                    (Some(cell), None) => {
+                        changed |= candidates.restrict(*cell, 1 << (value - 1))?
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Check that cells are in the grid and distinct.
+fn check_cells(cells: &[Cell]) -> Result<(), &'static str> {
+    if cells.iter().any(|cell| cell.0 >= 9 || cell.1 >= 9) {
+        return Err("cell outside of the grid");
+    }
+    if (1..cells.len()).any(|idx| cells[..idx].contains(&cells[idx])) {
+        return Err("cell given more than once");
+    }
+    Ok(())
+}
+
+/// Remove the candidates of two cells which have no matching candidate in
+/// the other cell, where `allowed` tells whether the values of the first
+/// and second cell may go together. Returns whether any were removed.
+pub fn revise(
+    candidates: &mut Candidates,
+    first: Cell,
+    second: Cell,
+    allowed: impl Fn(u8, u8) -> bool,
+) -> Result<bool, &'static str> {
+    let supported = |candidates: &Candidates, cell: Cell, other: Cell, flip: bool| {
+        (1..=9)
+            .filter(|value| candidates.has(cell, *value))
+            .filter(|value| {
+                (1..=9).any(|other_value| {
+                    candidates.has(other, other_value)
+                        && match flip {
+                            false => allowed(*value, other_value),
+                            true => allowed(other_value, *value),
+                        }
+                })
+            })
+            .fold(0, |mask, value| mask | 1 << (value - 1))
+    };
+    let mask = supported(candidates, first, second, false);
+    let mut changed = candidates.restrict(first, mask)?;
+    let mask = supported(candidates, second, first, true);
+    changed |= candidates.restrict(second, mask)?;
+    Ok(changed)
+}
+
+/// The rule of consecutive sudoku, in which the orthogonally adjacent cells
+/// holding consecutive values are marked by a bar between them. Cells which
+/// are marked differ by one, and all other adjacent cells do not.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Consecutive {
+    /// Marked pairs of cells, of which the first is above or left of the
+    /// second.
+    marked: Vec<(Cell, Cell)>,
+}
+
+impl Consecutive {
+    /// Create the rule from the marked pairs of cells, which must be
+    /// orthogonally adjacent.
+    pub fn new(marked: impl IntoIterator<Item = (Cell, Cell)>) -> Result<Self, &'static str> {
+        let mut pairs = Vec::new();
+        for (first, second) in marked {
+            if first.0 >= 9 || first.1 >= 9 || second.0 >= 9 || second.1 >= 9 {
+                return Err("marked cell outside of the grid");
+            }
+            if first.0.abs_diff(second.0) + first.1.abs_diff(second.1) != 1 {
+                return Err("marked cells are not orthogonally adjacent");
+            }
+            let pair = (first.min(second), first.max(second));
+            if !pairs.contains(&pair) {
+                pairs.push(pair);
+            }
+        }
+        Ok(Self { marked: pairs })
+    }
+
+    /// The marked pairs of cells.
+    pub fn marked(&self) -> &[(Cell, Cell)] {
+        &self.marked
+    }
+}
+
+impl Constraint for Consecutive {
+    fn prune(&self, candidates: &mut Candidates) -> Result<bool, &'static str> {
+        let mut changed = false;
+        for row in 0..9 {
+            for col in 0..9 {
+                let first = (row, col);
+                for second in [(row + 1, col), (row, col + 1)] {
+                    if second.0 >= 9 || second.1 >= 9 {
+                        continue;
+                    }
+                    let marked = self.marked.contains(&(first, second));
+                    changed |= revise(candidates, first, second, |a, b| {
+                        (a.abs_diff(b) == 1) == marked
+                    })?;
+                }
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Every 3x3 magic square of the values 1 to 9 read row by row, being the
+/// rotations and reflections of a single one.
+const MAGIC_SQUARES: [[u8; 9]; 8] = [
+    [2, 7, 6, 9, 5, 1, 4, 3, 8],
+    [2, 9, 4, 7, 5, 3, 6, 1, 8],
+    [4, 3, 8, 9, 5, 1, 2, 7, 6],
+    [4, 9, 2, 3, 5, 7, 8, 1, 6],
+    [6, 1, 8, 7, 5, 3, 2, 9, 4],
+    [6, 7, 2, 1, 5, 9, 8, 3, 4],
+    [8, 1, 6, 3, 5, 7, 4, 9, 2],
+    [8, 3, 4, 1, 5, 9, 6, 7, 2],
+];
+
+/// The rule that a 3x3 area of cells, usually the center square, forms a
+/// magic square, of which every row, column and diagonal sums to 15. As only
+/// eight such squares exist, the candidates of the area are pruned to the
+/// squares which still fit, which immediately places the 5 in the middle and
+/// leaves the even values for the corners.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MagicSquare {
+    /// The top left cell of the area.
+    corner: Cell,
+}
+
+impl MagicSquare {
+    /// Create the rule for the area of which the top left cell is given.
+    pub fn new(corner: Cell) -> Result<Self, &'static str> {
+        if corner.0 > 6 || corner.1 > 6 {
+            return Err("magic square does not fit in the grid");
+        }
+        Ok(Self { corner })
+    }
+
+    /// The rule for the center square of the grid.
+    pub fn center() -> Self {
+        Self { corner: (3, 3) }
+    }
+
+    /// The top left cell of the area.
+    pub fn corner(&self) -> Cell {
+        self.corner
+    }
+}
+
+impl Constraint for MagicSquare {
+    fn prune(&self, candidates: &mut Candidates) -> Result<bool, &'static str> {
+        let cell = |idx: usize| (self.corner.0 + idx / 3, self.corner.1 + idx % 3);
+        let mut masks = [0; 9];
+        for square in MAGIC_SQUARES {
+            if (0..9).all(|idx| candidates.has(cell(idx), square[idx])) {
+                for (mask, value) in masks.iter_mut().zip(square) {
+                    *mask |= 1 << (value - 1);
+                }
+            }
+        }
+        if masks[0] == 0 {
+            return Err("no magic square fits the candidates");
+        }
+        let mut changed = false;
+        for (idx, mask) in masks.into_iter().enumerate() {
+            changed |= candidates.restrict(cell(idx), mask)?;
+        }
+        Ok(changed)
+    }
+}
+
+/// A cage of killer sudoku, being cells which hold different values that
+/// add up to the sum of the cage, if it has one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cage {
+    region: Region,
+    sum: Option<u32>,
+}
+
+impl Cage {
+    /// Create a cage of at most nine distinct cells, of which the sum must be
+    /// reachable by distinct values in its cells.
+    pub fn new(cells: Vec<Cell>, sum: Option<u32>) -> Result<Self, &'static str> {
+        if cells.is_empty() {
+            return Err("cage has no cells");
+        }
+        if let Some(sum) = sum {
+            // The smallest and largest sums are those of the smallest and
+            // largest values, one for each cell.
+            let count = cells.len().min(9) as u32;
+            let min = count * (count + 1) / 2;
+            let max = count * (19 - count) / 2;
+            if !(min..=max).contains(&sum) {
+                return Err("cage sum cannot be reached by its cells");
+            }
+        }
+        Ok(Self {
+            region: Region::new(cells)?,
+            sum,
+        })
+    }
+
+    /// The cells of the cage.
+    pub fn cells(&self) -> &[Cell] {
+        self.region.cells()
+    }
+
+    /// The sum of the cage, if any.
+    pub fn sum(&self) -> Option<u32> {
+        self.sum
+    }
+}
+
+impl Constraint for Cage {
+    fn prune(&self, candidates: &mut Candidates) -> Result<bool, &'static str> {
+        let mut changed = self.region.prune(candidates)?;
+        let Some(sum) = self.sum else {
+            return Ok(changed);
+        };
+        // The values of the first cells of the cage are a set of distinct
+        // values, which determines their sum. Sets which can be reached by
+        // filling the cells in order and from which the remaining cells can
+        // be filled to reach the sum are found by dynamic programming over
+        // the 512 sets, instead of trying every combination of values.
+        let cells = self.region.cells();
+        let value_sum = |set: usize| {
+            (1..=9u32)
+                .filter(|value| set & 1 << (value - 1) != 0)
+                .sum::<u32>()
+        };
+        let mut reachable = [false; 512];
+        reachable[0] = true;
+        for set in 0..512usize {
+            let idx = set.count_ones() as usize;
+            if !reachable[set] || idx >= cells.len() {
+                continue;
+            }
+            for value in 1..=9 {
+                if set & 1 << (value - 1) == 0 && candidates.has(cells[idx], value) {
+                    reachable[set | 1 << (value - 1)] = true;
+                }
+            }
+        }
+        let mut completes = [false; 512];
+        for set in (0..512usize).rev() {
+            let idx = set.count_ones() as usize;
+            if !reachable[set] || idx > cells.len() {
+                continue;
+            }
+            completes[set] = match idx == cells.len() {
+                true => value_sum(set) == sum,
+                false => (1..=9).any(|value| {
+                    let bit = 1 << (value - 1);
+                    set & bit == 0 && candidates.has(cells[idx], value) && completes[set | bit]
+                }),
+            };
+        }
+        let mut masks = [0u16; 9];
+        for set in 0..512usize {
+            let idx = set.count_ones() as usize;
+            if !completes[set] || idx >= cells.len() {
+                continue;
+            }
+            for value in 1..=9 {
+                let bit = 1 << (value - 1);
+                if set & bit == 0 && candidates.has(cells[idx], value) && completes[set | bit] {
+                    masks[idx] |= bit as u16;
+                }
+            }
+        }
+        for (cell, mask) in cells.iter().zip(masks) {
+            changed |= candidates.restrict(*cell, mask)?;
+        }
+        Ok(changed)
+    }
+}
+
+/// A thermometer, along which the values strictly increase from the bulb,
+/// which is its first cell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Thermometer {
+    cells: Vec<Cell>,
+}
+
+impl Thermometer {
+    /// Create a thermometer from its cells, starting at the bulb.
+    pub fn new(cells: Vec<Cell>) -> Result<Self, &'static str> {
+        if cells.len() > 9 {
+            return Err("thermometer has more than nine cells");
+        }
+        check_cells(&cells)?;
+        Ok(Self { cells })
+    }
+
+    /// The cells of the thermometer, starting at the bulb.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+}
+
+impl Constraint for Thermometer {
+    fn prune(&self, candidates: &mut Candidates) -> Result<bool, &'static str> {
+        // Each cell is above the smallest candidate of the previous cell and
+        // below the largest candidate of the next cell.
+        let mut changed = false;
+        for pair in self.cells.windows(2) {
+            let lowest = candidates.get(pair[0]).trailing_zeros();
+            changed |= candidates.restrict(pair[1], ALL << (lowest + 1))?;
+        }
+        for pair in self.cells.windows(2).rev() {
+            let highest = 15 - candidates.get(pair[1]).leading_zeros();
+            changed |= candidates.restrict(pair[0], (1 << highest) - 1)?;
+        }
+        Ok(changed)
+    }
+}
+
+/// A palindrome line, which reads the same from either end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palindrome {
+    cells: Vec<Cell>,
+}
+
+impl Palindrome {
+    /// Create a palindrome from its cells in order along the line.
+    pub fn new(cells: Vec<Cell>) -> Result<Self, &'static str> {
+        check_cells(&cells)?;
+        Ok(Self { cells })
+    }
+
+    /// The cells of the line in order.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+}
+
+impl Constraint for Palindrome {
+    fn prune(&self, candidates: &mut Candidates) -> Result<bool, &'static str> {
+        let mut changed = false;
+        for (first, second) in self.cells.iter().zip(self.cells.iter().rev()) {
+            let mask = candidates.get(*first) & candidates.get(*second);
+            changed |= candidates.restrict(*first, mask)?;
+            changed |= candidates.restrict(*second, mask)?;
+        }
+        Ok(changed)
+    }
+}
+
+/// A German whispers line, along which adjacent cells differ by at least 5.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Whispers {
+    cells: Vec<Cell>,
+}
+
+impl Whispers {
+    /// Create a whispers line from its cells in order along the line.
+    pub fn new(cells: Vec<Cell>) -> Result<Self, &'static str> {
+        check_cells(&cells)?;
+        Ok(Self { cells })
+    }
+
+    /// The cells of the line in order.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+}
+
+impl Constraint for Whispers {
+    fn prune(&self, candidates: &mut Candidates) -> Result<bool, &'static str> {
+        let mut changed = false;
+        for pair in self.cells.windows(2) {
+            changed |= revise(candidates, pair[0], pair[1], |a, b| a.abs_diff(b) >= 5)?;
+        }
+        Ok(changed)
+    }
+}
+
+/// Kind of a dot of Kropki sudoku.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DotKind {
+    /// The values of the cells are consecutive.
+    White,
+    /// One value of the cells is double the other.
+    Black,
+}
+
+/// A dot between two cells, of which the values are related by its kind,
+/// as of Kropki sudoku.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dot {
+    cells: (Cell, Cell),
+    kind: DotKind,
+}
+
+impl Dot {
+    /// Create a dot between two distinct cells.
+    pub fn new(cells: (Cell, Cell), kind: DotKind) -> Result<Self, &'static str> {
+        check_cells(&[cells.0, cells.1])?;
+        Ok(Self { cells, kind })
+    }
+
+    /// The cells on either side of the dot.
+    pub fn cells(&self) -> (Cell, Cell) {
+        self.cells
+    }
+
+    /// The kind of the dot.
+    pub fn kind(&self) -> DotKind {
+        self.kind
+    }
+}
+
+impl Constraint for Dot {
+    fn prune(&self, candidates: &mut Candidates) -> Result<bool, &'static str> {
+        let (first, second) = self.cells;
+        match self.kind {
+            DotKind::White => revise(candidates, first, second, |a, b| a.abs_diff(b) == 1),
+            DotKind::Black => revise(candidates, first, second, |a, b| a == 2 * b || b == 2 * a),
+        }
+    }
+}
+
+/// The houses of the base rules, each of which must hold every value once.
+/// Classic sudoku has all of them, while Latin squares leave out the
+/// squares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Houses {
+    pub rows: bool,
+    pub columns: bool,
+    pub squares: bool,
+}
+
+impl Houses {
+    /// The rows, columns and squares of classic sudoku.
+    pub const ALL: Self = Self {
+        rows: true,
+        columns: true,
+        squares: true,
+    };
+
+    /// The rows and columns of a Latin square.
+    pub const LATIN_SQUARE: Self = Self {
+        rows: true,
+        columns: true,
+        squares: false,
+    };
+}
+
+/// The rules of a puzzle, composed of any number of constraints.
+#[derive(Debug, Default)]
+pub struct RuleSet {
+    constraints: Vec<Box<dyn Constraint>>,
+}
+
+impl RuleSet {
+    /// The classic rules, being regions for every row, column and square.
+    pub fn classic() -> Self {
+        Self::base(Houses::ALL)
+    }
+
+    /// The rules of a Latin square, being regions for every row and column.
+    pub fn latin_square() -> Self {
+        Self::base(Houses::LATIN_SQUARE)
+    }
+
+    /// Base rules of regions for every house of the included kinds, such as
+    /// to compare the classic rules with those lacking one kind of house.
+    pub fn base(houses: Houses) -> Self {
+        let mut rules = Self::default();
+        for idx in 0..9 {
+            let square = (idx / 3 * 3, idx % 3 * 3);
+            let included = [
+                (houses.rows, (0..9).map(|col| (idx, col)).collect()),
+                (houses.columns, (0..9).map(|row| (row, idx)).collect()),
+                (
+                    houses.squares,
+                    (0..9)
+                        .map(|cell| (square.0 + cell / 3, square.1 + cell % 3))
+                        .collect(),
+                ),
+            ];
+            for (included, cells) in included {
+                if included {
+                    rules.add(Region { cells });
+                }
+            }
+        }
+        rules
+    }
+
+    /// Add a constraint to the rules.
+    pub fn add(&mut self, constraint: impl Constraint + 'static) {
+        self.constraints.push(Box::new(constraint));
+    }
+
+    /// The rules with a constraint added to them.
+    pub fn with(mut self, constraint: impl Constraint + 'static) -> Self {
+        self.add(constraint);
+        self
+    }
+
+    /// The constraints of the rules.
+    pub fn constraints(&self) -> &[Box<dyn Constraint>] {
+        &self.constraints
+    }
+
+    /// Apply every constraint until none removes a candidate.
+    pub fn propagate(&self, candidates: &mut Candidates) -> Result<(), &'static str> {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for constraint in &self.constraints {
+                changed |= constraint.prune(candidates)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that a solution keeps the givens of a puzzle and satisfies every
+    /// constraint, independently of how it was found.
+    pub fn verify(&self, puzzle: &Puzzle, solution: &Solution) -> Result<(), &'static str> {
+        let cells = solution.cells();
+        if cells.iter().flatten().any(|value| !(1..=9).contains(value)) {
+            return Err("solution has a cell without a value from 1 to 9");
+        }
+        let kept = puzzle
+            .cells()
+            .iter()
+            .flatten()
+            .zip(cells.iter().flatten())
+            .all(|(given, value)| *given == 0 || given == value);
+        if !kept {
+            return Err("solution does not keep the givens of the puzzle");
+        }
+        let mut candidates = Candidates::new(&Puzzle::from(solution));
+        self.propagate(&mut candidates)
+            .map_err(|_| "solution violates the rules")
+    }
+
+    /// Solve a puzzle under the rules, returning the first solution found.
+    pub fn solve(&self, puzzle: &Puzzle) -> Result<Solution, &'static str> {
+        let mut search = Search::default();
+        let mut found = None;
+        self.search(Candidates::new(puzzle), &mut search, &mut |cells, depth| {
+            found = Some((cells, depth));
+            false
+        });
+        let (cells, depth) = found.ok_or("puzzle has no solution under the rules")?;
+        Ok(Solution {
+            cells,
+            brute_forces: depth.min(u8::MAX as usize) as u8,
+            guesses: search.guesses,
+            backtracks: search.guesses.saturating_sub(depth as u32),
+        })
+    }
+
+    /// Count the solutions of a puzzle under the rules, stopping at a limit.
+    pub fn count_solutions(&self, puzzle: &Puzzle, limit: usize) -> usize {
+        let mut count = 0;
+        if limit > 0 {
+            self.search(
+                Candidates::new(puzzle),
+                &mut Search::default(),
+                &mut |_, _| {
+                    count += 1;
+                    count < limit
+                },
+            );
+        }
+        count
+    }
+
+    /// Search the solutions from a state, calling `found` with each solution
+    /// and the number of guesses leading to it until it returns false.
+    /// Returns whether the search should continue.
+    fn search(
+        &self,
+        mut candidates: Candidates,
+        search: &mut Search,
+        found: &mut impl FnMut([[u8; 9]; 9], usize) -> bool,
+    ) -> bool {
+        if self.propagate(&mut candidates).is_err() {
+            return true;
+        }
+        // Guess the values of the cell with the fewest candidates.
+        let cell = (0..9)
+            .flat_map(|row| (0..9).map(move |col| (row, col)))
+            .filter(|cell| candidates.get(*cell).count_ones() > 1)
+            .min_by_key(|cell| candidates.get(*cell).count_ones());
+        let Some(cell) = cell else {
+            let mut cells = [[0; 9]; 9];
+            for (row, values) in cells.iter_mut().enumerate() {
+                for (col, value) in values.iter_mut().enumerate() {
+                    *value = candidates.value((row, col)).unwrap_or(0);
+                }
+            }
+            return found(cells, search.depth);
+        };
+        for value in (1..=9).filter(|value| candidates.has(cell, *value)) {
+            let mut branch = candidates;
+            branch.cells[cell.0][cell.1] = 1 << (value - 1);
+            search.guesses = search.guesses.saturating_add(1);
+            search.depth += 1;
+            let proceed = self.search(branch, search, found);
+            search.depth -= 1;
+            if !proceed {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Statistics of a search under a rule set.
+#[derive(Default)]
+struct Search {
+    guesses: u32,
+    depth: usize,
+}
@@ -0,0 +1,139 @@
+//! Bindings for WebAssembly using wasm-bindgen, such that web applications
+//! can solve, rate, generate, hint and animate puzzles client-side. Puzzles are passed
+//! as strings of 81 values, and failures are thrown as JavaScript errors.
+
+use crate::generate;
+use crate::logic::{self, SolveEvent};
+use crate::random::Rng;
+use crate::rating::{self, Difficulty};
+use crate::{Options, Puzzle, Solver};
+use wasm_bindgen::prelude::*;
+
+/// Maximum number of puzzles generated when looking for one of a requested
+/// difficulty.
+const GENERATE_ATTEMPTS: usize = 1000;
+
+/// Difficulty rating of a puzzle.
+#[wasm_bindgen(getter_with_clone)]
+pub struct PuzzleRating {
+    /// Name of the difficulty tier, such as "Hard".
+    pub difficulty: String,
+    /// Sum of the scores of the techniques of all steps.
+    pub score: u32,
+    /// Name of the hardest technique required, if any.
+    pub hardest: Option<String>,
+    /// Number of steps taken to solve the puzzle.
+    pub steps: usize,
+}
+
+/// Next step towards the solution of a puzzle.
+#[wasm_bindgen(getter_with_clone)]
+pub struct Hint {
+    /// Name of the technique used.
+    pub technique: String,
+    /// Description of the step, in the common notation for cells.
+    pub description: String,
+}
+
+/// Event of a logical solve, for animating it.
+#[wasm_bindgen(getter_with_clone)]
+pub struct Event {
+    /// Kind of event: "start" or "end" of a step, or "fill" or "eliminate"
+    /// of a digit in a cell.
+    pub kind: String,
+    /// Index of the step to which the event belongs, starting from zero.
+    pub step: usize,
+    /// Name of the technique of the step, except for the end of a step.
+    pub technique: Option<String>,
+    /// Row of the cell, numbered from one, if any.
+    pub row: Option<usize>,
+    /// Column of the cell, numbered from one, if any.
+    pub column: Option<usize>,
+    /// Digit filled or eliminated, if any.
+    pub digit: Option<u8>,
+}
+
+fn parse(puzzle: &str) -> Result<Puzzle, JsError> {
+    puzzle.parse().map_err(JsError::new)
+}
+
+/// Solve a puzzle, returning its solution.
+#[wasm_bindgen]
+pub fn solve(puzzle: &str) -> Result<String, JsError> {
+    let solution =
+        Solver::solve_with_options(&parse(puzzle)?, &Options::default()).map_err(JsError::new)?;
+    Ok(solution.row_representation())
+}
+
+/// Rate the difficulty of a puzzle.
+#[wasm_bindgen]
+pub fn rate(puzzle: &str) -> Result<PuzzleRating, JsError> {
+    let rating = rating::rate(&parse(puzzle)?).map_err(JsError::new)?;
+    Ok(PuzzleRating {
+        difficulty: rating.difficulty.name().to_string(),
+        score: rating.score,
+        hardest: rating.hardest.map(|technique| technique.name().to_string()),
+        steps: rating.steps,
+    })
+}
+
+/// Find the next step towards the solution of a puzzle.
+#[wasm_bindgen]
+pub fn hint(puzzle: &str) -> Result<Hint, JsError> {
+    let step = logic::hint(&parse(puzzle)?).map_err(JsError::new)?;
+    Ok(Hint {
+        technique: step.technique.name().to_string(),
+        description: step.to_string(),
+    })
+}
+
+/// Solve a puzzle step by step, returning the events of all steps in order.
+#[wasm_bindgen]
+pub fn events(puzzle: &str) -> Result<Vec<Event>, JsError> {
+    let events = logic::solve_events(&parse(puzzle)?).map_err(JsError::new)?;
+    Ok(events
+        .into_iter()
+        .map(|event| {
+            let (kind, technique, cell, digit) = match event {
+                SolveEvent::StepStart { technique, .. } => ("start", Some(technique), None, None),
+                SolveEvent::Fill {
+                    technique,
+                    cell,
+                    digit,
+                    ..
+                } => ("fill", Some(technique), Some(cell), Some(digit)),
+                SolveEvent::Eliminate {
+                    technique,
+                    cell,
+                    digit,
+                    ..
+                } => ("eliminate", Some(technique), Some(cell), Some(digit)),
+                SolveEvent::StepEnd { .. } => ("end", None, None, None),
+            };
+            Event {
+                kind: kind.to_string(),
+                step: event.step(),
+                technique: technique.map(|technique| technique.name().to_string()),
+                row: cell.map(|(row, _)| row + 1),
+                column: cell.map(|(_, col)| col + 1),
+                digit,
+            }
+        })
+        .collect())
+}
+
+/// Generate a random puzzle with a unique solution, optionally of a specific
+/// difficulty such as "hard". The same seed generates the same puzzle.
+#[wasm_bindgen]
+pub fn generate(difficulty: Option<String>, seed: u64) -> Result<String, JsError> {
+    let mut rng = Rng::new(seed);
+    let puzzle = match difficulty {
+        None => generate::generate(&mut rng),
+        Some(difficulty) => {
+            let difficulty: Difficulty = difficulty.parse().map_err(JsError::new)?;
+            generate::generate_with_difficulty(&mut rng, difficulty, GENERATE_ATTEMPTS)
+                .map_err(JsError::new)?
+        }
+    };
+    Ok(puzzle.to_string())
+}
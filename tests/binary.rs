@@ -0,0 +1,138 @@
+//! Checks that puzzles survive a round trip through both binary encodings,
+//! that run-length records fall back to packing when they are not shorter,
+//! and that malformed records are rejected.
+
+use sudoku_solver::binary::{self, Encoding, PACKED_LEN};
+use sudoku_solver::Puzzle;
+
+/// A puzzle with 17 clues, the fewest of any puzzle with a unique solution.
+const SEVENTEEN: &str =
+    "000000010400000000020000000000050407008000300001090000300400200050100000000806000";
+
+/// A completely filled grid.
+const FULL: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+/// Encode a puzzle, returning the record.
+fn encode(puzzle: &Puzzle, encoding: Encoding) -> Vec<u8> {
+    let mut out = [0; PACKED_LEN];
+    let len = binary::encode(puzzle, encoding, &mut out);
+    out[..len].to_vec()
+}
+
+/// A puzzle with clues at the given cells and every other cell empty.
+fn clues(cells: &[usize]) -> Puzzle {
+    let mut grid = ['.'; 81];
+    for (idx, cell) in cells.iter().enumerate() {
+        grid[*cell] = char::from(b'1' + (idx % 9) as u8);
+    }
+    grid.iter().collect::<String>().parse().unwrap()
+}
+
+/// Check that a puzzle round-trips through both encodings, returning the
+/// length of its run-length record.
+fn round_trip(puzzle: &Puzzle) -> usize {
+    for encoding in [Encoding::Packed, Encoding::RunLength] {
+        let record = encode(puzzle, encoding);
+        assert!(record.len() <= PACKED_LEN);
+        assert_eq!(binary::decode(&record), Ok((*puzzle, record.len())));
+    }
+    encode(puzzle, Encoding::RunLength).len()
+}
+
+#[test]
+fn empty_grid() {
+    // A single run of 81 empty cells, of which the length takes three
+    // groups of the varint.
+    assert_eq!(round_trip(&clues(&[])), 3);
+}
+
+#[test]
+fn full_grid() {
+    // Without empty cells, a run-length record would be as long as a packed
+    // one, so it is packed.
+    let record = encode(&FULL.parse().unwrap(), Encoding::RunLength);
+    assert_eq!(record.len(), PACKED_LEN);
+    assert_eq!(record[0] & 0xf, 0);
+    round_trip(&FULL.parse().unwrap());
+}
+
+#[test]
+fn seventeen_clues() {
+    let puzzle = SEVENTEEN.parse().unwrap();
+    let record = encode(&puzzle, Encoding::RunLength);
+    assert_eq!(record[0] & 0xf, 1);
+    assert!(round_trip(&puzzle) < PACKED_LEN);
+}
+
+#[test]
+fn long_runs() {
+    // Runs of 10 and 69 empty cells, longer than a group of three bits and
+    // than two of them.
+    for cells in [&[0, 11][..], &[0, 70], &[0, 11, 80], &[40]] {
+        round_trip(&clues(cells));
+    }
+}
+
+#[test]
+fn packing_when_not_shorter() {
+    // Every other cell empty, of which each run takes two nibbles.
+    let cells: Vec<usize> = (0..81).step_by(2).collect();
+    let record = encode(&clues(&cells), Encoding::RunLength);
+    assert_eq!(record.len(), PACKED_LEN);
+    assert_eq!(record[0] & 0xf, 0);
+    round_trip(&clues(&cells));
+}
+
+#[test]
+fn consecutive_records() {
+    let puzzles: [Puzzle; 3] = [
+        SEVENTEEN.parse().unwrap(),
+        FULL.parse().unwrap(),
+        clues(&[]),
+    ];
+    let bytes: Vec<u8> = puzzles
+        .iter()
+        .flat_map(|puzzle| encode(puzzle, Encoding::RunLength))
+        .collect();
+    let mut rest = &bytes[..];
+    for puzzle in puzzles {
+        let (decoded, len) = binary::decode(rest).unwrap();
+        assert_eq!(decoded, puzzle);
+        rest = &rest[len..];
+    }
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn truncated_records() {
+    for encoding in [Encoding::Packed, Encoding::RunLength] {
+        let record = encode(&SEVENTEEN.parse().unwrap(), encoding);
+        for len in 0..record.len() {
+            assert!(binary::decode(&record[..len]).is_err());
+        }
+    }
+}
+
+#[test]
+fn invalid_records() {
+    let header = binary::VERSION << 4;
+    for record in [
+        // A run of 82 empty cells.
+        &[header | 1, 0x09, 0xa1][..],
+        // A clue followed by a run of 81 empty cells.
+        &[header | 1, 0x10, 0x8a, 0x10],
+        // A run of which the length has too many groups.
+        &[header | 1, 0x08, 0x88, 0x88, 0x00],
+        // A value above nine.
+        &[
+            header, 0xa0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        // An unknown encoding and version.
+        &[header | 2, 0x00],
+        &[(binary::VERSION + 1) << 4, 0x00],
+    ] {
+        assert!(binary::decode(record).is_err(), "{:02x?}", record);
+    }
+}
@@ -0,0 +1,66 @@
+//! Checks that calibrations fitted to labeled samples keep the levels of the
+//! scale in order, and that scales which the samples do not cover are
+//! rejected.
+
+#![cfg(feature = "rater")]
+
+use sudoku_solver::logic::{self, Step, Technique};
+use sudoku_solver::rating::Calibration;
+
+/// A puzzle with a unique solution, of which a step is taken.
+const PUZZLE: &str =
+    "..3.7..4...6..23.1.89.........1.7.8.517.....6...4.....271..9..5.95..........2....";
+
+/// Steps of a solve taking a number of steps of a technique.
+fn steps(technique: Technique, count: usize) -> Vec<Step> {
+    let mut step = logic::hint(&PUZZLE.parse().unwrap()).unwrap();
+    step.technique = technique;
+    vec![step; count]
+}
+
+fn labels(labels: &[&str]) -> Vec<String> {
+    labels.iter().map(|label| label.to_string()).collect()
+}
+
+#[test]
+fn samples_end_up_at_their_label() {
+    let samples = [
+        (steps(Technique::NakedSingle, 40), 0),
+        (steps(Technique::NakedSingle, 45), 0),
+        (steps(Technique::HiddenPair, 20), 1),
+        (steps(Technique::HiddenPair, 25), 1),
+        (steps(Technique::Swordfish, 10), 2),
+    ];
+    let calibration = Calibration::fit(labels(&["gentle", "tough", "fiendish"]), &samples).unwrap();
+    assert!(calibration.thresholds().is_sorted());
+    for (steps, label) in &samples {
+        assert_eq!(calibration.level(calibration.score(steps)), *label);
+    }
+}
+
+#[test]
+fn levels_without_samples_are_rejected() {
+    let samples = [
+        (steps(Technique::NakedSingle, 40), 0),
+        (steps(Technique::Swordfish, 10), 2),
+    ];
+    let fitted = Calibration::fit(labels(&["gentle", "tough", "fiendish"]), &samples);
+    assert!(fitted.is_err());
+}
+
+#[test]
+fn unreached_levels_start_above_the_samples() {
+    // The harder label scores lower, so the best fit puts both samples at
+    // the easiest level.
+    let samples = [
+        (steps(Technique::NakedSingle, 40), 0),
+        (steps(Technique::NakedSingle, 10), 1),
+    ];
+    let calibration = Calibration::fit(labels(&["gentle", "tough"]), &samples).unwrap();
+    let highest = samples
+        .iter()
+        .map(|(steps, _)| calibration.score(steps))
+        .max()
+        .unwrap();
+    assert_eq!(calibration.thresholds(), [highest + 1]);
+}
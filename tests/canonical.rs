@@ -0,0 +1,64 @@
+//! Checks that canonical forms are the same for all transformations of a
+//! puzzle, and that they are canonical forms of themselves.
+
+use sudoku_solver::random::Rng;
+use sudoku_solver::transform::{self, Transformation};
+use sudoku_solver::Puzzle;
+
+/// Puzzles of various patterns: a typical puzzle, one of 17 clues and one
+/// with a rotationally symmetric pattern of clues.
+const PUZZLES: [&str; 3] = [
+    "..3.7..4...6..23.1.89.........1.7.8.517.....6...4.....271..9..5.95..........2....",
+    "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+    "..5.1.6....3...8..2.......4.6..4..5.7...8...1.4..2..3.8.......9..1...4....2.9.7..",
+];
+
+#[test]
+fn transformations_share_the_canonical_form() {
+    let mut rng = Rng::new(0);
+    for puzzle in PUZZLES {
+        let puzzle: Puzzle = puzzle.parse().unwrap();
+        let canonical = transform::canonical(&puzzle);
+        for _ in 0..20 {
+            let transformed = Transformation::random(&mut rng).apply(&puzzle);
+            assert_eq!(
+                transform::canonical(&transformed),
+                canonical,
+                "{}",
+                transformed
+            );
+        }
+        for transformation in [
+            Transformation::rotate(),
+            Transformation::mirror_horizontal(),
+            Transformation::mirror_vertical(),
+        ] {
+            let transformed = transformation.apply(&puzzle);
+            assert_eq!(
+                transform::canonical(&transformed),
+                canonical,
+                "{}",
+                transformed
+            );
+        }
+    }
+}
+
+#[test]
+fn canonical_forms_are_idempotent() {
+    for puzzle in PUZZLES {
+        let canonical = transform::canonical(&puzzle.parse().unwrap());
+        assert_eq!(transform::canonical(&canonical), canonical);
+    }
+}
+
+#[test]
+fn different_puzzles_have_different_forms() {
+    let forms: Vec<Puzzle> = PUZZLES
+        .iter()
+        .map(|puzzle| transform::canonical(&puzzle.parse().unwrap()))
+        .collect();
+    for (idx, form) in forms.iter().enumerate() {
+        assert!(!forms[..idx].contains(form));
+    }
+}
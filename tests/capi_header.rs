@@ -0,0 +1,15 @@
+//! Checks that the C header in the checkout matches the one generated by the
+//! build, such that changes to the C interface are not forgotten in it.
+
+#![cfg(feature = "capi")]
+
+#[test]
+fn header_is_up_to_date() {
+    let generated = include_str!(env!("SUDOKU_SOLVER_HEADER"));
+    let checked_in = include_str!("../include/sudoku_solver.h");
+    assert!(
+        generated == checked_in,
+        "include/sudoku_solver.h is stale, copy {} over it",
+        env!("SUDOKU_SOLVER_HEADER")
+    );
+}
@@ -0,0 +1,55 @@
+//! Checks that options from the configuration file act as defaults, which
+//! never make an otherwise valid command line invalid.
+
+#![cfg(feature = "cli")]
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::{env, fs, process};
+
+/// A puzzle with a unique solution.
+const PUZZLE: &str =
+    "..3.7..4...6..23.1.89.........1.7.8.517.....6...4.....271..9..5.95..........2....";
+
+/// Run the binary in a fresh directory holding the given configuration and
+/// a file `puzzles.txt` with a single puzzle.
+fn run(name: &str, config: &str, args: &[&str]) -> Output {
+    let dir: PathBuf = env::temp_dir().join(format!("sudoku-solver-{}-{}", name, process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("sudoku-solver.toml"), config).unwrap();
+    fs::write(dir.join("puzzles.txt"), format!("{}\n", PUZZLE)).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_sudoku-solver"))
+        .args(args)
+        .current_dir(&dir)
+        .env("XDG_CONFIG_HOME", &dir)
+        .env("HOME", &dir)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+#[test]
+fn defaults_apply_without_conflicts() {
+    let output = run(
+        "applied",
+        "[solve]\nformat = \"csv\"\n",
+        &["solve", "puzzles.txt"],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("label,puzzle,solution"));
+}
+
+#[test]
+fn conflicting_defaults_are_left_out() {
+    let output = run(
+        "conflicting",
+        "[solve]\nformat = \"csv\"\n",
+        &["solve", "--puzzle", PUZZLE],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,99 @@
+//! Checks that game sessions are saved and restored with their entries,
+//! pencil marks and moves, that moves are undone in order, and that
+//! malformed saves are rejected.
+
+#![cfg(feature = "rater")]
+
+use sudoku_solver::game::{GameSession, Move};
+
+const PUZZLE: &str =
+    "..3.7..4...6..23.1.89.........1.7.8.517.....6...4.....271..9..5.95..........2....";
+
+/// A session in which digits were entered and erased and pencil marks
+/// toggled.
+fn played() -> GameSession {
+    let mut session = GameSession::new(PUZZLE.parse().unwrap()).unwrap();
+    session.toggle_note((0, 0), 2).unwrap();
+    session.toggle_note((0, 0), 5).unwrap();
+    session.enter((0, 0), 2).unwrap();
+    session.enter((0, 0), 5).unwrap();
+    session.toggle_note((8, 8), 9).unwrap();
+    session.enter((4, 4), 1).unwrap();
+    session.erase((4, 4)).unwrap();
+    session.toggle_note((0, 0), 5).unwrap();
+    session
+}
+
+#[test]
+fn round_trip() {
+    let session = played();
+    let saved = session.to_string();
+    assert_eq!(
+        saved,
+        format!(
+            "v1:{}:5{}:r1c1=2,r9c9=9:r1c1#2,r1c1#5,r1c1=2,r1c1=5,r9c9#9,r5c5=1,r5c5=.,r1c1#5",
+            PUZZLE,
+            ".".repeat(80)
+        )
+    );
+    let restored: GameSession = saved.parse().unwrap();
+    assert_eq!(restored, session);
+    assert_eq!(restored.moves(), session.moves());
+    assert_eq!(restored.notes((0, 0)).collect::<Vec<_>>(), [2]);
+    assert_eq!(restored.entry((0, 0)), Some(5));
+}
+
+#[test]
+fn undo() {
+    let mut session = played();
+    assert_eq!(
+        session.undo(),
+        Some(Move::Note {
+            cell: (0, 0),
+            digit: 5
+        })
+    );
+    assert!(session.has_note((0, 0), 5));
+    // Undoing the erase brings back the digit it erased.
+    assert!(matches!(
+        session.undo(),
+        Some(Move::Entry { digit: None, .. })
+    ));
+    assert_eq!(session.entry((4, 4)), Some(1));
+    session.undo();
+    assert_eq!(session.entry((4, 4)), None);
+    session.undo();
+    assert!(!session.has_note((8, 8), 9));
+    // Undoing an entry restores the one it replaced.
+    session.undo();
+    assert_eq!(session.entry((0, 0)), Some(2));
+    while session.undo().is_some() {}
+    assert_eq!(session, GameSession::new(PUZZLE.parse().unwrap()).unwrap());
+}
+
+#[test]
+fn malformed_saves() {
+    let empty = ".".repeat(81);
+    for saved in [
+        String::new(),
+        format!("{}:{}::", PUZZLE, empty),
+        format!("v2:{}:{}::", PUZZLE, empty),
+        format!("v1:{}:{}:", PUZZLE, empty),
+        format!("v1:{}:{}:::", PUZZLE, empty),
+        format!("v1:{}::", PUZZLE),
+        format!("v1:{}:{}::", &PUZZLE[1..], empty),
+        // Notes and moves of invalid cells or digits.
+        format!("v1:{}:{}:r0c1=2:r0c1#2", PUZZLE, empty),
+        format!("v1:{}:{}:r1c1=0:r1c1#0", PUZZLE, empty),
+        format!("v1:{}:{}::r1c1#.", PUZZLE, empty),
+        format!("v1:{}:{}::r1c1", PUZZLE, empty),
+        // A move into a given cell.
+        format!("v1:{}:..4{}::r1c3=4", PUZZLE, &empty[3..]),
+        // Entries and notes which do not match the moves.
+        format!("v1:{}:5{}::", PUZZLE, &empty[1..]),
+        format!("v1:{}:{}:r1c1=2:", PUZZLE, empty),
+        format!("v1:{}:{}::r1c1=5", PUZZLE, empty),
+    ] {
+        assert!(saved.parse::<GameSession>().is_err(), "{}", saved);
+    }
+}
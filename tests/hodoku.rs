@@ -0,0 +1,81 @@
+//! Checks that entries of Hodoku libraries are read with their givens,
+//! placed values and candidates, are written back unchanged, and that the
+//! techniques find the effects they expect.
+
+#![cfg(feature = "techniques")]
+
+use sudoku_solver::hodoku::{self, Entry};
+use sudoku_solver::logic::Technique;
+
+/// Entries of the techniques which have a library code, with values placed
+/// after the start and candidates deleted before the technique applies.
+const ENTRIES: [(Technique, &str); 6] = [
+    (Technique::Pointing, ":0100:5:+1+23.7..4.+4+56..23+71+789+3+1+4..+2...1.7.8+3517+2..+4+96...4....+7271.+49.+35.95.....+4....2....::517::"),
+    (Technique::Claiming, ":0101:3:...2...5.9..6...2.81+2+9..+6.4+2+8+1347+59+6..75...+81...+8....25+6+8.+39+2.+7+7+2+9.68..+5134+7...6.:525 526 187:317 327 338::"),
+    (Technique::NakedPair, ":0200:89:+14+6.9.+7+538+5+3...26+99+27+5+6+31+4+8.7.93.+6.4+3..+62.+59+76.....3.+2...+3.6.2+1.3+1..+947+6.+6..1..+35:473 493 565 875 894 896:873 973::"),
+    (Technique::HiddenPair, ":0210:18:..+49+5.81.+5.21+48.+96..+9...5.+4.9.+8.54...+45726..+9...+4...+5....3..9.59.6.....74........:631 632:331 731 332 732::"),
+    (Technique::XWing, ":0300:6:+4..+1.+932.1+2..+47+9.8.9..52+7419.+4.+3+5..+2+5.2+9.4.7+3.8..21+59+4+2+3.59.+4.7...4..2..64.+2.....:344 364 182 385 395 685 885 895 648:613 623 624 644::"),
+    (Technique::XYWing, ":0800:389:+38.1...+7+5..+7.38.9+12+1.7...+4.7.4+82.+1..+13+8..+7..2...+3.1+784....+734+1..715+8...6..3.+16..7:424 963 973 533 287 297 657 591 592 621 622 663 673 916 986 954 637:337::"),
+];
+
+#[test]
+fn entries_round_trip() {
+    for (technique, line) in ENTRIES {
+        let entry: Entry = line.parse().unwrap();
+        assert_eq!(entry.technique, Some(technique));
+        assert_eq!(entry.to_string(), line);
+        assert_eq!(entry.to_string().parse::<Entry>(), Ok(entry));
+    }
+}
+
+#[test]
+fn givens_and_placed_values() {
+    let entry: Entry = ENTRIES[0].1.parse().unwrap();
+    // The first row is `+1+23.7..4.`, of which the 1 and 2 were placed.
+    assert_eq!(entry.givens.cells()[0][..3], [0, 0, 3]);
+    assert_eq!(entry.puzzle.cells()[0][..3], [1, 2, 3]);
+    assert_eq!(entry.digits, "5");
+    assert_eq!(entry.eliminations, [((0, 6), 5)]);
+    assert!(entry.deleted.is_empty() && entry.placements.is_empty());
+
+    // Empty cells may be written as zeroes as well.
+    let zeroes = ENTRIES[0].1.replace('.', "0");
+    assert_eq!(zeroes.parse::<Entry>(), Ok(entry));
+}
+
+#[test]
+fn techniques_find_the_expected_effects() {
+    for (_, line) in ENTRIES {
+        let entry: Entry = line.parse().unwrap();
+        let step = entry.find().unwrap();
+        assert!(entry.matches(&step), "{}", line);
+    }
+}
+
+#[test]
+fn codes() {
+    for technique in Technique::ALL {
+        if let Some(code) = hodoku::code(technique) {
+            assert_eq!(hodoku::technique(code), Some(technique));
+        }
+    }
+    assert_eq!(hodoku::technique("0000"), Some(Technique::NakedSingle));
+    assert_eq!(hodoku::technique("0100-1"), Some(Technique::Pointing));
+    assert_eq!(hodoku::technique("0901"), None);
+}
+
+#[test]
+fn malformed_entries() {
+    let (_, line) = ENTRIES[0];
+    for malformed in [
+        &line[1..],
+        ":0100",
+        ":0100:5",
+        &line.replace("::517::", "::5170::"),
+        &line.replace("::517::", "::017::"),
+        &line.replacen(".7", "", 1),
+        &line.replacen(".7", "x7", 1),
+    ] {
+        assert!(malformed.parse::<Entry>().is_err(), "{}", malformed);
+    }
+}
@@ -0,0 +1,45 @@
+//! Checks that puzzles solved in lockstep get the solutions of the regular
+//! solver, and that contradictions are left to it, with or without the
+//! `simd` feature.
+
+#![cfg(feature = "generator")]
+
+use sudoku_solver::random::Rng;
+use sudoku_solver::rating::Difficulty;
+use sudoku_solver::{generate, lockstep, Options, Puzzle, Solver};
+
+#[test]
+fn solutions_match_the_solver() {
+    let mut rng = Rng::new(0);
+    let mut puzzles: Vec<Puzzle> = (0..lockstep::LANES / 2)
+        .map(|_| generate::generate_with_difficulty(&mut rng, Difficulty::Easy, 1000).unwrap())
+        .collect();
+    puzzles.extend((0..lockstep::LANES / 2).map(|_| generate::generate(&mut rng)));
+    let solutions = lockstep::solve_singles(&puzzles).unwrap();
+    for (puzzle, solution) in puzzles.iter().zip(&solutions) {
+        if let Some(solution) = solution {
+            assert_eq!(
+                solution.cells(),
+                Solver::solve_with_options(puzzle, &Options::default())
+                    .unwrap()
+                    .cells()
+            );
+        }
+    }
+    assert!(solutions[..lockstep::LANES / 2].iter().all(Option::is_some));
+}
+
+#[test]
+fn contradictions_are_not_solved() {
+    let mut rng = Rng::new(1);
+    let puzzle = generate::generate_with_difficulty(&mut rng, Difficulty::Easy, 1000).unwrap();
+    let solved = generate::solution(&mut rng);
+    // Repeating the first clue of the first row in the next cell of it.
+    let mut cells: Vec<char> = puzzle.to_string().chars().collect();
+    let col = cells[..9].iter().position(|cell| *cell != '.').unwrap();
+    cells[(col + 1) % 9] = cells[col];
+    let puzzle: Puzzle = cells.into_iter().collect::<String>().parse().unwrap();
+    let solutions = lockstep::solve_singles(&[solved, puzzle]).unwrap();
+    assert!(solutions[0].is_some());
+    assert!(solutions[1].is_none());
+}
@@ -0,0 +1,556 @@
+//! Checks that the public API does not panic on any input, by feeding it
+//! random, malformed, out-of-range and adversarial inputs and catching the
+//! panics. Failures must surface as errors instead.
+
+#![cfg(all(feature = "generator", feature = "variants"))]
+
+use std::cell::RefCell;
+use std::io::Cursor;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+use std::time::Duration;
+use sudoku_solver::analysis;
+use sudoku_solver::bench::{self, Baseline, Measurement, Report};
+use sudoku_solver::binary::{self, Encoding};
+use sudoku_solver::decisions::{self, Decision, Recording};
+use sudoku_solver::game::GameSession;
+use sudoku_solver::generate;
+use sudoku_solver::hodoku::{self, Entry};
+use sudoku_solver::incremental::Incremental;
+use sudoku_solver::lockstep;
+use sudoku_solver::logic::{self, Grid, HintLevel, Provenance, Step, Technique};
+use sudoku_solver::moves::{self, Move};
+use sudoku_solver::random::Rng;
+use sudoku_solver::rating::{self, Difficulty};
+use sudoku_solver::transform::{self, Group, Transformation};
+use sudoku_solver::variants::{self, Cage, Candidates, Consecutive, Dot, DotKind, RuleSet};
+use sudoku_solver::{constant, Options, Puzzle, Solver};
+
+/// Number of rounds of random inputs of each part of the API.
+const ROUNDS: usize = 8;
+
+/// A puzzle with a unique solution, which inputs are derived from.
+const VALID: &str =
+    "..3.7..4...6..23.1.89.........1.7.8.517.....6...4.....271..9..5.95..........2....";
+
+thread_local! {
+    /// Message of the last panic on this thread, as recorded by the panic hook.
+    static LAST_PANIC: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Random inputs of a round.
+#[derive(Debug)]
+struct Input {
+    text: String,
+    puzzle: Puzzle,
+    other: Puzzle,
+    cell: (usize, usize),
+    value: u8,
+    options: Options,
+    limit: usize,
+}
+
+impl Input {
+    fn new(rng: &mut Rng) -> Self {
+        Self {
+            text: string(rng),
+            puzzle: puzzle(rng),
+            other: puzzle(rng),
+            cell: coords(rng),
+            value: digit(rng),
+            options: options(rng),
+            limit: rng.below(20),
+        }
+    }
+}
+
+/// Check that a part of the API does not panic on the inputs of any round,
+/// failing with the inputs of the rounds which panicked.
+fn check(name: &str, f: impl Fn(&Input)) {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        panic::set_hook(Box::new(|info| {
+            LAST_PANIC.with(|last| *last.borrow_mut() = info.to_string());
+        }));
+    });
+    let mut rng = Rng::new(0x5eed);
+    let mut failures = Vec::new();
+    for _ in 0..ROUNDS {
+        let input = Input::new(&mut rng);
+        if panic::catch_unwind(AssertUnwindSafe(|| f(&input))).is_err() {
+            let input: String = format!("{:?}", input).chars().take(200).collect();
+            let message = LAST_PANIC.with(|last| last.borrow().clone());
+            failures.push(format!("{}: {}", input, message));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} panicked {} times:\n{}",
+        name,
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+/// A random string, being garbage, a mutated puzzle or a giant string.
+fn string(rng: &mut Rng) -> String {
+    const ALPHABET: &[char] = &[
+        '0', '1', '5', '9', '.', ':', ',', '#', '=', '<', '>', 'r', 'c', 'v', '1', ' ', '\n', '-',
+        '+', 'é', '٣', '９', '\0', '\u{feff}',
+    ];
+    match rng.below(4) {
+        0 => (0..rng.below(120))
+            .map(|_| ALPHABET[rng.below(ALPHABET.len())])
+            .collect(),
+        1 | 2 => {
+            let mut chars: Vec<char> = VALID.chars().collect();
+            for _ in 0..1 + rng.below(4) {
+                let idx = rng.below(chars.len());
+                match rng.below(3) {
+                    0 => chars[idx] = ALPHABET[rng.below(ALPHABET.len())],
+                    1 => drop(chars.remove(idx)),
+                    _ => chars.insert(idx, ALPHABET[rng.below(ALPHABET.len())]),
+                }
+            }
+            chars.into_iter().collect()
+        }
+        _ => "1".repeat(1 << 16),
+    }
+}
+
+/// Random coordinates, which are often outside of the grid.
+fn coords(rng: &mut Rng) -> (usize, usize) {
+    let coordinate = |rng: &mut Rng| match rng.below(8) {
+        0 => usize::MAX,
+        _ => rng.below(12),
+    };
+    (coordinate(rng), coordinate(rng))
+}
+
+/// A random digit, which is often outside of the range 1 to 9.
+fn digit(rng: &mut Rng) -> u8 {
+    match rng.below(8) {
+        0 => u8::MAX,
+        _ => rng.below(11) as u8,
+    }
+}
+
+/// A random puzzle, being valid, conflicting, ambiguous or empty.
+fn puzzle(rng: &mut Rng) -> Puzzle {
+    let mut cells = *VALID.parse::<Puzzle>().unwrap().cells();
+    match rng.below(4) {
+        0 => cells = [[0; 9]; 9],
+        1 => {
+            for _ in 0..rng.below(10) {
+                cells[rng.below(9)][rng.below(9)] = rng.below(10) as u8;
+            }
+        }
+        2 => {
+            for row in cells.iter_mut() {
+                for cell in row.iter_mut() {
+                    if rng.below(3) == 0 {
+                        *cell = 0;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    Puzzle::from_cells(cells).unwrap()
+}
+
+/// A random step, of which the cells and digits may be out of range.
+fn step(rng: &mut Rng) -> Step {
+    let mut step = logic::hint(&VALID.parse().unwrap()).unwrap();
+    step.placements = (0..rng.below(3))
+        .map(|_| (coords(rng), digit(rng)))
+        .collect();
+    step.eliminations = (0..rng.below(3))
+        .map(|_| (coords(rng), digit(rng)))
+        .collect();
+    step
+}
+
+/// Random options, which limit the search tightly.
+fn options(rng: &mut Rng) -> Options {
+    Options {
+        #[cfg(feature = "parallel")]
+        parallel: false,
+        max_guesses: Some(rng.below(1000) as u32),
+        timeout: Some(Duration::from_millis(rng.below(20) as u64)),
+    }
+}
+
+#[test]
+fn parsing() {
+    check("parsing", |input| {
+        let text = &input.text;
+        let _ = text.parse::<Puzzle>();
+        let _ = Puzzle::from_literal(text);
+        let _ = Puzzle::try_from(text.chars().collect::<Vec<_>>());
+        let _ = text.parse::<GameSession>();
+        let _ = text.parse::<Recording>();
+        let _ = text.parse::<Decision>();
+        let _ = text.parse::<Entry>();
+        let _ = text.parse::<Difficulty>();
+        let _ = text.parse::<Technique>();
+        let _ = text.parse::<HintLevel>();
+        let _ = text.parse::<Move>();
+        let _ = moves::parse(text);
+        let _ = moves::parse_script(text);
+        let _ = moves::parse_cells(text);
+        let _ = hodoku::technique(text);
+        let _ = bench::load_corpus(Cursor::new(text));
+        let _ = Baseline::load(Cursor::new(text));
+        let _ = binary::decode(text.as_bytes());
+        let _ = text.parse::<Group>();
+    });
+}
+
+#[test]
+fn from_cells() {
+    check("from_cells", |input| {
+        let mut cells = [[0; 9]; 9];
+        cells[input.cell.0 % 9][input.cell.1 % 9] = input.value;
+        let _ = Puzzle::from_cells(cells);
+    });
+}
+
+#[test]
+fn solving() {
+    check("solving", |input| {
+        let Input {
+            puzzle,
+            other,
+            cell,
+            limit,
+            ..
+        } = *input;
+        let options = &input.options;
+        puzzle.clues();
+        puzzle.fingerprint();
+        let _ = puzzle.to_string();
+        if let Ok(solution) = Solver::solve_with_options(&puzzle, options) {
+            let _ = solution.verify(&other);
+            let _ = solution.row_representation();
+            let _ = solution.to_string();
+        }
+        let _ = Solver::solve_with_snapshot(&puzzle, options).map_err(|err| {
+            let _ = err.snapshot.value(cell);
+            let _ = err.snapshot.candidates(cell).count();
+            let _ = err.snapshot.to_string();
+        });
+        Solver::count_solutions(&puzzle, limit);
+        let _ = constant::check(&puzzle);
+        let _ = lockstep::solve_batch(&[puzzle, other], options);
+        let _ = lockstep::solve_singles(&vec![puzzle; 2 * limit]);
+    });
+}
+
+#[test]
+fn binary() {
+    check("binary", |input| {
+        for encoding in [Encoding::Packed, Encoding::RunLength] {
+            let mut out = [0; binary::PACKED_LEN];
+            let len = binary::encode(&input.puzzle, encoding, &mut out);
+            let _ = binary::decode(&out[..len]);
+            let _ = binary::decode(&out[..len / 2]);
+        }
+    });
+}
+
+#[test]
+fn transform() {
+    check("transform", |input| {
+        let Input {
+            puzzle,
+            cell,
+            value,
+            limit,
+            ..
+        } = *input;
+        let _ = Transformation::swap_bands(cell.0, cell.1);
+        let _ = Transformation::swap_stacks(cell.0, cell.1);
+        let _ = Transformation::relabel([value; 9]);
+        let _ = Transformation::random(&mut Rng::new(cell.0 as u64)).apply(&puzzle);
+        // The canonical form of sparse puzzles takes seconds to search for.
+        if puzzle.clues() >= 25 {
+            let _ = transform::canonical(&puzzle);
+        }
+        let _ = transform::symmetries(&puzzle).count();
+        for group in Group::ALL {
+            transform::count_essentially_different(&puzzle, group, limit);
+        }
+    });
+}
+
+#[test]
+fn incremental() {
+    check("incremental", |input| {
+        let Input {
+            puzzle,
+            cell,
+            value,
+            limit,
+            ..
+        } = *input;
+        if let Ok(mut incremental) = Incremental::from_puzzle(&puzzle) {
+            let _ = incremental.add_clue(cell, value);
+            let _ = incremental.remove_clue(cell);
+            incremental.count_solutions(limit);
+        }
+    });
+}
+
+#[test]
+fn grid() {
+    check("grid", |input| {
+        let Input {
+            puzzle,
+            cell,
+            value,
+            ..
+        } = *input;
+        if let Ok(mut grid) = Grid::new(&puzzle) {
+            let _ = grid.value(cell);
+            let _ = grid.candidates(cell).count();
+            let _ = grid.has_candidate(cell, value);
+            let _ = grid.place(cell, value);
+            grid.eliminate(cell, value);
+            let _ = grid.apply(&step(&mut Rng::new(value.into())));
+            let _ = Move::Place(cell, value).apply(&mut grid);
+            let _ = Move::Eliminate(cell, value).apply(&mut grid);
+            let _ = moves::replay(&mut grid, &[Move::Place(cell, value)]);
+            for technique in Technique::ALL {
+                let _ = grid.find(technique);
+            }
+        }
+        let _ = logic::cell_name(cell);
+        let _ = logic::sees(cell, cell);
+    });
+}
+
+#[test]
+fn steps() {
+    check("steps", |input| {
+        let Input {
+            cell, value, limit, ..
+        } = *input;
+        let step = step(&mut Rng::new(value.into()));
+        for level in [
+            HintLevel::Region,
+            HintLevel::Cell,
+            HintLevel::Digit,
+            HintLevel::Explanation,
+        ] {
+            let _ = step.hint(level);
+        }
+        let _ = step.to_string();
+        let _ = step.hodoku().to_string();
+        let _ = step.events(0).count();
+        let mut empty = step.clone();
+        empty.placements.clear();
+        empty.eliminations.clear();
+        let _ = empty.hint(HintLevel::Digit);
+        let _ = rating::Rating::from_steps(&[step.clone(), empty.clone()]);
+        let labels: Vec<String> = (0..limit % 4).map(|level| level.to_string()).collect();
+        let samples = [(vec![step], limit % 5), (vec![empty], cell.0 % 4)];
+        if let Ok(calibration) = rating::Calibration::fit(labels.clone(), &samples) {
+            let _ = calibration.label(calibration.score(&samples[0].0));
+        }
+        let thresholds = vec![u32::from(value); labels.len().saturating_sub(cell.1 % 3)];
+        if let Ok(calibration) = rating::Calibration::new([0; 12], labels, thresholds) {
+            let _ = calibration.label(u32::MAX);
+        }
+    });
+}
+
+#[test]
+fn logic() {
+    check("logic", |input| {
+        let Input {
+            puzzle,
+            cell,
+            value,
+            limit,
+            ..
+        } = *input;
+        let _ = logic::hint(&puzzle);
+        let _ = rating::estimate(&puzzle);
+        // Solving sparse puzzles step by step takes long, as most steps are
+        // brute-forced, and cannot be limited like the search.
+        if puzzle.clues() >= 25 {
+            let _ = logic::solve_events(&puzzle);
+            let _ = logic::examples(&puzzle, Technique::Pointing);
+            let _ = rating::rate(&puzzle);
+            if let Ok(provenance) = Provenance::new(&puzzle) {
+                let _ = provenance.cause(cell, value);
+                let _ = provenance.removed_before(cell, limit).count();
+            }
+        }
+    });
+}
+
+#[test]
+fn analysis() {
+    check("analysis", |input| {
+        let Input {
+            puzzle,
+            other,
+            limit,
+            ..
+        } = *input;
+        let _ = analysis::check_grid(&puzzle, &other);
+        let _ = analysis::backdoor(&puzzle, 1);
+        let _ = analysis::unavoidable_sets(&puzzle, limit);
+        if puzzle.clues() > 25 {
+            let _ = analysis::ambiguous_cells(&puzzle);
+            let _ = analysis::clue_removals(&puzzle);
+        }
+    });
+}
+
+#[test]
+fn game() {
+    check("game", |input| {
+        let Input {
+            puzzle,
+            cell,
+            value,
+            ..
+        } = *input;
+        if let Ok(mut session) = GameSession::new(puzzle) {
+            session.is_given(cell);
+            let _ = session.entry(cell);
+            let _ = session.value(cell);
+            let _ = session.notes(cell).count();
+            session.has_note(cell, value);
+            let _ = session.enter(cell, value);
+            let _ = session.toggle_note(cell, value);
+            let _ = session.check_move(cell, value);
+            let _ = session.score_move(cell, value);
+            let _ = session.solvability_after(cell, value);
+            let _ = session.first_mistake();
+            let _ = session.erase(cell);
+            let _ = session.to_string().parse::<GameSession>();
+            session.undo();
+        }
+    });
+}
+
+#[test]
+fn decisions() {
+    check("decisions", |input| {
+        let Input {
+            puzzle,
+            cell,
+            value,
+            limit,
+            ..
+        } = *input;
+        let _ = decisions::record(&puzzle, &input.options);
+        let decision = Decision {
+            depth: limit,
+            cell,
+            value,
+            consistent: value.is_multiple_of(2),
+        };
+        let recording = Recording {
+            puzzle,
+            decisions: vec![decision; 1 + limit % 3],
+            solved: limit.is_multiple_of(2),
+        };
+        let _ = decisions::replay(&recording);
+        let _ = decisions::dot(&recording);
+        let _ = recording.to_string().parse::<Recording>();
+    });
+}
+
+#[test]
+fn variants() {
+    check("variants", |input| {
+        let Input {
+            puzzle,
+            other,
+            cell,
+            value,
+            limit,
+            ..
+        } = *input;
+        let mut candidates = Candidates::new(&puzzle);
+        let _ = candidates.get(cell);
+        let _ = candidates.has(cell, value);
+        let _ = candidates.value(cell);
+        let _ = candidates.restrict(cell, value.into());
+        let _ = candidates.remove(cell, value);
+        let _ = variants::Region::new(vec![cell; limit]);
+        let _ = variants::MagicSquare::new(cell);
+        let _ = variants::Thermometer::new(vec![cell, (0, 0)]);
+        let _ = variants::Palindrome::new(vec![cell, (0, 0)]);
+        let _ = variants::Whispers::new(vec![cell, (0, 0)]);
+        let _ = Dot::new((cell, (0, 0)), DotKind::Black);
+        let _ = Consecutive::new([(cell, (0, 0))]);
+        let sum = [None, Some(0), Some(value.into()), Some(u32::MAX)][limit % 4];
+        let mut rules = RuleSet::classic();
+        if let Ok(cage) = Cage::new(vec![(0, 0), (0, 1), cell], sum) {
+            rules.add(cage);
+        }
+        if puzzle.clues() > 25 {
+            if let Ok(solution) = rules.solve(&puzzle) {
+                let _ = rules.verify(&other, &solution);
+            }
+            rules.count_solutions(&puzzle, limit);
+            let _ = RuleSet::latin_square().solve(&puzzle);
+        }
+    });
+}
+
+#[test]
+fn generation() {
+    check("generation", |input| {
+        let cell = input.cell;
+        let mut rng = Rng::new(cell.0 as u64);
+        let _ = rng.below(cell.1 % 2);
+        let _ = generate::generate_with_difficulty(&mut rng, Difficulty::Easy, cell.1 % 2);
+        let _ = generate::generate_pack(&mut rng, cell.1 % 3, cell.1 % 2);
+    });
+}
+
+#[test]
+fn bench() {
+    check("bench", |input| {
+        let Input {
+            puzzle,
+            cell,
+            value,
+            ..
+        } = *input;
+        let report = Report {
+            measurements: (0..cell.1 % 3)
+                .map(|_| Measurement {
+                    puzzle,
+                    elapsed: match cell.0 {
+                        usize::MAX => Duration::MAX,
+                        millis => Duration::from_millis(millis as u64),
+                    },
+                    brute_forces: Some(value),
+                })
+                .collect(),
+        };
+        for percentage in [f64::NAN, -1.0, 0.0, 95.0, 1e300] {
+            report.percentile(percentage);
+        }
+        report.mean();
+        report.throughput();
+    });
+}
+
+/// The cell size is clamped instead of allocating a giant image, which is
+/// rendered once as the input is not random.
+#[test]
+fn render() {
+    let puzzle = VALID.parse().unwrap();
+    let _ = sudoku_solver::render::svg(&puzzle, None, u32::MAX);
+    #[cfg(feature = "image")]
+    let _ = sudoku_solver::render::png(&puzzle, None, u32::MAX);
+}
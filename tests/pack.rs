@@ -0,0 +1,31 @@
+//! Checks that packs of generated puzzles consist of distinct puzzles of
+//! non-decreasing difficulty.
+
+#![cfg(feature = "generator")]
+
+use std::collections::HashSet;
+use sudoku_solver::generate;
+use sudoku_solver::random::Rng;
+
+#[test]
+fn packs_are_distinct_and_non_decreasing() {
+    for seed in 0..3 {
+        let pack = generate::generate_pack(&mut Rng::new(seed), 8, 100).unwrap();
+        assert_eq!(pack.len(), 8);
+        let ratings: Vec<_> = pack
+            .iter()
+            .map(|(_, rating)| (rating.difficulty, rating.score))
+            .collect();
+        assert!(ratings.is_sorted(), "{:?}", ratings);
+        let fingerprints: HashSet<_> = pack
+            .iter()
+            .map(|(puzzle, _)| puzzle.fingerprint())
+            .collect();
+        assert_eq!(fingerprints.len(), pack.len());
+    }
+}
+
+#[test]
+fn oversized_packs_are_rejected() {
+    assert!(generate::generate_pack(&mut Rng::new(0), usize::MAX, 1).is_err());
+}
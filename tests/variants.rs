@@ -0,0 +1,131 @@
+//! Checks that variant puzzles are solved under their constraints: each
+//! puzzle has a unique solution under its rules, which it does not have
+//! under the classic rules alone, and solutions breaking a rule are rejected
+//! when verifying.
+
+#![cfg(feature = "variants")]
+
+use sudoku_solver::variants::{Consecutive, Houses, MagicSquare, Region, RuleSet};
+use sudoku_solver::{Puzzle, Solver};
+
+/// A solution of classic sudoku, of which the center square is no magic
+/// square and the diagonals repeat values.
+const CLASSIC: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+/// Check that a puzzle has a unique solution under the rules, which is the
+/// expected one and passes verification.
+fn solves(rules: &RuleSet, puzzle: &str, solution: &str) {
+    let puzzle: Puzzle = puzzle.parse().unwrap();
+    assert_eq!(rules.count_solutions(&puzzle, 2), 1);
+    let solved = rules.solve(&puzzle).unwrap();
+    assert_eq!(Puzzle::from(&solved).to_string(), solution);
+    assert_eq!(rules.verify(&puzzle, &solved), Ok(()));
+}
+
+/// Check that a solution of classic sudoku is rejected under the rules.
+fn rejects(rules: &RuleSet, solution: &str) {
+    let puzzle: Puzzle = solution.parse().unwrap();
+    let solution = Solver::solve(solution.chars().collect()).unwrap();
+    assert!(rules.verify(&puzzle, &solution).is_err());
+}
+
+/// The marked pairs of a solution of consecutive sudoku, being the adjacent
+/// cells holding consecutive values.
+fn marks(solution: &str) -> Vec<((usize, usize), (usize, usize))> {
+    let values: Vec<u8> = solution.bytes().collect();
+    let mut marks = Vec::new();
+    for row in 0..9 {
+        for col in 0..9 {
+            for (other_row, other_col) in [(row + 1, col), (row, col + 1)] {
+                if other_row < 9
+                    && other_col < 9
+                    && values[9 * row + col].abs_diff(values[9 * other_row + other_col]) == 1
+                {
+                    marks.push(((row, col), (other_row, other_col)));
+                }
+            }
+        }
+    }
+    marks
+}
+
+#[test]
+fn consecutive() {
+    let puzzle =
+        ".....................3..............4...53..1....2..5....5..2............4.2....9";
+    let rules = RuleSet::classic().with(Consecutive::new(marks(CLASSIC)).unwrap());
+    solves(&rules, puzzle, CLASSIC);
+    assert!(RuleSet::classic().count_solutions(&puzzle.parse().unwrap(), 2) > 1);
+
+    // Without any marks, no adjacent cells may hold consecutive values.
+    rejects(
+        &RuleSet::classic().with(Consecutive::new([]).unwrap()),
+        CLASSIC,
+    );
+}
+
+#[test]
+fn magic_square() {
+    let puzzle =
+        "...1..78...9.....612.8.......4......86....4.......85.26......5.9...8...3..37.....";
+    let solution =
+        "456123789389547126127869345534276891862951437791438562678312954915684273243795618";
+    let rules = RuleSet::classic().with(MagicSquare::center());
+    solves(&rules, puzzle, solution);
+    assert!(RuleSet::classic().count_solutions(&puzzle.parse().unwrap(), 2) > 1);
+    rejects(&rules, CLASSIC);
+}
+
+#[test]
+fn latin_square() {
+    // Every row is the one above shifted by a cell, which repeats values in
+    // every square, so the puzzle has no solution under the classic rules.
+    let puzzle =
+        ".234.6.89.3.5.78.....67.9124..7.9....678..2.4..89.2.45.89..3.5.891.3.5..9...45...";
+    let solution =
+        "123456789234567891345678912456789123567891234678912345789123456891234567912345678";
+    solves(&RuleSet::latin_square(), puzzle, solution);
+    solves(&RuleSet::base(Houses::LATIN_SQUARE), puzzle, solution);
+    assert_eq!(
+        RuleSet::classic().count_solutions(&puzzle.parse().unwrap(), 1),
+        0
+    );
+
+    // Leaving out the columns as well, the puzzle has many solutions.
+    let rows = Houses {
+        rows: true,
+        columns: false,
+        squares: false,
+    };
+    assert_eq!(
+        RuleSet::base(rows).count_solutions(&puzzle.parse().unwrap(), 2),
+        2
+    );
+}
+
+#[test]
+fn extra_regions() {
+    let puzzle =
+        "1.3.56.8........23...1.......52........5...9......7..129.....75.7...5....64......";
+    let solution =
+        "123456789456789123789123456935241867617538294842697531298314675371865942564972318";
+    let rules = RuleSet::classic()
+        .with(Region::main_diagonal())
+        .with(Region::anti_diagonal());
+    solves(&rules, puzzle, solution);
+    assert!(RuleSet::classic().count_solutions(&puzzle.parse().unwrap(), 2) > 1);
+    rejects(&rules, CLASSIC);
+
+    // Regions smaller than a house hold distinct values, while these cells
+    // both hold a 5.
+    let pair = Region::new(vec![(0, 0), (1, 5)]).unwrap();
+    rejects(&RuleSet::classic().with(pair), CLASSIC);
+}
+
+#[test]
+fn givens_are_kept() {
+    let solution = Solver::solve(CLASSIC.chars().collect()).unwrap();
+    let other: Puzzle = format!("6{}", &CLASSIC[1..]).parse().unwrap();
+    assert!(RuleSet::classic().verify(&other, &solution).is_err());
+}